@@ -0,0 +1,93 @@
+//! Bare-metal firmware verifying the checked-in fixture proof
+//!
+//! Linking a `#![no_main]` binary, with its own allocator and panic handler, checks that
+//! nothing on the verification path needs `std` or a symbol the target lacks:
+//!
+//! ```text
+//! cargo build -p p3-uni-stark-mt-embedded-verifier --target riscv32imac-unknown-none-elf --bin firmware
+//! ```
+//!
+//! It is a build check rather than a runnable image: a real firmware sets up its stack and
+//! memory map through its runtime crate before verifying. Host builds get an empty `main`.
+
+#![cfg_attr(target_os = "none", no_std, no_main)]
+
+#[cfg(not(target_os = "none"))]
+fn main() {}
+
+#[cfg(target_os = "none")]
+mod firmware {
+    use core::alloc::{GlobalAlloc, Layout};
+    use core::cell::UnsafeCell;
+    use core::panic::PanicInfo;
+    use core::ptr;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use p3_field::PrimeCharacteristicRing;
+    use p3_uni_stark_mt::fixtures::{Val, FIXTURE_SEED};
+    use p3_uni_stark_mt_embedded_verifier::verify_fibonacci_proof;
+
+    /// The proof of `tests/fixture_bytes.rs`, as the device would receive it
+    const PROOF_BYTES: &[u8] = include_bytes!("../../fixtures/fibonacci.postcard");
+
+    /// Enough for one verification of the fixture, which never frees much
+    const HEAP_SIZE: usize = 1 << 20;
+
+    /// Hands out a static arena front to back and never frees.
+    struct BumpAllocator {
+        heap: UnsafeCell<[u8; HEAP_SIZE]>,
+        next: AtomicUsize,
+    }
+
+    // The arena is only handed out through `next`, one disjoint range per allocation
+    unsafe impl Sync for BumpAllocator {}
+
+    unsafe impl GlobalAlloc for BumpAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let base = self.heap.get() as usize;
+            let mut offset = self.next.load(Ordering::Relaxed);
+            loop {
+                let start = (base + offset).next_multiple_of(layout.align()) - base;
+                let end = match start.checked_add(layout.size()) {
+                    Some(end) if end <= HEAP_SIZE => end,
+                    _ => return ptr::null_mut(),
+                };
+                match self.next.compare_exchange_weak(
+                    offset,
+                    end,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return (base + start) as *mut u8,
+                    Err(current) => offset = current,
+                }
+            }
+        }
+
+        unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: BumpAllocator = BumpAllocator {
+        heap: UnsafeCell::new([0; HEAP_SIZE]),
+        next: AtomicUsize::new(0),
+    };
+
+    #[panic_handler]
+    fn panic(_info: &PanicInfo) -> ! {
+        loop {
+            core::hint::spin_loop();
+        }
+    }
+
+    #[no_mangle]
+    pub extern "C" fn _start() -> ! {
+        let verified =
+            verify_fibonacci_proof(FIXTURE_SEED, PROOF_BYTES, &[Val::from_u32(21)]).is_ok();
+        // There is nowhere to report to; a debugger reads the result here
+        loop {
+            core::hint::black_box(verified);
+            core::hint::spin_loop();
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Verifying p3-uni-stark-mt proofs on bare-metal targets
+//!
+//! Everything here is `#![no_std]` and only needs an allocator: the proof arrives as a
+//! postcard-encoded byte array, is decoded under [`ProofLimits`] and checked against the
+//! Fibonacci fixture of [`p3_uni_stark_mt::fixtures`]. Building the crate for a target
+//! without `std` is the test that the whole verifier stack really is `no_std`:
+//!
+//! ```text
+//! rustup target add riscv32imac-unknown-none-elf
+//! cargo build -p p3-uni-stark-mt-embedded-verifier --target riscv32imac-unknown-none-elf
+//! ```
+//!
+//! The same build runs on `wasm32-unknown-unknown` for in-browser and light-client
+//! verification; `tests/wasm.rs` checks it in the wasm runtime.
+//!
+//! A firmware binary links this crate, provides its `#[global_allocator]` and panic
+//! handler, and calls [`verify_fibonacci_proof`] on the bytes it received; `src/bin/firmware.rs`
+//! does so for a checked-in proof, and building it for the bare-metal target links the
+//! whole verifier:
+//!
+//! ```text
+//! cargo build -p p3-uni-stark-mt-embedded-verifier --target riscv32imac-unknown-none-elf --bin firmware
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+use p3_uni_stark_mt::fixtures::{baby_bear_config, FibonacciAir, FixtureConfig, Val};
+use p3_uni_stark_mt::{keygen, verify, Proof, ProofLimits, VerificationError, VerifyingKey};
+use serde::de::DeserializeSeed;
+
+/// Why a proof was rejected.
+#[derive(Debug)]
+pub enum EmbeddedVerifyError {
+    /// The bytes are not a postcard-encoded proof within the AIR's limits
+    Decode(postcard::Error),
+    /// The proof decoded but does not verify
+    Verify(VerificationError),
+}
+
+/// Decode a postcard-encoded proof, rejecting anything larger than the AIR allows.
+pub fn decode_proof(
+    vk: &VerifyingKey<FixtureConfig>,
+    proof_bytes: &[u8],
+) -> Result<Proof<FixtureConfig>, EmbeddedVerifyError> {
+    let mut deserializer = postcard::Deserializer::from_bytes(proof_bytes);
+    ProofLimits::for_air::<FixtureConfig, _>(&FibonacciAir, vk)
+        .proof_seed::<FixtureConfig>()
+        .deserialize(&mut deserializer)
+        .map_err(EmbeddedVerifyError::Decode)
+}
+
+/// Verify a postcard-encoded proof of the Fibonacci fixture under
+/// [`baby_bear_config`]`(config_seed)`.
+pub fn verify_fibonacci_proof(
+    config_seed: u64,
+    proof_bytes: &[u8],
+    public_values: &[Val],
+) -> Result<(), EmbeddedVerifyError> {
+    let config = baby_bear_config(config_seed);
+    let vk = keygen(&config, &FibonacciAir);
+    let proof = decode_proof(&vk, proof_bytes)?;
+    verify(&config, &FibonacciAir, &vk, &proof, public_values).map_err(EmbeddedVerifyError::Verify)
+}
@@ -0,0 +1,76 @@
+//! Fixture proofs survive the postcard round trip and verify from bytes
+//!
+//! `fixtures/fibonacci.postcard` is the proof of [`fibonacci_proof`]`(FIXTURE_SEED, 3)`,
+//! checked in so that the bytes a device receives are verified as they are, not as this
+//! build would encode them. Set `UPDATE_FIXTURE_BYTES=1` to rewrite it, e.g. after a
+//! `PROOF_VERSION` bump.
+
+use std::fs;
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::fixtures::{fibonacci_proof, Val, FIXTURE_SEED};
+use p3_uni_stark_mt_embedded_verifier::{verify_fibonacci_proof, EmbeddedVerifyError};
+
+const PROOF_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/fibonacci.postcard");
+const PROOF_BYTES: &[u8] = include_bytes!("../fixtures/fibonacci.postcard");
+
+/// The last Fibonacci number of the 8-row trace
+const PUBLIC_VALUE: u32 = 21;
+
+#[test]
+fn test_verify_checked_in_bytes() {
+    verify_fibonacci_proof(FIXTURE_SEED, PROOF_BYTES, &[Val::from_u32(PUBLIC_VALUE)]).expect(
+        "checked-in proof does not verify; rerun with UPDATE_FIXTURE_BYTES=1 if the format changed",
+    );
+}
+
+#[test]
+fn test_checked_in_bytes_match_prover() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    assert_eq!(fixture.public_values, [Val::from_u32(PUBLIC_VALUE)]);
+    let bytes = postcard::to_allocvec(&fixture.proof).expect("serialization failed");
+    if std::env::var_os("UPDATE_FIXTURE_BYTES").is_some() {
+        fs::write(PROOF_FILE, &bytes).expect("failed to write the proof file");
+        return;
+    }
+
+    assert!(
+        bytes == PROOF_BYTES,
+        "fixture proof changed; rerun with UPDATE_FIXTURE_BYTES=1 if intended"
+    );
+}
+
+#[test]
+fn test_verify_from_bytes() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let bytes = postcard::to_allocvec(&fixture.proof).expect("serialization failed");
+
+    verify_fibonacci_proof(FIXTURE_SEED, &bytes, &fixture.public_values)
+        .expect("verification failed");
+}
+
+#[test]
+fn test_corrupted_bytes_rejected() {
+    let public_values = [Val::from_u32(PUBLIC_VALUE)];
+
+    // A truncated encoding does not decode
+    assert!(matches!(
+        verify_fibonacci_proof(
+            FIXTURE_SEED,
+            &PROOF_BYTES[..PROOF_BYTES.len() / 2],
+            &public_values
+        ),
+        Err(EmbeddedVerifyError::Decode(_))
+    ));
+
+    // A proof under another config, or for another statement, does not verify
+    assert!(verify_fibonacci_proof(FIXTURE_SEED + 1, PROOF_BYTES, &public_values).is_err());
+    assert!(matches!(
+        verify_fibonacci_proof(
+            FIXTURE_SEED,
+            PROOF_BYTES,
+            &[Val::from_u32(PUBLIC_VALUE + 1)]
+        ),
+        Err(EmbeddedVerifyError::Verify(_))
+    ));
+}
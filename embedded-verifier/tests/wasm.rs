@@ -0,0 +1,33 @@
+//! The verifier runs on wasm32-unknown-unknown
+//!
+//! Proves the Fibonacci fixture and verifies it from postcard bytes inside the wasm
+//! runtime, as a browser or light client would:
+//!
+//! ```text
+//! rustup target add wasm32-unknown-unknown
+//! cargo install wasm-bindgen-cli
+//! cargo test -p p3-uni-stark-mt-embedded-verifier --target wasm32-unknown-unknown
+//! ```
+
+#![cfg(target_arch = "wasm32")]
+
+use p3_uni_stark_mt::fixtures::{fibonacci_proof, FIXTURE_SEED};
+use p3_uni_stark_mt_embedded_verifier::{verify_fibonacci_proof, EmbeddedVerifyError};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_verify_in_wasm() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let bytes = postcard::to_allocvec(&fixture.proof).expect("serialization failed");
+
+    verify_fibonacci_proof(FIXTURE_SEED, &bytes, &fixture.public_values)
+        .expect("verification failed");
+    assert!(matches!(
+        verify_fibonacci_proof(
+            FIXTURE_SEED,
+            &bytes[..bytes.len() / 2],
+            &fixture.public_values
+        ),
+        Err(EmbeddedVerifyError::Decode(_))
+    ));
+}
@@ -0,0 +1,179 @@
+//! Prover phase benchmarks
+//!
+//! Proves a synthetic AIR of several widths and heights and reports, in one benchmark
+//! group per [`ProverPhase`], only the time spent in that phase: the main commitment, the
+//! aux phase (challenges, trace build and commitment), the quotient evaluation and
+//! commitment, and the PCS opening. A regression in one phase then shows up in its own
+//! group instead of being diluted in the whole proof.
+//!
+//! `cargo bench -p p3-uni-stark-mt --features fixtures --bench phases`
+
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove_with_telemetry, setup, AuxBuilder, AuxTraceBuilder, ProverPhase, ProverTelemetry,
+};
+
+const PHASES: [ProverPhase; 4] = [
+    ProverPhase::MainCommit,
+    ProverPhase::AuxPhase(0),
+    ProverPhase::Quotient,
+    ProverPhase::Opening,
+];
+const WIDTHS: [usize; 3] = [4, 16, 64];
+const LOG_HEIGHTS: [usize; 3] = [10, 12, 14];
+
+/// `width` columns with `next[i] = local[i]^2 + local[(i + 1) % width]`, and one aux
+/// column with the running sum `z[i] = z[i - 1] + γ·a[i]` of the first column.
+struct BenchAir {
+    width: usize,
+}
+
+impl BenchAir {
+    fn trace(&self, log_n: usize) -> RowMajorMatrix<Val> {
+        let mut row: Vec<Val> = (1..=self.width).map(Val::from_usize).collect();
+        let mut values = Vec::with_capacity(self.width << log_n);
+        for _ in 0..1 << log_n {
+            let next = (0..self.width)
+                .map(|i| row[i].square() + row[(i + 1) % self.width])
+                .collect();
+            values.extend(row);
+            row = next;
+        }
+        RowMajorMatrix::new(values, self.width)
+    }
+}
+
+impl<F> BaseAir<F> for BenchAir {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for BenchAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let gamma = challenges[0];
+        let mut sum = EF::ZERO;
+        let values = main
+            .values
+            .chunks_exact(self.width)
+            .map(|row| {
+                sum += gamma * row[0];
+                sum
+            })
+            .collect();
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for BenchAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+
+        let mut when_transition = builder.when_transition();
+        for i in 0..self.width {
+            let x: AB::Expr = local[i].clone().into();
+            when_transition.assert_eq(
+                x.square() + local[(i + 1) % self.width].clone(),
+                next[i].clone(),
+            );
+        }
+
+        let gamma: AB::ExprEF = builder.aux_challenges()[0].into();
+        let a: AB::Expr = local[0].clone().into();
+        let a_next: AB::Expr = next[0].clone().into();
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), gamma.clone() * AB::ExprEF::from(a));
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z + gamma * AB::ExprEF::from(a_next));
+    }
+}
+
+/// Adds up the time the prover spends in one phase.
+struct PhaseClock {
+    phase: ProverPhase,
+    started: Option<Instant>,
+    elapsed: Duration,
+}
+
+impl ProverTelemetry for &mut PhaseClock {
+    fn phase_started(&mut self, phase: ProverPhase, _rows: usize) {
+        if phase == self.phase {
+            self.started = Some(Instant::now());
+        }
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        if phase == self.phase {
+            if let Some(started) = self.started.take() {
+                self.elapsed += started.elapsed();
+            }
+        }
+    }
+}
+
+fn bench_phases(c: &mut Criterion) {
+    let config = baby_bear_config(FIXTURE_SEED);
+    for phase in PHASES {
+        let mut group = c.benchmark_group(format!("{phase:?}"));
+        group.sample_size(10);
+        for width in WIDTHS {
+            let air = BenchAir { width };
+            let (pk, _) = setup(&config, &air);
+            for log_n in LOG_HEIGHTS {
+                let trace = air.trace(log_n);
+                group.throughput(Throughput::Elements((trace.height() * width) as u64));
+                group.bench_with_input(
+                    BenchmarkId::new(format!("width_{width}"), format!("2^{log_n}")),
+                    &trace,
+                    |b, trace| {
+                        b.iter_custom(|iters| {
+                            let mut clock = PhaseClock {
+                                phase,
+                                started: None,
+                                elapsed: Duration::ZERO,
+                            };
+                            for _ in 0..iters {
+                                prove_with_telemetry(
+                                    &config,
+                                    &air,
+                                    &pk,
+                                    trace.clone(),
+                                    &[],
+                                    &mut clock,
+                                )
+                                .expect("trace is valid");
+                            }
+                            clock.elapsed
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_phases);
+criterion_main!(benches);
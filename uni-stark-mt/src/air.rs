@@ -1,15 +1,34 @@
 //! AIR trait extensions for multi-trace proving
 
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::Any;
+
 use p3_air::BaseAir;
 use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
 
+use crate::ChallengePlan;
+
 /// Trait for AIRs that can build auxiliary trace columns.
 ///
 /// The auxiliary trace is built after the main trace has been committed and challenges
 /// have been sampled. This enables patterns like LogUp lookup arguments where the
 /// auxiliary columns (e.g., running sums) depend on random challenges.
 ///
+/// Single-phase AIRs implement [`aux_width`](Self::aux_width),
+/// [`num_challenges`](Self::num_challenges) and [`build_aux_trace`](Self::build_aux_trace).
+/// AIRs needing several challenge rounds (e.g. a permutation phase followed by a lookup
+/// phase using its products) instead override the `*_phase` methods and declare their
+/// challenges with [`challenge_plan`](Self::challenge_plan): each phase samples its own
+/// challenges, is built from the main trace and all earlier phases, and gets its own
+/// commitment. Constraints see the columns of all phases concatenated in phase order.
+///
+/// A phase may declare challenges but no columns, e.g. for constraints taking a random
+/// linear combination of main columns: it samples its challenges, which constraints read
+/// through [`AuxBuilder::aux_challenges`](crate::AuxBuilder::aux_challenges), and commits
+/// nothing.
+///
 /// # Example: LogUp Lookup
 ///
 /// ```ignore
@@ -42,9 +61,13 @@ pub trait AuxTraceBuilder<F: Field, EF: ExtensionField<F>>: BaseAir<F> + Sync {
         0
     }
 
-    /// Number of random challenge elements needed to build the auxiliary trace.
+    /// Number of random challenge elements sampled after the main trace commitment.
     ///
-    /// Returns 0 for AIRs without auxiliary traces.
+    /// They are sampled even when [`aux_width`](Self::aux_width) is 0, for constraints
+    /// using verifier randomness directly. Returns 0 for AIRs without auxiliary traces.
+    ///
+    /// Shorthand for a single unnamed group in phase 0, ignored when
+    /// [`challenge_plan`](Self::challenge_plan) is overridden.
     fn num_challenges(&self) -> usize {
         0
     }
@@ -71,6 +94,328 @@ pub trait AuxTraceBuilder<F: Field, EF: ExtensionField<F>>: BaseAir<F> + Sync {
         let _ = (main_trace, challenges);
         panic!("build_aux_trace called but aux_width() is 0")
     }
+
+    /// Number of auxiliary phases.
+    ///
+    /// Defaults to a single phase when [`aux_width`](Self::aux_width) is non-zero or
+    /// phase 0 samples challenges.
+    fn num_aux_phases(&self) -> usize {
+        usize::from(self.aux_width() > 0 || self.aux_phase_num_challenges(0) > 0)
+    }
+
+    /// Number of auxiliary columns built in `phase`.
+    ///
+    /// A phase of width 0 is never built nor committed; it only samples its challenges.
+    fn aux_phase_width(&self, phase: usize) -> usize {
+        if phase == 0 {
+            self.aux_width()
+        } else {
+            0
+        }
+    }
+
+    /// Widths of the matrices the columns of `phase` form, in column order.
+    ///
+    /// The phase is still built as one trace with the columns of its matrices side by
+    /// side, and must sum to [`aux_phase_width(phase)`](Self::aux_phase_width). Each matrix
+    /// is committed as a matrix of its own within the phase's commitment, e.g. one per
+    /// lookup bus, so its leaves and openings do not span the others. Constraints find the
+    /// columns of a matrix with
+    /// [`AuxBuilder::aux_matrix_columns`](crate::AuxBuilder::aux_matrix_columns).
+    fn aux_phase_matrix_widths(&self, phase: usize) -> Vec<usize> {
+        match self.aux_phase_width(phase) {
+            0 => Vec::new(),
+            width => vec![width],
+        }
+    }
+
+    /// Whether the columns of `phase` hold base field values.
+    ///
+    /// [`build_aux_phase`](Self::build_aux_phase) still returns extension field values,
+    /// but each must lie in the base field. The phase is then committed as one base field
+    /// column per column instead of `EF::DIMENSION`, which suits columns such as
+    /// permutation flags or multiplicities that need no challenge.
+    fn aux_phase_in_base_field(&self, phase: usize) -> bool {
+        let _ = phase;
+        false
+    }
+
+    /// Number of base field columns committed for `phase`.
+    fn aux_phase_committed_width(&self, phase: usize) -> usize {
+        let width = self.aux_phase_width(phase);
+        if self.aux_phase_in_base_field(phase) {
+            width
+        } else {
+            width * EF::DIMENSION
+        }
+    }
+
+    /// Challenges sampled right before building each phase, as named groups.
+    ///
+    /// The prover and the verifier sample the groups of a phase in order. Aux builders
+    /// find a challenge by name with [`ChallengePlan::phase_challenge`], constraints with
+    /// [`AuxBuilder::aux_challenge`](crate::AuxBuilder::aux_challenge). Defaults to the
+    /// unnamed challenges of [`num_challenges`](Self::num_challenges).
+    fn challenge_plan(&self) -> ChallengePlan {
+        ChallengePlan::unnamed(self.num_challenges())
+    }
+
+    /// Number of challenges sampled right before building `phase`, as declared by
+    /// [`challenge_plan`](Self::challenge_plan).
+    fn aux_phase_num_challenges(&self, phase: usize) -> usize {
+        self.challenge_plan().phase_num_challenges(phase)
+    }
+
+    /// Build the auxiliary columns of `phase`.
+    ///
+    /// # Arguments
+    /// - `phase`: Index of the phase being built
+    /// - `main_trace`: The main execution trace
+    /// - `prior_phases`: Auxiliary traces of phases `0..phase`, empty for phases without columns
+    /// - `challenges`: Challenges sampled for this phase
+    ///
+    /// # Returns
+    /// A matrix of width [`aux_phase_width(phase)`](Self::aux_phase_width) and the same
+    /// height as `main_trace`.
+    ///
+    /// The prover calls this inside a `build_aux_trace` span recording the AIR type, the
+    /// phase, the output dimensions and (at debug level) the challenges; spans opened here
+    /// with [`crate::tracing`] show up as its children.
+    fn build_aux_phase(
+        &self,
+        phase: usize,
+        main_trace: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        let _ = (phase, prior_phases);
+        self.build_aux_trace(main_trace, challenges)
+    }
+
+    /// Build the auxiliary columns of `phase` with the hints passed to
+    /// [`crate::prove_with_hints`].
+    ///
+    /// Hints are nondeterministic advice computed outside the AIR, e.g. the sorted memory
+    /// accesses of a program, for columns that cannot be derived from the main trace alone.
+    /// Implementations downcast `hints` to the type their caller supplies; the other entry
+    /// points pass `&()`. Like any aux witness, the returned columns are only trusted once
+    /// the constraints hold.
+    ///
+    /// The prover calls this instead of [`build_aux_phase`](Self::build_aux_phase), which
+    /// the default delegates to, unless [`uses_main_trace_lde`](Self::uses_main_trace_lde)
+    /// is set.
+    fn build_aux_phase_with_hints(
+        &self,
+        phase: usize,
+        main_trace: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+        hints: &dyn Any,
+    ) -> RowMajorMatrix<EF> {
+        let _ = hints;
+        self.build_aux_phase(phase, main_trace, prior_phases, challenges)
+    }
+
+    /// Whether the prover should hand this AIR the main trace over its extended domain.
+    ///
+    /// When `true`, [`build_aux_phase_with_lde`](Self::build_aux_phase_with_lde) is called
+    /// instead of [`build_aux_phase`](Self::build_aux_phase). Computing the [`MainTraceLde`]
+    /// costs an extra copy of the main trace LDE, so this is off by default.
+    fn uses_main_trace_lde(&self) -> bool {
+        false
+    }
+
+    /// Advanced: build the auxiliary columns of `phase` with access to the committed main
+    /// trace evaluated over an extended domain.
+    ///
+    /// This only changes how the prover computes the auxiliary witness (e.g. derivative-like
+    /// columns read off the extension); it does not affect soundness. The returned columns
+    /// are committed and must satisfy the constraints on the trace domain exactly like the
+    /// output of [`build_aux_phase`](Self::build_aux_phase), so a wrong value computed from
+    /// `main_lde` only makes the proof fail to verify.
+    fn build_aux_phase_with_lde(
+        &self,
+        phase: usize,
+        main_trace: &RowMajorMatrix<F>,
+        main_lde: &MainTraceLde<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        let _ = main_lde;
+        self.build_aux_phase(phase, main_trace, prior_phases, challenges)
+    }
+
+    /// Number of values `phase` exposes, e.g. its final LogUp cumulative sum.
+    fn aux_phase_num_exposed_values(&self, phase: usize) -> usize {
+        let _ = phase;
+        0
+    }
+
+    /// Values exposed by `phase`, read off its freshly built columns.
+    ///
+    /// They are observed by the challenger right after the phase's commitment, carried in
+    /// the proof and visible to the constraints through
+    /// [`AuxBuilder::aux_exposed_values`](crate::AuxBuilder::aux_exposed_values). Nothing
+    /// binds them to the columns but the constraints, so an AIR exposing a cumulative sum
+    /// must also assert it equals the running-sum column on the last row.
+    ///
+    /// # Returns
+    /// [`aux_phase_num_exposed_values(phase)`](Self::aux_phase_num_exposed_values) values.
+    fn aux_phase_exposed_values(
+        &self,
+        phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        challenges: &[EF],
+    ) -> Vec<EF> {
+        let _ = (phase, aux_phase, challenges);
+        Vec::new()
+    }
+
+    /// The values `phase` must expose, when the verifier can derive them on its own.
+    ///
+    /// Some exposed values are fixed by the statement rather than the witness: a lookup
+    /// into a public table, for instance, exposes a sum the verifier can recompute from the
+    /// public values and the phase's `challenges`. [`crate::verify`] compares the values
+    /// returned here with those carried in the proof instead of trusting the prover.
+    ///
+    /// # Returns
+    /// `None` (the default) when the values depend on the witness.
+    fn expected_aux_exposed_values(
+        &self,
+        phase: usize,
+        public_values: &[F],
+        challenges: &[EF],
+    ) -> Option<Vec<EF>> {
+        let _ = (phase, public_values, challenges);
+        None
+    }
+
+    /// Exposed value holding the net sum of this AIR's bus interactions, as
+    /// `(phase, index)` into the values of [`aux_phase_exposed_values`](Self::aux_phase_exposed_values).
+    ///
+    /// Sends add to the sum and receives subtract from it, so the buses balance when the
+    /// sums of all tables taking part add up to zero. The verifier checks this over the
+    /// tables of a [`crate::MultiProof`] from [`crate::prove_multi`], and for every other
+    /// proof on its own. `None` (the default) for AIRs without bus interactions; see
+    /// [`crate::BusInteractions`] for a gadget that builds them.
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Total number of auxiliary columns over all phases, as seen by the constraints.
+    fn total_aux_width(&self) -> usize {
+        (0..self.num_aux_phases())
+            .map(|phase| self.aux_phase_width(phase))
+            .sum()
+    }
+
+    /// Runtime parameters of this AIR instance that affect its constraints.
+    ///
+    /// These are observed by the challenger before the main trace commitment, so a
+    /// proof for one parameterization (e.g. `MulAir { degree: 2 }`) cannot verify
+    /// against another instance sharing the same widths.
+    ///
+    /// Returns an empty vector for AIRs whose constraints are fully determined by
+    /// their type.
+    fn instance_parameters(&self) -> Vec<F> {
+        Vec::new()
+    }
+}
+
+/// Trait for AIRs that generate their own main trace.
+///
+/// Keeping trace generation next to the constraints lets [`crate::prove_from_inputs`] go
+/// from the inputs of a statement to its proof, checking the generated trace against the
+/// AIR like [`crate::prove`] checks a supplied one.
+pub trait TraceGenerator<F> {
+    /// What the trace is generated from, e.g. a program and its arguments
+    type Inputs: ?Sized;
+
+    /// Generate the main trace for `inputs`.
+    ///
+    /// # Returns
+    /// A matrix of width [`BaseAir::width`] and a power-of-two height
+    fn generate(&self, inputs: &Self::Inputs) -> RowMajorMatrix<F>;
+}
+
+/// Widths of the aux matrices of every phase of `air`, in phase order.
+pub(crate) fn aux_matrix_widths<F, EF, A>(air: &A) -> Vec<usize>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: AuxTraceBuilder<F, EF>,
+{
+    (0..air.num_aux_phases())
+        .flat_map(|phase| air.aux_phase_matrix_widths(phase))
+        .collect()
+}
+
+/// Phases of `air` with columns, in phase order: each has one aux commitment, the others
+/// only sample challenges.
+pub(crate) fn committed_aux_phases<F, EF, A>(air: &A) -> Vec<usize>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: AuxTraceBuilder<F, EF>,
+{
+    (0..air.num_aux_phases())
+        .filter(|&phase| air.aux_phase_width(phase) > 0)
+        .collect()
+}
+
+/// Base field columns committed for each aux matrix of `phase`.
+pub(crate) fn aux_matrix_committed_widths<F, EF, A>(air: &A, phase: usize) -> Vec<usize>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: AuxTraceBuilder<F, EF>,
+{
+    let per_column = if air.aux_phase_in_base_field(phase) {
+        1
+    } else {
+        EF::DIMENSION
+    };
+    air.aux_phase_matrix_widths(phase)
+        .into_iter()
+        .map(|width| width * per_column)
+        .collect()
+}
+
+/// The committed main trace evaluated over the quotient domain, a coset disjoint from the
+/// trace domain and `2^log_blowup` times larger.
+///
+/// Rows are in the PCS's natural order: for the two-adic PCS, row `i` is the evaluation at
+/// `first_point * g^i` for a generator `g` of the extended subgroup. With either the
+/// two-adic or the circle PCS, the row following row `i` on the trace domain is
+/// [`next_row_index(i)`](Self::next_row_index).
+#[derive(Clone, Debug)]
+pub struct MainTraceLde<F> {
+    /// Log2 of the trace height
+    pub log_trace_height: usize,
+    /// Log2 of the ratio between the extended domain and the trace domain sizes
+    pub log_blowup: usize,
+    /// First point of the extended domain (its coset shift)
+    pub first_point: F,
+    /// Evaluations of every main column, one row per point of the extended domain
+    pub evaluations: RowMajorMatrix<F>,
+}
+
+impl<F> MainTraceLde<F> {
+    /// Number of points in the extended domain.
+    pub const fn size(&self) -> usize {
+        1 << (self.log_trace_height + self.log_blowup)
+    }
+
+    /// Evaluations of the main columns at the `i`-th point of the extended domain.
+    pub fn row(&self, i: usize) -> &[F] {
+        let width = self.evaluations.width;
+        &self.evaluations.values[i * width..(i + 1) * width]
+    }
+
+    /// Index of the point one trace step after point `i`.
+    pub const fn next_row_index(&self, i: usize) -> usize {
+        (i + (1 << self.log_blowup)) % self.size()
+    }
 }
 
 /// Marker trait for AIRs that can be proven with this crate.
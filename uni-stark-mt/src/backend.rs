@@ -0,0 +1,586 @@
+//! Pluggable quotient evaluation
+//!
+//! Evaluating the constraints over the quotient domain dominates proving time. The prover
+//! hands that step to a [`QuotientBackend`], so a GPU or FPGA implementation can replace
+//! [`CpuBackend`] through [`crate::prove_with_backend`] without forking the prover. The
+//! low-degree extensions the backend reads come from the config's PCS, whose DFT is
+//! already pluggable.
+//!
+//! With [`StarkGenericConfig::quotient_block_size`], the backend is handed the quotient
+//! domain one block of points at a time, and each block's quotient values are written
+//! straight into the chunks to commit, see [`quotient_chunks`].
+//!
+//! [`evaluate_quotient`] splits the points into tasks of a few packed values each and runs
+//! them on the prover's worker pool with the `parallel` feature, see
+//! [`StarkGenericConfig::num_threads`].
+//!
+//! The Lagrange selectors reach the backend as [`QuotientSelectors`]. On quotient domains
+//! that can be walked point by point, as two-adic FRI's can, they are computed as the
+//! backend reads them rather than over the whole coset, so their storage does not grow
+//! with the quotient domain.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_air::Air;
+use p3_commit::{LagrangeSelectors, Pcs, PolynomialSpace};
+use p3_field::{BasedVectorSpace, PackedField, PackedValue, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::air::aux_matrix_widths;
+use crate::folder::unpack_challenge;
+use crate::{
+    Challenge, Domain, MultiTraceAir, PackedChallenge, PackedVal, ProverError, ProverFolder,
+    ProverPhase, ProverTelemetry, StarkGenericConfig, Val,
+};
+
+/// Everything the quotient evaluation reads, for one table.
+pub struct QuotientInputs<'a, SC: StarkGenericConfig, M> {
+    /// Domain of the trace, without the zero-knowledge rows
+    pub trace_domain: Domain<SC>,
+    /// Domain the quotient is evaluated on
+    pub quotient_domain: Domain<SC>,
+    /// Indices of the points of `quotient_domain` to evaluate at: the whole domain, or one
+    /// block of it under [`StarkGenericConfig::quotient_block_size`]
+    pub rows: Range<usize>,
+    /// Main trace evaluations on the quotient domain
+    pub main_on_quotient: &'a M,
+    /// Preprocessed trace evaluations on the quotient domain, if the AIR has any
+    pub preprocessed_on_quotient: Option<&'a M>,
+    /// Evaluations of each auxiliary matrix on the quotient domain, phase after phase,
+    /// flattened to the base field as committed: each group of `Challenge::DIMENSION`
+    /// columns is one extension column, except in phases declared over the base field, see
+    /// [`crate::AuxTraceBuilder::aux_phase_in_base_field`]
+    pub aux_on_quotient: &'a [M],
+    /// Selectors of the trace domain at every point of `rows`, in order
+    pub selectors: QuotientSelectors<SC>,
+    /// Challenges of all auxiliary phases, concatenated in phase order
+    pub aux_challenges: &'a [Challenge<SC>],
+    /// Values exposed by all auxiliary phases, concatenated in phase order
+    pub aux_exposed_values: &'a [Challenge<SC>],
+    /// Coefficient of each constraint in the folded constraint polynomial
+    pub alpha_powers: &'a [Challenge<SC>],
+    /// Public input/output values
+    pub public_values: &'a [Val<SC>],
+}
+
+/// Lagrange selectors of the trace domain at consecutive points of the quotient domain.
+///
+/// Reads them point by point as an iterator, or a packed value at a time through
+/// [`QuotientSelectors::next_packed`].
+pub struct QuotientSelectors<SC: StarkGenericConfig> {
+    source: SelectorSource<SC>,
+    remaining: usize,
+    /// Selectors of the packed value being assembled, one per lane
+    lanes: LagrangeSelectors<Vec<Val<SC>>>,
+}
+
+enum SelectorSource<SC: StarkGenericConfig> {
+    /// Computed at `point` and at the points after it, as they are read
+    Points {
+        trace_domain: Domain<SC>,
+        quotient_domain: Domain<SC>,
+        point: Val<SC>,
+    },
+    /// Read from the selectors of the whole quotient domain, from point `next` on
+    Coset {
+        selectors: LagrangeSelectors<Vec<Val<SC>>>,
+        next: usize,
+    },
+}
+
+impl<SC: StarkGenericConfig> QuotientSelectors<SC> {
+    /// The selectors at `len` points of `quotient_domain`, from `point` on in
+    /// [`PolynomialSpace::next_point`] order, each computed when it is read.
+    pub fn from_point(
+        trace_domain: Domain<SC>,
+        quotient_domain: Domain<SC>,
+        point: Val<SC>,
+        len: usize,
+    ) -> Self {
+        Self::new(
+            SelectorSource::Points {
+                trace_domain,
+                quotient_domain,
+                point,
+            },
+            len,
+        )
+    }
+
+    /// The selectors at every point of `quotient_domain`, computed up front over the
+    /// coset, for domains whose points do not come in `next_point` order.
+    pub fn on_coset(trace_domain: Domain<SC>, quotient_domain: Domain<SC>) -> Self {
+        Self::new(
+            SelectorSource::Coset {
+                selectors: trace_domain.selectors_on_coset(quotient_domain),
+                next: 0,
+            },
+            quotient_domain.size(),
+        )
+    }
+
+    fn new(source: SelectorSource<SC>, len: usize) -> Self {
+        let width = PackedVal::<SC>::WIDTH;
+        Self {
+            source,
+            remaining: len,
+            lanes: LagrangeSelectors {
+                is_first_row: Val::<SC>::zero_vec(width),
+                is_last_row: Val::<SC>::zero_vec(width),
+                is_transition: Val::<SC>::zero_vec(width),
+                inv_vanishing: Val::<SC>::zero_vec(width),
+            },
+        }
+    }
+
+    /// The selectors at the next `PackedVal::<SC>::WIDTH` points, one point per lane; the
+    /// lanes past the last point are zero.
+    pub fn next_packed(&mut self) -> LagrangeSelectors<PackedVal<SC>> {
+        for lane in 0..PackedVal::<SC>::WIDTH {
+            let at_point = self.next().unwrap_or(LagrangeSelectors {
+                is_first_row: Val::<SC>::ZERO,
+                is_last_row: Val::<SC>::ZERO,
+                is_transition: Val::<SC>::ZERO,
+                inv_vanishing: Val::<SC>::ZERO,
+            });
+            self.lanes.is_first_row[lane] = at_point.is_first_row;
+            self.lanes.is_last_row[lane] = at_point.is_last_row;
+            self.lanes.is_transition[lane] = at_point.is_transition;
+            self.lanes.inv_vanishing[lane] = at_point.inv_vanishing;
+        }
+        LagrangeSelectors {
+            is_first_row: *PackedVal::<SC>::from_slice(&self.lanes.is_first_row),
+            is_last_row: *PackedVal::<SC>::from_slice(&self.lanes.is_last_row),
+            is_transition: *PackedVal::<SC>::from_slice(&self.lanes.is_transition),
+            inv_vanishing: *PackedVal::<SC>::from_slice(&self.lanes.inv_vanishing),
+        }
+    }
+}
+
+impl<SC: StarkGenericConfig> Iterator for QuotientSelectors<SC> {
+    type Item = LagrangeSelectors<Val<SC>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(match &mut self.source {
+            SelectorSource::Points {
+                trace_domain,
+                quotient_domain,
+                point,
+            } => {
+                let at_point = trace_domain.selectors_at_point(*point);
+                *point = quotient_domain.next_point(*point).unwrap_or(*point);
+                at_point
+            }
+            SelectorSource::Coset { selectors, next } => {
+                let i = *next;
+                *next += 1;
+                LagrangeSelectors {
+                    is_first_row: selectors.is_first_row[i],
+                    is_last_row: selectors.is_last_row[i],
+                    is_transition: selectors.is_transition[i],
+                    inv_vanishing: selectors.inv_vanishing[i],
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<SC: StarkGenericConfig> ExactSizeIterator for QuotientSelectors<SC> {}
+
+/// Computes the quotient of the folded constraints by the vanishing polynomial.
+///
+/// The prover calls the backend from its worker pool, hence the `Sync` bound.
+pub trait QuotientBackend<SC: StarkGenericConfig>: Sync {
+    /// The quotient at the points `inputs.rows` of `inputs.quotient_domain`, in domain
+    /// order.
+    ///
+    /// Progress through the domain may be reported to `telemetry` as
+    /// [`ProverPhase::Quotient`].
+    fn quotient_values<A, M>(
+        &self,
+        air: &A,
+        inputs: QuotientInputs<'_, SC, M>,
+        telemetry: &mut dyn ProverTelemetry,
+    ) -> Vec<Challenge<SC>>
+    where
+        Val<SC>: PackedField,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<ProverFolder<'a, SC>>,
+        M: Matrix<Val<SC>> + Sync;
+}
+
+/// The default backend, evaluating the AIR on the CPU.
+///
+/// Each evaluation of the AIR covers `PackedVal::<SC>::WIDTH` consecutive points, one per
+/// lane.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl<SC: StarkGenericConfig> QuotientBackend<SC> for CpuBackend {
+    #[instrument(skip_all)]
+    fn quotient_values<A, M>(
+        &self,
+        air: &A,
+        inputs: QuotientInputs<'_, SC, M>,
+        telemetry: &mut dyn ProverTelemetry,
+    ) -> Vec<Challenge<SC>>
+    where
+        Val<SC>: PackedField,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<ProverFolder<'a, SC>>,
+        M: Matrix<Val<SC>> + Sync,
+    {
+        evaluate_quotient(air, inputs, telemetry, || (), |folder, ()| air.eval(folder))
+    }
+}
+
+/// Packed values of quotient points evaluated by one task
+const TASK_LEN: usize = 64;
+
+/// Tasks whose selectors are read ahead at once, bounding the selectors held in memory
+const TASKS_PER_BATCH: usize = 64;
+
+/// The quotient at the points `inputs.rows`, `PackedVal::<SC>::WIDTH` points at a time.
+///
+/// `fold` sets the accumulator of a [`ProverFolder`] holding the trace rows, selectors and
+/// challenges of those points, one point per lane, to the folded constraints there. The
+/// points are evaluated in parallel, [`TASK_LEN`] packed values per task, and each task
+/// gets its own `scratch` for `fold` to reuse from point to point.
+pub(crate) fn evaluate_quotient<SC, A, M, S>(
+    air: &A,
+    inputs: QuotientInputs<'_, SC, M>,
+    telemetry: &mut dyn ProverTelemetry,
+    scratch: impl Fn() -> S + Sync,
+    fold: impl Fn(&mut ProverFolder<'_, SC>, &mut S) + Sync,
+) -> Vec<Challenge<SC>>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+    M: Matrix<Val<SC>> + Sync,
+{
+    let QuotientInputs {
+        trace_domain,
+        quotient_domain,
+        rows,
+        main_on_quotient,
+        preprocessed_on_quotient,
+        aux_on_quotient,
+        mut selectors,
+        aux_challenges,
+        aux_exposed_values,
+        alpha_powers,
+        public_values,
+    } = inputs;
+    let quotient_size = quotient_domain.size();
+    let num_points = rows.len();
+    let width_main = main_on_quotient.width();
+    let width_preprocessed = preprocessed_on_quotient.map_or(0, |pp| pp.width());
+    let width_aux = air.total_aux_width();
+    let packing_width = PackedVal::<SC>::WIDTH;
+    let aux_matrix_widths = aux_matrix_widths(air);
+    let base_field_aux: Vec<bool> = (0..air.num_aux_phases())
+        .flat_map(|phase| {
+            let base_field = air.aux_phase_in_base_field(phase);
+            air.aux_phase_matrix_widths(phase)
+                .into_iter()
+                .map(move |_| base_field)
+        })
+        .collect();
+
+    // Calculate step size between consecutive trace points in quotient domain LDE
+    // quotient_domain is quotient_degree times larger than trace_domain
+    let log_quotient_degree =
+        p3_util::log2_strict_usize(quotient_size) - p3_util::log2_strict_usize(trace_domain.size());
+    let next_step = 1 << log_quotient_degree;
+
+    // Local and next rows of the main, preprocessed and aux traces at the points
+    // `i_start..i_start + WIDTH`, one point per lane
+    // Next row is next_step away, not just i+1, because quotient domain LDE
+    // interleaves trace points with intermediate evaluation points
+    let trace_rows = |i_start: usize| {
+        let main_view = RowMajorMatrix::new(
+            main_on_quotient.vertically_packed_row_pair(i_start, next_step),
+            width_main,
+        );
+
+        let preprocessed_view = RowMajorMatrix::new(
+            preprocessed_on_quotient.map_or_else(Vec::new, |pp| {
+                pp.vertically_packed_row_pair(i_start, next_step)
+            }),
+            width_preprocessed,
+        );
+
+        let aux_pairs: Vec<Vec<PackedVal<SC>>> = aux_on_quotient
+            .iter()
+            .map(|aux| aux.vertically_packed_row_pair(i_start, next_step))
+            .collect();
+        let aux_values: Vec<PackedChallenge<SC>> = [0, 1]
+            .into_iter()
+            .flat_map(|row| {
+                aux_pairs
+                    .iter()
+                    .zip(&base_field_aux)
+                    .flat_map(move |(pair, &base_field)| {
+                        let width = pair.len() / 2;
+                        let coords_per_column = if base_field {
+                            1
+                        } else {
+                            Challenge::<SC>::DIMENSION
+                        };
+                        pair[row * width..(row + 1) * width]
+                            .chunks_exact(coords_per_column)
+                            .map(move |coords| {
+                                if base_field {
+                                    PackedChallenge::<SC>::from(coords[0])
+                                } else {
+                                    PackedChallenge::<SC>::from_basis_coefficients_fn(|k| coords[k])
+                                }
+                            })
+                    })
+            })
+            .collect();
+        let aux_view = RowMajorMatrix::new(aux_values, width_aux);
+
+        (main_view, preprocessed_view, aux_view)
+    };
+
+    // The challenges and exposed values are the same in every lane
+    let aux_challenges: Vec<PackedChallenge<SC>> = aux_challenges
+        .iter()
+        .map(|&challenge| PackedChallenge::<SC>::from(challenge))
+        .collect();
+    let aux_exposed_values: Vec<PackedChallenge<SC>> = aux_exposed_values
+        .iter()
+        .map(|&value| PackedChallenge::<SC>::from(value))
+        .collect();
+
+    // The quotient at the WIDTH points from `i_start` on, one point per lane
+    let evaluate = |i_start: usize,
+                    selectors: &LagrangeSelectors<PackedVal<SC>>,
+                    scratch: &mut S|
+     -> PackedChallenge<SC> {
+        let (main_view, preprocessed_view, aux_view) = trace_rows(i_start);
+        let mut folder = ProverFolder {
+            main: main_view.as_view(),
+            preprocessed: preprocessed_view.as_view(),
+            aux: aux_view.as_view(),
+            aux_matrix_widths: &aux_matrix_widths,
+            public_values,
+            aux_challenges: &aux_challenges,
+            aux_exposed_values: &aux_exposed_values,
+            is_first_row: selectors.is_first_row,
+            is_last_row: selectors.is_last_row,
+            is_transition: selectors.is_transition,
+            alpha_powers,
+            accumulator: PackedChallenge::<SC>::ZERO,
+            constraint_index: 0,
+        };
+        fold(&mut folder, scratch);
+
+        // quotient(x) = constraints(x) / Z_H(x)
+        folder.accumulator * selectors.inv_vanishing
+    };
+
+    let num_packed = num_points.div_ceil(packing_width);
+    let mut quotient_values = Vec::with_capacity(num_packed * packing_width);
+    // Report progress about sixteen times over the domain, at batch boundaries
+    let report_every = (quotient_size / 16).max(1);
+    let batch_len = TASK_LEN * TASKS_PER_BATCH;
+    for batch_start in (0..num_packed).step_by(batch_len) {
+        // The selectors come in domain order, so they are read ahead of the tasks
+        let batch: Vec<LagrangeSelectors<PackedVal<SC>>> = (batch_start
+            ..(batch_start + batch_len).min(num_packed))
+            .map(|_| selectors.next_packed())
+            .collect();
+        let tasks: Vec<Vec<Challenge<SC>>> = (0..batch.len().div_ceil(TASK_LEN))
+            .into_par_iter()
+            .map(|task| {
+                let mut scratch = scratch();
+                let first = task * TASK_LEN;
+                batch[first..(first + TASK_LEN).min(batch.len())]
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(k, selectors)| {
+                        let i_start = rows.start + (batch_start + first + k) * packing_width;
+                        let quotient = evaluate(i_start, selectors, &mut scratch);
+                        (0..packing_width).map(move |lane| unpack_challenge::<SC>(quotient, lane))
+                    })
+                    .collect()
+            })
+            .collect();
+        quotient_values.extend(tasks.into_iter().flatten());
+
+        let (start, done) = (
+            rows.start + batch_start * packing_width,
+            (rows.start + quotient_values.len()).min(rows.end),
+        );
+        if done / report_every > start / report_every {
+            telemetry.progress(ProverPhase::Quotient, done);
+        }
+    }
+
+    // Drop the padding lanes
+    quotient_values.truncate(num_points);
+    quotient_values
+}
+
+/// The quotient split into `num_chunks` chunks flattened to the base field, as committed.
+///
+/// `inputs` completes the inputs of `backend` for the points `rows`, given their
+/// selectors. Without a `block_size`, the backend evaluates the whole domain at once and
+/// the values are split by [`PolynomialSpace::split_evals`]. With one, it evaluates
+/// `block_size` points at a time and each value is written into its chunk directly, which
+/// takes a domain that [`check_quotient_blocks`] accepts.
+///
+/// The selectors are computed point by point as the backend reads them when
+/// `points_in_order`, as [`quotient_points_in_order`] finds for the config, and over the
+/// whole coset otherwise.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn quotient_chunks<'a, SC, A, M, B>(
+    backend: &B,
+    air: &A,
+    trace_domain: Domain<SC>,
+    quotient_domain: Domain<SC>,
+    num_chunks: usize,
+    block_size: Option<usize>,
+    points_in_order: bool,
+    inputs: impl Fn(Range<usize>, QuotientSelectors<SC>) -> QuotientInputs<'a, SC, M>,
+    telemetry: &mut dyn ProverTelemetry,
+) -> Vec<RowMajorMatrix<Val<SC>>>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'b> Air<ProverFolder<'b, SC>>,
+    M: Matrix<Val<SC>> + Sync + 'a,
+    B: QuotientBackend<SC>,
+{
+    let quotient_size = quotient_domain.size();
+    let Some(block_size) = block_size else {
+        let selectors = if points_in_order {
+            QuotientSelectors::from_point(
+                trace_domain,
+                quotient_domain,
+                quotient_domain.first_point(),
+                quotient_size,
+            )
+        } else {
+            QuotientSelectors::on_coset(trace_domain, quotient_domain)
+        };
+        let values = backend.quotient_values(air, inputs(0..quotient_size, selectors), telemetry);
+        let flat = RowMajorMatrix::new_col(values).flatten_to_base();
+        return quotient_domain.split_evals(num_chunks, flat);
+    };
+
+    // Point `i` of the domain is row `i / num_chunks` of chunk `i % num_chunks`
+    let dimension = Challenge::<SC>::DIMENSION;
+    let chunk_len = quotient_size / num_chunks * dimension;
+    let mut chunks: Vec<Vec<Val<SC>>> = (0..num_chunks)
+        .map(|_| Val::<SC>::zero_vec(chunk_len))
+        .collect();
+    let mut point = quotient_domain.first_point();
+    for start in (0..quotient_size).step_by(block_size) {
+        let rows = start..(start + block_size).min(quotient_size);
+        let selectors =
+            QuotientSelectors::from_point(trace_domain, quotient_domain, point, rows.len());
+        for _ in rows.clone() {
+            point = quotient_domain.next_point(point).unwrap_or(point);
+        }
+
+        let values = backend.quotient_values(air, inputs(rows.clone(), selectors), telemetry);
+        for (i, value) in rows.zip(values) {
+            let row = i / num_chunks * dimension;
+            chunks[i % num_chunks][row..row + dimension]
+                .copy_from_slice(value.as_basis_coefficients_slice());
+        }
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| RowMajorMatrix::new(chunk, dimension))
+        .collect()
+}
+
+/// A small trace domain of the config's PCS and a quotient domain built from it like the
+/// prover's, `quotient_degree` times larger than the trace domain committed `1 << zk`
+/// times taller.
+///
+/// How a domain orders its points is a property of its kind, so the prover checks it on
+/// these rather than on its own domains.
+fn probe_domains<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_degree: usize,
+) -> (Domain<SC>, Domain<SC>) {
+    const PROBE_HEIGHT: usize = 4;
+    let pcs = config.pcs();
+    let zk = config.is_zk();
+    let trace_domain = pcs.natural_domain_for_degree(PROBE_HEIGHT);
+    let quotient_domain = pcs
+        .natural_domain_for_degree(PROBE_HEIGHT << zk)
+        .create_disjoint_domain((PROBE_HEIGHT << zk) * quotient_degree);
+    (trace_domain, quotient_domain)
+}
+
+/// Whether the points of the quotient domains of the config's PCS come in
+/// [`PolynomialSpace::next_point`] order, so that [`QuotientSelectors::from_point`] can
+/// walk them.
+pub(crate) fn quotient_points_in_order<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_degree: usize,
+) -> bool {
+    let (trace_domain, quotient_domain) = probe_domains(config, quotient_degree);
+    let selectors = trace_domain.selectors_on_coset(quotient_domain);
+    let mut point = quotient_domain.first_point();
+    (0..quotient_domain.size()).all(|i| {
+        let at_point = trace_domain.selectors_at_point(point);
+        let matches = at_point.is_first_row == selectors.is_first_row[i]
+            && at_point.is_last_row == selectors.is_last_row[i]
+            && at_point.is_transition == selectors.is_transition[i]
+            && at_point.inv_vanishing == selectors.inv_vanishing[i];
+        point = quotient_domain.next_point(point).unwrap_or(point);
+        matches
+    })
+}
+
+/// Check that quotient domains of the config's PCS can be evaluated in blocks by
+/// [`quotient_chunks`]: their points must come in [`PolynomialSpace::next_point`] order and
+/// their chunks must take every `num_chunks`-th point.
+pub(crate) fn check_quotient_blocks<SC: StarkGenericConfig>(
+    config: &SC,
+    quotient_degree: usize,
+    num_chunks: usize,
+) -> Result<(), ProverError> {
+    let (_, quotient_domain) = probe_domains(config, quotient_degree);
+    let quotient_size = quotient_domain.size();
+    let points_in_order = quotient_points_in_order(config, quotient_degree);
+
+    let indices = RowMajorMatrix::new_col((0..quotient_size).map(Val::<SC>::from_usize).collect());
+    let chunks_by_stride = quotient_domain
+        .split_evals(num_chunks, indices)
+        .iter()
+        .enumerate()
+        .all(|(chunk, values)| {
+            values
+                .values
+                .iter()
+                .enumerate()
+                .all(|(row, &index)| index == Val::<SC>::from_usize(row * num_chunks + chunk))
+        });
+
+    if points_in_order && chunks_by_stride {
+        Ok(())
+    } else {
+        Err(ProverError::UnsupportedConfig(
+            "the PCS's quotient domains cannot be evaluated in blocks",
+        ))
+    }
+}
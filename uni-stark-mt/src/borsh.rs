@@ -0,0 +1,734 @@
+//! Borsh encoding of proofs and verifying keys
+//!
+//! [`Proof`] and [`VerifyingKey`] implement [`BorshSerialize`] and [`BorshDeserialize`], so
+//! `borsh::to_vec(&proof)` and `borsh::from_slice::<Proof<SC>>(&bytes)` work as for any
+//! other Borsh type. The Plonky3 types inside them (commitments, field elements, opening
+//! proofs) only implement serde, so the encoding walks the serde data model and writes it
+//! in the Borsh layout: little-endian integers, `u32` length prefixes, one-byte option tags
+//! and `u8` enum variant indices, with struct fields in declaration order.
+//!
+//! [`proof_from_borsh`] decodes untrusted proofs within [`ProofLimits`], like
+//! [`ProofLimits::proof_seed`] does for serde formats.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use ::borsh::io::{Error, ErrorKind, Read, Write};
+use ::borsh::{BorshDeserialize, BorshSerialize};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::{Proof, ProofLimits, StarkGenericConfig, VerifyingKey};
+
+impl<SC: StarkGenericConfig> BorshSerialize for Proof<SC> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        to_writer(self, writer)
+    }
+}
+
+impl<SC: StarkGenericConfig> BorshDeserialize for Proof<SC> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        from_reader(reader)
+    }
+}
+
+impl<SC: StarkGenericConfig> BorshSerialize for VerifyingKey<SC> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        to_writer(self, writer)
+    }
+}
+
+impl<SC: StarkGenericConfig> BorshDeserialize for VerifyingKey<SC> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        from_reader(reader)
+    }
+}
+
+/// Decode a Borsh-encoded proof from `bytes`, rejecting one exceeding `limits` or followed
+/// by trailing bytes.
+pub fn proof_from_borsh<SC: StarkGenericConfig>(
+    mut bytes: &[u8],
+    limits: ProofLimits,
+) -> Result<Proof<SC>, Error> {
+    let mut reader = BorshReader { reader: &mut bytes };
+    let proof = limits
+        .proof_seed::<SC>()
+        .deserialize(&mut reader)
+        .map_err(CodecError::into_io)?;
+    if !bytes.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "trailing bytes after the proof",
+        ));
+    }
+    Ok(proof)
+}
+
+pub(crate) fn to_writer<T: Serialize + ?Sized, W: Write>(
+    value: &T,
+    writer: &mut W,
+) -> Result<(), Error> {
+    value
+        .serialize(&mut BorshWriter { writer })
+        .map_err(CodecError::into_io)
+}
+
+pub(crate) fn from_reader<T: DeserializeOwned, R: Read>(reader: &mut R) -> Result<T, Error> {
+    T::deserialize(&mut BorshReader { reader }).map_err(CodecError::into_io)
+}
+
+/// Why a value could not be encoded or decoded.
+#[derive(Debug)]
+enum CodecError {
+    /// The writer or reader failed, or the input ended early
+    Io(Error),
+    /// The value has no Borsh encoding, or the input encodes no valid value
+    Invalid(String),
+}
+
+impl CodecError {
+    fn into_io(self) -> Error {
+        match self {
+            Self::Io(error) => error,
+            Self::Invalid(_) => Error::new(ErrorKind::InvalidData, "invalid Borsh encoding"),
+        }
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::Invalid(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl core::error::Error for CodecError {}
+
+impl ser::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Invalid(msg.to_string())
+    }
+}
+
+impl de::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Invalid(msg.to_string())
+    }
+}
+
+fn invalid(msg: &str) -> CodecError {
+    CodecError::Invalid(msg.to_string())
+}
+
+/// A [`ser::Serializer`] writing the Borsh layout to `writer`.
+struct BorshWriter<'a, W> {
+    writer: &'a mut W,
+}
+
+impl<W: Write> BorshWriter<'_, W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), CodecError> {
+        self.writer.write_all(bytes).map_err(CodecError::Io)
+    }
+
+    fn len(&mut self, len: usize) -> Result<(), CodecError> {
+        let len = u32::try_from(len).map_err(|_| invalid("length does not fit a u32"))?;
+        self.write(&len.to_le_bytes())
+    }
+
+    fn variant(&mut self, index: u32) -> Result<(), CodecError> {
+        let index = u8::try_from(index).map_err(|_| invalid("variant index does not fit a u8"))?;
+        self.write(&[index])
+    }
+}
+
+impl<W: Write> ser::Serializer for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), CodecError> {
+        self.write(&[u8::from(v)])
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CodecError> {
+        self.write(&[v])
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CodecError> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CodecError> {
+        self.write(&u32::from(v).to_le_bytes())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CodecError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CodecError> {
+        self.len(v.len())?;
+        self.write(v)
+    }
+
+    fn serialize_none(self) -> Result<(), CodecError> {
+        self.write(&[0])
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CodecError> {
+        self.write(&[1])?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+    ) -> Result<(), CodecError> {
+        self.variant(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        self.variant(index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.len(len.ok_or_else(|| invalid("sequence of unknown length"))?)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, CodecError> {
+        self.variant(index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, CodecError> {
+        self.len(len.ok_or_else(|| invalid("map of unknown length"))?)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, CodecError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, CodecError> {
+        self.variant(index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeMap for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CodecError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for &mut BorshWriter<'_, W> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), CodecError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+/// Bytes read at once for strings and byte buffers, so that a forged length prefix cannot
+/// make the decoder allocate more than the input holds.
+const CHUNK: usize = 4096;
+
+/// A [`de::Deserializer`] reading the Borsh layout from `reader`.
+///
+/// Borsh is not self-describing, so values are decoded as the type being deserialized asks
+/// for them; `deserialize_any` is unsupported.
+struct BorshReader<'a, R> {
+    reader: &'a mut R,
+}
+
+impl<R: Read> BorshReader<'_, R> {
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], CodecError> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes).map_err(CodecError::Io)?;
+        Ok(bytes)
+    }
+
+    fn len(&mut self) -> Result<usize, CodecError> {
+        usize::try_from(u32::from_le_bytes(self.read()?))
+            .map_err(|_| invalid("length does not fit a usize"))
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>, CodecError> {
+        let len = self.len()?;
+        let mut bytes = Vec::with_capacity(len.min(CHUNK));
+        while bytes.len() < len {
+            let start = bytes.len();
+            bytes.resize(len.min(start + CHUNK), 0);
+            self.reader
+                .read_exact(&mut bytes[start..])
+                .map_err(CodecError::Io)?;
+        }
+        Ok(bytes)
+    }
+}
+
+impl<'de, R: Read> de::Deserializer<'de> for &mut BorshReader<'_, R> {
+    type Error = CodecError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(invalid("Borsh is not self-describing"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.read::<1>()? {
+            [0] => visitor.visit_bool(false),
+            [1] => visitor.visit_bool(true),
+            _ => Err(invalid("invalid bool")),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i8(i8::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i16(i16::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i32(i32::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i64(i64::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_i128(i128::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u8(u8::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u16(u16::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u32(u32::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u64(u64::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_u128(u128::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f32(f32::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_f64(f64::from_le_bytes(self.read()?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let c = char::from_u32(u32::from_le_bytes(self.read()?))
+            .ok_or_else(|| invalid("invalid char"))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let string = String::from_utf8(self.bytes()?).map_err(|_| invalid("invalid UTF-8"))?;
+        visitor.visit_string(string)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_byte_buf(self.bytes()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        match self.read::<1>()? {
+            [0] => visitor.visit_none(),
+            [1] => visitor.visit_some(self),
+            _ => Err(invalid("invalid option tag")),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let remaining = self.len()?;
+        visitor.visit_seq(Counted {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(Counted {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let remaining = self.len()?;
+        visitor.visit_map(Counted {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(invalid("Borsh encodes no identifiers"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(invalid("Borsh is not self-describing"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<'de, R: Read> de::EnumAccess<'de> for &mut BorshReader<'_, R> {
+    type Error = CodecError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), CodecError> {
+        let [index] = self.read::<1>()?;
+        let index: de::value::U32Deserializer<CodecError> = u32::from(index).into_deserializer();
+        let variant = seed.deserialize(index)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, R: Read> de::VariantAccess<'de> for &mut BorshReader<'_, R> {
+    type Error = CodecError;
+
+    fn unit_variant(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, CodecError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, CodecError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+}
+
+/// The `remaining` elements of a sequence, tuple or map.
+struct Counted<'a, 'b, R> {
+    de: &'a mut BorshReader<'b, R>,
+    remaining: usize,
+}
+
+impl<'de, R: Read> de::SeqAccess<'de> for Counted<'_, '_, R> {
+    type Error = CodecError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, CodecError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R: Read> de::MapAccess<'de> for Counted<'_, '_, R> {
+    type Error = CodecError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, CodecError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, CodecError> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
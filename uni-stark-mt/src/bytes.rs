@@ -0,0 +1,194 @@
+//! Byte operation lookup chip
+//!
+//! [`ByteOpChip`] is a table of every `(a, b, a op b)` triple over bytes for one bitwise
+//! [`ByteOp`], committed once as preprocessed columns, with a main column counting how often
+//! each triple is looked up. Like [`crate::RangeCheckChip`], it receives the triples on a
+//! bus: chips computing XOR, AND or OR send their operand and result columns there with
+//! [`ByteOpChip::register`] and fill in the counts with a [`ByteOpCounts`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::interaction::{build_table_receives, eval_table_receives};
+use crate::{AuxBuilder, AuxTraceBuilder, InteractionBuilder, Multiplicity};
+
+/// A bitwise operation on bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOp {
+    /// `a ^ b`
+    Xor,
+    /// `a & b`
+    And,
+    /// `a | b`
+    Or,
+}
+
+impl ByteOp {
+    /// `a op b`
+    pub const fn apply(self, a: u8, b: u8) -> u8 {
+        match self {
+            Self::Xor => a ^ b,
+            Self::And => a & b,
+            Self::Or => a | b,
+        }
+    }
+}
+
+/// A lookup table of `(a, b, a op b)` for all `a` and `b` below `2^bits`, receiving on `bus`
+/// the triples other AIRs look up.
+///
+/// The table has a row per `(a, b)`, at index `a·2^bits + b`. The main trace is one
+/// column: the multiplicity of each row. The aux trace is one phase with the LogUp helper
+/// `m / (α - (bus + β·a + β²·b + β³·c))` and its running sum, whose last value is exposed as
+/// the chip's net [`interaction_sum`](crate::AuxTraceBuilder::interaction_sum). The chip
+/// draws the two challenges of [`crate::BusInteractions`], so the AIRs sending to it use
+/// that gadget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteOpChip {
+    /// The operation tabulated
+    pub op: ByteOp,
+    /// Operands range over `0..2^bits`; 8 for bytes, fewer for smaller tables, never more
+    pub bits: usize,
+    /// The bus triples are received on
+    pub bus: usize,
+}
+
+impl ByteOpChip {
+    /// The table of `op` over bytes.
+    pub const fn new(op: ByteOp, bus: usize) -> Self {
+        Self { op, bits: 8, bus }
+    }
+
+    /// Number of table rows, which is also the height of the chip's main trace.
+    pub const fn height(&self) -> usize {
+        1 << (2 * self.bits)
+    }
+
+    /// Send the triple in columns `a`, `b` and `result` to this chip once per row, checking
+    /// that `result = a op b` with both operands in range.
+    pub fn register(
+        &self,
+        interactions: InteractionBuilder,
+        a: usize,
+        b: usize,
+        result: usize,
+    ) -> InteractionBuilder {
+        interactions.send(self.bus, vec![a, b, result], Multiplicity::One)
+    }
+
+    /// The chip's net sum: the running sum on the last row of its aux trace.
+    pub fn cumulative_sum<EF: Field>(&self, aux_trace: &RowMajorMatrix<EF>) -> EF {
+        aux_trace.values[aux_trace.values.len() - 1]
+    }
+}
+
+impl<F: Field> BaseAir<F> for ByteOpChip {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        let operands = (0..1u16 << self.bits).map(|operand| operand as u8);
+        let values = operands
+            .clone()
+            .flat_map(|a| operands.clone().map(move |b| (a, b)))
+            .flat_map(|(a, b)| [a, b, self.op.apply(a, b)].map(F::from_u8))
+            .collect();
+        Some(RowMajorMatrix::new(values, 3))
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ByteOpChip {
+    fn aux_width(&self) -> usize {
+        2
+    }
+
+    fn num_challenges(&self) -> usize {
+        crate::BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let table = BaseAir::<F>::preprocessed_trace(self).expect("the chip has a table");
+        build_table_receives(self.bus, &table, &main.values, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        vec![self.cumulative_sum(aux_phase)]
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for ByteOpChip {
+    fn eval(&self, builder: &mut AB) {
+        eval_table_receives(builder, self.bus);
+    }
+}
+
+/// Counts the triples an execution looks up, to fill in the main trace of a
+/// [`ByteOpChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteOpCounts {
+    bits: usize,
+    counts: Vec<u32>,
+}
+
+impl ByteOpCounts {
+    /// No lookups yet, for `chip`.
+    pub fn new(chip: &ByteOpChip) -> Self {
+        Self {
+            bits: chip.bits,
+            counts: vec![0; chip.height()],
+        }
+    }
+
+    /// Count one lookup of `(a, b, a op b)`.
+    ///
+    /// # Panics
+    /// If an operand is out of range: the proof could not balance
+    pub fn record(&mut self, a: u64, b: u64) {
+        assert!(
+            a >> self.bits == 0 && b >> self.bits == 0,
+            "({a}, {b}) is out of the byte operation table"
+        );
+        self.counts[((a << self.bits) | b) as usize] += 1;
+    }
+
+    /// Count a lookup of every row of `trace`, as [`ByteOpChip::register`] sends columns
+    /// `a` and `b`.
+    pub fn record_columns<F: PrimeField64>(
+        &mut self,
+        trace: &RowMajorMatrix<F>,
+        a: usize,
+        b: usize,
+    ) {
+        for row in trace.values.chunks_exact(trace.width) {
+            self.record(row[a].as_canonical_u64(), row[b].as_canonical_u64());
+        }
+    }
+
+    /// The main trace of the chip: one multiplicity per table row.
+    pub fn trace<F: Field>(&self) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(
+            self.counts
+                .iter()
+                .map(|&count| F::from_u32(count))
+                .collect(),
+        )
+    }
+}
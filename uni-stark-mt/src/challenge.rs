@@ -0,0 +1,157 @@
+//! Named aux challenges
+//!
+//! A [`ChallengePlan`] declares the challenges each aux phase samples as named groups, e.g.
+//! `lookup: {alpha, beta}` then `permutation: {gamma}`. The prover and the verifier sample
+//! the challenges of a phase in the order of its groups, so aux builders and constraints
+//! can find a challenge by name instead of by its position in the flat challenge list.
+
+use alloc::vec::Vec;
+
+/// Challenges sampled together under one name, in sampling order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChallengeGroup {
+    /// Name of the group, e.g. `"lookup"`
+    pub name: &'static str,
+    /// Name of each challenge of the group, e.g. `["alpha", "beta"]`
+    pub challenges: Vec<&'static str>,
+}
+
+/// The challenges every aux phase samples, as named groups.
+///
+/// Returned by [`crate::AuxTraceBuilder::challenge_plan`]. Challenges are numbered in phase
+/// order, then group order within a phase, then challenge order within a group: this is
+/// both the order they are sampled in and their position in
+/// [`AuxBuilder::aux_challenges`](crate::AuxBuilder::aux_challenges).
+///
+/// ```ignore
+/// let plan = ChallengePlan::new()
+///     .with_group(0, "lookup", &["alpha", "beta"])
+///     .with_group(1, "permutation", &["gamma"]);
+/// assert_eq!(plan.index("permutation", "gamma"), Some(2));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChallengePlan {
+    phases: Vec<Vec<ChallengeGroup>>,
+}
+
+impl ChallengePlan {
+    /// A plan sampling no challenges.
+    pub const fn new() -> Self {
+        Self { phases: Vec::new() }
+    }
+
+    /// A plan sampling `num_challenges` unnamed challenges in phase 0, as declared through
+    /// [`crate::AuxTraceBuilder::num_challenges`].
+    ///
+    /// They form one group named `""`, whose challenges are also named `""`: look them up
+    /// by position.
+    pub fn unnamed(num_challenges: usize) -> Self {
+        if num_challenges == 0 {
+            return Self::new();
+        }
+        Self::new().with_group(0, "", &alloc::vec![""; num_challenges])
+    }
+
+    /// Add the group `name` with `challenges` after the groups already declared for `phase`.
+    pub fn with_group(
+        mut self,
+        phase: usize,
+        name: &'static str,
+        challenges: &[&'static str],
+    ) -> Self {
+        if self.phases.len() <= phase {
+            self.phases.resize(phase + 1, Vec::new());
+        }
+        self.phases[phase].push(ChallengeGroup {
+            name,
+            challenges: challenges.to_vec(),
+        });
+        self
+    }
+
+    /// Number of phases the plan declares groups for, up to the last with any.
+    pub fn num_phases(&self) -> usize {
+        self.phases.len()
+    }
+
+    /// Groups sampled in `phase`, in sampling order.
+    pub fn groups(&self, phase: usize) -> &[ChallengeGroup] {
+        self.phases
+            .get(phase)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Number of challenges sampled in `phase`.
+    pub fn phase_num_challenges(&self, phase: usize) -> usize {
+        self.groups(phase)
+            .iter()
+            .map(|group| group.challenges.len())
+            .sum()
+    }
+
+    /// Number of challenges sampled over all phases.
+    pub fn num_challenges(&self) -> usize {
+        (0..self.num_phases())
+            .map(|phase| self.phase_num_challenges(phase))
+            .sum()
+    }
+
+    /// Phase sampling challenge `name` of `group`, and its position among the challenges
+    /// of that phase.
+    fn locate(&self, group: &str, name: &str) -> Option<(usize, usize)> {
+        self.phases.iter().enumerate().find_map(|(phase, groups)| {
+            let mut offset = 0;
+            for g in groups {
+                if g.name == group {
+                    if let Some(k) = g.challenges.iter().position(|&c| c == name) {
+                        return Some((phase, offset + k));
+                    }
+                }
+                offset += g.challenges.len();
+            }
+            None
+        })
+    }
+
+    /// Position of challenge `name` of `group` among the challenges passed to the
+    /// [`build_aux_phase`](crate::AuxTraceBuilder::build_aux_phase) of `phase`.
+    ///
+    /// # Returns
+    /// `None` if no group of `phase` declares that challenge
+    pub fn phase_index(&self, phase: usize, group: &str, name: &str) -> Option<usize> {
+        self.locate(group, name)
+            .filter(|&(p, _)| p == phase)
+            .map(|(_, index)| index)
+    }
+
+    /// Position of challenge `name` of `group` among the challenges of all phases, as seen
+    /// by the constraints through
+    /// [`AuxBuilder::aux_challenges`](crate::AuxBuilder::aux_challenges).
+    ///
+    /// # Returns
+    /// `None` if no phase declares that challenge
+    pub fn index(&self, group: &str, name: &str) -> Option<usize> {
+        let (phase, index) = self.locate(group, name)?;
+        let offset: usize = (0..phase).map(|p| self.phase_num_challenges(p)).sum();
+        Some(offset + index)
+    }
+
+    /// Challenge `name` of `group` out of the `challenges` sampled for `phase`, for aux
+    /// builders.
+    ///
+    /// # Panics
+    /// If no group of `phase` declares that challenge, or `challenges` is too short
+    pub fn phase_challenge<T: Copy>(
+        &self,
+        phase: usize,
+        challenges: &[T],
+        group: &str,
+        name: &str,
+    ) -> T {
+        let index = self
+            .phase_index(phase, group, name)
+            .unwrap_or_else(|| panic!("aux phase {phase} declares no challenge {group}.{name}"));
+        challenges[index]
+    }
+}
@@ -0,0 +1,78 @@
+//! Main traces assembled from row chunks
+//!
+//! A witness generator that produces rows in chunks can feed them to a [`ChunkedTrace`]
+//! instead of collecting them into growing vectors: the trace buffer is allocated once at
+//! its final size and each chunk is copied into it as it arrives, so the generator only
+//! ever holds one chunk besides the trace. [`crate::prove`] then takes the trace over
+//! without copying it unless an aux phase reads it.
+//!
+//! The PCS computes the low-degree extension of whole columns, so the trace is committed
+//! once complete rather than chunk by chunk.
+
+use alloc::vec::Vec;
+
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::Matrix;
+
+use crate::ProverError;
+
+/// A main trace of known dimensions filled chunk by chunk.
+#[derive(Clone, Debug)]
+pub struct ChunkedTrace<F> {
+    width: usize,
+    height: usize,
+    values: Vec<F>,
+}
+
+impl<F: Clone + Send + Sync> ChunkedTrace<F> {
+    /// An empty trace of `height` rows of `width` columns, allocated at its final size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            values: Vec::with_capacity(width * height),
+        }
+    }
+
+    /// Number of rows pushed so far.
+    pub fn rows(&self) -> usize {
+        self.values.len().checked_div(self.width).unwrap_or(0)
+    }
+
+    /// Append the rows of `chunk`.
+    ///
+    /// # Returns
+    /// A [`ProverError`], leaving the trace unchanged
+    /// - If the chunk width differs from the trace width
+    /// - If the chunk holds more rows than the trace has left
+    pub fn push_chunk(&mut self, chunk: RowMajorMatrixView<'_, F>) -> Result<(), ProverError> {
+        if chunk.width() != self.width {
+            return Err(ProverError::MainTraceWidthMismatch {
+                expected: self.width,
+                actual: chunk.width(),
+            });
+        }
+        if self.rows() + chunk.height() > self.height {
+            return Err(ProverError::TraceRowCountMismatch {
+                expected: self.height,
+                actual: self.rows() + chunk.height(),
+            });
+        }
+        self.values.extend_from_slice(chunk.values);
+        Ok(())
+    }
+
+    /// The complete trace.
+    ///
+    /// # Returns
+    /// A [`ProverError`] if fewer rows than the trace height were pushed
+    pub fn finish(self) -> Result<RowMajorMatrix<F>, ProverError> {
+        if self.rows() != self.height {
+            return Err(ProverError::TraceRowCountMismatch {
+                expected: self.height,
+                actual: self.rows(),
+            });
+        }
+        Ok(RowMajorMatrix::new(self.values, self.width))
+    }
+}
@@ -2,7 +2,7 @@
 
 use p3_challenger::{CanObserve, CanSample, FieldChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::ExtensionField;
+use p3_field::{ExtensionField, Field, TwoAdicField};
 
 /// Domain type from the PCS
 pub type Domain<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
@@ -14,8 +14,7 @@ pub type Domain<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
 pub type Val<SC> = <Domain<SC> as PolynomialSpace>::Val;
 
 /// Packed base field value
-pub type PackedVal<SC> =
-    <<SC as StarkGenericConfig>::Challenge as ExtensionField<Val<SC>>>::ExtensionPacking;
+pub type PackedVal<SC> = <Val<SC> as Field>::Packing;
 
 /// Challenge type
 pub type Challenge<SC> = <SC as StarkGenericConfig>::Challenge;
@@ -30,7 +29,17 @@ pub type Challenger<SC> = <SC as StarkGenericConfig>::Challenger;
 /// Generic STARK configuration trait matching upstream p3-uni-stark pattern
 pub trait StarkGenericConfig {
     /// Polynomial commitment scheme
-    type Pcs: Pcs<Self::Challenge, Self::Challenger>;
+    ///
+    /// The prover commits and opens on its worker pool, so the PCS and the data it passes
+    /// between those steps must be shareable across threads.
+    type Pcs: Pcs<
+            Self::Challenge,
+            Self::Challenger,
+            Domain: Send + Sync,
+            Commitment: Send,
+            ProverData: Send + Sync,
+            Proof: Send,
+        > + Sync;
 
     /// Extension field for challenges
     type Challenge: ExtensionField<Val<Self>>;
@@ -38,7 +47,8 @@ pub trait StarkGenericConfig {
     /// Fiat-Shamir challenger
     type Challenger: FieldChallenger<Val<Self>>
         + CanObserve<<Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::Commitment>
-        + CanSample<Self::Challenge>;
+        + CanSample<Self::Challenge>
+        + Send;
 
     /// Get the PCS instance
     fn pcs(&self) -> &Self::Pcs;
@@ -50,6 +60,69 @@ pub trait StarkGenericConfig {
     fn is_zk(&self) -> usize {
         Self::Pcs::ZK as usize
     }
+
+    /// Largest log2 trace height this configuration can prove, if known.
+    ///
+    /// [`crate::prove`] rejects larger traces up front and [`crate::verify`] rejects proofs
+    /// claiming a larger degree, rather than failing deep inside the DFT or PCS.
+    fn max_supported_log_degree(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of independent out-of-domain points the proof is opened at.
+    ///
+    /// The constraint identity is checked at each of them; values above 1 trade proof size
+    /// and verifier time for a soundness margin beyond a single out-of-domain point.
+    fn num_ood_points(&self) -> usize {
+        1
+    }
+
+    /// Most base field columns the MMCS can hash into one leaf, if limited.
+    ///
+    /// The quotient chunks are committed in as many column groups as needed to stay within
+    /// it, rather than failing inside the Merkle layer.
+    fn max_leaf_width(&self) -> Option<usize> {
+        None
+    }
+
+    /// Largest acceptable proof, in bytes of [`crate::serialized_size`], if limited.
+    ///
+    /// [`crate::prove`] warns when it produces a larger proof, and [`crate::verify`] rejects
+    /// one, catching parameter choices that blow up bandwidth before they reach production.
+    fn max_proof_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether constraints are folded with a challenge per phase rather than one `alpha`.
+    ///
+    /// A fresh `alpha` is then sampled after the main trace and after each aux phase is
+    /// committed, and each constraint is folded with the `alpha` of the last phase it
+    /// reads, see [`crate::VerifyingKey::constraint_phases`]. A later phase can then not
+    /// be chosen knowing the challenge that folds an earlier one. Multi-table proofs keep
+    /// a single `alpha`.
+    fn per_phase_alpha(&self) -> bool {
+        false
+    }
+
+    /// Number of worker threads the prover runs the DFTs, the Merkle hashing and the
+    /// quotient backend on, if not rayon's global pool.
+    ///
+    /// Only read with the `parallel` feature; without it the prover runs on the calling
+    /// thread. The pool is started for each proof, and the calling thread waits for it.
+    fn num_threads(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of quotient domain points the prover evaluates the constraints on at once,
+    /// if not the whole domain.
+    ///
+    /// Each block is evaluated with its own Lagrange selectors and written straight into
+    /// the quotient chunks, so the prover holds neither the selectors nor the quotient of
+    /// the whole domain next to the chunks. Only PCS whose quotient domains split into
+    /// chunks by stride, such as two-adic FRI, support it; the prover rejects others.
+    fn quotient_block_size(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// Concrete STARK configuration
@@ -59,6 +132,23 @@ pub struct StarkConfig<Pcs, Challenge, Challenger> {
     pub pcs: Pcs,
     /// Initial challenger state
     pub challenger: Challenger,
+    /// Largest supported log2 trace height, if known
+    pub(crate) max_log_degree: Option<usize>,
+    /// FRI parameters of the PCS, if recorded
+    #[cfg(feature = "fri")]
+    pub(crate) fri_shape: Option<crate::fri::FriShape>,
+    /// Number of out-of-domain points
+    num_ood_points: usize,
+    /// Largest leaf width of the MMCS, if limited
+    max_leaf_width: Option<usize>,
+    /// Largest acceptable serialized proof, if limited
+    max_proof_size: Option<usize>,
+    /// Whether each phase folds its constraints with its own challenge
+    per_phase_alpha: bool,
+    /// Size of the prover's worker pool, if not the global pool
+    num_threads: Option<usize>,
+    /// Quotient domain points evaluated at once, if not the whole domain
+    quotient_block_size: Option<usize>,
     _phantom: core::marker::PhantomData<Challenge>,
 }
 
@@ -67,19 +157,116 @@ impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
         Self {
             pcs,
             challenger,
+            max_log_degree: None,
+            #[cfg(feature = "fri")]
+            fri_shape: None,
+            num_ood_points: 1,
+            max_leaf_width: None,
+            max_proof_size: None,
+            per_phase_alpha: false,
+            num_threads: None,
+            quotient_block_size: None,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Open proofs at `num_ood_points` independent out-of-domain points.
+    ///
+    /// # Panics
+    /// - If `num_ood_points` is 0
+    pub fn with_ood_points(mut self, num_ood_points: usize) -> Self {
+        assert!(
+            num_ood_points > 0,
+            "At least one out-of-domain point is required"
+        );
+        self.num_ood_points = num_ood_points;
+        self
+    }
+
+    /// Keep every quotient commitment within `max_leaf_width` base field columns.
+    ///
+    /// # Panics
+    /// - If `max_leaf_width` is 0
+    pub fn with_max_leaf_width(mut self, max_leaf_width: usize) -> Self {
+        assert!(max_leaf_width > 0, "Leaves must hold at least one column");
+        self.max_leaf_width = Some(max_leaf_width);
+        self
+    }
+
+    /// Accept proofs of at most `max_proof_size` bytes, see
+    /// [`StarkGenericConfig::max_proof_size`].
+    pub const fn with_max_proof_size(mut self, max_proof_size: usize) -> Self {
+        self.max_proof_size = Some(max_proof_size);
+        self
+    }
+
+    /// Fold the constraints of each phase with their own challenge, see
+    /// [`StarkGenericConfig::per_phase_alpha`].
+    pub const fn with_per_phase_alpha(mut self) -> Self {
+        self.per_phase_alpha = true;
+        self
+    }
+
+    /// Prove on a pool of `num_threads` workers instead of rayon's global pool, see
+    /// [`StarkGenericConfig::num_threads`].
+    ///
+    /// # Panics
+    /// - If `num_threads` is 0
+    pub fn with_num_threads(mut self, num_threads: usize) -> Self {
+        assert!(num_threads > 0, "The worker pool needs at least one thread");
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Evaluate the quotient `quotient_block_size` points at a time, see
+    /// [`StarkGenericConfig::quotient_block_size`].
+    ///
+    /// # Panics
+    /// - If `quotient_block_size` is 0
+    pub fn with_quotient_block_size(mut self, quotient_block_size: usize) -> Self {
+        assert!(
+            quotient_block_size > 0,
+            "Quotient blocks must hold at least one point"
+        );
+        self.quotient_block_size = Some(quotient_block_size);
+        self
+    }
 }
 
-impl<P, Challenge, C> StarkGenericConfig for StarkConfig<P, Challenge, C>
+impl<P, Challenge, C> StarkConfig<P, Challenge, C>
 where
     Challenge: ExtensionField<<P::Domain as PolynomialSpace>::Val>,
     P: Pcs<Challenge, C>,
+    <P::Domain as PolynomialSpace>::Val: TwoAdicField,
+{
+    /// Record the FRI blowup of the PCS, so that the config can report the largest trace
+    /// it supports through [`StarkGenericConfig::max_supported_log_degree`].
+    ///
+    /// The LDE (`log_blowup`) must fit in the field's two-adic subgroup. The quotient
+    /// domain is evaluated from the LDE, so it is never larger.
+    pub fn with_log_blowup(mut self, log_blowup: usize) -> Self {
+        self.max_log_degree =
+            Some(<P::Domain as PolynomialSpace>::Val::TWO_ADICITY.saturating_sub(log_blowup));
+        self
+    }
+}
+
+impl<P, Challenge, C> StarkGenericConfig for StarkConfig<P, Challenge, C>
+where
+    Challenge: ExtensionField<<P::Domain as PolynomialSpace>::Val>,
+    P: Pcs<
+            Challenge,
+            C,
+            Domain: Send + Sync,
+            Commitment: Send,
+            ProverData: Send + Sync,
+            Proof: Send,
+        > + Sync,
     C: FieldChallenger<<P::Domain as PolynomialSpace>::Val>
         + CanObserve<P::Commitment>
         + CanSample<Challenge>
-        + Clone,
+        + Clone
+        + Send,
 {
     type Pcs = P;
     type Challenge = Challenge;
@@ -92,4 +279,32 @@ where
     fn initialise_challenger(&self) -> Self::Challenger {
         self.challenger.clone()
     }
+
+    fn max_supported_log_degree(&self) -> Option<usize> {
+        self.max_log_degree
+    }
+
+    fn num_ood_points(&self) -> usize {
+        self.num_ood_points
+    }
+
+    fn max_leaf_width(&self) -> Option<usize> {
+        self.max_leaf_width
+    }
+
+    fn max_proof_size(&self) -> Option<usize> {
+        self.max_proof_size
+    }
+
+    fn per_phase_alpha(&self) -> bool {
+        self.per_phase_alpha
+    }
+
+    fn num_threads(&self) -> Option<usize> {
+        self.num_threads
+    }
+
+    fn quotient_block_size(&self) -> Option<usize> {
+        self.quotient_block_size
+    }
 }
@@ -0,0 +1,370 @@
+//! Proving a DAG of dependent statements
+//!
+//! A [`ProofDag`] holds statements, each an AIR with its proving key and a witness
+//! generator, and [`Link`]s feeding a public value of one statement into a public value of
+//! another. Proving runs the generators in dependency order, handing each one the linked
+//! values it consumes, and emits either a [`ProofBundle`] of independent proofs or an
+//! [`AggregatedProof`] proving every statement as one table of a [`MultiProof`]. The
+//! verifier checks the proofs and that every link holds between the public values.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_air::Air;
+use p3_field::PackedField;
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    prove, prove_multi, verify, verify_multi, Challenge, MultiProof, MultiTraceAir, Proof,
+    ProverError, ProverFolder, ProvingKey, StarkGenericConfig, Val, VerificationError,
+    VerifierFolder, VerifyingKey,
+};
+
+/// Index of a statement in the order it was added to a [`ProofDag`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatementId(pub usize);
+
+/// Public value `output` of statement `from` is public value `input` of statement `to`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// Statement producing the value
+    pub from: StatementId,
+    /// Index of the value in the public values of `from`
+    pub output: usize,
+    /// Statement consuming the value
+    pub to: StatementId,
+    /// Index of the value in the public values of `to`
+    pub input: usize,
+}
+
+/// Main trace and public values of one statement, built by its witness generator.
+pub struct StatementWitness<F> {
+    /// The main execution trace
+    pub main_trace: RowMajorMatrix<F>,
+    /// Public values, including the linked inputs at their indices
+    pub public_values: Vec<F>,
+}
+
+/// Reasons a [`ProofDag`] cannot be proven.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DagError {
+    /// The links form a cycle
+    Cycle,
+    /// A linked statement has no public value at the linked index
+    MissingPublicValue(Link),
+    /// A witness generator did not place a linked value at its input index
+    LinkMismatch(Link),
+    /// The prover rejected a witness or the configuration
+    Prover(ProverError),
+}
+
+impl fmt::Display for DagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle => f.write_str("statement links form a cycle"),
+            Self::MissingPublicValue(link) => {
+                write!(f, "linked public value missing: {link:?}")
+            }
+            Self::LinkMismatch(link) => write!(f, "linked public values differ: {link:?}"),
+            Self::Prover(err) => write!(f, "proving failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for DagError {}
+
+impl From<ProverError> for DagError {
+    fn from(err: ProverError) -> Self {
+        Self::Prover(err)
+    }
+}
+
+/// Independent proofs of every statement of a [`ProofDag`], in statement order.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProofBundle<SC: StarkGenericConfig> {
+    /// Proof of each statement
+    pub proofs: Vec<Proof<SC>>,
+    /// Public values of each statement
+    pub public_values: Vec<Vec<Val<SC>>>,
+}
+
+/// One proof of every statement of a [`ProofDag`], one table per statement.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AggregatedProof<SC: StarkGenericConfig> {
+    /// Multi-table proof with the statements as tables, in statement order
+    pub proof: MultiProof<SC>,
+    /// Public values of each statement
+    pub public_values: Vec<Vec<Val<SC>>>,
+}
+
+type WitnessFn<'a, F> = Box<dyn FnOnce(&[(usize, F)]) -> StatementWitness<F> + 'a>;
+
+struct Statement<'a, SC: StarkGenericConfig, A> {
+    air: A,
+    pk: ProvingKey<SC>,
+    witness: WitnessFn<'a, Val<SC>>,
+}
+
+/// Statements whose public values feed each other, proven in dependency order.
+pub struct ProofDag<'a, SC: StarkGenericConfig, A> {
+    statements: Vec<Statement<'a, SC, A>>,
+    links: Vec<Link>,
+}
+
+impl<SC: StarkGenericConfig, A> Default for ProofDag<'_, SC, A> {
+    fn default() -> Self {
+        Self {
+            statements: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+}
+
+impl<'a, SC, A> ProofDag<'a, SC, A>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'b> Air<ProverFolder<'b, SC>>
+        + for<'b> Air<VerifierFolder<'b, SC>>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a statement of `air` under `pk`.
+    ///
+    /// `witness` runs once every statement it is linked from has its witness. It receives
+    /// the linked values as `(input, value)` pairs sorted by input index, and must return
+    /// public values holding each of them at its input index.
+    pub fn add_statement(
+        &mut self,
+        air: A,
+        pk: ProvingKey<SC>,
+        witness: impl FnOnce(&[(usize, Val<SC>)]) -> StatementWitness<Val<SC>> + 'a,
+    ) -> StatementId {
+        self.statements.push(Statement {
+            air,
+            pk,
+            witness: Box::new(witness),
+        });
+        StatementId(self.statements.len() - 1)
+    }
+
+    /// Feed public value `output` of `from` into public value `input` of `to`.
+    ///
+    /// # Panics
+    /// - If `from` or `to` was not returned by [`ProofDag::add_statement`]
+    pub fn link(&mut self, from: StatementId, output: usize, to: StatementId, input: usize) {
+        assert!(from.0 < self.statements.len(), "Unknown statement {from:?}");
+        assert!(to.0 < self.statements.len(), "Unknown statement {to:?}");
+        self.links.push(Link {
+            from,
+            output,
+            to,
+            input,
+        });
+    }
+
+    /// The links added so far, which the verifier needs too.
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// The order in which witnesses are generated: every statement after the statements
+    /// it is linked from, ties broken by statement index.
+    pub fn proving_order(&self) -> Result<Vec<StatementId>, DagError> {
+        let mut in_degree = vec![0usize; self.statements.len()];
+        for link in &self.links {
+            in_degree[link.to.0] += 1;
+        }
+        let mut ready: BTreeSet<usize> = (0..self.statements.len())
+            .filter(|&i| in_degree[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.statements.len());
+        while let Some(i) = ready.pop_first() {
+            order.push(StatementId(i));
+            for link in self.links.iter().filter(|link| link.from.0 == i) {
+                in_degree[link.to.0] -= 1;
+                if in_degree[link.to.0] == 0 {
+                    ready.insert(link.to.0);
+                }
+            }
+        }
+        if order.len() < self.statements.len() {
+            return Err(DagError::Cycle);
+        }
+        Ok(order)
+    }
+
+    /// Prove every statement on its own.
+    #[instrument(skip_all, fields(num_statements = self.statements.len()))]
+    pub fn prove_bundle(self, config: &SC) -> Result<ProofBundle<SC>, DagError> {
+        let (airs, pks, witnesses) = self.generate_witnesses()?;
+        let mut proofs = Vec::with_capacity(airs.len());
+        let mut public_values = Vec::with_capacity(airs.len());
+        for ((air, pk), witness) in airs.iter().zip(&pks).zip(witnesses) {
+            proofs.push(prove(
+                config,
+                air,
+                pk,
+                witness.main_trace,
+                &witness.public_values,
+            )?);
+            public_values.push(witness.public_values);
+        }
+        Ok(ProofBundle {
+            proofs,
+            public_values,
+        })
+    }
+
+    /// Prove every statement as one table of a single [`MultiProof`].
+    #[instrument(skip_all, fields(num_statements = self.statements.len()))]
+    pub fn prove_aggregated(self, config: &SC) -> Result<AggregatedProof<SC>, DagError> {
+        let (airs, pks, witnesses) = self.generate_witnesses()?;
+        let (main_traces, public_values): (Vec<_>, Vec<_>) = witnesses
+            .into_iter()
+            .map(|witness| (witness.main_trace, witness.public_values))
+            .unzip();
+        let proof = prove_multi(config, &airs, &pks, main_traces, &public_values)?;
+        Ok(AggregatedProof {
+            proof,
+            public_values,
+        })
+    }
+
+    /// Run the witness generators in [`ProofDag::proving_order`], wiring the linked values.
+    #[allow(clippy::type_complexity)]
+    fn generate_witnesses(
+        self,
+    ) -> Result<(Vec<A>, Vec<ProvingKey<SC>>, Vec<StatementWitness<Val<SC>>>), DagError> {
+        let order = self.proving_order()?;
+        let Self { statements, links } = self;
+
+        let mut airs = Vec::with_capacity(statements.len());
+        let mut pks = Vec::with_capacity(statements.len());
+        let mut generators = Vec::with_capacity(statements.len());
+        for statement in statements {
+            airs.push(statement.air);
+            pks.push(statement.pk);
+            generators.push(Some(statement.witness));
+        }
+
+        let mut witnesses: Vec<Option<StatementWitness<Val<SC>>>> =
+            (0..airs.len()).map(|_| None).collect();
+        for id in order {
+            let incoming: Vec<Link> = links.iter().filter(|link| link.to == id).copied().collect();
+            let mut inputs = Vec::with_capacity(incoming.len());
+            for &link in &incoming {
+                let value = witnesses[link.from.0]
+                    .as_ref()
+                    .expect("Linked statements come first in the proving order")
+                    .public_values
+                    .get(link.output)
+                    .copied()
+                    .ok_or(DagError::MissingPublicValue(link))?;
+                inputs.push((link.input, value));
+            }
+            inputs.sort_by_key(|&(input, _)| input);
+
+            let generate = generators[id.0]
+                .take()
+                .expect("Each statement appears once in the proving order");
+            let witness = generate(&inputs);
+            for (&link, &(_, value)) in incoming.iter().zip(&inputs) {
+                match witness.public_values.get(link.input) {
+                    None => return Err(DagError::MissingPublicValue(link)),
+                    Some(&input) if input != value => return Err(DagError::LinkMismatch(link)),
+                    Some(_) => {}
+                }
+            }
+            witnesses[id.0] = Some(witness);
+        }
+
+        let witnesses = witnesses
+            .into_iter()
+            .map(|witness| witness.expect("Every statement appears in the proving order"))
+            .collect();
+        Ok((airs, pks, witnesses))
+    }
+}
+
+/// Check that every link holds between the public values of the statements.
+pub fn check_links<F: PartialEq>(
+    links: &[Link],
+    public_values: &[Vec<F>],
+) -> Result<(), VerificationError> {
+    for link in links {
+        let output = public_values
+            .get(link.from.0)
+            .and_then(|values| values.get(link.output));
+        let input = public_values
+            .get(link.to.0)
+            .and_then(|values| values.get(link.input));
+        match (output, input) {
+            (Some(output), Some(input)) if output == input => {}
+            _ => {
+                return Err(VerificationError::InvalidProof(
+                    "linked public values differ",
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verify a [`ProofBundle`] of statements of `airs` under `vks`, linked by `links`.
+pub fn verify_bundle<SC, A>(
+    config: &SC,
+    airs: &[A],
+    vks: &[VerifyingKey<SC>],
+    links: &[Link],
+    bundle: &ProofBundle<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    if vks.len() != airs.len()
+        || bundle.proofs.len() != airs.len()
+        || bundle.public_values.len() != airs.len()
+    {
+        return Err(VerificationError::InvalidProof(
+            "statement count does not match the number of AIRs",
+        ));
+    }
+    check_links(links, &bundle.public_values)?;
+    for (((air, vk), proof), public_values) in airs
+        .iter()
+        .zip(vks)
+        .zip(&bundle.proofs)
+        .zip(&bundle.public_values)
+    {
+        verify(config, air, vk, proof, public_values)?;
+    }
+    Ok(())
+}
+
+/// Verify an [`AggregatedProof`] of statements of `airs` under `vks`, linked by `links`.
+pub fn verify_aggregated<SC, A>(
+    config: &SC,
+    airs: &[A],
+    vks: &[VerifyingKey<SC>],
+    links: &[Link],
+    proof: &AggregatedProof<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    check_links(links, &proof.public_values)?;
+    verify_multi(config, airs, vks, &proof.proof, &proof.public_values)
+}
@@ -0,0 +1,309 @@
+//! Verification cost model of a config preset
+//!
+//! A [`CostModel`] holds the parameters of a config preset that drive proof size and
+//! verification work: field and digest sizes, the hash rate and the FRI parameters. Its
+//! [`CostModel::estimate`] gives, for a trace height and an [`AirShape`], the serialized
+//! proof size, the hash permutations the verifier runs and the [`VerificationCost`] that
+//! [`crate::verify_metered`] charges, so integrators can compare presets without proving
+//! anything. Shipped presets expose their model next to their config, such as
+//! `fixtures::baby_bear_cost_model` with the `fixtures` feature.
+//!
+//! The model covers proofs of [`crate::prove`] under a config without zero knowledge,
+//! per-phase alphas or a leaf width limit, whose PCS is FRI over a binary Merkle tree. The
+//! metered cost is exact; the proof size and hash count depend on the PCS's proof layout
+//! and are estimates.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Challenge, MultiTraceAir, StarkGenericConfig, Val, VerificationCost, VerifyingKey};
+
+/// Length prefix of a sequence in the reference layout of [`crate::serialized_size`]
+const LEN: usize = 8;
+
+/// Parameters of a config preset that drive proof size and verification work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// Serialized bytes of a base field element
+    pub val_bytes: usize,
+    /// Degree of the challenge field over the base field
+    pub challenge_degree: usize,
+    /// Base field elements in a Merkle digest
+    pub digest_elems: usize,
+    /// Base field elements absorbed per permutation by the leaf hash and the challenger
+    pub hash_rate: usize,
+    /// Log2 of the FRI blowup
+    pub log_blowup: usize,
+    /// Log2 of the length of the final FRI polynomial
+    pub log_final_poly_len: usize,
+    /// Number of FRI queries
+    pub num_queries: usize,
+    /// Number of out-of-domain points, see [`StarkGenericConfig::num_ood_points`]
+    pub num_ood_points: usize,
+}
+
+/// The dimensions of an AIR that verification cost depends on.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirShape {
+    /// Number of main trace columns
+    pub main_width: usize,
+    /// Number of preprocessed columns
+    pub preprocessed_width: usize,
+    /// Number of extension field columns of each auxiliary phase
+    pub aux_widths: Vec<usize>,
+    /// Whether each auxiliary phase is committed over the base field, see
+    /// [`crate::AuxTraceBuilder::aux_phase_in_base_field`] (all extension phases if empty)
+    #[serde(default)]
+    pub base_field_aux: Vec<bool>,
+    /// Challenges sampled by all auxiliary phases
+    pub num_challenges: usize,
+    /// Values exposed by all auxiliary phases
+    pub num_exposed_values: usize,
+    /// Number of AIR instance parameters
+    pub num_instance_parameters: usize,
+    /// Number of public values
+    pub num_public_values: usize,
+    /// Number of constraints
+    pub num_constraints: usize,
+    /// Log2 of the number of quotient chunks
+    pub log_quotient_degree: usize,
+}
+
+impl AirShape {
+    /// The shape of `air`, as measured into `vk`, with `num_public_values` public values.
+    pub fn new<SC, A>(vk: &VerifyingKey<SC>, air: &A, num_public_values: usize) -> Self
+    where
+        SC: StarkGenericConfig,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+    {
+        let num_phases = air.num_aux_phases();
+        Self {
+            main_width: vk.main_width,
+            preprocessed_width: vk.preprocessed_width(),
+            aux_widths: vk.aux_widths.clone(),
+            base_field_aux: (0..num_phases)
+                .map(|phase| air.aux_phase_in_base_field(phase))
+                .collect(),
+            num_challenges: (0..num_phases)
+                .map(|phase| air.aux_phase_num_challenges(phase))
+                .sum(),
+            num_exposed_values: (0..num_phases)
+                .map(|phase| air.aux_phase_num_exposed_values(phase))
+                .sum(),
+            num_instance_parameters: air.instance_parameters().len(),
+            num_public_values,
+            num_constraints: vk.num_constraints,
+            log_quotient_degree: vk.log_quotient_degree,
+        }
+    }
+
+    /// Number of auxiliary phases with columns, each committed once.
+    fn num_committed_phases(&self) -> usize {
+        self.aux_widths.iter().filter(|&&width| width > 0).count()
+    }
+
+    /// Base field columns committed for each auxiliary phase with columns.
+    fn aux_committed_widths(&self, challenge_degree: usize) -> impl Iterator<Item = usize> + '_ {
+        self.aux_widths
+            .iter()
+            .enumerate()
+            .filter(|&(_, &width)| width > 0)
+            .map(move |(phase, &width)| {
+                if self.base_field_aux.get(phase).copied().unwrap_or(false) {
+                    width
+                } else {
+                    width * challenge_degree
+                }
+            })
+    }
+
+    /// Base field columns of each committed batch, in commitment order: preprocessed (if
+    /// any), main, each aux phase, then the quotient chunks.
+    fn batches(&self, challenge_degree: usize) -> Vec<Vec<usize>> {
+        let preprocessed = (self.preprocessed_width > 0).then(|| vec![self.preprocessed_width]);
+        let aux = self
+            .aux_committed_widths(challenge_degree)
+            .map(|width| vec![width]);
+        let quotient = vec![challenge_degree; 1 << self.log_quotient_degree];
+        preprocessed
+            .into_iter()
+            .chain([vec![self.main_width]])
+            .chain(aux)
+            .chain([quotient])
+            .collect()
+    }
+}
+
+/// Expected proof size and verification work of one proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Bytes of [`crate::serialized_size`]
+    pub proof_size: usize,
+    /// Hash permutations run by the verifier: Merkle leaves and nodes and the challenger
+    pub hashes: u64,
+    /// The cost [`crate::verify_metered`] charges, summed over its steps
+    pub cost: VerificationCost,
+}
+
+impl CostModel {
+    /// Proof size and verification work of a proof of an AIR of shape `shape` over a
+    /// trace of `1 << log_degree` rows.
+    pub fn estimate(&self, log_degree: usize, shape: &AirShape) -> CostEstimate {
+        CostEstimate {
+            proof_size: self.proof_size(log_degree, shape),
+            hashes: self.hashes(log_degree, shape),
+            cost: self.metered_cost(shape),
+        }
+    }
+
+    /// The cost charged by [`crate::verify_metered`], which does not depend on the height.
+    fn metered_cost(&self, shape: &AirShape) -> VerificationCost {
+        let degree = self.challenge_degree;
+        let num_phases = shape.aux_widths.len();
+        let num_chunks = 1 << shape.log_quotient_degree;
+
+        // The steps of `VerifyingKey::transcript_spec`, commitments counting for one
+        let transcript_ops = 1
+            + shape.num_instance_parameters
+            + 3
+            + num_phases
+            + 1
+            + usize::from(shape.preprocessed_width > 0)
+            + 1
+            + shape.num_public_values
+            + shape.num_challenges
+            + shape.num_committed_phases()
+            + shape.num_exposed_values * degree
+            + 1
+            + 1
+            + self.num_ood_points;
+
+        let aux_opened: usize = shape.aux_committed_widths(degree).sum();
+        let opened_per_point =
+            2 * (shape.main_width + shape.preprocessed_width + aux_opened) + num_chunks * degree;
+
+        let constraint_ops = 2 * shape.num_constraints;
+        let recomposition_ops = 4 * num_chunks * num_chunks + num_chunks * (2 * degree + 1);
+
+        VerificationCost {
+            transcript_ops: transcript_ops as u64,
+            opened_values: (opened_per_point * self.num_ood_points) as u64,
+            field_ops: ((constraint_ops + recomposition_ops) * self.num_ood_points) as u64,
+        }
+    }
+
+    /// Number of FRI folding rounds.
+    const fn num_fri_rounds(&self, log_degree: usize) -> usize {
+        log_degree.saturating_sub(self.log_final_poly_len)
+    }
+
+    fn proof_size(&self, log_degree: usize, shape: &AirShape) -> usize {
+        let degree = self.challenge_degree;
+        let ext_bytes = degree * self.val_bytes;
+        let digest_bytes = self.digest_elems * self.val_bytes;
+        let num_phases = shape.aux_widths.len();
+        let num_committed = shape.num_committed_phases();
+        let num_chunks = 1 << shape.log_quotient_degree;
+        let aux_opened: usize = shape.aux_committed_widths(degree).sum();
+
+        // Values opened at one out-of-domain point and the next row
+        let trace_openings = 2 * (LEN + shape.main_width * ext_bytes)
+            + 2 * (LEN + shape.preprocessed_width * ext_bytes)
+            + 2 * (1 + if num_committed > 0 {
+                LEN + num_committed * LEN + aux_opened * ext_bytes
+            } else {
+                0
+            });
+        let quotient_openings = LEN + num_chunks * (LEN + degree * ext_bytes);
+        let openings = trace_openings + quotient_openings;
+
+        let commitments = digest_bytes + LEN + num_committed * digest_bytes + LEN + digest_bytes;
+        let exposed_values = LEN + num_phases * LEN + shape.num_exposed_values * ext_bytes;
+        // The version header, then the log degree, the absent public values digest and
+        // challenge counts
+        let header = 1;
+        let trailer = 1 + 1 + 1;
+        let additional_openings = LEN + self.num_ood_points.saturating_sub(1) * openings;
+
+        header
+            + commitments
+            + exposed_values
+            + openings
+            + additional_openings
+            + trailer
+            + self.fri_proof_size(log_degree, shape)
+    }
+
+    fn fri_proof_size(&self, log_degree: usize, shape: &AirShape) -> usize {
+        let ext_bytes = self.challenge_degree * self.val_bytes;
+        let digest_bytes = self.digest_elems * self.val_bytes;
+        let log_lde_height = log_degree + self.log_blowup;
+        let num_rounds = self.num_fri_rounds(log_degree);
+
+        // Opened rows and Merkle path of every committed batch
+        let input_proof: usize = LEN
+            + shape
+                .batches(self.challenge_degree)
+                .iter()
+                .map(|widths| {
+                    let values: usize = widths
+                        .iter()
+                        .map(|width| LEN + width * self.val_bytes)
+                        .sum();
+                    LEN + values + LEN + log_lde_height * digest_bytes
+                })
+                .sum::<usize>();
+        // Sibling value and Merkle path of every folding round
+        let commit_phase_openings: usize = LEN
+            + (0..num_rounds)
+                .map(|round| ext_bytes + LEN + (log_lde_height - 1 - round) * digest_bytes)
+                .sum::<usize>();
+        let query_proofs = LEN + self.num_queries * (input_proof + commit_phase_openings);
+
+        let commit_phase_commits = LEN + num_rounds * digest_bytes;
+        let final_poly = LEN + (1 << self.log_final_poly_len) * ext_bytes;
+        let pow_witness = self.val_bytes;
+
+        commit_phase_commits + query_proofs + final_poly + pow_witness
+    }
+
+    fn hashes(&self, log_degree: usize, shape: &AirShape) -> u64 {
+        let degree = self.challenge_degree;
+        let log_lde_height = log_degree + self.log_blowup;
+        let num_rounds = self.num_fri_rounds(log_degree);
+        let absorb = |elements: usize| elements.div_ceil(self.hash_rate);
+
+        // Each query hashes the opened row of every batch and walks its Merkle path, then
+        // does the same for the sibling pair of every folding round
+        let input_hashes: usize = shape
+            .batches(degree)
+            .iter()
+            .map(|widths| absorb(widths.iter().sum()) + log_lde_height)
+            .sum();
+        let fold_hashes: usize = (0..num_rounds)
+            .map(|round| absorb(2 * degree) + log_lde_height - 1 - round)
+            .sum();
+        let query_hashes = self.num_queries * (input_hashes + fold_hashes);
+
+        // The challenger absorbs commitments as digests and squeezes challenges in
+        // extension coordinates
+        let cost = self.metered_cost(shape);
+        let num_commitments =
+            1 + usize::from(shape.preprocessed_width > 0) + shape.num_committed_phases() + 1;
+        let num_samples = shape.num_challenges + 1 + self.num_ood_points;
+        let transcript_elements = cost.transcript_ops as usize
+            + num_commitments * (self.digest_elems - 1)
+            + num_samples * (degree - 1)
+            // FRI: batching challenge, a commitment and a folding challenge per round, the
+            // final polynomial, the proof of work witness and the query indices
+            + degree
+            + num_rounds * (self.digest_elems + degree)
+            + (1 << self.log_final_poly_len) * degree
+            + 1
+            + self.num_queries;
+
+        (query_hashes + absorb(transcript_elements)) as u64
+    }
+}
@@ -0,0 +1,173 @@
+//! Witness debugging without the prover
+//!
+//! [`check_constraints`] evaluates every constraint of an AIR on every row of a raw
+//! witness and reports the first one that does not vanish, so a bad witness can be
+//! located without committing anything or reading a failed quotient check.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_air::Air;
+use p3_field::PrimeCharacteristicRing;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::air::aux_matrix_widths;
+use crate::{Challenge, MultiTraceAir, StarkGenericConfig, Val, VerifierFolder};
+
+/// A constraint that does not vanish on a row of the witness.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstraintViolation<EF> {
+    /// The row, whose next row is `row + 1` or row 0 on the last row
+    pub row: usize,
+    /// Index of the constraint, in the order the AIR asserts them
+    pub constraint: usize,
+    /// Label the constraint was asserted with, see [`crate::AuxBuilder::assert_zero_labeled`]
+    pub label: Option<&'static str>,
+    /// Value of the constraint on the row
+    pub value: EF,
+}
+
+impl<EF: fmt::Debug> fmt::Display for ConstraintViolation<EF> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.label {
+            Some(label) => write!(f, "constraint {} ({label})", self.constraint)?,
+            None => write!(f, "constraint {}", self.constraint)?,
+        }
+        write!(f, " evaluates to {:?} on row {}", self.value, self.row)
+    }
+}
+
+impl<EF: fmt::Debug> core::error::Error for ConstraintViolation<EF> {}
+
+/// Evaluate the constraints of `air` on every row of a witness, in row order.
+///
+/// `aux` holds the trace of each aux phase and `challenges` the challenges it was built
+/// from, as the prover passes them to [`crate::AuxTraceBuilder::build_aux_phase`]; a phase
+/// without columns has an empty trace. The
+/// preprocessed trace comes from the AIR and the exposed values from
+/// [`crate::AuxTraceBuilder::aux_phase_exposed_values`].
+///
+/// # Returns
+/// The first row and constraint, in evaluation order, whose value is not zero, with its
+/// label if the AIR asserted it with one
+///
+/// # Panics
+/// - If `aux` or `challenges` does not have one entry per aux phase
+/// - If a trace has fewer rows than the main trace, or the main trace has none
+pub fn check_constraints<SC, A>(
+    air: &A,
+    main: &RowMajorMatrix<Val<SC>>,
+    aux: &[RowMajorMatrix<Challenge<SC>>],
+    challenges: &[Vec<Challenge<SC>>],
+    public_values: &[Val<SC>],
+) -> Result<(), ConstraintViolation<Challenge<SC>>>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let num_phases = air.num_aux_phases();
+    assert_eq!(aux.len(), num_phases, "one aux trace per phase is needed");
+    assert_eq!(
+        challenges.len(),
+        num_phases,
+        "one challenge set per phase is needed"
+    );
+    let height = main.height();
+    assert!(height > 0, "the main trace is empty");
+
+    let preprocessed = air.preprocessed_trace();
+    let exposed_values: Vec<Challenge<SC>> = (0..num_phases)
+        .flat_map(|phase| air.aux_phase_exposed_values(phase, &aux[phase], &challenges[phase]))
+        .collect();
+    let challenges = challenges.concat();
+    let aux_matrix_widths = aux_matrix_widths(air);
+
+    // Row `row` of every trace, lifted to the challenge field like the verifier's openings
+    let lift = |trace: &RowMajorMatrix<Val<SC>>, row: usize| -> Vec<Challenge<SC>> {
+        trace
+            .row_slice(row)
+            .expect("trace is too short")
+            .iter()
+            .map(|&v| v.into())
+            .collect()
+    };
+    let aux_row = |row: usize| -> Vec<Challenge<SC>> {
+        aux.iter()
+            .filter(|trace| trace.width > 0)
+            .flat_map(|trace| {
+                trace
+                    .row_slice(row)
+                    .expect("aux trace is too short")
+                    .to_vec()
+            })
+            .collect()
+    };
+
+    // The constraints on `row`, folded with the verifier's folder: with `phases` set to
+    // `0..n` and coefficients one, each constraint lands in an accumulator of its own
+    let evaluate = |row: usize, phases: &[usize], ones: &[Challenge<SC>]| {
+        let next = (row + 1) % height;
+        let main_local = lift(main, row);
+        let main_next = lift(main, next);
+        let (preprocessed_local, preprocessed_next) = preprocessed
+            .as_ref()
+            .map_or((vec![], vec![]), |pp| (lift(pp, row), lift(pp, next)));
+        let aux_local = aux_row(row);
+        let aux_next = aux_row(next);
+
+        let mut folder = VerifierFolder {
+            main_local: &main_local,
+            main_next: &main_next,
+            preprocessed_local: &preprocessed_local,
+            preprocessed_next: &preprocessed_next,
+            aux_local: &aux_local,
+            aux_next: &aux_next,
+            aux_matrix_widths: &aux_matrix_widths,
+            public_values,
+            aux_challenges: &challenges,
+            aux_exposed_values: &exposed_values,
+            is_first_row: Challenge::<SC>::from_bool(row == 0),
+            is_last_row: Challenge::<SC>::from_bool(row == height - 1),
+            is_transition: Challenge::<SC>::from_bool(row != height - 1),
+            alpha: Challenge::<SC>::ZERO,
+            phase_alphas: ones,
+            constraint_phases: phases,
+            phase_accumulators: vec![Challenge::<SC>::ZERO; ones.len()],
+            accumulator: Challenge::<SC>::ZERO,
+            constraint_index: 0,
+            constraint_labels: Vec::new(),
+        };
+        air.eval(&mut folder);
+        (
+            folder.phase_accumulators,
+            folder.constraint_index,
+            folder.constraint_labels,
+        )
+    };
+
+    let (_, num_constraints, labels) = evaluate(0, &[], &[]);
+    let phases: Vec<usize> = (0..num_constraints).collect();
+    let ones = vec![Challenge::<SC>::ONE; num_constraints];
+    for row in 0..height {
+        let (values, _, _) = evaluate(row, &phases, &ones);
+        if let Some((constraint, &value)) = values
+            .iter()
+            .enumerate()
+            .find(|&(_, &value)| value != Challenge::<SC>::ZERO)
+        {
+            let label = labels
+                .iter()
+                .find(|&&(index, _)| index == constraint)
+                .map(|&(_, label)| label);
+            return Err(ConstraintViolation {
+                row,
+                constraint,
+                label,
+                value,
+            });
+        }
+    }
+    Ok(())
+}
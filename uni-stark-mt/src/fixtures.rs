@@ -0,0 +1,189 @@
+//! Deterministic fixtures for docs and tests
+//!
+//! Everything here is derived from a `u64` seed: the same seed always gives the same
+//! config, trace and proof bytes. Doctests, downstream integration tests and serialization
+//! test vectors can share one canonical setup instead of each rebuilding the BabyBear
+//! stack by hand.
+//!
+//! ```
+//! use p3_uni_stark_mt::fixtures::{fibonacci_proof, FIXTURE_SEED};
+//! use p3_uni_stark_mt::verify;
+//!
+//! let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+//! verify(
+//!     &fixture.config,
+//!     &fixture.air,
+//!     &fixture.vk,
+//!     &fixture.proof,
+//!     &fixture.public_values,
+//! )
+//! .unwrap();
+//! ```
+
+#[cfg(feature = "prover")]
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use crate::{
+    perm_mmcs, AuxTraceBuilder, CostModel, PermCompress, PermHash, PermMmcs, StarkConfig,
+    TraceGenerator,
+};
+#[cfg(feature = "prover")]
+use crate::{prove, setup, Proof, VerifyingKey};
+
+/// Seed used by the crate's own docs and test vectors.
+pub const FIXTURE_SEED: u64 = 1;
+
+pub type Val = BabyBear;
+pub type Perm = Poseidon2BabyBear<16>;
+pub type MyHash = PermHash<Perm, 16, 8, 8>;
+pub type MyCompress = PermCompress<Perm, 16, 8>;
+pub type ValMmcs = PermMmcs<Val, Perm, 16, 8, 8>;
+pub type Challenge = BinomialExtensionField<Val, 4>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+pub type Dft = Radix2DitParallel<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+/// The canonical BabyBear / Poseidon2 / FRI configuration.
+pub type FixtureConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+/// Log2 of the final FRI polynomial length of [`baby_bear_config`]
+const LOG_FINAL_POLY_LEN: usize = 2;
+
+/// Build the canonical config, with the Poseidon2 permutation drawn from `seed`.
+///
+/// The config knows the FRI parameters of its PCS, so it rejects traces too tall for them
+/// and exports the query indices of its proofs.
+pub fn baby_bear_config(seed: u64) -> FixtureConfig {
+    let (pcs, challenger) = baby_bear_pcs(seed);
+    FixtureConfig::new(pcs, challenger)
+        .with_fri_params(&create_test_fri_params((), LOG_FINAL_POLY_LEN))
+}
+
+/// The PCS and challenger of [`baby_bear_config`]`(seed)`, e.g. to build another crate's
+/// config over the same commitment scheme.
+pub fn baby_bear_pcs(seed: u64) -> (Pcs, Challenger) {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let val_mmcs = perm_mmcs::<Val, Perm, 16, 8, 8>(perm.clone());
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, LOG_FINAL_POLY_LEN);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    (pcs, Challenger::new(perm))
+}
+
+/// The cost model of [`baby_bear_config`]: 4-byte BabyBear elements, a degree 4
+/// extension, 8-element Poseidon2 digests absorbed at rate 8, and its FRI parameters.
+pub fn baby_bear_cost_model() -> CostModel {
+    let fri_params = create_test_fri_params((), LOG_FINAL_POLY_LEN);
+    CostModel {
+        val_bytes: 4,
+        challenge_degree: 4,
+        digest_elems: 8,
+        hash_rate: 8,
+        log_blowup: fri_params.log_blowup,
+        log_final_poly_len: fri_params.log_final_poly_len,
+        num_queries: fri_params.num_queries,
+        num_ood_points: 1,
+    }
+}
+
+/// Fibonacci AIR over two columns starting from `(0, 1)`.
+///
+/// Public values: `[x]`, the expected final value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FibonacciAir {}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let x = builder.public_values()[0];
+
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let (left, right) = (local[0].clone(), local[1].clone());
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_zero(left.clone());
+        when_first_row.assert_one(right.clone());
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(right.clone(), next[0].clone());
+        when_transition.assert_eq(left + right.clone(), next[1].clone());
+
+        builder.when_last_row().assert_eq(right, x);
+    }
+}
+
+/// Generates [`fibonacci_trace`] from its log2 height.
+impl<F: Field> TraceGenerator<F> for FibonacciAir {
+    type Inputs = usize;
+
+    fn generate(&self, &log_n: &usize) -> RowMajorMatrix<F> {
+        fibonacci_trace(log_n)
+    }
+}
+
+/// Fibonacci trace with `1 << log_n` rows starting from `(0, 1)`.
+pub fn fibonacci_trace<F: Field>(log_n: usize) -> RowMajorMatrix<F> {
+    let n = 1 << log_n;
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut left, mut right) = (F::ZERO, F::ONE);
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+/// A proof of [`FibonacciAir`] together with everything needed to verify it.
+#[cfg(feature = "prover")]
+pub struct FibonacciFixture {
+    pub config: FixtureConfig,
+    pub air: FibonacciAir,
+    pub vk: VerifyingKey<FixtureConfig>,
+    pub public_values: Vec<Val>,
+    pub proof: Proof<FixtureConfig>,
+}
+
+/// Prove a `1 << log_n` row Fibonacci trace under [`baby_bear_config`]`(seed)`.
+#[cfg(feature = "prover")]
+pub fn fibonacci_proof(seed: u64, log_n: usize) -> FibonacciFixture {
+    let config = baby_bear_config(seed);
+    let air = FibonacciAir;
+    let trace: RowMajorMatrix<Val> = air.generate(&log_n);
+    let public_values = vec![trace.get(trace.height() - 1, 1).expect("trace is empty")];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("fixture trace is valid");
+    FibonacciFixture {
+        config,
+        air,
+        vk,
+        public_values,
+        proof,
+    }
+}
@@ -1,54 +1,172 @@
 //! Constraint folders for prover and verifier
-
-use p3_air::{AirBuilder, ExtensionBuilder};
-use p3_field::PackedField;
+//!
+//! Both folders combine the constraints as [`ConstraintFolding`] defines: the verifier
+//! through its Horner steps, one constraint after the other, the prover through the
+//! coefficients those steps amount to, which it applies to every point of the quotient
+//! domain at once.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_air::{AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PairBuilder};
+use p3_field::Field;
+#[cfg(feature = "prover")]
+use p3_field::{BasedVectorSpace, PackedField, PackedValue};
+#[cfg(feature = "prover")]
 use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::Matrix;
+
+use crate::{Challenge, ChallengePlan, Val};
+#[cfg(feature = "prover")]
+use crate::{PackedChallenge, PackedVal};
+
+/// How the constraints of an AIR are combined into one value.
+///
+/// Each phase's constraints are Horner-evaluated in that phase's alpha, in the order the
+/// AIR asserts them, and the phases are summed: with a single alpha, the `n` constraints
+/// fold to `C_0·α^(n-1) + … + C_(n-2)·α + C_(n-1)`.
+#[derive(Clone, Copy, Debug)]
+pub enum ConstraintFolding<'a, EF> {
+    /// Every constraint in a single alpha
+    Single(EF),
+    /// Each constraint in the alpha of its phase
+    PerPhase {
+        /// The alpha of each phase
+        alphas: &'a [EF],
+        /// The phase of each constraint, indexing `alphas`
+        constraint_phases: &'a [usize],
+    },
+}
+
+impl<EF: Field> ConstraintFolding<'_, EF> {
+    /// Number of accumulators [`fold`](Self::fold) takes: one per phase.
+    pub const fn num_accumulators(&self) -> usize {
+        match self {
+            Self::Single(_) => 1,
+            Self::PerPhase { alphas, .. } => alphas.len(),
+        }
+    }
+
+    /// Horner step folding `x`, the value of constraint `index`, into the accumulator of
+    /// its phase.
+    ///
+    /// A constraint beyond the known phases is not folded; its caller rejects the count
+    /// afterwards.
+    pub fn fold(&self, accumulators: &mut [EF], index: usize, x: EF) {
+        let (alpha, accumulator) = match self {
+            Self::Single(alpha) => (Some(alpha), accumulators.first_mut()),
+            Self::PerPhase {
+                alphas,
+                constraint_phases,
+            } => match constraint_phases.get(index) {
+                Some(&phase) => (alphas.get(phase), accumulators.get_mut(phase)),
+                None => (None, None),
+            },
+        };
+        if let (Some(&alpha), Some(accumulator)) = (alpha, accumulator) {
+            *accumulator = *accumulator * alpha + x;
+        }
+    }
+
+    /// The folded value of the constraints from the accumulators of [`fold`](Self::fold).
+    pub fn combine(accumulators: &[EF]) -> EF {
+        accumulators.iter().copied().sum()
+    }
 
-use crate::{Challenge, Val};
+    /// The coefficient each of `num_constraints` constraints ends up multiplied by in the
+    /// folded value: within each phase, the powers of its alpha, highest first.
+    pub fn coefficients(&self, num_constraints: usize) -> Vec<EF> {
+        let phase = |index: usize| match self {
+            Self::Single(_) => 0,
+            Self::PerPhase {
+                constraint_phases, ..
+            } => constraint_phases[index],
+        };
+        let alpha = |phase: usize| match self {
+            Self::Single(alpha) => *alpha,
+            Self::PerPhase { alphas, .. } => alphas[phase],
+        };
+
+        // The last constraint of each phase is folded last, so it keeps coefficient one
+        let mut powers = vec![EF::ONE; self.num_accumulators()];
+        let mut coefficients = vec![EF::ZERO; num_constraints];
+        for index in (0..num_constraints).rev() {
+            let phase = phase(index);
+            coefficients[index] = powers[phase];
+            powers[phase] *= alpha(phase);
+        }
+        coefficients
+    }
+}
 
 /// Builder for evaluating constraints during proving.
 ///
-/// This folder accumulates constraints using random challenges, computing:
-/// `C_0 + α·C_1 + α²·C_2 + ...`
+/// This folder accumulates constraints with the precomputed
+/// [`ConstraintFolding::coefficients`]: `c_0·C_0 + c_1·C_1 + …`
+///
+/// Like upstream p3-uni-stark, it works on packed values: each lane of a [`PackedVal`]
+/// holds a different point of the quotient domain, so one evaluation of the AIR covers
+/// `PackedVal::<SC>::WIDTH` points.
+#[cfg(feature = "prover")]
 pub struct ProverFolder<'a, SC: crate::StarkGenericConfig>
 where
     Val<SC>: PackedField,
 {
     /// Main trace values (local and next rows, packed)
-    pub main: RowMajorMatrixView<'a, Val<SC>>,
+    pub main: RowMajorMatrixView<'a, PackedVal<SC>>,
+
+    /// Preprocessed trace values (local and next rows, packed)
+    /// Empty if no preprocessed trace
+    pub preprocessed: RowMajorMatrixView<'a, PackedVal<SC>>,
 
     /// Auxiliary trace values (local and next rows, packed)
     /// Empty if no auxiliary trace
-    pub aux: RowMajorMatrixView<'a, Challenge<SC>>,
+    pub aux: RowMajorMatrixView<'a, PackedChallenge<SC>>,
+
+    /// Widths of the aux matrices of all phases, in phase order
+    pub aux_matrix_widths: &'a [usize],
+
+    /// Public input/output values
+    pub public_values: &'a [Val<SC>],
+
+    /// Challenges of all auxiliary phases, concatenated in phase order and broadcast to
+    /// every lane
+    pub aux_challenges: &'a [PackedChallenge<SC>],
+
+    /// Values exposed by all auxiliary phases, concatenated in phase order and broadcast to
+    /// every lane
+    pub aux_exposed_values: &'a [PackedChallenge<SC>],
 
     /// Selector: 1 on first row, 0 elsewhere
-    pub is_first_row: Val<SC>,
+    pub is_first_row: PackedVal<SC>,
 
     /// Selector: 1 on last row, 0 elsewhere
-    pub is_last_row: Val<SC>,
+    pub is_last_row: PackedVal<SC>,
 
     /// Selector: 1 on all rows except last, 0 on last
-    pub is_transition: Val<SC>,
+    pub is_transition: PackedVal<SC>,
 
-    /// Powers of α for constraint randomization
+    /// Coefficient of each constraint, see [`ConstraintFolding::coefficients`]
     pub alpha_powers: &'a [Challenge<SC>],
 
     /// Accumulated constraint value
-    pub accumulator: Challenge<SC>,
+    pub accumulator: PackedChallenge<SC>,
 
     /// Current constraint index
     pub constraint_index: usize,
 }
 
+#[cfg(feature = "prover")]
 impl<'a, SC> AirBuilder for ProverFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
     Val<SC>: PackedField,
 {
     type F = Val<SC>;
-    type Expr = Val<SC>;
-    type Var = Val<SC>;
-    type M = RowMajorMatrixView<'a, Val<SC>>;
+    type Expr = PackedVal<SC>;
+    type Var = PackedVal<SC>;
+    type M = RowMajorMatrixView<'a, PackedVal<SC>>;
 
     fn main(&self) -> Self::M {
         self.main
@@ -68,52 +186,157 @@ where
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
-        let x = x.into();
-        let alpha = self.alpha_powers[self.constraint_index];
+        let x: PackedVal<SC> = x.into();
+        let alpha = PackedChallenge::<SC>::from(self.alpha_powers[self.constraint_index]);
         self.accumulator += alpha * x;
         self.constraint_index += 1;
     }
 }
 
+#[cfg(feature = "prover")]
+impl<'a, SC> PairBuilder for ProverFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+{
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<'a, SC> AirBuilderWithPublicValues for ProverFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+{
+    type PublicVar = Val<SC>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+#[cfg(feature = "prover")]
 impl<'a, SC> ExtensionBuilder for ProverFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
     Val<SC>: PackedField,
 {
     type EF = Challenge<SC>;
-    type ExprEF = Challenge<SC>;
-    type VarEF = Challenge<SC>;
+    type ExprEF = PackedChallenge<SC>;
+    type VarEF = PackedChallenge<SC>;
 
     fn assert_zero_ext<I>(&mut self, x: I)
     where
         I: Into<Self::ExprEF>,
     {
-        let x = x.into();
-        let alpha = self.alpha_powers[self.constraint_index];
+        let x: PackedChallenge<SC> = x.into();
+        let alpha = PackedChallenge::<SC>::from(self.alpha_powers[self.constraint_index]);
         self.accumulator += alpha * x;
         self.constraint_index += 1;
     }
 }
 
+/// The challenge in lane `lane` of `packed`.
+#[cfg(feature = "prover")]
+pub(crate) fn unpack_challenge<SC>(packed: PackedChallenge<SC>, lane: usize) -> Challenge<SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    let coefficients = packed.as_basis_coefficients_slice();
+    Challenge::<SC>::from_basis_coefficients_fn(|k| coefficients[k].as_slice()[lane])
+}
+
 /// Extension trait for accessing auxiliary trace in constraints.
 pub trait AuxBuilder: ExtensionBuilder {
-    /// Matrix type for auxiliary trace
-    type MAux;
+    /// Matrix type for auxiliary trace (local and next rows)
+    type MAux: Matrix<Self::VarEF>;
 
     /// Access the auxiliary trace columns
     fn aux(&self) -> Self::MAux;
+
+    /// Widths of the aux matrices of all phases, in phase order, see
+    /// [`crate::AuxTraceBuilder::aux_phase_matrix_widths`]
+    fn aux_matrix_widths(&self) -> &[usize];
+
+    /// Columns of [`aux`](Self::aux) holding aux matrix `matrix`, counting the matrices of
+    /// all phases in phase order
+    ///
+    /// # Panics
+    /// If there are not more than `matrix` aux matrices
+    fn aux_matrix_columns(&self, matrix: usize) -> Range<usize> {
+        let widths = self.aux_matrix_widths();
+        let start = widths[..matrix].iter().sum();
+        start..start + widths[matrix]
+    }
+
+    /// Challenges sampled for all auxiliary phases, concatenated in phase order
+    ///
+    /// Constraints use them like any other extension variable, e.g.
+    /// `let r: AB::ExprEF = builder.aux_challenges()[0].into();` for a LogUp denominator.
+    fn aux_challenges(&self) -> &[Self::VarEF];
+
+    /// Challenge `name` of `group` in `plan`, the AIR's
+    /// [`challenge_plan`](crate::AuxTraceBuilder::challenge_plan)
+    ///
+    /// The lookup walks the plan, so AIRs evaluated row by row should hold on to the plan
+    /// rather than rebuild it for every call.
+    ///
+    /// # Panics
+    /// If `plan` declares no such challenge
+    fn aux_challenge(&self, plan: &ChallengePlan, group: &str, name: &str) -> Self::VarEF {
+        let index = plan
+            .index(group, name)
+            .unwrap_or_else(|| panic!("no aux challenge {group}.{name} in the challenge plan"));
+        self.aux_challenges()[index]
+    }
+
+    /// Values exposed by all auxiliary phases, concatenated in phase order
+    ///
+    /// See [`crate::AuxTraceBuilder::aux_phase_exposed_values`].
+    fn aux_exposed_values(&self) -> &[Self::VarEF];
+
+    /// [`assert_zero`](AirBuilder::assert_zero), naming the constraint
+    ///
+    /// The constraint is folded like any other; debug tooling such as
+    /// `debug::check_constraints` reports it by `label` rather than by index when it fails.
+    fn assert_zero_labeled<I: Into<Self::Expr>>(&mut self, x: I, label: &'static str) {
+        let _ = label;
+        self.assert_zero(x);
+    }
+
+    /// [`assert_zero_ext`](ExtensionBuilder::assert_zero_ext), naming the constraint like
+    /// [`assert_zero_labeled`](Self::assert_zero_labeled)
+    fn assert_zero_ext_labeled<I: Into<Self::ExprEF>>(&mut self, x: I, label: &'static str) {
+        let _ = label;
+        self.assert_zero_ext(x);
+    }
 }
 
+#[cfg(feature = "prover")]
 impl<'a, SC> AuxBuilder for ProverFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
     Val<SC>: PackedField,
 {
-    type MAux = RowMajorMatrixView<'a, Challenge<SC>>;
+    type MAux = RowMajorMatrixView<'a, PackedChallenge<SC>>;
 
     fn aux(&self) -> Self::MAux {
         self.aux
     }
+
+    fn aux_matrix_widths(&self) -> &[usize] {
+        self.aux_matrix_widths
+    }
+
+    fn aux_challenges(&self) -> &[Self::VarEF] {
+        self.aux_challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
 }
 
 /// Builder for verifying constraints.
@@ -127,12 +350,30 @@ pub struct VerifierFolder<'a, SC: crate::StarkGenericConfig> {
     /// Main trace values (next row)
     pub main_next: &'a [Challenge<SC>],
 
+    /// Preprocessed trace values (local row)
+    pub preprocessed_local: &'a [Challenge<SC>],
+
+    /// Preprocessed trace values (next row)
+    pub preprocessed_next: &'a [Challenge<SC>],
+
     /// Auxiliary trace values (local row)
     pub aux_local: &'a [Challenge<SC>],
 
     /// Auxiliary trace values (next row)
     pub aux_next: &'a [Challenge<SC>],
 
+    /// Widths of the aux matrices of all phases, in phase order
+    pub aux_matrix_widths: &'a [usize],
+
+    /// Public input/output values
+    pub public_values: &'a [Val<SC>],
+
+    /// Challenges of all auxiliary phases, concatenated in phase order
+    pub aux_challenges: &'a [Challenge<SC>],
+
+    /// Values exposed by all auxiliary phases, concatenated in phase order
+    pub aux_exposed_values: &'a [Challenge<SC>],
+
     /// Selector: 1 on first row, 0 elsewhere
     pub is_first_row: Challenge<SC>,
 
@@ -145,8 +386,54 @@ pub struct VerifierFolder<'a, SC: crate::StarkGenericConfig> {
     /// Randomness for combining constraints
     pub alpha: Challenge<SC>,
 
+    /// Randomness for combining the constraints of each phase, replacing `alpha` when not
+    /// empty
+    pub phase_alphas: &'a [Challenge<SC>],
+
+    /// Phase of each constraint, indexing `phase_alphas`
+    pub constraint_phases: &'a [usize],
+
+    /// Accumulated constraint value of each phase, when `phase_alphas` is not empty
+    pub phase_accumulators: Vec<Challenge<SC>>,
+
     /// Accumulated constraint value
     pub accumulator: Challenge<SC>,
+
+    /// Number of constraints folded so far
+    pub constraint_index: usize,
+
+    /// Index and label of each constraint asserted with a label, in assertion order
+    pub constraint_labels: Vec<(usize, &'static str)>,
+}
+
+impl<'a, SC: crate::StarkGenericConfig> VerifierFolder<'a, SC> {
+    /// The folding of this folder: `alpha` into `accumulator`, or with `phase_alphas` set,
+    /// the alpha of each constraint's phase into `phase_accumulators`.
+    pub const fn folding(&self) -> ConstraintFolding<'a, Challenge<SC>> {
+        if self.phase_alphas.is_empty() {
+            ConstraintFolding::Single(self.alpha)
+        } else {
+            ConstraintFolding::PerPhase {
+                alphas: self.phase_alphas,
+                constraint_phases: self.constraint_phases,
+            }
+        }
+    }
+
+    /// Fold `x` with [`ConstraintFolding::fold`].
+    ///
+    /// A constraint beyond `constraint_phases` is counted but not folded; the caller rejects
+    /// the count afterwards.
+    fn fold(&mut self, x: Challenge<SC>) {
+        let folding = self.folding();
+        let accumulators = if self.phase_alphas.is_empty() {
+            core::slice::from_mut(&mut self.accumulator)
+        } else {
+            &mut self.phase_accumulators[..]
+        };
+        folding.fold(accumulators, self.constraint_index, x);
+        self.constraint_index += 1;
+    }
 }
 
 /// Simple view for verifier (just vectors of challenges)
@@ -171,7 +458,7 @@ impl<'a, EF: Copy> VerifierView<'a, EF> {
 }
 
 // Implement Matrix trait for VerifierView
-impl<'a, EF: Copy + Send + Sync> p3_matrix::Matrix<EF> for VerifierView<'a, EF> {
+impl<'a, EF: Copy + Send + Sync> Matrix<EF> for VerifierView<'a, EF> {
     fn width(&self) -> usize {
         self.local.len()
     }
@@ -224,7 +511,27 @@ where
     }
 
     fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
-        self.accumulator = self.accumulator * self.alpha + x.into();
+        self.fold(x.into());
+    }
+}
+
+impl<'a, SC> PairBuilder for VerifierFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    fn preprocessed(&self) -> Self::M {
+        VerifierView::new(self.preprocessed_local, self.preprocessed_next)
+    }
+}
+
+impl<'a, SC> AirBuilderWithPublicValues for VerifierFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    type PublicVar = Val<SC>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
     }
 }
 
@@ -240,7 +547,7 @@ where
     where
         I: Into<Self::ExprEF>,
     {
-        self.accumulator = self.accumulator * self.alpha + x.into();
+        self.fold(x.into());
     }
 }
 
@@ -253,4 +560,26 @@ where
     fn aux(&self) -> Self::MAux {
         VerifierView::new(self.aux_local, self.aux_next)
     }
+
+    fn aux_matrix_widths(&self) -> &[usize] {
+        self.aux_matrix_widths
+    }
+
+    fn aux_challenges(&self) -> &[Self::VarEF] {
+        self.aux_challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn assert_zero_labeled<I: Into<Self::Expr>>(&mut self, x: I, label: &'static str) {
+        self.constraint_labels.push((self.constraint_index, label));
+        self.fold(x.into());
+    }
+
+    fn assert_zero_ext_labeled<I: Into<Self::ExprEF>>(&mut self, x: I, label: &'static str) {
+        self.constraint_labels.push((self.constraint_index, label));
+        self.fold(x.into());
+    }
 }
@@ -0,0 +1,112 @@
+//! FRI parameters of a [`StarkConfig`]
+//!
+//! `TwoAdicFriPcs` keeps its [`FriParameters`] to itself, so a config records them through
+//! [`StarkConfig::with_fri_params`]. The blowup bounds the trace height, see
+//! [`crate::StarkGenericConfig::max_supported_log_degree`], and the rest lets
+//! [`crate::export_transcript`] replay where FRI queries the opening proof: the PCS observes
+//! the opened values and samples its batching challenge, FRI observes each commit-phase
+//! commitment before sampling its folding challenge, observes the final polynomial, checks
+//! the proof-of-work witness and only then samples one index per query.
+
+use alloc::vec::Vec;
+
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+use p3_commit::{Mmcs, Pcs};
+use p3_field::{BasedVectorSpace, ExtensionField, TwoAdicField};
+use p3_fri::{FriParameters, FriProof, TwoAdicFriPcs};
+
+use crate::verifier::OpeningClaims;
+use crate::{QueryIndices, StarkConfig, StarkGenericConfig};
+
+/// The FRI parameters [`StarkConfig::with_fri_params`] records.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FriShape {
+    log_blowup: usize,
+    log_final_poly_len: usize,
+    proof_of_work_bits: usize,
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger>
+    StarkConfig<TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>, Challenge, Challenger>
+where
+    Val: TwoAdicField,
+{
+    /// Record `fri_params`, the parameters the PCS was built with.
+    ///
+    /// Like [`StarkConfig::with_log_blowup`], the config then rejects traces whose LDE does
+    /// not fit in the field's two-adic subgroup; it also reports the FRI query indices of a
+    /// proof through [`QueryIndices`].
+    pub fn with_fri_params<M>(mut self, fri_params: &FriParameters<M>) -> Self {
+        self.max_log_degree = Some(Val::TWO_ADICITY.saturating_sub(fri_params.log_blowup));
+        self.fri_shape = Some(FriShape {
+            log_blowup: fri_params.log_blowup,
+            log_final_poly_len: fri_params.log_final_poly_len,
+            proof_of_work_bits: fri_params.proof_of_work_bits,
+        });
+        self
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger, Witness, InputProof> QueryIndices
+    for StarkConfig<TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>, Challenge, Challenger>
+where
+    Self: StarkGenericConfig<
+        Pcs = TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>,
+        Challenge = Challenge,
+        Challenger = Challenger,
+    >,
+    TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>:
+        Pcs<Challenge, Challenger, Proof = FriProof<Challenge, FriMmcs, Witness, InputProof>>,
+    Challenge: ExtensionField<Val>,
+    FriMmcs: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val>
+        + CanObserve<FriMmcs::Commitment>
+        + CanSample<Challenge>
+        + CanSampleBits<usize>
+        + GrindingChallenger<Witness = Witness>,
+    Witness: Copy,
+{
+    fn query_indices(
+        &self,
+        claims: &OpeningClaims<Self>,
+        proof: &FriProof<Challenge, FriMmcs, Witness, InputProof>,
+        challenger: &mut Challenger,
+    ) -> Option<Vec<usize>> {
+        let shape = self.fri_shape?;
+
+        // The opened values in claim order, then the batching challenge
+        for (_, matrices) in claims {
+            for (_, points) in matrices {
+                for (_, values) in points {
+                    for value in values {
+                        challenger.observe_slice(value.as_basis_coefficients_slice());
+                    }
+                }
+            }
+        }
+        let _alpha: Challenge = challenger.sample();
+
+        // One folding round per commitment, each folding challenge sampled after it
+        for commit in &proof.commit_phase_commits {
+            challenger.observe(commit.clone());
+            let _beta: Challenge = challenger.sample();
+        }
+        for coefficient in &proof.final_poly {
+            challenger.observe_slice(coefficient.as_basis_coefficients_slice());
+        }
+        // Whether the witness holds is for verification to say
+        let _ = challenger.check_witness(shape.proof_of_work_bits, proof.pow_witness);
+
+        // Queries index the largest LDE, from which the rounds fold down to the final
+        // polynomial
+        let log_max_height =
+            proof.commit_phase_commits.len() + shape.log_blowup + shape.log_final_poly_len;
+        Some(
+            proof
+                .query_proofs
+                .iter()
+                .map(|_| challenger.sample_bits(log_max_height))
+                .collect(),
+        )
+    }
+}
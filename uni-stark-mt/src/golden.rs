@@ -0,0 +1,237 @@
+//! Golden-file regression tracking for prover performance
+//!
+//! [`record_golden`] proves every case of [`GOLDEN_CASES`] on the fixtures config and
+//! records its proof size and the time spent in each [`ProverPhase`]. The records are
+//! serde-serializable, to be kept in a JSON golden file, and [`regressions`] compares a
+//! fresh run against them:
+//!
+//! ```text
+//! cargo test --release -p p3-uni-stark-mt --features golden --test golden -- --ignored
+//! ```
+//!
+//! compares against `golden/prover.json` and writes it when it is missing or when
+//! `UPDATE_GOLDEN` is set.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fixtures::{baby_bear_config, fibonacci_trace, FibonacciAir, Val, FIXTURE_SEED};
+use crate::{prove_with_telemetry, serialized_size, setup, ProverPhase, ProverTelemetry};
+
+/// Timing differences below this are noise and never reported.
+pub const MIN_TIMING_REGRESSION: Duration = Duration::from_millis(1);
+
+/// A proof whose size and timings are tracked.
+#[derive(Clone, Copy, Debug)]
+pub struct GoldenCase {
+    /// Name of the case in the golden file
+    pub name: &'static str,
+    /// Log2 of the Fibonacci trace height
+    pub log_n: usize,
+    /// Quotient leaf width of the config, see [`crate::StarkConfig::with_max_leaf_width`]
+    pub max_leaf_width: Option<usize>,
+}
+
+/// The tracked cases: Fibonacci traces of a few heights, and one with narrow quotient leaves.
+pub const GOLDEN_CASES: &[GoldenCase] = &[
+    GoldenCase {
+        name: "fibonacci_2^8",
+        log_n: 8,
+        max_leaf_width: None,
+    },
+    GoldenCase {
+        name: "fibonacci_2^12",
+        log_n: 12,
+        max_leaf_width: None,
+    },
+    GoldenCase {
+        name: "fibonacci_2^16",
+        log_n: 16,
+        max_leaf_width: None,
+    },
+    GoldenCase {
+        name: "fibonacci_2^12_narrow_leaves",
+        log_n: 12,
+        max_leaf_width: Some(2),
+    },
+];
+
+/// Time spent in one prover phase.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    /// The phase
+    pub phase: ProverPhase,
+    /// Wall-clock time from its start to its end, in microseconds
+    pub micros: u64,
+}
+
+/// Proof size and phase timings of one [`GoldenCase`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenRecord {
+    /// Name of the case
+    pub name: String,
+    /// [`serialized_size`] of the proof
+    pub proof_size: usize,
+    /// Time spent in each phase, in the order the prover ran them
+    pub phases: Vec<PhaseTiming>,
+}
+
+/// Records how long each phase takes.
+#[derive(Debug, Default)]
+pub struct PhaseTimer {
+    running: Vec<(ProverPhase, Instant)>,
+    timings: Vec<PhaseTiming>,
+}
+
+impl PhaseTimer {
+    /// The phases finished so far, in order.
+    pub fn timings(&self) -> &[PhaseTiming] {
+        &self.timings
+    }
+}
+
+impl ProverTelemetry for &mut PhaseTimer {
+    fn phase_started(&mut self, phase: ProverPhase, _rows: usize) {
+        self.running.push((phase, Instant::now()));
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        if let Some(i) = self.running.iter().rposition(|&(p, _)| p == phase) {
+            let (_, started) = self.running.remove(i);
+            self.timings.push(PhaseTiming {
+                phase,
+                micros: started.elapsed().as_micros() as u64,
+            });
+        }
+    }
+}
+
+/// Prove `case` and record its proof size and phase timings.
+pub fn record_case(case: &GoldenCase) -> GoldenRecord {
+    let mut config = baby_bear_config(FIXTURE_SEED);
+    if let Some(max_leaf_width) = case.max_leaf_width {
+        config = config.with_max_leaf_width(max_leaf_width);
+    }
+    let air = FibonacciAir;
+    let trace = fibonacci_trace::<Val>(case.log_n);
+    let public_values = [*trace.values.last().expect("trace is empty")];
+
+    let (pk, _) = setup(&config, &air);
+    let mut timer = PhaseTimer::default();
+    let proof = prove_with_telemetry(&config, &air, &pk, trace, &public_values, &mut timer)
+        .expect("fixture trace is valid");
+    GoldenRecord {
+        name: case.name.into(),
+        proof_size: serialized_size(&proof),
+        phases: timer.timings,
+    }
+}
+
+/// Record every case of [`GOLDEN_CASES`].
+pub fn record_golden() -> Vec<GoldenRecord> {
+    GOLDEN_CASES.iter().map(record_case).collect()
+}
+
+/// A measurement worse than its golden value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Regression {
+    /// A golden case is missing from the current run
+    MissingCase(String),
+    /// The proof grew
+    ProofSize {
+        /// Name of the case
+        case: String,
+        /// Golden proof size in bytes
+        golden: usize,
+        /// Current proof size in bytes
+        current: usize,
+    },
+    /// A phase got slower, or disappeared
+    Timing {
+        /// Name of the case
+        case: String,
+        /// The phase
+        phase: ProverPhase,
+        /// Golden time in microseconds
+        golden: u64,
+        /// Current time in microseconds, `None` if the phase no longer runs
+        current: Option<u64>,
+    },
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingCase(case) => write!(f, "{case}: missing from the current run"),
+            Self::ProofSize {
+                case,
+                golden,
+                current,
+            } => write!(f, "{case}: proof size {golden} -> {current} bytes"),
+            Self::Timing {
+                case,
+                phase,
+                golden,
+                current: Some(current),
+            } => write!(f, "{case}: {phase:?} {golden} -> {current} µs"),
+            Self::Timing {
+                case,
+                phase,
+                golden,
+                current: None,
+            } => write!(f, "{case}: {phase:?} took {golden} µs and no longer runs"),
+        }
+    }
+}
+
+/// Compare `current` against `golden`, reporting every measurement more than `threshold`
+/// (a fraction, `0.1` for 10%) above its golden value.
+///
+/// Timings also have to be [`MIN_TIMING_REGRESSION`] slower to count. Cases and phases
+/// only present in `current` are new, not regressions.
+pub fn regressions(
+    golden: &[GoldenRecord],
+    current: &[GoldenRecord],
+    threshold: f64,
+) -> Vec<Regression> {
+    let exceeds = |golden: u64, current: u64| current as f64 > golden as f64 * (1.0 + threshold);
+    let min_micros = MIN_TIMING_REGRESSION.as_micros() as u64;
+
+    let mut found = Vec::new();
+    for golden in golden {
+        let Some(current) = current.iter().find(|record| record.name == golden.name) else {
+            found.push(Regression::MissingCase(golden.name.clone()));
+            continue;
+        };
+        if exceeds(golden.proof_size as u64, current.proof_size as u64) {
+            found.push(Regression::ProofSize {
+                case: golden.name.clone(),
+                golden: golden.proof_size,
+                current: current.proof_size,
+            });
+        }
+        for timing in &golden.phases {
+            let current_micros = current
+                .phases
+                .iter()
+                .find(|current| current.phase == timing.phase)
+                .map(|current| current.micros);
+            let slower = current_micros.is_none_or(|micros| {
+                exceeds(timing.micros, micros) && micros.saturating_sub(timing.micros) >= min_micros
+            });
+            if slower {
+                found.push(Regression::Timing {
+                    case: golden.name.clone(),
+                    phase: timing.phase,
+                    golden: timing.micros,
+                    current: current_micros,
+                });
+            }
+        }
+    }
+    found
+}
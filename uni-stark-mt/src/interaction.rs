@@ -0,0 +1,324 @@
+//! Bus interactions between tables
+//!
+//! Chips declare what they send to and receive from numbered buses with an
+//! [`InteractionBuilder`], SP1/Valida style. The resulting [`BusInteractions`] compiles the
+//! declarations into LogUp columns and constraints like [`crate::LogUpLookup`], but each
+//! fingerprint also covers its bus, and the running sum ends at an exposed value instead of
+//! zero: the net sum of the table, which the verifier adds up over the tables of a proof
+//! through [`crate::AuxTraceBuilder::interaction_sum`].
+
+use alloc::vec::Vec;
+
+use p3_air::PairBuilder;
+use p3_field::{batch_multiplicative_inverse, ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::lookup::fingerprint;
+use crate::{AuxBuilder, LookupKind, Multiplicity};
+
+/// A tuple of main trace columns sent to or received from a bus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BusInteraction {
+    /// The bus, shared by the tables exchanging these values
+    pub bus: usize,
+    /// Whether the values are sent or received
+    pub kind: LookupKind,
+    /// Main trace columns forming the tuple
+    pub columns: Vec<usize>,
+    /// Multiplicity of each row
+    pub multiplicity: Multiplicity,
+}
+
+/// Collects the bus interactions of one chip.
+///
+/// ```ignore
+/// let bus = InteractionBuilder::new()
+///     .send(MEMORY_BUS, vec![ADDR, VALUE], Multiplicity::Column(IS_WRITE))
+///     .receive(RANGE_BUS, vec![VALUE], Multiplicity::One)
+///     .build();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InteractionBuilder {
+    interactions: Vec<BusInteraction>,
+}
+
+impl InteractionBuilder {
+    pub const fn new() -> Self {
+        Self {
+            interactions: Vec::new(),
+        }
+    }
+
+    /// Send `columns` to `bus`, `multiplicity` times per row.
+    pub fn send(mut self, bus: usize, columns: Vec<usize>, multiplicity: Multiplicity) -> Self {
+        self.interactions.push(BusInteraction {
+            bus,
+            kind: LookupKind::Send,
+            columns,
+            multiplicity,
+        });
+        self
+    }
+
+    /// Receive `columns` from `bus`, `multiplicity` times per row.
+    pub fn receive(mut self, bus: usize, columns: Vec<usize>, multiplicity: Multiplicity) -> Self {
+        self.interactions.push(BusInteraction {
+            bus,
+            kind: LookupKind::Receive,
+            columns,
+            multiplicity,
+        });
+        self
+    }
+
+    pub fn build(self) -> BusInteractions {
+        BusInteractions {
+            interactions: self.interactions,
+        }
+    }
+}
+
+/// The bus interactions of one chip, as a LogUp argument over its main trace.
+///
+/// Every interaction contributes `± m / (α - fingerprint)` per row, with the fingerprint
+/// `bus + β·v_0 + β²·v_1 + …`, so equal values on different buses do not cancel. An AIR
+/// using the gadget forwards [`aux_width`](Self::aux_width),
+/// [`NUM_CHALLENGES`](Self::NUM_CHALLENGES) and [`build_aux_trace`](Self::build_aux_trace)
+/// from its [`crate::AuxTraceBuilder`] impl, exposes
+/// [`cumulative_sum`](Self::cumulative_sum) from phase 0 and points
+/// [`interaction_sum`](crate::AuxTraceBuilder::interaction_sum) at it, and calls
+/// [`eval`](Self::eval) from its `Air::eval`. As with [`crate::LogUpLookup`], the gadget
+/// expects its challenges first among the AIR's aux challenges, and every table of a proof
+/// draws the same ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BusInteractions {
+    /// Interactions of this chip
+    pub interactions: Vec<BusInteraction>,
+}
+
+impl BusInteractions {
+    /// Challenges used by the gadget: `α` (the pole) and `β` (the fingerprint base).
+    pub const NUM_CHALLENGES: usize = 2;
+
+    /// Number of aux columns: one helper per interaction plus the running sum.
+    pub fn aux_width(&self) -> usize {
+        self.interactions.len() + 1
+    }
+
+    /// Build the helper and running-sum columns.
+    ///
+    /// # Panics
+    /// - If fewer than [`NUM_CHALLENGES`](Self::NUM_CHALLENGES) challenges are given
+    /// - If a fingerprint hits `α` (negligible probability for honest traces)
+    pub fn build_aux_trace<F, EF>(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF>
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+    {
+        let (alpha, beta) = (challenges[0], challenges[1]);
+        let height = main.height();
+        let width = self.aux_width();
+
+        // Denominators α - fingerprint for every (row, interaction), inverted in one batch
+        let denominators: Vec<EF> = main
+            .values
+            .chunks_exact(main.width)
+            .flat_map(|row| {
+                self.interactions.iter().map(move |interaction| {
+                    alpha
+                        - (beta * fingerprint(row, &interaction.columns, beta)
+                            + F::from_usize(interaction.bus))
+                })
+            })
+            .collect();
+        let inverses = batch_multiplicative_inverse(&denominators);
+
+        let mut values = EF::zero_vec(height * width);
+        let mut sum = EF::ZERO;
+        for (r, (aux_row, row_inverses)) in values
+            .chunks_exact_mut(width)
+            .zip(inverses.chunks_exact(self.interactions.len().max(1)))
+            .enumerate()
+        {
+            for (k, (interaction, &inverse)) in
+                self.interactions.iter().zip(row_inverses).enumerate()
+            {
+                let multiplicity = match interaction.multiplicity {
+                    Multiplicity::One => F::ONE,
+                    Multiplicity::Column(c) => main.values[r * main.width + c],
+                };
+                let helper = inverse * multiplicity;
+                aux_row[k] = helper;
+                match interaction.kind {
+                    LookupKind::Send => sum += helper,
+                    LookupKind::Receive => sum -= helper,
+                }
+            }
+            aux_row[width - 1] = sum;
+        }
+
+        RowMajorMatrix::new(values, width)
+    }
+
+    /// The net sum of the chip's interactions: the running sum on the last row of the aux
+    /// trace `aux_phase`, with the gadget's columns from `aux_offset` on.
+    pub fn cumulative_sum<EF: Field>(
+        &self,
+        aux_phase: &RowMajorMatrix<EF>,
+        aux_offset: usize,
+    ) -> EF {
+        let last_row = aux_phase.height() - 1;
+        aux_phase.values[last_row * aux_phase.width + aux_offset + self.interactions.len()]
+    }
+
+    /// Emit the LogUp constraints, reading the gadget's columns from `aux_offset` on and the
+    /// chip's net sum from exposed value `sum_index`.
+    ///
+    /// - each helper satisfies `h · (α - fingerprint) = m`
+    /// - the running sum starts at the first row's signed helper sum, accumulates the next
+    ///   row's on every transition, and ends at the exposed net sum on the last row
+    pub fn eval<AB: AuxBuilder>(&self, builder: &mut AB, aux_offset: usize, sum_index: usize) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+
+        let aux = builder.aux();
+        let aux_local = aux.row_slice(0).expect("Matrix is empty?");
+        let aux_next = aux.row_slice(1).expect("Matrix only has 1 row?");
+
+        let challenges = builder.aux_challenges();
+        let alpha: AB::ExprEF = challenges[0].into();
+        let beta: AB::ExprEF = challenges[1].into();
+        let net_sum: AB::ExprEF = builder.aux_exposed_values()[sum_index].into();
+
+        let num_interactions = self.interactions.len();
+        let sum_local: AB::ExprEF = aux_local[aux_offset + num_interactions].into();
+        let sum_next: AB::ExprEF = aux_next[aux_offset + num_interactions].into();
+
+        let signed_sum = |helpers: &[AB::VarEF]| {
+            self.interactions
+                .iter()
+                .enumerate()
+                .fold(AB::ExprEF::ZERO, |acc, (k, interaction)| {
+                    let helper: AB::ExprEF = helpers[aux_offset + k].into();
+                    match interaction.kind {
+                        LookupKind::Send => acc + helper,
+                        LookupKind::Receive => acc - helper,
+                    }
+                })
+        };
+        let local_delta = signed_sum(&aux_local[..]);
+        let next_delta = signed_sum(&aux_next[..]);
+
+        for (k, interaction) in self.interactions.iter().enumerate() {
+            let values = interaction.columns.iter().map(|&c| {
+                let value: AB::Expr = local[c].clone().into();
+                AB::ExprEF::from(value)
+            });
+            let bus = AB::ExprEF::from(AB::Expr::from_usize(interaction.bus));
+            let fingerprint = values
+                .rev()
+                .fold(AB::ExprEF::ZERO, |acc, v| acc * beta.clone() + v)
+                * beta.clone()
+                + bus;
+            let multiplicity: AB::Expr = match interaction.multiplicity {
+                Multiplicity::One => AB::Expr::ONE,
+                Multiplicity::Column(c) => local[c].clone().into(),
+            };
+            let helper: AB::ExprEF = aux_local[aux_offset + k].into();
+            builder.assert_eq_ext(
+                helper * (alpha.clone() - fingerprint),
+                AB::ExprEF::from(multiplicity),
+            );
+        }
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(sum_local.clone(), local_delta);
+        builder
+            .when_transition()
+            .assert_eq_ext(sum_next, sum_local.clone() + next_delta);
+        builder.when_last_row().assert_eq_ext(sum_local, net_sum);
+    }
+}
+
+/// Helper and running-sum columns of a table chip receiving every row of its preprocessed
+/// `table` on `bus`, as many times as `multiplicities` says.
+///
+/// The helper is `m / (α - fingerprint)` with the fingerprint of [`BusInteractions`], and
+/// the running sum subtracts the helpers, ending at the chip's net sum.
+pub(crate) fn build_table_receives<F, EF>(
+    bus: usize,
+    table: &RowMajorMatrix<F>,
+    multiplicities: &[F],
+    challenges: &[EF],
+) -> RowMajorMatrix<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    let (alpha, beta) = (challenges[0], challenges[1]);
+    let all_columns: Vec<usize> = (0..table.width).collect();
+    let denominators: Vec<EF> = table
+        .values
+        .chunks_exact(table.width)
+        .map(|row| alpha - (beta * fingerprint(row, &all_columns, beta) + F::from_usize(bus)))
+        .collect();
+    let inverses = batch_multiplicative_inverse(&denominators);
+
+    let mut sum = EF::ZERO;
+    let values = inverses
+        .into_iter()
+        .zip(multiplicities)
+        .flat_map(|(inverse, &multiplicity)| {
+            let helper = inverse * multiplicity;
+            sum -= helper;
+            [helper, sum]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+/// Constraints of [`build_table_receives`]: the helper of each row satisfies
+/// `h · (α - fingerprint) = m` against the preprocessed row and the multiplicity in main
+/// column 0, and the running sum subtracts each helper, ending at exposed value 0.
+pub(crate) fn eval_table_receives<AB: AuxBuilder + PairBuilder>(builder: &mut AB, bus: usize) {
+    let main = builder.main();
+    let multiplicity: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+        .clone()
+        .into();
+
+    let challenges = builder.aux_challenges();
+    let alpha: AB::ExprEF = challenges[0].into();
+    let beta: AB::ExprEF = challenges[1].into();
+    let net_sum: AB::ExprEF = builder.aux_exposed_values()[0].into();
+
+    let preprocessed = builder.preprocessed();
+    let row = preprocessed.row_slice(0).expect("Matrix is empty?");
+    let fingerprint = row.iter().rev().fold(AB::ExprEF::ZERO, |acc, v| {
+        let value: AB::Expr = v.clone().into();
+        acc * beta.clone() + AB::ExprEF::from(value)
+    }) * beta
+        + AB::ExprEF::from(AB::Expr::from_usize(bus));
+
+    let aux = builder.aux();
+    let aux_local = aux.row_slice(0).expect("Matrix is empty?");
+    let aux_next = aux.row_slice(1).expect("Matrix only has 1 row?");
+    let (helper, sum): (AB::ExprEF, AB::ExprEF) = (aux_local[0].into(), aux_local[1].into());
+    let (helper_next, sum_next): (AB::ExprEF, AB::ExprEF) =
+        (aux_next[0].into(), aux_next[1].into());
+
+    builder.assert_eq_ext(
+        helper.clone() * (alpha - fingerprint),
+        AB::ExprEF::from(multiplicity),
+    );
+    builder.when_first_row().assert_eq_ext(sum.clone(), -helper);
+    builder
+        .when_transition()
+        .assert_eq_ext(sum_next, sum.clone() - helper_next);
+    builder.when_last_row().assert_eq_ext(sum, net_sum);
+}
@@ -0,0 +1,67 @@
+//! JSON encoding of proofs and verifying keys
+//!
+//! For tooling, explorers and debugging across languages. The encoding is the serde one of
+//! [`Proof`] and [`VerifyingKey`]: a JSON object per struct, whose keys are the Rust field
+//! names listed in [`PROOF_FIELDS`] and [`VERIFYING_KEY_FIELDS`]. Those names only change
+//! along with [`crate::PROOF_VERSION`], which every proof carries as `"version"`.
+//! Commitments, field elements and opening proofs are encoded by their Plonky3 types.
+//!
+//! ```ignore
+//! let json = json::proof_to_json(&proof);
+//! let limits = ProofLimits::for_air::<MyConfig, _>(&air, &vk);
+//! let decoded: Proof<MyConfig> = json::proof_from_json(&json, limits)?;
+//! ```
+
+use alloc::string::String;
+
+use serde::de::DeserializeSeed;
+
+use crate::{Proof, ProofLimits, StarkGenericConfig, VerifyingKey};
+
+/// Keys of the JSON object encoding a [`Proof`], in encoding order.
+pub const PROOF_FIELDS: &[&str] = crate::limits::PROOF_FIELDS;
+
+/// Keys of the JSON object encoding a [`VerifyingKey`], in encoding order.
+pub const VERIFYING_KEY_FIELDS: &[&str] = &[
+    "main_width",
+    "aux_widths",
+    "instance_parameters",
+    "num_constraints",
+    "constraint_phases",
+    "max_constraint_degree",
+    "log_quotient_degree",
+    "preprocessed",
+];
+
+/// Encode `proof` as compact JSON.
+pub fn proof_to_json<SC: StarkGenericConfig>(proof: &Proof<SC>) -> String {
+    serde_json::to_string(proof).expect("a proof always encodes")
+}
+
+/// Decode a proof from `json`, rejecting one exceeding `limits` as
+/// [`ProofLimits::proof_seed`] does.
+///
+/// # Returns
+/// The proof, or the error of the first malformed or oversized part, including trailing
+/// characters after the proof object
+pub fn proof_from_json<SC: StarkGenericConfig>(
+    json: &str,
+    limits: ProofLimits,
+) -> Result<Proof<SC>, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let proof = limits.proof_seed::<SC>().deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(proof)
+}
+
+/// Encode `vk` as compact JSON.
+pub fn vk_to_json<SC: StarkGenericConfig>(vk: &VerifyingKey<SC>) -> String {
+    serde_json::to_string(vk).expect("a verifying key always encodes")
+}
+
+/// Decode a verifying key from `json`.
+pub fn vk_from_json<SC: StarkGenericConfig>(
+    json: &str,
+) -> Result<VerifyingKey<SC>, serde_json::Error> {
+    serde_json::from_str(json)
+}
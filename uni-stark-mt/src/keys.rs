@@ -0,0 +1,217 @@
+//! Proving and verifying keys
+//!
+//! Preprocessed (fixed) columns such as selectors, lookup tables or round constants are
+//! the same for every proof of an AIR. [`setup`] commits them once; the prover reuses the
+//! committed data from the [`ProvingKey`] and the verifier only needs the commitment held
+//! in the [`VerifyingKey`].
+//!
+//! The verifying key also records the shape of the AIR: its widths, its number of
+//! constraints and their maximum degree, read once off the [`ConstraintDag`] of its
+//! symbolic constraints. The degree fixes the size of the quotient domain, so prover and
+//! verifier read it from the key rather than each guessing it, and a light verifier
+//! holding only [`keygen`]'s output knows every size it checks. The proving key keeps the
+//! DAG itself, from which the prover evaluates the quotient.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_commit::Pcs;
+use p3_field::PrimeCharacteristicRing;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::{log2_ceil_usize, log2_strict_usize};
+use serde::{Deserialize, Serialize};
+use tracing::{info_span, instrument};
+
+use crate::{Challenge, ConstraintDag, MultiTraceAir, StarkGenericConfig, SymbolicBuilder, Val};
+
+/// Most public values an AIR may read while [`setup`] measures its constraints.
+pub(crate) const MAX_PUBLIC_VALUES: usize = 1 << 10;
+
+type Commitment<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+
+type ProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
+
+/// Commitment to the preprocessed columns of an AIR.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedCommitment<SC: StarkGenericConfig> {
+    /// Commitment to the preprocessed trace
+    pub commit: Commitment<SC>,
+    /// Number of preprocessed columns
+    pub width: usize,
+    /// Log2 of the preprocessed trace height; proofs must use the same height
+    pub log_degree: u8,
+}
+
+/// Everything the verifier needs about an AIR beyond its constraints.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct VerifyingKey<SC: StarkGenericConfig> {
+    /// Number of main trace columns
+    pub main_width: usize,
+    /// Number of extension field columns of each auxiliary phase
+    pub aux_widths: Vec<usize>,
+    /// Runtime parameters of the AIR instance, see
+    /// [`crate::AuxTraceBuilder::instance_parameters`]
+    pub instance_parameters: Vec<Val<SC>>,
+    /// Number of constraints the AIR asserts
+    pub num_constraints: usize,
+    /// Phase of each constraint, in evaluation order, under a config with
+    /// [`StarkGenericConfig::per_phase_alpha`] (empty otherwise)
+    ///
+    /// Phase 0 holds the constraints reading only the main and preprocessed traces and
+    /// the public values; phase `p + 1` those reading aux phase `p` and no later one.
+    pub constraint_phases: Vec<usize>,
+    /// Maximum degree of the constraints, selectors included
+    pub max_constraint_degree: usize,
+    /// Log2 of the ratio between the quotient domain and the (randomized) trace domain
+    pub log_quotient_degree: usize,
+    /// Commitment to the preprocessed columns, if the AIR has any
+    pub preprocessed: Option<PreprocessedCommitment<SC>>,
+}
+
+impl<SC: StarkGenericConfig> VerifyingKey<SC> {
+    /// Number of preprocessed columns (0 without a preprocessed trace).
+    pub fn preprocessed_width(&self) -> usize {
+        self.preprocessed.as_ref().map_or(0, |pp| pp.width)
+    }
+
+    /// Ratio between the quotient domain and the (randomized) trace domain.
+    ///
+    /// The quotient is split into this many chunks, twice as many under a zero-knowledge
+    /// config.
+    pub const fn quotient_degree(&self) -> usize {
+        1 << self.log_quotient_degree
+    }
+}
+
+/// Everything the prover needs about an AIR beyond its constraints.
+pub struct ProvingKey<SC: StarkGenericConfig> {
+    /// The matching verifying key
+    pub vk: VerifyingKey<SC>,
+    /// PCS data of the committed preprocessed trace, if the AIR has one
+    pub preprocessed_data: Option<ProverData<SC>>,
+    /// The constraints of the AIR, compiled once at setup
+    pub(crate) dag: Arc<ConstraintDag<Val<SC>, Challenge<SC>>>,
+}
+
+impl<SC: StarkGenericConfig> ProvingKey<SC> {
+    /// The constraints of the AIR, compiled by [`setup`].
+    pub fn constraint_dag(&self) -> &ConstraintDag<Val<SC>, Challenge<SC>> {
+        &self.dag
+    }
+}
+
+/// Measure `air` and commit to its preprocessed columns, producing the keys used by
+/// [`crate::prove`] and [`crate::verify`].
+///
+/// AIRs without a [`p3_air::BaseAir::preprocessed_trace`] work for any trace height;
+/// otherwise every proof must use the height of the preprocessed trace.
+///
+/// Under a zero-knowledge config the preprocessed trace is committed on a domain twice its
+/// height, like the randomized traces of [`crate::prove_zk`]; being public, it is padded
+/// with zero rows rather than random ones. The randomized traces also raise the degree of
+/// every constraint by one, which the quotient degree of the keys accounts for.
+///
+/// # Panics
+/// - If the preprocessed trace height is not a power of two
+/// - If the AIR reads more than 1024 public values
+#[instrument(skip_all)]
+pub fn setup<SC, A>(config: &SC, air: &A) -> (ProvingKey<SC>, VerifyingKey<SC>)
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + Air<SymbolicBuilder<Val<SC>, Challenge<SC>>>,
+{
+    let preprocessed_trace = air.preprocessed_trace();
+    let preprocessed_width = preprocessed_trace.as_ref().map_or(0, |trace| trace.width());
+    let dag = info_span!("compile constraints")
+        .in_scope(|| ConstraintDag::<Val<SC>, Challenge<SC>>::new(air, preprocessed_width));
+    let num_constraints = dag.num_constraints();
+    let max_constraint_degree = dag.constraint_degrees().into_iter().max().unwrap_or(0);
+    let constraint_phases = if config.per_phase_alpha() {
+        dag.constraint_phases(air)
+    } else {
+        Vec::new()
+    };
+    let zk = config.is_zk();
+    let log_quotient_degree = log2_ceil_usize((max_constraint_degree + zk).max(2) - 1);
+
+    let (preprocessed, preprocessed_data) = match preprocessed_trace {
+        None => (None, None),
+        Some(preprocessed_trace) => {
+            let pcs = config.pcs();
+            let height = preprocessed_trace.height();
+            let log_degree = log2_strict_usize(height) as u8;
+            let domain = pcs.natural_domain_for_degree(height << zk);
+            let preprocessed_trace = if zk == 1 {
+                randomize_rows(&preprocessed_trace, &mut || Val::<SC>::ZERO)
+            } else {
+                preprocessed_trace
+            };
+
+            let (commit, data) = info_span!("pcs_commit_preprocessed")
+                .in_scope(|| pcs.commit([(domain, preprocessed_trace)]));
+            let preprocessed = PreprocessedCommitment {
+                commit,
+                width: preprocessed_width,
+                log_degree,
+            };
+            (Some(preprocessed), Some(data))
+        }
+    };
+
+    let vk = VerifyingKey {
+        main_width: air.width(),
+        aux_widths: (0..air.num_aux_phases())
+            .map(|phase| air.aux_phase_width(phase))
+            .collect(),
+        instance_parameters: air.instance_parameters(),
+        num_constraints,
+        constraint_phases,
+        max_constraint_degree,
+        log_quotient_degree,
+        preprocessed,
+    };
+    let pk = ProvingKey {
+        vk: vk.clone(),
+        preprocessed_data,
+        dag: Arc::new(dag),
+    };
+    (pk, vk)
+}
+
+/// The [`VerifyingKey`] of `air`, for distribution to verifiers.
+///
+/// This is the second half of [`setup`], with the same panics.
+pub fn keygen<SC, A>(config: &SC, air: &A) -> VerifyingKey<SC>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + Air<SymbolicBuilder<Val<SC>, Challenge<SC>>>,
+{
+    setup(config, air).1
+}
+
+/// Insert a random row after every row of `trace`, doubling its height.
+///
+/// Over a domain twice the trace height, the even rows are the points of the trace domain,
+/// so the committed polynomial still takes the trace values there.
+pub(crate) fn randomize_rows<F: Copy>(
+    trace: &RowMajorMatrix<F>,
+    random_value: &mut dyn FnMut() -> F,
+) -> RowMajorMatrix<F> {
+    let width = trace.width;
+    let mut values = Vec::with_capacity(2 * trace.values.len());
+    for row in trace.values.chunks_exact(width) {
+        values.extend_from_slice(row);
+        values.extend((0..width).map(|_| random_value()));
+    }
+    RowMajorMatrix::new(values, width)
+}
@@ -10,24 +10,160 @@
 //!
 //! The key abstraction is [`AuxTraceBuilder`], which allows AIRs to specify how to build
 //! auxiliary trace columns from the main trace and random challenges.
+//!
+//! Gadget subsystems sit behind their own cargo features (`lookups`, on by default, then
+//! `memory`, `machine` and `recursion`), so verifier-only and minimal prover builds can
+//! leave them out with `default-features = false`.
+//!
+//! The prover sits behind the `prover` feature, on by default. Light clients and embedded
+//! verifiers build with `default-features = false` and get the verifier, keys and proof
+//! types only, without the quotient evaluation, trace handling and telemetry code.
+//!
+//! [`stable`] re-exports the semver-committed subset of the API; [`unstable`] groups the
+//! experimental subsystems, which may change in any release.
 
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+// wasm32-unknown-unknown has neither files nor threads; the verifier and the prover build
+// for it without these features
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "mmap"))]
+compile_error!("the `mmap` feature reads traces from files, which wasm32-unknown-unknown lacks");
+#[cfg(all(target_arch = "wasm32", target_os = "unknown", feature = "parallel"))]
+compile_error!("the `parallel` feature spawns threads, which wasm32-unknown-unknown lacks");
 
 mod air;
+#[cfg(feature = "prover")]
+mod backend;
+#[cfg(feature = "borsh")]
+pub mod borsh;
+#[cfg(feature = "lookups")]
+mod bytes;
+mod challenge;
+#[cfg(feature = "prover")]
+mod chunked;
 mod config;
+#[cfg(all(feature = "std", feature = "prover"))]
+mod coordinator;
+mod cost;
+#[cfg(feature = "debug")]
+pub mod debug;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 mod folder;
+#[cfg(feature = "fri")]
+mod fri;
+#[cfg(feature = "golden")]
+pub mod golden;
+#[cfg(feature = "lookups")]
+mod interaction;
+#[cfg(feature = "json")]
+pub mod json;
+mod keys;
+mod limits;
+#[cfg(feature = "lookups")]
+mod lookup;
+#[cfg(feature = "machine")]
+mod machine;
+#[cfg(feature = "memory")]
+mod memory;
+mod meter;
+#[cfg(all(feature = "std", feature = "prover"))]
+mod metrics;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "mmcs")]
+mod mmcs;
+#[cfg(feature = "prover")]
+mod pool;
+#[cfg(feature = "presets")]
+pub mod presets;
 mod proof;
+#[cfg(feature = "prover")]
 mod prover;
+#[cfg(feature = "lookups")]
+mod range;
+#[cfg(feature = "lookups")]
+mod rom;
+mod row;
+mod segment;
+#[cfg(all(feature = "std", feature = "prover"))]
+mod self_check;
+mod size;
+pub mod stable;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+mod symbolic;
+#[cfg(feature = "tamper")]
+pub mod tamper;
+#[cfg(feature = "prover")]
+mod telemetry;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+mod tiled;
+mod transcript;
+pub mod unstable;
 mod verifier;
+#[cfg(all(feature = "std", feature = "prover"))]
+mod watchdog;
 
 pub use air::*;
+#[cfg(feature = "prover")]
+pub use backend::*;
+#[cfg(feature = "lookups")]
+pub use bytes::*;
+pub use challenge::*;
+#[cfg(feature = "prover")]
+pub use chunked::*;
 pub use config::*;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub use coordinator::*;
+pub use cost::*;
 pub use folder::*;
+#[cfg(feature = "lookups")]
+pub use interaction::*;
+pub use keys::*;
+pub use limits::*;
+#[cfg(feature = "lookups")]
+pub use lookup::*;
+#[cfg(feature = "machine")]
+pub use machine::*;
+#[cfg(feature = "memory")]
+pub use memory::*;
+pub use meter::*;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub use metrics::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+#[cfg(feature = "mmcs")]
+pub use mmcs::*;
 pub use proof::*;
+#[cfg(feature = "prover")]
 pub use prover::*;
+#[cfg(feature = "lookups")]
+pub use range::*;
+#[cfg(feature = "lookups")]
+pub use rom::*;
+pub use row::*;
+pub use segment::*;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub use self_check::*;
+pub use size::*;
+pub use symbolic::*;
+#[cfg(feature = "prover")]
+pub use telemetry::*;
+pub use tiled::*;
+pub use transcript::*;
 pub use verifier::*;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub use watchdog::*;
+
+// The prover runs each aux builder in a `build_aux_trace` span; builders can open their
+// own sub-spans with the same `tracing` version
+pub use tracing;
 
 // Re-export key Plonky3 types
 pub use p3_air::{Air as P3Air, AirBuilder, BaseAir};
@@ -0,0 +1,659 @@
+//! Bounded proof deserialization
+//!
+//! A derived `Deserialize` for [`Proof`] trusts the length prefixes of its vectors, so a
+//! hostile encoding can make the verifier allocate far more than any honest proof needs.
+//! [`ProofSeed`] deserializes a proof while rejecting any opened-value vector or quotient
+//! chunk list longer than the AIR allows, before the elements are read, and optionally any
+//! proof larger than a byte budget.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use p3_field::BasedVectorSpace;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+
+use crate::{
+    serialized_size, Challenge, MultiTraceAir, OpenedValues, Proof, StarkGenericConfig, Val,
+    VerifyingKey,
+};
+
+type Commitment<SC> = <<SC as StarkGenericConfig>::Pcs as p3_commit::Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+
+/// Maximum lengths of the variable-length parts of a [`Proof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofLimits {
+    /// Number of main trace values opened per point
+    pub main_width: usize,
+    /// Number of preprocessed trace values opened per point
+    pub preprocessed_width: usize,
+    /// Number of auxiliary phases
+    pub num_aux_phases: usize,
+    /// Number of (flattened) auxiliary trace values opened per phase and point
+    pub aux_opened_width: usize,
+    /// Number of quotient chunks
+    pub num_quotient_chunks: usize,
+    /// Number of values opened per quotient chunk
+    pub quotient_chunk_width: usize,
+    /// Number of out-of-domain points opened beyond the first
+    pub num_additional_openings: usize,
+    /// Number of values exposed per auxiliary phase
+    pub aux_exposed_width: usize,
+    /// Largest acceptable [`crate::serialized_size`] of the whole proof, if limited
+    pub max_proof_size: Option<usize>,
+}
+
+impl ProofLimits {
+    /// Derive the limits for proofs of `air` under `vk`.
+    pub fn for_air<SC, A>(air: &A, vk: &VerifyingKey<SC>) -> Self
+    where
+        SC: StarkGenericConfig,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+    {
+        Self {
+            main_width: air.width(),
+            preprocessed_width: vk.preprocessed_width(),
+            num_aux_phases: air.num_aux_phases(),
+            aux_opened_width: (0..air.num_aux_phases())
+                .map(|phase| air.aux_phase_committed_width(phase))
+                .max()
+                .unwrap_or(0),
+            num_quotient_chunks: vk.quotient_degree(),
+            quotient_chunk_width: Challenge::<SC>::DIMENSION,
+            num_additional_openings: 0,
+            aux_exposed_width: (0..air.num_aux_phases())
+                .map(|phase| air.aux_phase_num_exposed_values(phase))
+                .max()
+                .unwrap_or(0),
+            max_proof_size: None,
+        }
+    }
+
+    /// Allow the openings of a config with
+    /// [`num_ood_points`](StarkGenericConfig::num_ood_points) `n`.
+    pub const fn with_num_ood_points(mut self, n: usize) -> Self {
+        self.num_additional_openings = n.saturating_sub(1);
+        self
+    }
+
+    /// Allow the quotient chunks of a zero-knowledge config, which has twice as many.
+    pub const fn with_zk(mut self) -> Self {
+        self.num_quotient_chunks *= 2;
+        self
+    }
+
+    /// Reject proofs larger than `max_proof_size` bytes, usually
+    /// [`StarkGenericConfig::max_proof_size`].
+    pub const fn with_max_proof_size(mut self, max_proof_size: usize) -> Self {
+        self.max_proof_size = Some(max_proof_size);
+        self
+    }
+
+    /// A [`DeserializeSeed`] producing proofs that respect these limits.
+    pub const fn proof_seed<SC: StarkGenericConfig>(self) -> ProofSeed<SC> {
+        ProofSeed {
+            limits: self,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Deserializes a [`Proof`] while enforcing [`ProofLimits`].
+pub struct ProofSeed<SC> {
+    limits: ProofLimits,
+    _phantom: PhantomData<SC>,
+}
+
+impl<'de, SC: StarkGenericConfig> DeserializeSeed<'de> for ProofSeed<SC> {
+    type Value = Proof<SC>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Proof<SC>, D::Error> {
+        let max_proof_size = self.limits.max_proof_size;
+        let proof = deserializer.deserialize_struct("Proof", PROOF_FIELDS, self)?;
+        if max_proof_size.is_some_and(|max_proof_size| serialized_size(&proof) > max_proof_size) {
+            return Err(de::Error::custom("proof exceeds the maximum size"));
+        }
+        Ok(proof)
+    }
+}
+
+pub(crate) const PROOF_FIELDS: &[&str] = &[
+    "version",
+    "main_commit",
+    "aux_commits",
+    "aux_exposed_values",
+    "quotient_commits",
+    "main_local",
+    "main_next",
+    "preprocessed_local",
+    "preprocessed_next",
+    "aux_local",
+    "aux_next",
+    "quotient_chunks",
+    "opening_proof",
+    "additional_openings",
+    "log_degree",
+    "public_values_digest",
+    "aux_challenge_counts",
+];
+
+const OPENED_VALUES_FIELDS: &[&str] = &[
+    "main_local",
+    "main_next",
+    "preprocessed_local",
+    "preprocessed_next",
+    "aux_local",
+    "aux_next",
+    "quotient_chunks",
+];
+
+impl<SC: StarkGenericConfig> ProofSeed<SC> {
+    fn opened(&self, max_len: usize) -> BoundedSeq<PhantomData<Challenge<SC>>> {
+        opened(max_len)
+    }
+
+    fn aux_commits(&self) -> BoundedSeq<PhantomData<Commitment<SC>>> {
+        BoundedSeq {
+            max_len: self.limits.num_aux_phases,
+            element: PhantomData,
+        }
+    }
+
+    /// At most one commitment per quotient column, the narrowest possible leaves.
+    fn quotient_commits(&self) -> BoundedSeq<PhantomData<Commitment<SC>>> {
+        BoundedSeq {
+            max_len: self.limits.num_quotient_chunks * self.limits.quotient_chunk_width,
+            element: PhantomData,
+        }
+    }
+
+    fn aux_opened(&self) -> OptionalSeed<BoundedSeq<BoundedSeq<PhantomData<Challenge<SC>>>>> {
+        OptionalSeed(BoundedSeq {
+            max_len: self.limits.num_aux_phases,
+            element: self.opened(self.limits.aux_opened_width),
+        })
+    }
+
+    fn aux_exposed_values(&self) -> BoundedSeq<BoundedSeq<PhantomData<Challenge<SC>>>> {
+        BoundedSeq {
+            max_len: self.limits.num_aux_phases,
+            element: self.opened(self.limits.aux_exposed_width),
+        }
+    }
+
+    fn quotient_chunks(&self) -> BoundedSeq<BoundedSeq<PhantomData<Challenge<SC>>>> {
+        BoundedSeq {
+            max_len: self.limits.num_quotient_chunks,
+            element: self.opened(self.limits.quotient_chunk_width),
+        }
+    }
+
+    fn aux_challenge_counts(&self) -> OptionalSeed<BoundedSeq<PhantomData<usize>>> {
+        OptionalSeed(BoundedSeq {
+            max_len: self.limits.num_aux_phases,
+            element: PhantomData,
+        })
+    }
+
+    fn additional_openings(&self) -> BoundedSeq<OpenedValuesSeed<Challenge<SC>>> {
+        BoundedSeq {
+            max_len: self.limits.num_additional_openings,
+            element: OpenedValuesSeed {
+                limits: self.limits,
+                _phantom: PhantomData,
+            },
+        }
+    }
+}
+
+fn opened<EF>(max_len: usize) -> BoundedSeq<PhantomData<EF>> {
+    BoundedSeq {
+        max_len,
+        element: PhantomData,
+    }
+}
+
+impl<'de, SC: StarkGenericConfig> Visitor<'de> for ProofSeed<SC> {
+    type Value = Proof<SC>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a multi-trace STARK proof")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Proof<SC>, S::Error> {
+        let missing = |i: usize| -> S::Error { de::Error::invalid_length(i, &"a complete proof") };
+        Ok(Proof {
+            version: seq.next_element()?.ok_or_else(|| missing(0))?,
+            main_commit: seq.next_element()?.ok_or_else(|| missing(1))?,
+            aux_commits: seq
+                .next_element_seed(self.aux_commits())?
+                .ok_or_else(|| missing(2))?,
+            aux_exposed_values: seq
+                .next_element_seed(self.aux_exposed_values())?
+                .ok_or_else(|| missing(3))?,
+            quotient_commits: seq
+                .next_element_seed(self.quotient_commits())?
+                .ok_or_else(|| missing(4))?,
+            main_local: seq
+                .next_element_seed(self.opened(self.limits.main_width))?
+                .ok_or_else(|| missing(5))?,
+            main_next: seq
+                .next_element_seed(self.opened(self.limits.main_width))?
+                .ok_or_else(|| missing(6))?,
+            preprocessed_local: seq
+                .next_element_seed(self.opened(self.limits.preprocessed_width))?
+                .ok_or_else(|| missing(7))?,
+            preprocessed_next: seq
+                .next_element_seed(self.opened(self.limits.preprocessed_width))?
+                .ok_or_else(|| missing(8))?,
+            aux_local: seq
+                .next_element_seed(self.aux_opened())?
+                .ok_or_else(|| missing(9))?
+                .and_then(non_empty),
+            aux_next: seq
+                .next_element_seed(self.aux_opened())?
+                .ok_or_else(|| missing(10))?
+                .and_then(non_empty),
+            quotient_chunks: seq
+                .next_element_seed(self.quotient_chunks())?
+                .ok_or_else(|| missing(11))?,
+            opening_proof: seq.next_element()?.ok_or_else(|| missing(12))?,
+            additional_openings: seq
+                .next_element_seed(self.additional_openings())?
+                .ok_or_else(|| missing(13))?,
+            log_degree: seq.next_element()?.ok_or_else(|| missing(14))?,
+            public_values_digest: seq.next_element()?.ok_or_else(|| missing(15))?,
+            aux_challenge_counts: seq
+                .next_element_seed(self.aux_challenge_counts())?
+                .ok_or_else(|| missing(16))?,
+        })
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Proof<SC>, M::Error> {
+        let mut version = None;
+        let mut main_commit = None;
+        let mut aux_commits = None;
+        let mut aux_exposed_values = None;
+        let mut quotient_commits = None;
+        let mut main_local = None;
+        let mut main_next = None;
+        let mut preprocessed_local = None;
+        let mut preprocessed_next = None;
+        let mut aux_local = None;
+        let mut aux_next = None;
+        let mut quotient_chunks = None;
+        let mut opening_proof = None;
+        let mut additional_openings = None;
+        let mut log_degree = None;
+        let mut public_values_digest = None;
+        let mut aux_challenge_counts = None;
+
+        while let Some(key) = map.next_key::<ProofField>()? {
+            match key {
+                ProofField::Version => version = Some(map.next_value()?),
+                ProofField::MainCommit => main_commit = Some(map.next_value()?),
+                ProofField::AuxCommits => {
+                    aux_commits = Some(map.next_value_seed(self.aux_commits())?)
+                }
+                ProofField::AuxExposedValues => {
+                    aux_exposed_values = Some(map.next_value_seed(self.aux_exposed_values())?)
+                }
+                ProofField::QuotientCommits => {
+                    quotient_commits = Some(map.next_value_seed(self.quotient_commits())?)
+                }
+                ProofField::MainLocal => {
+                    main_local = Some(map.next_value_seed(self.opened(self.limits.main_width))?)
+                }
+                ProofField::MainNext => {
+                    main_next = Some(map.next_value_seed(self.opened(self.limits.main_width))?)
+                }
+                ProofField::PreprocessedLocal => {
+                    preprocessed_local =
+                        Some(map.next_value_seed(self.opened(self.limits.preprocessed_width))?)
+                }
+                ProofField::PreprocessedNext => {
+                    preprocessed_next =
+                        Some(map.next_value_seed(self.opened(self.limits.preprocessed_width))?)
+                }
+                ProofField::AuxLocal => aux_local = Some(map.next_value_seed(self.aux_opened())?),
+                ProofField::AuxNext => aux_next = Some(map.next_value_seed(self.aux_opened())?),
+                ProofField::QuotientChunks => {
+                    quotient_chunks = Some(map.next_value_seed(self.quotient_chunks())?)
+                }
+                ProofField::OpeningProof => opening_proof = Some(map.next_value()?),
+                ProofField::AdditionalOpenings => {
+                    additional_openings = Some(map.next_value_seed(self.additional_openings())?)
+                }
+                ProofField::LogDegree => log_degree = Some(map.next_value()?),
+                ProofField::PublicValuesDigest => public_values_digest = Some(map.next_value()?),
+                ProofField::AuxChallengeCounts => {
+                    aux_challenge_counts = Some(map.next_value_seed(self.aux_challenge_counts())?)
+                }
+                ProofField::Ignore => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let missing = |name: &'static str| -> M::Error { de::Error::missing_field(name) };
+        Ok(Proof {
+            version: version.ok_or_else(|| missing("version"))?,
+            main_commit: main_commit.ok_or_else(|| missing("main_commit"))?,
+            aux_commits: aux_commits.ok_or_else(|| missing("aux_commits"))?,
+            aux_exposed_values: aux_exposed_values.ok_or_else(|| missing("aux_exposed_values"))?,
+            quotient_commits: quotient_commits.ok_or_else(|| missing("quotient_commits"))?,
+            main_local: main_local.ok_or_else(|| missing("main_local"))?,
+            main_next: main_next.ok_or_else(|| missing("main_next"))?,
+            preprocessed_local: preprocessed_local.ok_or_else(|| missing("preprocessed_local"))?,
+            preprocessed_next: preprocessed_next.ok_or_else(|| missing("preprocessed_next"))?,
+            aux_local: aux_local.flatten().and_then(non_empty),
+            aux_next: aux_next.flatten().and_then(non_empty),
+            quotient_chunks: quotient_chunks.ok_or_else(|| missing("quotient_chunks"))?,
+            opening_proof: opening_proof.ok_or_else(|| missing("opening_proof"))?,
+            additional_openings: additional_openings
+                .ok_or_else(|| missing("additional_openings"))?,
+            log_degree: log_degree.ok_or_else(|| missing("log_degree"))?,
+            public_values_digest: public_values_digest.unwrap_or_default(),
+            aux_challenge_counts: aux_challenge_counts.unwrap_or_default(),
+        })
+    }
+}
+
+/// Field identifiers of [`Proof`], matching its derived `Serialize` impl.
+enum ProofField {
+    Version,
+    MainCommit,
+    AuxCommits,
+    AuxExposedValues,
+    QuotientCommits,
+    MainLocal,
+    MainNext,
+    PreprocessedLocal,
+    PreprocessedNext,
+    AuxLocal,
+    AuxNext,
+    QuotientChunks,
+    OpeningProof,
+    AdditionalOpenings,
+    LogDegree,
+    PublicValuesDigest,
+    AuxChallengeCounts,
+    Ignore,
+}
+
+impl<'de> serde::Deserialize<'de> for ProofField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        FieldKey(PROOF_FIELDS).deserialize(deserializer)
+    }
+}
+
+/// Deserializes a [`ProofField`], resolving integer keys against the given field list.
+///
+/// [`OpenedValues`] reuses the names of the matching [`Proof`] fields, in its own order.
+#[derive(Clone, Copy)]
+struct FieldKey(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldKey {
+    type Value = ProofField;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<ProofField, D::Error> {
+        struct FieldVisitor(&'static [&'static str]);
+
+        impl Visitor<'_> for FieldVisitor {
+            type Value = ProofField;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a proof field name")
+            }
+
+            fn visit_u64<E: de::Error>(self, index: u64) -> Result<ProofField, E> {
+                let name = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| self.0.get(i).copied())
+                    .unwrap_or("");
+                self.visit_str(name)
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<ProofField, E> {
+                Ok(match name {
+                    "version" => ProofField::Version,
+                    "main_commit" => ProofField::MainCommit,
+                    "aux_commits" => ProofField::AuxCommits,
+                    "aux_exposed_values" => ProofField::AuxExposedValues,
+                    "quotient_commits" => ProofField::QuotientCommits,
+                    "main_local" => ProofField::MainLocal,
+                    "main_next" => ProofField::MainNext,
+                    "preprocessed_local" => ProofField::PreprocessedLocal,
+                    "preprocessed_next" => ProofField::PreprocessedNext,
+                    "aux_local" => ProofField::AuxLocal,
+                    "aux_next" => ProofField::AuxNext,
+                    "quotient_chunks" => ProofField::QuotientChunks,
+                    "opening_proof" => ProofField::OpeningProof,
+                    "additional_openings" => ProofField::AdditionalOpenings,
+                    "log_degree" => ProofField::LogDegree,
+                    "public_values_digest" => ProofField::PublicValuesDigest,
+                    "aux_challenge_counts" => ProofField::AuxChallengeCounts,
+                    _ => ProofField::Ignore,
+                })
+            }
+        }
+
+        deserializer.deserialize_identifier(FieldVisitor(self.0))
+    }
+}
+
+/// Deserializes one [`OpenedValues`] while enforcing the per-point [`ProofLimits`].
+struct OpenedValuesSeed<EF> {
+    limits: ProofLimits,
+    _phantom: PhantomData<EF>,
+}
+
+impl<EF> Clone for OpenedValuesSeed<EF> {
+    fn clone(&self) -> Self {
+        Self {
+            limits: self.limits,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<EF> OpenedValuesSeed<EF> {
+    fn aux_opened(&self) -> OptionalSeed<BoundedSeq<BoundedSeq<PhantomData<EF>>>> {
+        OptionalSeed(BoundedSeq {
+            max_len: self.limits.num_aux_phases,
+            element: opened(self.limits.aux_opened_width),
+        })
+    }
+
+    fn quotient_chunks(&self) -> BoundedSeq<BoundedSeq<PhantomData<EF>>> {
+        BoundedSeq {
+            max_len: self.limits.num_quotient_chunks,
+            element: opened(self.limits.quotient_chunk_width),
+        }
+    }
+}
+
+impl<'de, EF: serde::Deserialize<'de>> DeserializeSeed<'de> for OpenedValuesSeed<EF> {
+    type Value = OpenedValues<EF>;
+
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<OpenedValues<EF>, D::Error> {
+        deserializer.deserialize_struct("OpenedValues", OPENED_VALUES_FIELDS, self)
+    }
+}
+
+impl<'de, EF: serde::Deserialize<'de>> Visitor<'de> for OpenedValuesSeed<EF> {
+    type Value = OpenedValues<EF>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("values opened at an out-of-domain point")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<OpenedValues<EF>, S::Error> {
+        let missing =
+            |i: usize| -> S::Error { de::Error::invalid_length(i, &"complete opened values") };
+        Ok(OpenedValues {
+            main_local: seq
+                .next_element_seed(opened(self.limits.main_width))?
+                .ok_or_else(|| missing(0))?,
+            main_next: seq
+                .next_element_seed(opened(self.limits.main_width))?
+                .ok_or_else(|| missing(1))?,
+            preprocessed_local: seq
+                .next_element_seed(opened(self.limits.preprocessed_width))?
+                .ok_or_else(|| missing(2))?,
+            preprocessed_next: seq
+                .next_element_seed(opened(self.limits.preprocessed_width))?
+                .ok_or_else(|| missing(3))?,
+            aux_local: seq
+                .next_element_seed(self.aux_opened())?
+                .ok_or_else(|| missing(4))?
+                .and_then(non_empty),
+            aux_next: seq
+                .next_element_seed(self.aux_opened())?
+                .ok_or_else(|| missing(5))?
+                .and_then(non_empty),
+            quotient_chunks: seq
+                .next_element_seed(self.quotient_chunks())?
+                .ok_or_else(|| missing(6))?,
+        })
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<OpenedValues<EF>, M::Error> {
+        let mut main_local = None;
+        let mut main_next = None;
+        let mut preprocessed_local = None;
+        let mut preprocessed_next = None;
+        let mut aux_local = None;
+        let mut aux_next = None;
+        let mut quotient_chunks = None;
+
+        while let Some(key) = map.next_key_seed(FieldKey(OPENED_VALUES_FIELDS))? {
+            match key {
+                ProofField::MainLocal => {
+                    main_local = Some(map.next_value_seed(opened(self.limits.main_width))?)
+                }
+                ProofField::MainNext => {
+                    main_next = Some(map.next_value_seed(opened(self.limits.main_width))?)
+                }
+                ProofField::PreprocessedLocal => {
+                    preprocessed_local =
+                        Some(map.next_value_seed(opened(self.limits.preprocessed_width))?)
+                }
+                ProofField::PreprocessedNext => {
+                    preprocessed_next =
+                        Some(map.next_value_seed(opened(self.limits.preprocessed_width))?)
+                }
+                ProofField::AuxLocal => aux_local = Some(map.next_value_seed(self.aux_opened())?),
+                ProofField::AuxNext => aux_next = Some(map.next_value_seed(self.aux_opened())?),
+                ProofField::QuotientChunks => {
+                    quotient_chunks = Some(map.next_value_seed(self.quotient_chunks())?)
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let missing = |name: &'static str| -> M::Error { de::Error::missing_field(name) };
+        Ok(OpenedValues {
+            main_local: main_local.ok_or_else(|| missing("main_local"))?,
+            main_next: main_next.ok_or_else(|| missing("main_next"))?,
+            preprocessed_local: preprocessed_local.ok_or_else(|| missing("preprocessed_local"))?,
+            preprocessed_next: preprocessed_next.ok_or_else(|| missing("preprocessed_next"))?,
+            aux_local: aux_local.flatten().and_then(non_empty),
+            aux_next: aux_next.flatten().and_then(non_empty),
+            quotient_chunks: quotient_chunks.ok_or_else(|| missing("quotient_chunks"))?,
+        })
+    }
+}
+
+/// Canonical form of optional aux openings: a proof without aux phases has none.
+///
+/// Proofs encoded while the openings were plain lists carry empty ones instead.
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    (!values.is_empty()).then_some(values)
+}
+
+/// Deserializes a sequence of at most `max_len` elements, each with `element`.
+#[derive(Clone, Copy)]
+struct BoundedSeq<S> {
+    max_len: usize,
+    element: S,
+}
+
+impl<'de, S> DeserializeSeed<'de> for BoundedSeq<S>
+where
+    S: DeserializeSeed<'de> + Clone,
+{
+    type Value = Vec<S::Value>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, S> Visitor<'de> for BoundedSeq<S>
+where
+    S: DeserializeSeed<'de> + Clone,
+{
+    type Value = Vec<S::Value>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of at most {} elements", self.max_len)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let hint = seq.size_hint().unwrap_or(0);
+        if hint > self.max_len {
+            return Err(de::Error::invalid_length(hint, &self));
+        }
+
+        let mut values = Vec::with_capacity(hint);
+        while let Some(value) = seq.next_element_seed(self.element.clone())? {
+            if values.len() == self.max_len {
+                return Err(de::Error::invalid_length(values.len() + 1, &self));
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+/// Deserializes an optional value with `S`.
+#[derive(Clone, Copy)]
+struct OptionalSeed<S>(S);
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for OptionalSeed<S> {
+    type Value = Option<S::Value>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> Visitor<'de> for OptionalSeed<S> {
+    type Value = Option<S::Value>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an optional value")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0.deserialize(deserializer).map(Some)
+    }
+}
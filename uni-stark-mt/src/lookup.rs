@@ -0,0 +1,228 @@
+//! LogUp lookup gadget
+//!
+//! [`LogUpLookup`] turns a list of send/receive [`LookupInteraction`]s over main trace
+//! columns into the auxiliary columns, constraints and boundary check of a LogUp argument,
+//! so AIRs don't have to hand-roll them on top of [`crate::AuxTraceBuilder`].
+//!
+//! Every interaction contributes `± m / (α - fingerprint(values))` per row, with the
+//! fingerprint `v_0 + β·v_1 + β²·v_2 + …`. The gadget commits one helper column per
+//! interaction holding that fraction, plus a running-sum column. Sends and receives
+//! balance when the running sum ends at zero on the last row.
+
+use alloc::vec::Vec;
+
+use p3_air::AirBuilder;
+use p3_field::{batch_multiplicative_inverse, ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::AuxBuilder;
+
+/// Direction of a lookup interaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LookupKind {
+    /// Values looked up (added to the running sum)
+    Send,
+    /// Values provided by a table (subtracted from the running sum)
+    Receive,
+}
+
+/// How many times a row takes part in an interaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiplicity {
+    /// Every row, once
+    One,
+    /// As many times as the given main trace column says
+    Column(usize),
+}
+
+/// A tuple of main trace columns sent to or received from the lookup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupInteraction {
+    /// Whether the values are sent or received
+    pub kind: LookupKind,
+    /// Main trace columns forming the looked-up tuple
+    pub columns: Vec<usize>,
+    /// Multiplicity of each row
+    pub multiplicity: Multiplicity,
+}
+
+impl LookupInteraction {
+    /// Send `columns` once per row.
+    pub const fn send(columns: Vec<usize>) -> Self {
+        Self {
+            kind: LookupKind::Send,
+            columns,
+            multiplicity: Multiplicity::One,
+        }
+    }
+
+    /// Receive `columns` as many times as `multiplicity_column` says.
+    pub const fn receive(columns: Vec<usize>, multiplicity_column: usize) -> Self {
+        Self {
+            kind: LookupKind::Receive,
+            columns,
+            multiplicity: Multiplicity::Column(multiplicity_column),
+        }
+    }
+}
+
+/// A LogUp argument over the main trace of one AIR.
+///
+/// An AIR using the gadget forwards [`aux_width`](Self::aux_width),
+/// [`NUM_CHALLENGES`](Self::NUM_CHALLENGES) and [`build_aux_trace`](Self::build_aux_trace)
+/// from its [`crate::AuxTraceBuilder`] impl, and calls [`eval`](Self::eval) from its
+/// `Air::eval`. The gadget expects its challenges first among the AIR's aux challenges
+/// and its columns at `aux_offset` in the aux trace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogUpLookup {
+    /// Interactions of this AIR
+    pub interactions: Vec<LookupInteraction>,
+}
+
+impl LogUpLookup {
+    /// Challenges used by the gadget: `α` (the pole) and `β` (the fingerprint base).
+    pub const NUM_CHALLENGES: usize = 2;
+
+    pub const fn new(interactions: Vec<LookupInteraction>) -> Self {
+        Self { interactions }
+    }
+
+    /// Number of aux columns: one helper per interaction plus the running sum.
+    pub fn aux_width(&self) -> usize {
+        self.interactions.len() + 1
+    }
+
+    /// Build the helper and running-sum columns.
+    ///
+    /// # Panics
+    /// - If fewer than [`NUM_CHALLENGES`](Self::NUM_CHALLENGES) challenges are given
+    /// - If a fingerprint hits `α` (negligible probability for honest traces)
+    pub fn build_aux_trace<F, EF>(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF>
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+    {
+        let (alpha, beta) = (challenges[0], challenges[1]);
+        let height = main.height();
+        let width = self.aux_width();
+
+        // Denominators α - fingerprint for every (row, interaction), inverted in one batch
+        let denominators: Vec<EF> = main
+            .values
+            .chunks_exact(main.width)
+            .flat_map(|row| {
+                self.interactions
+                    .iter()
+                    .map(move |interaction| alpha - fingerprint(row, &interaction.columns, beta))
+            })
+            .collect();
+        let inverses = batch_multiplicative_inverse(&denominators);
+
+        let mut values = EF::zero_vec(height * width);
+        let mut sum = EF::ZERO;
+        for (r, (aux_row, row_inverses)) in values
+            .chunks_exact_mut(width)
+            .zip(inverses.chunks_exact(self.interactions.len().max(1)))
+            .enumerate()
+        {
+            for (k, (interaction, &inverse)) in
+                self.interactions.iter().zip(row_inverses).enumerate()
+            {
+                let multiplicity = match interaction.multiplicity {
+                    Multiplicity::One => F::ONE,
+                    Multiplicity::Column(c) => main.values[r * main.width + c],
+                };
+                let helper = inverse * multiplicity;
+                aux_row[k] = helper;
+                match interaction.kind {
+                    LookupKind::Send => sum += helper,
+                    LookupKind::Receive => sum -= helper,
+                }
+            }
+            aux_row[width - 1] = sum;
+        }
+
+        RowMajorMatrix::new(values, width)
+    }
+
+    /// Emit the LogUp constraints, reading the gadget's columns from `aux_offset` on.
+    ///
+    /// - each helper satisfies `h · (α - fingerprint) = m`
+    /// - the running sum starts at the first row's signed helper sum, accumulates the next
+    ///   row's on every transition, and ends at zero on the last row
+    pub fn eval<AB: AuxBuilder>(&self, builder: &mut AB, aux_offset: usize) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+
+        let aux = builder.aux();
+        let aux_local = aux.row_slice(0).expect("Matrix is empty?");
+        let aux_next = aux.row_slice(1).expect("Matrix only has 1 row?");
+
+        let challenges = builder.aux_challenges();
+        let alpha: AB::ExprEF = challenges[0].into();
+        let beta: AB::ExprEF = challenges[1].into();
+
+        let num_interactions = self.interactions.len();
+        let sum_local: AB::ExprEF = aux_local[aux_offset + num_interactions].into();
+        let sum_next: AB::ExprEF = aux_next[aux_offset + num_interactions].into();
+
+        let signed_sum = |helpers: &[AB::VarEF]| {
+            self.interactions
+                .iter()
+                .enumerate()
+                .fold(AB::ExprEF::ZERO, |acc, (k, interaction)| {
+                    let helper: AB::ExprEF = helpers[aux_offset + k].into();
+                    match interaction.kind {
+                        LookupKind::Send => acc + helper,
+                        LookupKind::Receive => acc - helper,
+                    }
+                })
+        };
+        let local_delta = signed_sum(&aux_local[..]);
+        let next_delta = signed_sum(&aux_next[..]);
+
+        for (k, interaction) in self.interactions.iter().enumerate() {
+            let values = interaction.columns.iter().map(|&c| {
+                let value: AB::Expr = local[c].clone().into();
+                AB::ExprEF::from(value)
+            });
+            let fingerprint = values
+                .rev()
+                .fold(AB::ExprEF::ZERO, |acc, v| acc * beta.clone() + v);
+            let multiplicity: AB::Expr = match interaction.multiplicity {
+                Multiplicity::One => AB::Expr::ONE,
+                Multiplicity::Column(c) => local[c].clone().into(),
+            };
+            let helper: AB::ExprEF = aux_local[aux_offset + k].into();
+            builder.assert_eq_ext(
+                helper * (alpha.clone() - fingerprint),
+                AB::ExprEF::from(multiplicity),
+            );
+        }
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(sum_local.clone(), local_delta);
+        builder
+            .when_transition()
+            .assert_eq_ext(sum_next, sum_local.clone() + next_delta);
+        builder.when_last_row().assert_zero_ext(sum_local);
+    }
+}
+
+/// `values[0] + β·values[1] + β²·values[2] + …` over the selected columns of `row`.
+pub(crate) fn fingerprint<F: Field, EF: ExtensionField<F>>(
+    row: &[F],
+    columns: &[usize],
+    beta: EF,
+) -> EF {
+    columns
+        .iter()
+        .rev()
+        .fold(EF::ZERO, |acc, &c| acc * beta + row[c])
+}
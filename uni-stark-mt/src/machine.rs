@@ -0,0 +1,131 @@
+//! Chips and machines
+//!
+//! A [`Chip`] is one table of a larger statement: an AIR that generates its own trace from
+//! the execution record of the whole machine. A [`Machine`] owns its chips and proves them
+//! together in one [`MultiProof`], so they share the aux challenges of every phase and
+//! their [`crate::BusInteractions`] balance across chips, e.g. a CPU chip sending to a
+//! [`crate::ProgramRom`] and a [`crate::RangeCheckChip`].
+//!
+//! ```ignore
+//! let (pks, vks) = machine.setup(&config);
+//! let public_values = machine.public_values(&record);
+//! let proof = machine.prove(&config, &pks, &record)?;
+//! machine.verify(&config, &vks, &proof, &public_values)?;
+//! ```
+
+use alloc::vec::Vec;
+
+use p3_air::Air;
+#[cfg(feature = "prover")]
+use p3_field::PackedField;
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+#[cfg(feature = "prover")]
+use tracing::info_span;
+
+#[cfg(feature = "prover")]
+use crate::{prove_multi, ProverError, ProverFolder};
+use crate::{
+    setup, verify_multi, Challenge, MultiProof, MultiTraceAir, ProvingKey, StarkGenericConfig,
+    SymbolicBuilder, TraceGenerator, Val, VerificationError, VerifierFolder, VerifyingKey,
+};
+
+/// One table of a [`Machine`].
+///
+/// Its [`TraceGenerator::Inputs`] is the execution record shared by all chips of the
+/// machine: each chip reads the events it tabulates from it, and table chips such as
+/// [`crate::RangeCheckChip`] count the lookups the other chips make.
+pub trait Chip<F: Field, EF: ExtensionField<F>>: MultiTraceAir<F, EF> + TraceGenerator<F> {
+    /// Name of the chip, unique within its machine, e.g. `"cpu"`
+    fn name(&self) -> &'static str;
+
+    /// Public values of the chip's table for `record`, none by default.
+    fn public_values(&self, _record: &Self::Inputs) -> Vec<F> {
+        Vec::new()
+    }
+}
+
+/// A statement made of several [`Chip`]s proven in one [`MultiProof`].
+///
+/// Chips of different kinds are usually variants of one enum forwarding to them, since
+/// every table of a [`MultiProof`] has the same AIR type. The provided methods handle keys,
+/// trace generation, proving and verification for all chips in the order of
+/// [`Machine::chips`], which is also the table order of the proof.
+pub trait Machine<SC: StarkGenericConfig> {
+    /// The chips of the machine
+    type Chip: Chip<Val<SC>, Challenge<SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>
+        + Air<SymbolicBuilder<Val<SC>, Challenge<SC>>>;
+
+    /// The chips of the machine, in table order.
+    fn chips(&self) -> &[Self::Chip];
+
+    /// Position of the chip called `name` among [`Machine::chips`], which is also the
+    /// index of its table in the machine's proofs.
+    fn chip_index(&self, name: &str) -> Option<usize> {
+        self.chips().iter().position(|chip| chip.name() == name)
+    }
+
+    /// Proving and verifying keys of every chip, in table order.
+    fn setup(&self, config: &SC) -> (Vec<ProvingKey<SC>>, Vec<VerifyingKey<SC>>) {
+        self.chips().iter().map(|chip| setup(config, chip)).unzip()
+    }
+
+    /// Main trace of every chip for `record`, in table order.
+    fn generate_traces(
+        &self,
+        record: &<Self::Chip as TraceGenerator<Val<SC>>>::Inputs,
+    ) -> Vec<RowMajorMatrix<Val<SC>>> {
+        self.chips()
+            .iter()
+            .map(|chip| chip.generate(record))
+            .collect()
+    }
+
+    /// Public values of every chip for `record`, in table order.
+    fn public_values(
+        &self,
+        record: &<Self::Chip as TraceGenerator<Val<SC>>>::Inputs,
+    ) -> Vec<Vec<Val<SC>>> {
+        self.chips()
+            .iter()
+            .map(|chip| chip.public_values(record))
+            .collect()
+    }
+
+    /// Generate the traces of every chip for `record` and prove them in one proof.
+    ///
+    /// # Returns
+    /// A proof that can be verified with [`Machine::verify`], or a [`ProverError`] on any
+    /// condition under which [`crate::prove_multi`] fails
+    ///
+    /// # Panics
+    /// - If a trace generator or an aux builder panics
+    #[cfg(feature = "prover")]
+    fn prove(
+        &self,
+        config: &SC,
+        pks: &[ProvingKey<SC>],
+        record: &<Self::Chip as TraceGenerator<Val<SC>>>::Inputs,
+    ) -> Result<MultiProof<SC>, ProverError>
+    where
+        Val<SC>: PackedField,
+        Self::Chip: for<'a> Air<ProverFolder<'a, SC>>,
+    {
+        let main_traces = info_span!("generate traces").in_scope(|| self.generate_traces(record));
+        let public_values = self.public_values(record);
+        prove_multi(config, self.chips(), pks, main_traces, &public_values)
+    }
+
+    /// Verify a proof of [`Machine::prove`], including that the bus interactions of all
+    /// chips balance.
+    fn verify(
+        &self,
+        config: &SC,
+        vks: &[VerifyingKey<SC>],
+        proof: &MultiProof<SC>,
+        public_values: &[Vec<Val<SC>>],
+    ) -> Result<(), VerificationError> {
+        verify_multi(config, self.chips(), vks, proof, public_values)
+    }
+}
@@ -0,0 +1,348 @@
+//! Offline memory checking gadget
+//!
+//! [`MemoryChecker`] proves that the memory accesses of a main trace, one per row as
+//! `(address, timestamp, value, is_write)` columns, are read/write consistent: every read
+//! returns the value of the latest earlier write to its address.
+//!
+//! The first aux phase holds the accesses sorted by address then timestamp, in the base
+//! field, with the columns checking each step of the sorted trace: the address either
+//! stays or grows by a range-checked amount, so each address forms a single block. The second samples `α`
+//! and `β` and proves with a LogUp running sum that the sorted trace is a permutation of the
+//! accesses, fingerprinting each as `address + β·timestamp + β²·value + β³·is_write`.
+
+use alloc::vec::Vec;
+
+use p3_field::{
+    batch_multiplicative_inverse, ExtensionField, Field, PrimeCharacteristicRing, PrimeField64,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{AuxBuilder, ChallengePlan};
+
+/// Sorted columns: address, timestamp, value and is_write, then the address step columns
+const SORTED_ADDRESS: usize = 0;
+const SORTED_TIMESTAMP: usize = 1;
+const SORTED_VALUE: usize = 2;
+const SORTED_IS_WRITE: usize = 3;
+/// 1 when the next sorted access has the same address
+const SAME_ADDRESS: usize = 4;
+/// Inverse of the address step, 0 when there is none
+const ADDRESS_STEP_INVERSE: usize = 5;
+/// First bit of the timestamp step decomposition, followed by the address step bits
+const TIMESTAMP_STEP_BITS: usize = 6;
+
+/// Read/write consistency of the memory accesses in a main trace.
+///
+/// An AIR using the gadget has [`NUM_PHASES`](Self::NUM_PHASES) aux phases, all of them the
+/// gadget's: it forwards `aux_phase_width`, `aux_phase_in_base_field`, `challenge_plan` and
+/// `build_aux_phase` from its [`crate::AuxTraceBuilder`] impl, and calls
+/// [`eval`](Self::eval) from its `Air::eval`.
+///
+/// Every row of the main trace is one access, and the first access to each address must be
+/// a write; padding rows can repeat a read. Accesses to one address need distinct
+/// timestamps, at most `2^timestamp_bits` apart from one to the next, and `2^timestamp_bits`
+/// must stay below the field's characteristic. Consecutive distinct addresses must be at
+/// most `2^address_bits` apart, and the trace height times `2^address_bits` must stay below
+/// the characteristic, so that the sorted addresses cannot wrap around the field back to
+/// an address already left behind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryChecker {
+    /// Main column holding the address
+    pub address: usize,
+    /// Main column holding the timestamp
+    pub timestamp: usize,
+    /// Main column holding the value read or written
+    pub value: usize,
+    /// Main column holding 1 for a write and 0 for a read
+    pub is_write: usize,
+    /// Bits of the gap between consecutive timestamps of an address, minus one
+    pub timestamp_bits: usize,
+    /// Bits of the gap between consecutive distinct addresses, minus one
+    pub address_bits: usize,
+}
+
+impl MemoryChecker {
+    /// Aux phases of the gadget: the sorted trace, then the permutation check.
+    pub const NUM_PHASES: usize = 2;
+
+    /// Challenges of the permutation phase: `α` (the pole) and `β` (the fingerprint base).
+    pub const NUM_CHALLENGES: usize = 2;
+
+    /// Number of aux columns of `phase`.
+    pub const fn aux_phase_width(&self, phase: usize) -> usize {
+        match phase {
+            0 => self.address_step_bits() + self.address_bits,
+            1 => 3,
+            _ => 0,
+        }
+    }
+
+    /// First column of the address step decomposition in the sorted trace.
+    const fn address_step_bits(&self) -> usize {
+        TIMESTAMP_STEP_BITS + self.timestamp_bits
+    }
+
+    /// Whether `phase` holds base field values: the sorted trace does.
+    pub const fn aux_phase_in_base_field(&self, phase: usize) -> bool {
+        phase == 0
+    }
+
+    /// The permutation phase samples `memory: {alpha, beta}`.
+    pub fn challenge_plan(&self) -> ChallengePlan {
+        ChallengePlan::new().with_group(1, "memory", &["alpha", "beta"])
+    }
+
+    /// Build the columns of `phase`.
+    ///
+    /// # Panics
+    /// - If the permutation phase gets fewer than [`NUM_CHALLENGES`](Self::NUM_CHALLENGES)
+    ///   challenges, or no sorted trace
+    /// - If a fingerprint hits `α` (negligible probability for honest traces)
+    pub fn build_aux_phase<F, EF>(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF>
+    where
+        F: PrimeField64,
+        EF: ExtensionField<F>,
+    {
+        match phase {
+            0 => self.build_sorted(main),
+            _ => self.build_permutation(main, &prior_phases[0], challenges),
+        }
+    }
+
+    /// The accesses sorted by address then timestamp, with their step columns.
+    fn build_sorted<F, EF>(&self, main: &RowMajorMatrix<F>) -> RowMajorMatrix<EF>
+    where
+        F: PrimeField64,
+        EF: ExtensionField<F>,
+    {
+        let mut accesses: Vec<[F; 4]> = main
+            .values
+            .chunks_exact(main.width)
+            .map(|row| {
+                [
+                    row[self.address],
+                    row[self.timestamp],
+                    row[self.value],
+                    row[self.is_write],
+                ]
+            })
+            .collect();
+        accesses.sort_by_key(|access| {
+            (
+                access[SORTED_ADDRESS].as_canonical_u64(),
+                access[SORTED_TIMESTAMP].as_canonical_u64(),
+            )
+        });
+
+        let width = self.aux_phase_width(0);
+        let mut values = EF::zero_vec(accesses.len() * width);
+        for (i, row) in values.chunks_exact_mut(width).enumerate() {
+            let access = accesses[i];
+            for (column, &value) in access.iter().enumerate() {
+                row[column] = EF::from(value);
+            }
+            let Some(next) = accesses.get(i + 1) else {
+                continue;
+            };
+            let address_step = next[SORTED_ADDRESS] - access[SORTED_ADDRESS];
+            if address_step == F::ZERO {
+                row[SAME_ADDRESS] = EF::ONE;
+                let gap =
+                    (next[SORTED_TIMESTAMP] - access[SORTED_TIMESTAMP] - F::ONE).as_canonical_u64();
+                let bits = &mut row[TIMESTAMP_STEP_BITS..self.address_step_bits()];
+                for (bit, cell) in bits.iter_mut().enumerate() {
+                    *cell = EF::from_bool((gap >> bit) & 1 == 1);
+                }
+            } else {
+                row[ADDRESS_STEP_INVERSE] = EF::from(address_step.inverse());
+                let gap = (address_step - F::ONE).as_canonical_u64();
+                for (bit, cell) in row[self.address_step_bits()..].iter_mut().enumerate() {
+                    *cell = EF::from_bool((gap >> bit) & 1 == 1);
+                }
+            }
+        }
+
+        RowMajorMatrix::new(values, width)
+    }
+
+    /// The helpers `1 / (α - fingerprint)` of each access and each sorted access, and their
+    /// running difference.
+    fn build_permutation<F, EF>(
+        &self,
+        main: &RowMajorMatrix<F>,
+        sorted: &RowMajorMatrix<EF>,
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF>
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+    {
+        let (alpha, beta) = (challenges[0], challenges[1]);
+        let fingerprint = |access: [EF; 4]| {
+            access
+                .into_iter()
+                .rev()
+                .fold(EF::ZERO, |acc, v| acc * beta + v)
+        };
+
+        // Denominators of each access and its sorted counterpart, inverted in one batch
+        let denominators: Vec<EF> = main
+            .values
+            .chunks_exact(main.width)
+            .zip(sorted.values.chunks_exact(sorted.width))
+            .flat_map(|(row, sorted_row)| {
+                let access = [self.address, self.timestamp, self.value, self.is_write]
+                    .map(|column| EF::from(row[column]));
+                let sorted_access = [
+                    sorted_row[SORTED_ADDRESS],
+                    sorted_row[SORTED_TIMESTAMP],
+                    sorted_row[SORTED_VALUE],
+                    sorted_row[SORTED_IS_WRITE],
+                ];
+                [
+                    alpha - fingerprint(access),
+                    alpha - fingerprint(sorted_access),
+                ]
+            })
+            .collect();
+        let inverses = batch_multiplicative_inverse(&denominators);
+
+        let mut sum = EF::ZERO;
+        let values = inverses
+            .chunks_exact(2)
+            .flat_map(|pair| {
+                sum += pair[0] - pair[1];
+                [pair[0], pair[1], sum]
+            })
+            .collect();
+        RowMajorMatrix::new(values, 3)
+    }
+
+    /// Emit the memory consistency constraints.
+    ///
+    /// On the sorted trace, on every step to the next access:
+    /// - `same_address` is 1 exactly when the address does not change, witnessed by the
+    ///   inverse of the address step
+    /// - the address grows by one plus the value of the address step bits when it changes,
+    ///   so an address the sorted trace left cannot come back
+    /// - an access to a new address is a write, as is the first access
+    /// - a read returns the value of the previous access to its address
+    /// - the timestamp grows by one plus the value of the timestamp step bits
+    ///
+    /// On the accesses, `is_write` is boolean, and the permutation running sum starts at the
+    /// first row's helper difference, accumulates the next row's on every transition, and
+    /// ends at zero on the last row.
+    pub fn eval<AB: AuxBuilder>(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+
+        let aux = builder.aux();
+        let aux_local = aux.row_slice(0).expect("Matrix is empty?");
+        let aux_next = aux.row_slice(1).expect("Matrix only has 1 row?");
+
+        let challenges = builder.aux_challenges();
+        let alpha: AB::ExprEF = challenges[0].into();
+        let beta: AB::ExprEF = challenges[1].into();
+
+        let main_access = |row: &[AB::Var]| {
+            [self.address, self.timestamp, self.value, self.is_write].map(|column| {
+                let value: AB::Expr = row[column].clone().into();
+                AB::ExprEF::from(value)
+            })
+        };
+        let sorted_access = |row: &[AB::VarEF]| {
+            [
+                SORTED_ADDRESS,
+                SORTED_TIMESTAMP,
+                SORTED_VALUE,
+                SORTED_IS_WRITE,
+            ]
+            .map(|column| -> AB::ExprEF { row[column].into() })
+        };
+        let fingerprint = |access: [AB::ExprEF; 4]| {
+            access
+                .into_iter()
+                .rev()
+                .fold(AB::ExprEF::ZERO, |acc, v| acc * beta.clone() + v)
+        };
+
+        let is_write: AB::Expr = local[self.is_write].clone().into();
+        builder.assert_bool(is_write);
+
+        // Steps of the sorted trace
+        let sorted = sorted_access(&aux_local[..]);
+        let sorted_next = sorted_access(&aux_next[..]);
+        let same_address: AB::ExprEF = aux_local[SAME_ADDRESS].into();
+        let step_inverse: AB::ExprEF = aux_local[ADDRESS_STEP_INVERSE].into();
+        let address_step = sorted_next[SORTED_ADDRESS].clone() - sorted[SORTED_ADDRESS].clone();
+        let timestamp_step =
+            sorted_next[SORTED_TIMESTAMP].clone() - sorted[SORTED_TIMESTAMP].clone();
+        let value_step = sorted_next[SORTED_VALUE].clone() - sorted[SORTED_VALUE].clone();
+        let bits = |first: usize, len: usize| {
+            (first..first + len)
+                .rev()
+                .fold(AB::ExprEF::ZERO, |acc, column| {
+                    let bit: AB::ExprEF = aux_local[column].into();
+                    acc.double() + bit
+                })
+        };
+        let gap = bits(TIMESTAMP_STEP_BITS, self.timestamp_bits);
+        let address_gap = bits(self.address_step_bits(), self.address_bits);
+        for column in TIMESTAMP_STEP_BITS..self.aux_phase_width(0) {
+            let bit: AB::ExprEF = aux_local[column].into();
+            builder.assert_zero_ext(bit.clone() * (bit - AB::ExprEF::ONE));
+        }
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(sorted[SORTED_IS_WRITE].clone(), AB::ExprEF::ONE);
+        let mut transition = builder.when_transition();
+        transition.assert_eq_ext(
+            same_address.clone(),
+            AB::ExprEF::ONE - address_step.clone() * step_inverse,
+        );
+        transition.assert_zero_ext(same_address.clone() * address_step.clone());
+        transition.assert_zero_ext(
+            (AB::ExprEF::ONE - same_address.clone())
+                * (address_step - AB::ExprEF::ONE - address_gap),
+        );
+        let next_is_read = AB::ExprEF::ONE - sorted_next[SORTED_IS_WRITE].clone();
+        transition.assert_zero_ext((AB::ExprEF::ONE - same_address.clone()) * next_is_read.clone());
+        transition.assert_zero_ext(same_address.clone() * next_is_read * value_step);
+        transition.assert_zero_ext(same_address * (timestamp_step - AB::ExprEF::ONE - gap));
+
+        // Permutation between the accesses and the sorted trace
+        let permutation = self.aux_phase_width(0);
+        let helpers = |row: &[AB::VarEF]| -> (AB::ExprEF, AB::ExprEF) {
+            (row[permutation].into(), row[permutation + 1].into())
+        };
+        let (access_helper, sorted_helper) = helpers(&aux_local[..]);
+        let (access_helper_next, sorted_helper_next) = helpers(&aux_next[..]);
+        let sum_local: AB::ExprEF = aux_local[permutation + 2].into();
+        let sum_next: AB::ExprEF = aux_next[permutation + 2].into();
+
+        builder.assert_eq_ext(
+            access_helper.clone() * (alpha.clone() - fingerprint(main_access(&local[..]))),
+            AB::ExprEF::ONE,
+        );
+        builder.assert_eq_ext(
+            sorted_helper.clone() * (alpha - fingerprint(sorted)),
+            AB::ExprEF::ONE,
+        );
+        builder
+            .when_first_row()
+            .assert_eq_ext(sum_local.clone(), access_helper - sorted_helper);
+        builder.when_transition().assert_eq_ext(
+            sum_next,
+            sum_local.clone() + access_helper_next - sorted_helper_next,
+        );
+        builder.when_last_row().assert_zero_ext(sum_local);
+    }
+}
@@ -0,0 +1,91 @@
+//! Work metering for verification
+//!
+//! Environments that must strictly bound per-request CPU (sequencers, gateways) can pass a
+//! [`VerificationMeter`] to [`crate::verify_metered`]. The verifier charges the meter with an
+//! estimate of each step's cost *before* performing it, and aborts with
+//! [`crate::VerificationError::BudgetExceeded`] as soon as the meter refuses a charge.
+//!
+//! The hashes the PCS runs to check Merkle paths are not metered: their number depends on
+//! the PCS's parameters, which the verifier cannot see. Bound them ahead of verification,
+//! for instance with [`crate::CostModel::estimate`].
+
+use serde::{Deserialize, Serialize};
+
+/// A step of verification that is charged to the meter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationStep {
+    /// Replaying the Fiat-Shamir transcript
+    Transcript,
+    /// Checking the PCS opening proof, charged by the values opened and not by the hashes
+    /// of its Merkle paths
+    PcsVerification,
+    /// Evaluating the AIR constraints at the out-of-domain point
+    ConstraintEvaluation,
+    /// Recomposing the quotient from its chunks
+    QuotientRecomposition,
+}
+
+/// Estimated cost of a verification step.
+///
+/// Hash counts depend on the concrete challenger and PCS, so the verifier reports the
+/// quantities it can count generically and leaves the conversion to the meter. The hashes
+/// of the PCS opening proof, one Merkle path per query and commitment, are not counted
+/// here at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationCost {
+    /// Elements and commitments observed by or sampled from the challenger
+    pub transcript_ops: u64,
+    /// Opened values checked by the PCS
+    pub opened_values: u64,
+    /// Extension field operations performed by the verifier itself
+    pub field_ops: u64,
+}
+
+/// Receives cost estimates during verification and decides whether to continue.
+pub trait VerificationMeter {
+    /// Charge `cost` for `step`. Returning `false` aborts verification.
+    fn charge(&mut self, step: VerificationStep, cost: VerificationCost) -> bool;
+}
+
+/// A meter that accepts every charge.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unmetered;
+
+impl VerificationMeter for Unmetered {
+    fn charge(&mut self, _step: VerificationStep, _cost: VerificationCost) -> bool {
+        true
+    }
+}
+
+/// A meter enforcing fixed upper bounds on the total cost of a verification.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WorkBudget {
+    /// Maximum total cost
+    pub limit: VerificationCost,
+    /// Cost charged so far
+    pub used: VerificationCost,
+}
+
+impl WorkBudget {
+    pub const fn new(limit: VerificationCost) -> Self {
+        Self {
+            limit,
+            used: VerificationCost {
+                transcript_ops: 0,
+                opened_values: 0,
+                field_ops: 0,
+            },
+        }
+    }
+}
+
+impl VerificationMeter for WorkBudget {
+    fn charge(&mut self, _step: VerificationStep, cost: VerificationCost) -> bool {
+        self.used.transcript_ops = self.used.transcript_ops.saturating_add(cost.transcript_ops);
+        self.used.opened_values = self.used.opened_values.saturating_add(cost.opened_values);
+        self.used.field_ops = self.used.field_ops.saturating_add(cost.field_ops);
+        self.used.transcript_ops <= self.limit.transcript_ops
+            && self.used.opened_values <= self.limit.opened_values
+            && self.used.field_ops <= self.limit.field_ops
+    }
+}
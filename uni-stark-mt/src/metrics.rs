@@ -0,0 +1,251 @@
+//! Structured prover metrics
+//!
+//! [`MetricsRecorder`] is a [`ProverTelemetry`] collecting, for each phase of a proof, its
+//! wall-clock time, the field operations the prover reports for it and, with a
+//! [`CountingAllocator`] installed as the global allocator, its peak heap usage, into a
+//! serde-serializable [`ProverMetrics`]. [`prove_with_metrics`] proves and returns them
+//! next to the proof, for benchmarking services that store numbers rather than tracing
+//! spans.
+
+use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::alloc::System;
+use std::time::{Duration, Instant};
+
+use p3_air::Air;
+use p3_field::PackedField;
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prove_with_telemetry, Challenge, MultiTraceAir, Proof, ProverError, ProverFolder, ProverPhase,
+    ProverTelemetry, ProvingKey, StarkGenericConfig, Val, VerifierFolder,
+};
+
+/// What one phase of a proof cost.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseMetrics {
+    /// The phase
+    pub phase: ProverPhase,
+    /// Wall-clock time from its start to its end
+    pub wall_time: Duration,
+    /// Extension field operations the prover reported for it, see
+    /// [`ProverTelemetry::field_ops`]
+    pub field_ops: u64,
+    /// Most heap bytes in use at once during the phase, above the usage at its start;
+    /// `None` without a [`CountingAllocator`]
+    pub peak_allocation: Option<usize>,
+}
+
+/// What each phase of a proof cost, in the order the prover ran them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverMetrics {
+    /// One entry per phase
+    pub phases: Vec<PhaseMetrics>,
+}
+
+impl ProverMetrics {
+    /// The metrics of `phase`, if it ran.
+    pub fn phase(&self, phase: ProverPhase) -> Option<&PhaseMetrics> {
+        self.phases.iter().find(|metrics| metrics.phase == phase)
+    }
+
+    /// Wall-clock time of all phases.
+    pub fn wall_time(&self) -> Duration {
+        self.phases.iter().map(|metrics| metrics.wall_time).sum()
+    }
+
+    /// Field operations of all phases.
+    pub fn field_ops(&self) -> u64 {
+        self.phases.iter().map(|metrics| metrics.field_ops).sum()
+    }
+}
+
+/// A global allocator counting the heap bytes in use, for [`MetricsRecorder`] to report
+/// peak allocation per phase.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+///
+/// let recorder = MetricsRecorder::with_allocator(&ALLOCATOR);
+/// ```
+///
+/// Allocations are served by [`System`]. The counts cover every thread.
+#[derive(Debug, Default)]
+pub struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    /// No bytes counted yet.
+    pub const fn new() -> Self {
+        Self {
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    /// Heap bytes in use.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Most heap bytes in use at once since the last [`CountingAllocator::reset_peak`].
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Restart the peak from the bytes in use now.
+    pub fn reset_peak(&self) {
+        self.peak.store(self.current(), Ordering::Relaxed);
+    }
+
+    fn grew(&self, bytes: usize) {
+        let current = self.current.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn shrank(&self, bytes: usize) {
+        self.current.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+// SAFETY: every call is forwarded to `System` unchanged; only the counters are updated
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            self.grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            self.grew(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        self.shrank(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.shrank(layout.size());
+            self.grew(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Collects [`ProverMetrics`] from the prover's telemetry.
+#[derive(Debug, Default)]
+pub struct MetricsRecorder {
+    allocator: Option<&'static CountingAllocator>,
+    running: Vec<RunningPhase>,
+    metrics: ProverMetrics,
+}
+
+#[derive(Debug)]
+struct RunningPhase {
+    phase: ProverPhase,
+    started: Instant,
+    field_ops: u64,
+    allocated_at_start: usize,
+}
+
+impl MetricsRecorder {
+    /// Record wall time and field operations only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also record peak allocation, read from `allocator`, which must be the global
+    /// allocator.
+    pub fn with_allocator(allocator: &'static CountingAllocator) -> Self {
+        Self {
+            allocator: Some(allocator),
+            ..Self::default()
+        }
+    }
+
+    /// The metrics of the phases finished so far.
+    pub const fn metrics(&self) -> &ProverMetrics {
+        &self.metrics
+    }
+
+    /// The metrics of the phases finished so far.
+    pub fn into_metrics(self) -> ProverMetrics {
+        self.metrics
+    }
+}
+
+impl ProverTelemetry for &mut MetricsRecorder {
+    fn phase_started(&mut self, phase: ProverPhase, _rows: usize) {
+        let allocated_at_start = self.allocator.map_or(0, |allocator| {
+            allocator.reset_peak();
+            allocator.current()
+        });
+        self.running.push(RunningPhase {
+            phase,
+            started: Instant::now(),
+            field_ops: 0,
+            allocated_at_start,
+        });
+    }
+
+    fn field_ops(&mut self, phase: ProverPhase, ops: u64) {
+        if let Some(running) = self.running.iter_mut().rfind(|r| r.phase == phase) {
+            running.field_ops = running.field_ops.saturating_add(ops);
+        }
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        let Some(i) = self.running.iter().rposition(|r| r.phase == phase) else {
+            return;
+        };
+        let running = self.running.remove(i);
+        self.metrics.phases.push(PhaseMetrics {
+            phase,
+            wall_time: running.started.elapsed(),
+            field_ops: running.field_ops,
+            peak_allocation: self
+                .allocator
+                .map(|allocator| allocator.peak().saturating_sub(running.allocated_at_start)),
+        });
+    }
+}
+
+/// [`crate::prove`], also returning the [`ProverMetrics`] of the proof.
+///
+/// Peak allocation is recorded if `allocator` is given; it must be the global allocator.
+pub fn prove_with_metrics<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    allocator: Option<&'static CountingAllocator>,
+) -> Result<(Proof<SC>, ProverMetrics), ProverError>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let mut recorder = MetricsRecorder {
+        allocator,
+        ..MetricsRecorder::default()
+    };
+    let proof = prove_with_telemetry(config, air, pk, main_trace, public_values, &mut recorder)?;
+    Ok((proof, recorder.into_metrics()))
+}
@@ -0,0 +1,178 @@
+//! Main traces backed by memory-mapped files
+//!
+//! A trace generator running as a separate process can write the trace to a file as the raw
+//! in-memory representation of its field elements, row after row. [`MmapTrace`] maps that
+//! file and reads it as a [`Matrix`] in place, after checking its length, alignment and
+//! shape, so it never goes through a read buffer.
+//!
+//! The PCS commits to an owned [`crate::RowMajorMatrix`], which it extends into a low-degree
+//! extension `blowup` times larger. [`Matrix::to_row_major_matrix`] fills that input
+//! directly from the mapping, which is the only copy of the trace the prover makes.
+
+use core::marker::PhantomData;
+use core::{fmt, mem, slice};
+use std::fs::File;
+use std::io;
+
+use memmap2::Mmap;
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::Matrix;
+
+/// Why a mapped file is not a trace.
+#[derive(Debug)]
+pub enum MmapTraceError {
+    /// The file could not be mapped
+    Io(io::Error),
+    /// The trace width is zero
+    ZeroWidth,
+    /// The file length is not a whole number of field elements
+    PartialElement {
+        /// File length in bytes
+        len: usize,
+        /// Size of one field element in bytes
+        element_size: usize,
+    },
+    /// The mapping is not aligned for the field element type
+    Misaligned {
+        /// Required alignment in bytes
+        align: usize,
+    },
+    /// The number of elements is not a whole number of rows
+    PartialRow {
+        /// Number of elements in the file
+        elements: usize,
+        /// Trace width
+        width: usize,
+    },
+    /// The trace height is not a nonzero power of two
+    HeightNotPowerOfTwo(usize),
+}
+
+impl fmt::Display for MmapTraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to map trace file: {err}"),
+            Self::ZeroWidth => f.write_str("trace width is zero"),
+            Self::PartialElement { len, element_size } => write!(
+                f,
+                "trace file length {len} is not a multiple of the element size {element_size}"
+            ),
+            Self::Misaligned { align } => {
+                write!(f, "trace mapping is not aligned to {align} bytes")
+            }
+            Self::PartialRow { elements, width } => write!(
+                f,
+                "{elements} trace elements do not fill rows of width {width}"
+            ),
+            Self::HeightNotPowerOfTwo(height) => {
+                write!(f, "trace height {height} is not a power of two")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MmapTraceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MmapTraceError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// A row-major main trace read in place from a memory-mapped file.
+#[derive(Debug)]
+pub struct MmapTrace<F> {
+    map: Mmap,
+    width: usize,
+    height: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> MmapTrace<F> {
+    /// Map `file` as a trace of `width` columns.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while it is mapped, and its bytes must be valid values
+    /// of `F` in the in-memory representation of this build (for Montgomery fields, the
+    /// Montgomery form), as written by viewing a `&[F]` as bytes.
+    pub unsafe fn map(file: &File, width: usize) -> Result<Self, MmapTraceError> {
+        let map = unsafe { Mmap::map(file)? };
+        unsafe { Self::from_mmap(map, width) }
+    }
+
+    /// Read an existing mapping as a trace of `width` columns.
+    ///
+    /// # Safety
+    ///
+    /// The mapped bytes must be valid values of `F` in the in-memory representation of this
+    /// build, as for [`Self::map`].
+    pub unsafe fn from_mmap(map: Mmap, width: usize) -> Result<Self, MmapTraceError> {
+        if width == 0 {
+            return Err(MmapTraceError::ZeroWidth);
+        }
+        let element_size = mem::size_of::<F>();
+        if map.len() % element_size != 0 {
+            return Err(MmapTraceError::PartialElement {
+                len: map.len(),
+                element_size,
+            });
+        }
+        let align = mem::align_of::<F>();
+        if map.as_ptr().align_offset(align) != 0 {
+            return Err(MmapTraceError::Misaligned { align });
+        }
+        let elements = map.len() / element_size;
+        if elements % width != 0 {
+            return Err(MmapTraceError::PartialRow { elements, width });
+        }
+        let height = elements / width;
+        if !height.is_power_of_two() {
+            return Err(MmapTraceError::HeightNotPowerOfTwo(height));
+        }
+        Ok(Self {
+            map,
+            width,
+            height,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The trace values, row after row.
+    pub fn values(&self) -> &[F] {
+        // The constructor checked the length and alignment, and its caller vouched for the
+        // contents
+        unsafe { slice::from_raw_parts(self.map.as_ptr().cast::<F>(), self.width * self.height) }
+    }
+
+    /// Borrow the trace as a dense matrix view.
+    pub fn as_view(&self) -> RowMajorMatrixView<'_, F> {
+        RowMajorMatrixView::new(self.values(), self.width)
+    }
+}
+
+impl<F: Field> Matrix<F> for MmapTrace<F> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    unsafe fn get_unchecked(&self, row: usize, col: usize) -> F {
+        *self.values().get_unchecked(row * self.width + col)
+    }
+
+    fn row_slice(&self, r: usize) -> Option<&[F]> {
+        self.values().get(r * self.width..(r + 1) * self.width)
+    }
+}
@@ -0,0 +1,81 @@
+//! Merkle tree MMCS built from one cryptographic permutation
+//!
+//! The column commitments hash each leaf with a sponge over the permutation and compress
+//! pairs of digests with the truncated permutation. Spelling this out by hand means picking
+//! `PaddingFreeSponge<Perm, WIDTH, RATE, DIGEST>` and `TruncatedPermutation<Perm, 2, DIGEST,
+//! WIDTH>` parameters that agree with each other and with the permutation; [`perm_mmcs`]
+//! takes the three numbers once, checks them and builds the MMCS.
+//!
+//! ```
+//! use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+//! use p3_uni_stark_mt::perm_mmcs;
+//! use rand::rngs::SmallRng;
+//! use rand::SeedableRng;
+//!
+//! let perm = Poseidon2BabyBear::<16>::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+//! // 16-element permutation, 8 elements absorbed per call, 8-element digests
+//! let mmcs = perm_mmcs::<BabyBear, _, 16, 8, 8>(perm);
+//! # let _ = mmcs;
+//! ```
+
+use p3_field::Field;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CryptographicPermutation, PaddingFreeSponge, TruncatedPermutation};
+
+/// Leaf hash: a sponge absorbing `RATE` elements per call and squeezing `DIGEST` elements.
+pub type PermHash<Perm, const WIDTH: usize, const RATE: usize, const DIGEST: usize> =
+    PaddingFreeSponge<Perm, WIDTH, RATE, DIGEST>;
+
+/// Node compression: two `DIGEST`-element digests in, the truncated permutation output out.
+pub type PermCompress<Perm, const WIDTH: usize, const DIGEST: usize> =
+    TruncatedPermutation<Perm, 2, DIGEST, WIDTH>;
+
+/// Binary Merkle tree MMCS over `F`, hashed and compressed with `Perm`.
+pub type PermMmcs<F, Perm, const WIDTH: usize, const RATE: usize, const DIGEST: usize> =
+    MerkleTreeMmcs<
+        <F as Field>::Packing,
+        <F as Field>::Packing,
+        PermHash<Perm, WIDTH, RATE, DIGEST>,
+        PermCompress<Perm, WIDTH, DIGEST>,
+        DIGEST,
+    >;
+
+/// Rate and digest choices for a `WIDTH`-element permutation.
+///
+/// The sponge must keep some capacity (`RATE < WIDTH`) of at least one digest
+/// (`RATE + DIGEST <= WIDTH`), and the compression must fit two digests in one
+/// permutation call (`2 * DIGEST <= WIDTH`).
+pub struct MmcsParams<const WIDTH: usize, const RATE: usize, const DIGEST: usize>;
+
+impl<const WIDTH: usize, const RATE: usize, const DIGEST: usize> MmcsParams<WIDTH, RATE, DIGEST> {
+    /// Evaluating this fails the build on invalid parameters.
+    pub const CHECK: () = {
+        assert!(RATE > 0, "The sponge rate must be positive");
+        assert!(DIGEST > 0, "Digests must hold at least one element");
+        assert!(RATE < WIDTH, "The sponge rate must leave some capacity");
+        assert!(
+            RATE + DIGEST <= WIDTH,
+            "The sponge capacity must hold at least one digest"
+        );
+        assert!(
+            2 * DIGEST <= WIDTH,
+            "Two digests must fit in one permutation call"
+        );
+    };
+}
+
+/// Build the [`PermMmcs`] with `RATE` and `DIGEST` for the `WIDTH`-element `perm`.
+///
+/// `perm` must permute `[F; WIDTH]`, and the parameters are checked by
+/// [`MmcsParams::CHECK`] at compile time.
+pub fn perm_mmcs<F, Perm, const WIDTH: usize, const RATE: usize, const DIGEST: usize>(
+    perm: Perm,
+) -> PermMmcs<F, Perm, WIDTH, RATE, DIGEST>
+where
+    F: Field,
+    Perm: CryptographicPermutation<[F; WIDTH]>,
+{
+    #[allow(clippy::let_unit_value)]
+    let () = MmcsParams::<WIDTH, RATE, DIGEST>::CHECK;
+    PermMmcs::new(PermHash::new(perm.clone()), PermCompress::new(perm))
+}
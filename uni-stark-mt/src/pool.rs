@@ -0,0 +1,51 @@
+//! Worker pool of the prover
+//!
+//! With the `parallel` feature, the DFTs and Merkle hashing of the PCS and the quotient
+//! backend run on rayon's global pool, unless [`crate::StarkGenericConfig::num_threads`]
+//! asks for a pool of their own. [`WorkerPool`] holds that pool for the duration of a
+//! proof, and the prover runs each of those steps through [`WorkerPool::install`].
+
+use crate::ProverError;
+
+/// The pool the heavy steps of one proof run on.
+pub(crate) struct WorkerPool {
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl WorkerPool {
+    /// A pool of `num_threads` workers, or the global pool for `None`.
+    ///
+    /// Without the `parallel` feature every step runs on the calling thread.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn new(num_threads: Option<usize>) -> Result<Self, ProverError> {
+        let pool = num_threads
+            .map(|num_threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .thread_name(|index| alloc::format!("p3-uni-stark-mt-{index}"))
+                    .build()
+                    .map_err(|_| ProverError::ThreadPoolUnavailable(num_threads))
+            })
+            .transpose()?;
+        Ok(Self { pool })
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) const fn new(_num_threads: Option<usize>) -> Result<Self, ProverError> {
+        Ok(Self {})
+    }
+
+    /// Run `op` on the pool, within the current tracing span, and wait for it.
+    ///
+    /// `op` and its result must be `Send` with or without the `parallel` feature, so the
+    /// bounds the prover relies on do not change with the features enabled.
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            let span = tracing::Span::current();
+            return pool.install(move || span.in_scope(op));
+        }
+        op()
+    }
+}
@@ -0,0 +1,281 @@
+//! Ready-made configs for common field and hash choices
+//!
+//! Assembling a [`StarkConfig`] by hand means picking a permutation, the sponge and
+//! compression built on it, the MMCS over the base and extension fields, a DFT, FRI
+//! parameters and a challenger that all agree with each other. Each preset here builds the
+//! whole stack from a target security level, with fixed permutation round constants, and
+//! has its [`CostModel`] next to it.
+//!
+//! ```
+//! use p3_uni_stark_mt::presets::{baby_bear_poseidon2_config, baby_bear_poseidon2_cost_model};
+//!
+//! // Instead of a page of type aliases and constructors
+//! let config = baby_bear_poseidon2_config(100);
+//! assert_eq!(baby_bear_poseidon2_cost_model(100).num_queries, 50);
+//! # let _ = config;
+//! ```
+//!
+//! Besides the Poseidon2 presets, [`baby_bear_keccak_config`] hashes its Merkle trees and
+//! transcript with Keccak-256, for proofs checked where only Keccak is cheap, such as the
+//! EVM.
+//!
+//! Security levels are conjectured FRI soundness: every query contributes `log_blowup`
+//! bits, proof-of-work grinding aside.
+
+use alloc::vec::Vec;
+use p3_baby_bear::{default_babybear_poseidon2_16, BabyBear, Poseidon2BabyBear};
+
+use p3_challenger::{DuplexChallenger, HashChallenger, SerializingChallenger32};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::{create_test_fri_params, FriParameters, TwoAdicFriPcs};
+use p3_goldilocks::{Goldilocks, Poseidon2Goldilocks};
+use p3_keccak::Keccak256Hash;
+use p3_koala_bear::{default_koalabear_poseidon2_16, KoalaBear, Poseidon2KoalaBear};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+use crate::{perm_mmcs, CostModel, PermMmcs, StarkConfig};
+
+/// Log2 of the FRI blowup of every preset, enough for constraints of degree up to 5
+const LOG_BLOWUP: usize = 2;
+
+/// Log2 of the final FRI polynomial length of every preset
+const LOG_FINAL_POLY_LEN: usize = 0;
+
+/// Highest security level of every preset, below what their challenge fields leave room for
+const MAX_SECURITY_BITS: usize = 100;
+
+/// Seed the round constants of [`GoldilocksPerm`] are drawn from, ASCII `goldiloc`
+const GOLDILOCKS_PERM_SEED: u64 = 0x676f_6c64_696c_6f63;
+
+/// Poseidon2 over BabyBear, 16 elements wide.
+pub type BabyBearPerm = Poseidon2BabyBear<16>;
+/// Merkle tree MMCS over BabyBear hashed with [`BabyBearPerm`].
+pub type BabyBearPoseidon2Mmcs = PermMmcs<BabyBear, BabyBearPerm, 16, 8, 8>;
+/// Degree 4 extension of BabyBear the challenges are drawn from.
+pub type BabyBearChallenge = BinomialExtensionField<BabyBear, 4>;
+/// FRI PCS over BabyBear hashed with [`BabyBearPerm`].
+pub type BabyBearPoseidon2Pcs = TwoAdicFriPcs<
+    BabyBear,
+    Radix2DitParallel<BabyBear>,
+    BabyBearPoseidon2Mmcs,
+    ExtensionMmcs<BabyBear, BabyBearChallenge, BabyBearPoseidon2Mmcs>,
+>;
+/// Config built by [`baby_bear_poseidon2_config`].
+pub type BabyBearPoseidon2Config = StarkConfig<
+    BabyBearPoseidon2Pcs,
+    BabyBearChallenge,
+    DuplexChallenger<BabyBear, BabyBearPerm, 16, 8>,
+>;
+
+/// BabyBear with a degree 4 extension, Poseidon2 Merkle commitments and FRI, at
+/// `security_bits` of conjectured security.
+///
+/// # Panics
+/// - If `security_bits` is 0 or above 100
+pub fn baby_bear_poseidon2_config(security_bits: usize) -> BabyBearPoseidon2Config {
+    let perm = default_babybear_poseidon2_16();
+    let val_mmcs = perm_mmcs::<BabyBear, BabyBearPerm, 16, 8, 8>(perm.clone());
+    let challenge_mmcs = ExtensionMmcs::new(val_mmcs.clone());
+    let pcs = TwoAdicFriPcs::new(
+        Radix2DitParallel::default(),
+        val_mmcs,
+        fri_params(challenge_mmcs, security_bits),
+    );
+    StarkConfig::new(pcs, DuplexChallenger::new(perm))
+        .with_fri_params(&fri_params((), security_bits))
+}
+
+/// The cost model of [`baby_bear_poseidon2_config`] at `security_bits`.
+pub fn baby_bear_poseidon2_cost_model(security_bits: usize) -> CostModel {
+    let fri_params = fri_params((), security_bits);
+    CostModel {
+        val_bytes: 4,
+        challenge_degree: 4,
+        digest_elems: 8,
+        hash_rate: 8,
+        log_blowup: fri_params.log_blowup,
+        log_final_poly_len: fri_params.log_final_poly_len,
+        num_queries: fri_params.num_queries,
+        num_ood_points: 1,
+    }
+}
+
+/// Leaf hash: Keccak-256 of the little-endian bytes of the field elements.
+pub type KeccakFieldHash = SerializingHasher<Keccak256Hash>;
+/// Node compression: Keccak-256 of the two digests side by side.
+pub type KeccakCompress = CompressionFunctionFromHasher<Keccak256Hash, 2, 32>;
+/// Merkle tree MMCS over `F` with 32-byte Keccak-256 digests.
+pub type KeccakMmcs<F> = MerkleTreeMmcs<F, u8, KeccakFieldHash, KeccakCompress, 32>;
+/// FRI PCS over BabyBear hashed with Keccak-256.
+pub type BabyBearKeccakPcs = TwoAdicFriPcs<
+    BabyBear,
+    Radix2DitParallel<BabyBear>,
+    KeccakMmcs<BabyBear>,
+    ExtensionMmcs<BabyBear, BabyBearChallenge, KeccakMmcs<BabyBear>>,
+>;
+/// Config built by [`baby_bear_keccak_config`].
+pub type BabyBearKeccakConfig = StarkConfig<
+    BabyBearKeccakPcs,
+    BabyBearChallenge,
+    SerializingChallenger32<BabyBear, HashChallenger<u8, Keccak256Hash, 32>>,
+>;
+
+/// BabyBear with a degree 4 extension, Keccak-256 Merkle commitments and FRI, at
+/// `security_bits` of conjectured security.
+///
+/// Leaves hash the serialized field elements with Keccak-256, nodes hash the concatenation
+/// of their children's digests, and the challenger absorbs serialized elements into
+/// Keccak-256, so every commitment and challenge can be recomputed with nothing but a
+/// Keccak-256 implementation.
+///
+/// # Panics
+/// - If `security_bits` is 0 or above 100
+pub fn baby_bear_keccak_config(security_bits: usize) -> BabyBearKeccakConfig {
+    let val_mmcs = KeccakMmcs::new(
+        KeccakFieldHash::new(Keccak256Hash {}),
+        KeccakCompress::new(Keccak256Hash {}),
+    );
+    let challenge_mmcs = ExtensionMmcs::new(val_mmcs.clone());
+    let pcs = TwoAdicFriPcs::new(
+        Radix2DitParallel::default(),
+        val_mmcs,
+        fri_params(challenge_mmcs, security_bits),
+    );
+    StarkConfig::new(
+        pcs,
+        SerializingChallenger32::from_hasher(Vec::new(), Keccak256Hash {}),
+    )
+    .with_fri_params(&fri_params((), security_bits))
+}
+
+/// The cost model of [`baby_bear_keccak_config`] at `security_bits`.
+///
+/// A 32-byte digest counts as 8 BabyBear elements, and Keccak-256 absorbs 136 bytes, 34
+/// serialized elements, per permutation.
+pub fn baby_bear_keccak_cost_model(security_bits: usize) -> CostModel {
+    CostModel {
+        digest_elems: 8,
+        hash_rate: 34,
+        ..baby_bear_poseidon2_cost_model(security_bits)
+    }
+}
+
+/// Poseidon2 over KoalaBear, 16 elements wide.
+pub type KoalaBearPerm = Poseidon2KoalaBear<16>;
+/// Merkle tree MMCS over KoalaBear hashed with [`KoalaBearPerm`].
+pub type KoalaBearPoseidon2Mmcs = PermMmcs<KoalaBear, KoalaBearPerm, 16, 8, 8>;
+/// Degree 4 extension of KoalaBear the challenges are drawn from.
+pub type KoalaBearChallenge = BinomialExtensionField<KoalaBear, 4>;
+/// FRI PCS over KoalaBear hashed with [`KoalaBearPerm`].
+pub type KoalaBearPoseidon2Pcs = TwoAdicFriPcs<
+    KoalaBear,
+    Radix2DitParallel<KoalaBear>,
+    KoalaBearPoseidon2Mmcs,
+    ExtensionMmcs<KoalaBear, KoalaBearChallenge, KoalaBearPoseidon2Mmcs>,
+>;
+/// Config built by [`koala_bear_poseidon2_config`].
+pub type KoalaBearPoseidon2Config = StarkConfig<
+    KoalaBearPoseidon2Pcs,
+    KoalaBearChallenge,
+    DuplexChallenger<KoalaBear, KoalaBearPerm, 16, 8>,
+>;
+
+/// KoalaBear with a degree 4 extension, Poseidon2 Merkle commitments and FRI, at
+/// `security_bits` of conjectured security.
+///
+/// # Panics
+/// - If `security_bits` is 0 or above 100
+pub fn koala_bear_poseidon2_config(security_bits: usize) -> KoalaBearPoseidon2Config {
+    let perm = default_koalabear_poseidon2_16();
+    let val_mmcs = perm_mmcs::<KoalaBear, KoalaBearPerm, 16, 8, 8>(perm.clone());
+    let challenge_mmcs = ExtensionMmcs::new(val_mmcs.clone());
+    let pcs = TwoAdicFriPcs::new(
+        Radix2DitParallel::default(),
+        val_mmcs,
+        fri_params(challenge_mmcs, security_bits),
+    );
+    StarkConfig::new(pcs, DuplexChallenger::new(perm))
+        .with_fri_params(&fri_params((), security_bits))
+}
+
+/// The cost model of [`koala_bear_poseidon2_config`] at `security_bits`.
+///
+/// The same as BabyBear's: both are 31-bit fields hashed with a 16-wide Poseidon2.
+pub fn koala_bear_poseidon2_cost_model(security_bits: usize) -> CostModel {
+    baby_bear_poseidon2_cost_model(security_bits)
+}
+
+/// Poseidon2 over Goldilocks, 8 elements wide.
+pub type GoldilocksPerm = Poseidon2Goldilocks<8>;
+/// Merkle tree MMCS over Goldilocks hashed with [`GoldilocksPerm`].
+pub type GoldilocksPoseidon2Mmcs = PermMmcs<Goldilocks, GoldilocksPerm, 8, 4, 4>;
+/// Quadratic extension of Goldilocks the challenges are drawn from.
+pub type GoldilocksChallenge = BinomialExtensionField<Goldilocks, 2>;
+/// FRI PCS over Goldilocks hashed with [`GoldilocksPerm`].
+pub type GoldilocksPoseidon2Pcs = TwoAdicFriPcs<
+    Goldilocks,
+    Radix2DitParallel<Goldilocks>,
+    GoldilocksPoseidon2Mmcs,
+    ExtensionMmcs<Goldilocks, GoldilocksChallenge, GoldilocksPoseidon2Mmcs>,
+>;
+/// Config built by [`goldilocks_poseidon2_config`].
+pub type GoldilocksPoseidon2Config = StarkConfig<
+    GoldilocksPoseidon2Pcs,
+    GoldilocksChallenge,
+    DuplexChallenger<Goldilocks, GoldilocksPerm, 8, 4>,
+>;
+
+/// Goldilocks with a quadratic extension, Poseidon2 Merkle commitments and FRI, at
+/// `security_bits` of conjectured security.
+///
+/// Suits traces carried over from Plonky2-era systems, which are natively Goldilocks. Plonky3
+/// ships no canonical Goldilocks Poseidon2 instance, so the round constants are drawn from a
+/// fixed seed: every call builds the same permutation.
+///
+/// # Panics
+/// - If `security_bits` is 0 or above 100
+pub fn goldilocks_poseidon2_config(security_bits: usize) -> GoldilocksPoseidon2Config {
+    let perm = GoldilocksPerm::new_from_rng_128(&mut SmallRng::seed_from_u64(GOLDILOCKS_PERM_SEED));
+    let val_mmcs = perm_mmcs::<Goldilocks, GoldilocksPerm, 8, 4, 4>(perm.clone());
+    let challenge_mmcs = ExtensionMmcs::new(val_mmcs.clone());
+    let pcs = TwoAdicFriPcs::new(
+        Radix2DitParallel::default(),
+        val_mmcs,
+        fri_params(challenge_mmcs, security_bits),
+    );
+    StarkConfig::new(pcs, DuplexChallenger::new(perm))
+        .with_fri_params(&fri_params((), security_bits))
+}
+
+/// The cost model of [`goldilocks_poseidon2_config`] at `security_bits`.
+pub fn goldilocks_poseidon2_cost_model(security_bits: usize) -> CostModel {
+    let fri_params = fri_params((), security_bits);
+    CostModel {
+        val_bytes: 8,
+        challenge_degree: 2,
+        digest_elems: 4,
+        hash_rate: 4,
+        log_blowup: fri_params.log_blowup,
+        log_final_poly_len: fri_params.log_final_poly_len,
+        num_queries: fri_params.num_queries,
+        num_ood_points: 1,
+    }
+}
+
+/// FRI parameters with enough queries for `security_bits`.
+fn fri_params<M>(mmcs: M, security_bits: usize) -> FriParameters<M> {
+    assert!(
+        (1..=MAX_SECURITY_BITS).contains(&security_bits),
+        "presets support 1 to {MAX_SECURITY_BITS} bits of security"
+    );
+    FriParameters {
+        log_blowup: LOG_BLOWUP,
+        num_queries: security_bits.div_ceil(LOG_BLOWUP),
+        ..create_test_fri_params(mmcs, LOG_FINAL_POLY_LEN)
+    }
+}
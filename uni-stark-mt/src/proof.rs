@@ -1,18 +1,46 @@
 //! Proof structures
 
 use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_challenger::{CanObserve, CanSample};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use serde::{Deserialize, Serialize};
+
+use crate::{Challenge, MultiTraceAir, StarkGenericConfig, Val};
+
+/// Revision of the proof protocol written by this version of the prover.
+///
+/// Bumped whenever the transcript or the proof layout changes, so that [`crate::verify`],
+/// [`crate::verify_multi`] and [`crate::verify_batch`] reject a proof from another
+/// revision up front.
+pub const PROOF_VERSION: u16 = 1;
 
 /// A multi-trace STARK proof.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Proof<SC: crate::StarkGenericConfig> {
+    /// Protocol revision the proof was produced under, [`PROOF_VERSION`] for new proofs
+    ///
+    /// Serialized first, so a decoder can tell which revision it holds before reading
+    /// anything else.
+    pub version: u16,
+
     /// Commitment to the main trace
     pub main_commit: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment,
 
-    /// Commitment to the auxiliary trace (None if no aux trace)
-    pub aux_commit: Option<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+    /// Commitments to the auxiliary trace of each phase (empty if no aux trace)
+    pub aux_commits: Vec<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+
+    /// Values exposed by each auxiliary phase, e.g. final cumulative sums
+    pub aux_exposed_values: Vec<Vec<SC::Challenge>>,
 
-    /// Commitment to quotient polynomial chunks (all chunks in one commitment)
-    pub quotient_commit: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment,
+    /// Commitments to the quotient polynomial chunks, one per column group
+    ///
+    /// A single commitment holds every chunk unless
+    /// [`crate::StarkGenericConfig::max_leaf_width`] asks for narrower leaves.
+    pub quotient_commits:
+        Vec<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
 
     /// Opened values of main trace at ζ (out-of-domain point)
     pub main_local: Vec<SC::Challenge>,
@@ -20,11 +48,19 @@ pub struct Proof<SC: crate::StarkGenericConfig> {
     /// Opened values of main trace at ζ·g (next row)
     pub main_next: Vec<SC::Challenge>,
 
-    /// Opened values of aux trace at ζ (if aux trace exists)
-    pub aux_local: Vec<SC::Challenge>,
+    /// Opened values of the preprocessed trace at ζ (empty without preprocessed columns)
+    pub preprocessed_local: Vec<SC::Challenge>,
 
-    /// Opened values of aux trace at ζ·g (if aux trace exists)
-    pub aux_next: Vec<SC::Challenge>,
+    /// Opened values of the preprocessed trace at ζ·g
+    pub preprocessed_next: Vec<SC::Challenge>,
+
+    /// Opened values of each aux phase at ζ (flattened to base field coordinates)
+    ///
+    /// `None` when the AIR has no aux phase, so single-phase proofs carry no empty list.
+    pub aux_local: Option<Vec<Vec<SC::Challenge>>>,
+
+    /// Opened values of each aux phase at ζ·g, `None` like [`Proof::aux_local`]
+    pub aux_next: Option<Vec<Vec<SC::Challenge>>>,
 
     /// Opened values of quotient chunks at ζ
     /// Each chunk is a Vec<Challenge> (all columns in that chunk at zeta)
@@ -33,6 +69,185 @@ pub struct Proof<SC: crate::StarkGenericConfig> {
     /// PCS opening proof
     pub opening_proof: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Proof,
 
+    /// Values opened at the additional out-of-domain points, in sampling order
+    ///
+    /// Empty unless [`crate::StarkGenericConfig::num_ood_points`] is above 1.
+    pub additional_openings: Vec<OpenedValues<SC::Challenge>>,
+
     /// Degree (log2 of trace height)
     pub log_degree: u8,
+
+    /// Optional digest of the public values this proof attests to.
+    ///
+    /// Set with [`Proof::commit_to_public_values`]; when present, [`crate::verify`]
+    /// rejects the proof if the supplied public values hash to something else.
+    pub public_values_digest: Option<SC::Challenge>,
+
+    /// Optional record of the challenges sampled by each aux phase, in phase order.
+    ///
+    /// Set with [`Proof::record_aux_challenges`]; when present, [`crate::verify`] rejects
+    /// the proof with [`crate::VerificationError::AuxChallengeMismatch`] if the AIR declares
+    /// different counts, instead of failing on an unexplained constraint mismatch.
+    pub aux_challenge_counts: Option<Vec<usize>>,
+}
+
+/// Values opened at one additional out-of-domain point `ζ'` (and `ζ'·g`).
+///
+/// Mirrors the opened values stored directly in [`Proof`] for the first point.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenedValues<EF> {
+    /// Main trace at ζ'
+    pub main_local: Vec<EF>,
+    /// Main trace at ζ'·g
+    pub main_next: Vec<EF>,
+    /// Preprocessed trace at ζ'
+    pub preprocessed_local: Vec<EF>,
+    /// Preprocessed trace at ζ'·g
+    pub preprocessed_next: Vec<EF>,
+    /// Each aux phase at ζ' (flattened to base field coordinates), `None` without aux phases
+    pub aux_local: Option<Vec<Vec<EF>>>,
+    /// Each aux phase at ζ'·g (flattened to base field coordinates), `None` without aux phases
+    pub aux_next: Option<Vec<Vec<EF>>>,
+    /// Quotient chunks at ζ'
+    pub quotient_chunks: Vec<Vec<EF>>,
+}
+
+/// A proof of several AIRs ("tables") sharing one transcript and one PCS opening proof.
+///
+/// Produced by [`crate::prove_multi`] and checked by [`crate::verify_multi`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MultiProof<SC: crate::StarkGenericConfig> {
+    /// Protocol revision the proof was produced under, like [`Proof::version`]
+    pub version: u16,
+
+    /// Commitments and opened values of each table, in the order the AIRs were given
+    pub tables: Vec<TableProof<SC>>,
+
+    /// PCS opening proof covering the commitments of every table
+    pub opening_proof: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Proof,
+}
+
+/// Commitments and opened values of one table of a [`MultiProof`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct TableProof<SC: crate::StarkGenericConfig> {
+    /// Commitment to the main trace
+    pub main_commit: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment,
+
+    /// Commitments to the auxiliary trace of each phase of this table
+    pub aux_commits: Vec<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+
+    /// Values exposed by each auxiliary phase, e.g. final cumulative sums
+    pub aux_exposed_values: Vec<Vec<SC::Challenge>>,
+
+    /// Commitments to the quotient polynomial chunks, one per column group
+    pub quotient_commits:
+        Vec<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+
+    /// Values opened at ζ and at ζ·g on this table's trace domain
+    pub opened_values: OpenedValues<SC::Challenge>,
+
+    /// Degree (log2 of trace height)
+    pub log_degree: u8,
+}
+
+impl<SC: StarkGenericConfig> Proof<SC> {
+    /// Record a digest of `public_values` in the proof, making a stored proof
+    /// self-describing about the statement it proves.
+    pub fn commit_to_public_values(&mut self, config: &SC, public_values: &[Val<SC>]) {
+        self.public_values_digest = Some(public_values_digest(config, public_values));
+    }
+
+    /// Record how many challenges each aux phase of `air` sampled, so that a verifier built
+    /// with a different declaration reports it explicitly.
+    pub fn record_aux_challenges<A>(&mut self, air: &A)
+    where
+        A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+    {
+        self.aux_challenge_counts = Some(aux_challenge_counts::<Val<SC>, Challenge<SC>, A>(air));
+    }
+}
+
+/// Number of challenges each aux phase of `air` samples, in phase order.
+pub(crate) fn aux_challenge_counts<F, EF, A>(air: &A) -> Vec<usize>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    (0..air.num_aux_phases())
+        .map(|phase| air.aux_phase_num_challenges(phase))
+        .collect()
+}
+
+/// The pieces of the flattened quotient chunks held by each quotient commitment.
+///
+/// The `num_chunks` chunks of `chunk_width` base field columns are laid side by side and cut
+/// into groups of at most `max_width` columns. Each group is one commitment holding one
+/// matrix per `(chunk, columns)` piece, in order; without a limit there is a single group
+/// with one piece per chunk.
+pub(crate) fn quotient_column_groups(
+    num_chunks: usize,
+    chunk_width: usize,
+    max_width: Option<usize>,
+) -> Vec<Vec<(usize, Range<usize>)>> {
+    let max_width = max_width.unwrap_or(usize::MAX).max(1);
+    let mut groups = Vec::new();
+    let mut group = Vec::new();
+    let mut group_width = 0;
+    for chunk in 0..num_chunks {
+        let mut start = 0;
+        while start < chunk_width {
+            if group_width == max_width {
+                groups.push(core::mem::take(&mut group));
+                group_width = 0;
+            }
+            let end = start + (max_width - group_width).min(chunk_width - start);
+            group.push((chunk, start..end));
+            group_width += end - start;
+            start = end;
+        }
+    }
+    if !group.is_empty() {
+        groups.push(group);
+    }
+    groups
+}
+
+/// Observe the shape of a trace: its log2 height, the width of its main trace and of each
+/// auxiliary phase, and its number of public values.
+///
+/// Prover and verifier both bind it before any commitment, so a proof cannot be replayed
+/// against a statement of another shape.
+pub(crate) fn observe_trace_shape<SC, A>(
+    challenger: &mut SC::Challenger,
+    air: &A,
+    log_degree: u8,
+    num_public_values: usize,
+) where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    challenger.observe(Val::<SC>::from_u8(log_degree));
+    challenger.observe(Val::<SC>::from_usize(air.width()));
+    challenger.observe(Val::<SC>::from_usize(air.num_aux_phases()));
+    for phase in 0..air.num_aux_phases() {
+        challenger.observe(Val::<SC>::from_usize(air.aux_phase_width(phase)));
+    }
+    challenger.observe(Val::<SC>::from_usize(num_public_values));
+}
+
+/// Hash a public values vector with a fresh challenger from `config`.
+///
+/// The length is absorbed first so that vectors differing only by trailing zeros
+/// produce different digests.
+pub fn public_values_digest<SC: StarkGenericConfig>(
+    config: &SC,
+    public_values: &[Val<SC>],
+) -> Challenge<SC> {
+    let mut challenger = config.initialise_challenger();
+    challenger.observe(Val::<SC>::from_usize(public_values.len()));
+    challenger.observe_slice(public_values);
+    challenger.sample()
 }
@@ -1,348 +1,2260 @@
 //! Prover implementation for multi-trace STARK
 
+use alloc::boxed::Box;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt;
+use core::ops::Range;
 
 use p3_air::Air;
 use p3_challenger::{CanObserve, CanSample};
 use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::{PackedField, PrimeCharacteristicRing};
+use p3_field::{BasedVectorSpace, ExtensionField, Field, PackedField, PrimeCharacteristicRing};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::log2_strict_usize;
+use rand::distr::{Distribution, StandardUniform};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
-use crate::{Challenge, MultiTraceAir, Proof, ProverFolder, Val};
+use crate::air::{aux_matrix_committed_widths, committed_aux_phases};
+use crate::backend::{check_quotient_blocks, quotient_chunks, quotient_points_in_order};
+use crate::keys::randomize_rows;
+use crate::pool::WorkerPool;
+use crate::proof::{observe_trace_shape, quotient_column_groups};
+use crate::{
+    serialized_size, Challenge, ConstraintFolding, DagBackend, Domain, MainTraceLde, MultiProof,
+    MultiTraceAir, NoTelemetry, OpenedValues, Proof, ProverFolder, ProverPhase, ProverTelemetry,
+    ProvingKey, QuotientBackend, QuotientInputs, StarkGenericConfig, TableProof, TraceGenerator,
+    Val, PROOF_VERSION,
+};
+
+/// Reasons the prover rejects a witness or a configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverError {
+    /// The main trace width differs from the AIR width
+    MainTraceWidthMismatch {
+        /// Columns the AIR declares
+        expected: usize,
+        /// Columns of the trace
+        actual: usize,
+    },
+    /// The main trace height is not a nonzero power of two
+    TraceHeightNotPowerOfTwo(usize),
+    /// The trace is taller than [`StarkGenericConfig::max_supported_log_degree`]
+    TraceTooTall {
+        /// Log2 of the committed trace height
+        log_degree: usize,
+        /// Largest log2 height the configuration supports
+        max_log_degree: usize,
+    },
+    /// A [`crate::ChunkedTrace`] was given more or fewer rows than its height
+    TraceRowCountMismatch {
+        /// Rows of the trace
+        expected: usize,
+        /// Rows given
+        actual: usize,
+    },
+    /// The trace height differs from the preprocessed trace height
+    PreprocessedHeightMismatch {
+        /// Log2 of the preprocessed trace height
+        expected: u8,
+        /// Log2 of the main trace height
+        actual: u8,
+    },
+    /// An auxiliary phase declares neither columns nor challenges
+    EmptyAuxPhase(usize),
+    /// An aux builder returned a trace of the wrong width or height
+    AuxTraceShapeMismatch {
+        /// The auxiliary phase
+        phase: usize,
+        /// Declared width and expected height
+        expected: (usize, usize),
+        /// Width and height of the built trace
+        actual: (usize, usize),
+    },
+    /// The matrix widths of an auxiliary phase include a zero or do not add up to its
+    /// width, see [`crate::AuxTraceBuilder::aux_phase_matrix_widths`]
+    AuxMatrixWidthsMismatch(usize),
+    /// A base field auxiliary phase built a value outside the base field, see
+    /// [`crate::AuxTraceBuilder::aux_phase_in_base_field`]
+    AuxTraceNotInBaseField {
+        /// The auxiliary phase
+        phase: usize,
+        /// Row of the value
+        row: usize,
+    },
+    /// An auxiliary phase exposed a different number of values than it declares
+    ExposedValueCountMismatch {
+        /// The auxiliary phase
+        phase: usize,
+        /// Values the phase declares
+        expected: usize,
+        /// Values it exposed
+        actual: usize,
+    },
+    /// The number of keys, traces or public value vectors differs from the number of AIRs
+    TableCountMismatch(&'static str),
+    /// The configuration is not supported by this entry point
+    UnsupportedConfig(&'static str),
+    /// The worker pool of [`StarkGenericConfig::num_threads`] threads could not be started
+    ThreadPoolUnavailable(usize),
+    /// The state cannot be checkpointed, see [`ProverState::checkpoint`]
+    CheckpointUnavailable(&'static str),
+    /// A [`ProverCheckpoint`] does not replay under the given config, AIR, key and public
+    /// values
+    CheckpointMismatch(&'static str),
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MainTraceWidthMismatch { expected, actual } => {
+                write!(f, "main trace has {actual} columns, the AIR {expected}")
+            }
+            Self::TraceHeightNotPowerOfTwo(height) => {
+                write!(f, "trace height {height} is not a power of two")
+            }
+            Self::TraceTooTall {
+                log_degree,
+                max_log_degree,
+            } => write!(
+                f,
+                "trace height 2^{log_degree} exceeds the maximum 2^{max_log_degree} supported by this configuration"
+            ),
+            Self::TraceRowCountMismatch { expected, actual } => {
+                write!(f, "main trace chunks hold {actual} rows, the trace {expected}")
+            }
+            Self::PreprocessedHeightMismatch { expected, actual } => write!(
+                f,
+                "trace height 2^{actual} differs from the preprocessed trace height 2^{expected}"
+            ),
+            Self::EmptyAuxPhase(phase) => write!(
+                f,
+                "auxiliary phase {phase} has neither columns nor challenges"
+            ),
+            Self::AuxTraceShapeMismatch {
+                phase,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "auxiliary phase {phase} built a {}x{} trace, expected {}x{}",
+                actual.1, actual.0, expected.1, expected.0
+            ),
+            Self::AuxMatrixWidthsMismatch(phase) => write!(
+                f,
+                "auxiliary phase {phase} declares matrix widths not adding up to its width"
+            ),
+            Self::AuxTraceNotInBaseField { phase, row } => write!(
+                f,
+                "auxiliary phase {phase} is declared over the base field but row {row} is not"
+            ),
+            Self::ExposedValueCountMismatch {
+                phase,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "auxiliary phase {phase} exposed {actual} values, expected {expected}"
+            ),
+            Self::TableCountMismatch(what) => write!(f, "{what}"),
+            Self::UnsupportedConfig(reason) => write!(f, "{reason}"),
+            Self::ThreadPoolUnavailable(num_threads) => {
+                write!(f, "could not start a worker pool of {num_threads} threads")
+            }
+            Self::CheckpointUnavailable(reason) => write!(f, "cannot checkpoint: {reason}"),
+            Self::CheckpointMismatch(what) => {
+                write!(f, "checkpoint does not replay: {what} differ")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ProverError {}
 
 /// Prove a computation using a multi-trace AIR.
 ///
 /// # Arguments
 /// - `config`: STARK configuration (PCS, challenger)
 /// - `air`: The AIR defining the computation
+/// - `pk`: Proving key from [`crate::setup`] holding the committed preprocessed trace
 /// - `main_trace`: The main execution trace
 /// - `public_values`: Public input/output values
 ///
 /// # Returns
-/// A proof that can be verified with [`crate::verify`]
+/// A proof that can be verified with [`crate::verify`], or a [`ProverError`]
+/// - If trace dimensions don't match AIR width
+/// - If the trace height is not a power of two
+/// - If the trace is taller than [`crate::StarkGenericConfig::max_supported_log_degree`]
+/// - If the trace height differs from the preprocessed trace height
+/// - If an aux builder returns a trace or exposed values of the wrong shape, or a base
+///   field phase a value outside the base field
+/// - If the config is zero-knowledge; use [`prove_zk`] instead
 ///
 /// # Panics
-/// - If trace dimensions don't match AIR width
-/// - If auxiliary trace building fails
+/// - If an aux builder panics
+/// - If `pk` was set up under a config without
+///   [`StarkGenericConfig::per_phase_alpha`] and `config` has it
 #[instrument(skip_all, fields(trace_height = main_trace.height()))]
 pub fn prove<SC, A>(
     config: &SC,
     air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs need randomness, use prove_zk",
+        ));
+    }
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        &mut config.initialise_challenger(),
+        None,
+        &(),
+        &DagBackend::from_proving_key(pk),
+        &mut NoTelemetry,
+    )
+}
+
+/// Prove a computation whose main trace `air` generates from `inputs`.
+///
+/// The trace is generated in a `generate trace` span, then proven like with [`prove`].
+///
+/// # Returns
+/// A proof that can be verified with [`crate::verify`], or a [`ProverError`] on any
+/// condition under which [`prove`] fails, in particular a generated trace whose width
+/// differs from the AIR's or whose height is not a power of two
+///
+/// # Panics
+/// - If the trace generator or an aux builder panics
+#[instrument(skip_all)]
+pub fn prove_from_inputs<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    inputs: &A::Inputs,
+    public_values: &[Val<SC>],
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + TraceGenerator<Val<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    let main_trace = info_span!("generate trace").in_scope(|| air.generate(inputs));
+    prove(config, air, pk, main_trace, public_values)
+}
+
+/// [`prove`], handing `hints` to the aux builders of `air`.
+///
+/// Hints are prover-side advice for building aux columns that do not follow from the main
+/// trace alone, downcast by
+/// [`build_aux_phase_with_hints`](crate::AuxTraceBuilder::build_aux_phase_with_hints). They
+/// are not part of the proof, and the verifier never sees them.
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_hints<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    hints: &dyn Any,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs need randomness, use prove_zk",
+        ));
+    }
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        &mut config.initialise_challenger(),
+        None,
+        hints,
+        &DagBackend::from_proving_key(pk),
+        &mut NoTelemetry,
+    )
+}
+
+/// [`prove`], reporting the progress of each phase to `telemetry`.
+///
+/// Pass a [`crate::Watchdog`] (with the `std` feature) for heartbeat events and stuck-phase
+/// detection.
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_telemetry<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    mut telemetry: impl ProverTelemetry,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs need randomness, use prove_zk",
+        ));
+    }
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        &mut config.initialise_challenger(),
+        None,
+        &(),
+        &DagBackend::from_proving_key(pk),
+        &mut telemetry,
+    )
+}
+
+/// [`prove`], evaluating the quotient with `backend` instead of the [`DagBackend`] of
+/// `pk`.
+///
+/// [`crate::CpuBackend`] runs the AIR itself at every point, as a reference or for AIRs whose
+/// constraints differ between builders. Multi-table proofs always use the [`DagBackend`]
+/// of each table's proving key.
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_backend<SC, A, B>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    backend: &B,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+    B: QuotientBackend<SC>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs need randomness, use prove_zk",
+        ));
+    }
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        &mut config.initialise_challenger(),
+        None,
+        &(),
+        backend,
+        &mut NoTelemetry,
+    )
+}
+
+/// [`prove`], continuing the transcript of `challenger` instead of starting a fresh one.
+///
+/// Embeds this STARK as one step of a larger Fiat-Shamir protocol: every challenge of the
+/// proof depends on what `challenger` observed before, and on success `challenger` has
+/// observed the whole proof, so the outer protocol carries on from there. The proof
+/// verifies with [`crate::verify_with_challenger`] given a challenger in the same state.
+///
+/// # Returns
+/// A proof, or a [`ProverError`] on any condition under which [`prove`] fails, in which
+/// case `challenger` is left untouched
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_challenger<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    challenger: &mut SC::Challenger,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs need randomness, use prove_zk",
+        ));
+    }
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        challenger,
+        None,
+        &(),
+        &DagBackend::from_proving_key(pk),
+        &mut NoTelemetry,
+    )
+}
+
+/// Prove a computation without revealing anything about the witness beyond the statement.
+///
+/// Requires a config whose PCS has hiding commitments
+/// ([`crate::StarkGenericConfig::is_zk`] returns 1). Every main and aux row is followed by
+/// a random row drawn from `rng`, so the committed polynomials agree with the traces on the
+/// trace domain but are otherwise random, and their openings out of the domain are
+/// independent of the witness. The traces are committed on a domain twice their height
+/// and the quotient is split into twice as many chunks.
+///
+/// `rng` must be cryptographically secure for the proof to hide the witness.
+///
+/// # Returns
+/// A proof, or a [`ProverError`]
+/// - If the config is not zero-knowledge
+/// - On any condition under which [`prove`] fails
+///
+/// # Panics
+/// - If an aux builder panics
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_zk<SC, A, R>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    rng: &mut R,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    StandardUniform: Distribution<Val<SC>>,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+    R: Rng,
+{
+    if config.is_zk() != 1 {
+        return Err(ProverError::UnsupportedConfig(
+            "prove_zk needs a config with hiding commitments",
+        ));
+    }
+    let mut random_value = || rng.random::<Val<SC>>();
+    prove_with(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        &mut config.initialise_challenger(),
+        Some(&mut random_value as &mut dyn FnMut() -> Val<SC>),
+        &(),
+        &DagBackend::from_proving_key(pk),
+        &mut NoTelemetry,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_with<SC, A, B>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+    challenger: &mut SC::Challenger,
+    random_value: Option<&mut dyn FnMut() -> Val<SC>>,
+    hints: &dyn Any,
+    backend: &B,
+    telemetry: &mut dyn ProverTelemetry,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+    B: QuotientBackend<SC>,
+{
+    let mut state = ProverState::start(
+        config,
+        air,
+        pk,
+        main_trace,
+        public_values,
+        challenger.clone(),
+        random_value.map(|random_value| Box::new(random_value) as Box<dyn FnMut() -> Val<SC> + '_>),
+        Box::new(telemetry),
+    )?;
+    state.commit_main();
+    prove_aux_phases(&mut state, hints)?;
+
+    let (proof, transcript) = state.conclude(backend);
+    *challenger = transcript;
+    Ok(proof)
+}
+
+/// Build and commit every aux phase of `state` with the AIR's own builders.
+fn prove_aux_phases<SC, A>(
+    state: &mut ProverState<'_, SC, A>,
+    hints: &dyn Any,
+) -> Result<(), ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    let air = state.air;
+    prove_aux_phases_with(state, |state, phase, challenges| {
+        // Pass the original main_trace (not LDE) and the earlier phases
+        build_aux_phase_traced(
+            air,
+            phase,
+            state.main_trace(),
+            state.main_trace_lde(),
+            state.aux_traces(),
+            challenges,
+            hints,
+        )
+    })
+}
+
+/// Build every aux phase of `state` with `build`, given the phase and its challenges, and
+/// commit it.
+fn prove_aux_phases_with<'a, SC, A, F>(
+    state: &mut ProverState<'a, SC, A>,
+    mut build: F,
+) -> Result<(), ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'b> Air<ProverFolder<'b, SC>>
+        + for<'b> Air<crate::VerifierFolder<'b, SC>>,
+    F: FnMut(&ProverState<'a, SC, A>, usize, &[Challenge<SC>]) -> RowMajorMatrix<Challenge<SC>>,
+{
+    let air = state.air;
+    while let Some(phase) = state.next_aux_phase() {
+        info_span!("auxiliary phase", phase).in_scope(|| -> Result<(), ProverError> {
+            let challenges = state.sample_challenges();
+
+            // A phase without columns only samples its challenges
+            let aux_trace = if air.aux_phase_width(phase) == 0 {
+                RowMajorMatrix::new(Vec::new(), 0)
+            } else {
+                build(state, phase, &challenges)
+            };
+            state.commit_aux(aux_trace)?;
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
+
+/// [`prove`], committing the aux traces `aux_trace` returns instead of building them with
+/// the AIR.
+///
+/// For provers that compute aux columns elsewhere, e.g. on specialized hardware. The aux
+/// traces depend on the challenges, so `aux_trace` is called once per phase with columns,
+/// in order, with the phase and the challenges it sampled. Each trace it returns is
+/// checked against the phase's declared width, the main trace height and, for base field
+/// phases, the base field, like those the AIR builds.
+///
+/// # Returns
+/// A proof, or a [`ProverError`] on any condition under which [`prove`] fails
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_aux_trace<SC, A, F>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
     main_trace: RowMajorMatrix<Val<SC>>,
     public_values: &[Val<SC>],
-) -> Proof<SC>
+    mut aux_trace: F,
+) -> Result<Proof<SC>, ProverError>
 where
     SC: crate::StarkGenericConfig,
     Val<SC>: PackedField,
     A: MultiTraceAir<Val<SC>, Challenge<SC>>
         + for<'a> Air<ProverFolder<'a, SC>>
         + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+    F: FnMut(usize, &[Challenge<SC>]) -> RowMajorMatrix<Challenge<SC>>,
 {
-    assert_eq!(main_trace.width(), air.width(), "Main trace width mismatch");
+    let mut state = ProverState::new(config, air, pk, main_trace, public_values)?;
+    state.commit_main();
+    prove_aux_phases_with(&mut state, |_, phase, challenges| {
+        aux_trace(phase, challenges)
+    })?;
+    state.finish()
+}
 
+/// Commit `main_trace` the way [`prove`] does, for [`prove_with_committed_main`].
+///
+/// The commitment can be published long before the proof; the prover data must be kept
+/// to open it.
+pub fn commit_main_trace<SC: StarkGenericConfig>(
+    config: &SC,
+    main_trace: RowMajorMatrix<Val<SC>>,
+) -> (Commitment<SC>, PcsProverData<SC>) {
     let pcs = config.pcs();
-    let mut challenger = config.initialise_challenger();
+    let trace_domain = pcs.natural_domain_for_degree(main_trace.height());
+    info_span!("pcs_commit_main").in_scope(|| pcs.commit([(trace_domain, main_trace)]))
+}
 
-    // Trace dimensions
-    let height = main_trace.height();
-    let log_degree = log2_strict_usize(height) as u8;
-    let trace_domain = pcs.natural_domain_for_degree(height);
+/// [`prove`], binding the proof to a main trace committed earlier with
+/// [`commit_main_trace`].
+///
+/// The proof's `main_commit` is `main_commit`, and `main_data` is opened instead of
+/// committing the trace again. `main_trace` must be the committed trace, which the aux
+/// builders read; otherwise the proof does not verify.
+///
+/// # Returns
+/// A proof, or a [`ProverError`]
+/// - If the config is zero-knowledge, whose main commitment must be randomized
+/// - On any condition under which [`prove`] fails
+#[instrument(skip_all, fields(trace_height = main_trace.height()))]
+pub fn prove_with_committed_main<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    main_commit: Commitment<SC>,
+    main_data: PcsProverData<SC>,
+    public_values: &[Val<SC>],
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "zero-knowledge configs randomize the main commitment, it cannot be made earlier",
+        ));
+    }
+    let mut state = ProverState::new(config, air, pk, main_trace, public_values)?;
+    state.use_committed_main(main_commit, main_data);
+    prove_aux_phases(&mut state, &())?;
+    state.finish()
+}
 
-    // ==================== PHASE 1: Main Trace ====================
-    info_span!("commit main trace").in_scope(|| {
-        tracing::info!("Committing main trace (height={})", height);
-    });
+type Commitment<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
 
-    let (main_commit, main_data) =
-        info_span!("pcs_commit_main").in_scope(|| pcs.commit([(trace_domain, main_trace.clone())]));
+type PcsProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
 
-    // Observe main trace commitment
-    challenger.observe(main_commit.clone());
-    challenger.observe_slice(public_values);
+/// A single-table proof in progress, advanced one phase at a time.
+///
+/// [`prove`] runs every step itself. Drivers that interleave their own logic between the
+/// phases, e.g. a zkVM deriving lookup multiplicities from the main commitment or a
+/// distributed prover building aux traces on other machines, take the steps one by one:
+///
+/// 1. [`ProverState::new`] checks the main trace and binds the AIR and the trace shape
+/// 2. [`commit_main`](Self::commit_main) commits the main trace
+/// 3. for every aux phase, in order, [`sample_challenges`](Self::sample_challenges) draws
+///    the phase's challenges and [`commit_aux`](Self::commit_aux) commits the trace built
+///    from them
+/// 4. [`finish`](Self::finish) computes and commits the quotient, then opens everything
+///
+/// The transcript is the one [`prove`] writes, so the proof verifies with
+/// [`crate::verify`]. After a step returns an error the state must be dropped.
+///
+/// Between steps, [`checkpoint`](Self::checkpoint) saves the state so that
+/// [`resume`](Self::resume) carries on later, possibly on another machine, e.g. while a
+/// slow external oracle builds the next aux trace.
+///
+/// # Panics
+/// - If the steps are taken out of order
+pub struct ProverState<'a, SC: StarkGenericConfig, A> {
+    config: &'a SC,
+    air: &'a A,
+    pk: &'a ProvingKey<SC>,
+    public_values: &'a [Val<SC>],
+    random_value: Option<Box<dyn FnMut() -> Val<SC> + 'a>>,
+    telemetry: Box<dyn ProverTelemetry + 'a>,
+    pool: WorkerPool,
+    challenger: SC::Challenger,
+    log_degree: u8,
+    trace_domain: Domain<SC>,
+    ext_trace_domain: Domain<SC>,
+    quotient_domain: Domain<SC>,
+    /// The main trace until it is committed, then only if aux builders read it
+    main_trace: RowMajorMatrix<Val<SC>>,
+    main: Option<(Commitment<SC>, PcsProverData<SC>)>,
+    main_lde: Option<MainTraceLde<Val<SC>>>,
+    phase_alphas: Vec<Challenge<SC>>,
+    /// Challenges of the aux phase sampled but not committed yet
+    pending_challenges: Option<Vec<Challenge<SC>>>,
+    aux_commits: Vec<Commitment<SC>>,
+    aux_data: Vec<PcsProverData<SC>>,
+    aux_traces: Vec<RowMajorMatrix<Challenge<SC>>>,
+    aux_challenges: Vec<Challenge<SC>>,
+    aux_exposed_values: Vec<Vec<Challenge<SC>>>,
+}
 
-    // ==================== PHASE 2: Auxiliary Trace ====================
-    let (aux_commit, aux_data, _aux_trace) = if air.aux_width() > 0 {
-        info_span!("auxiliary phase").in_scope(|| {
-            // Sample challenges
-            let num_challenges = air.num_challenges();
-            let challenges: Vec<Challenge<SC>> =
-                (0..num_challenges).map(|_| challenger.sample()).collect();
+/// A serializable [`ProverState`] between two steps, from [`ProverState::checkpoint`].
+///
+/// It holds the witness committed so far rather than the PCS prover data: resuming commits
+/// it again, which the transcript makes deterministic, and replays the challenges. Only
+/// the aux traces, which may be costly to build, are never rebuilt.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProverCheckpoint<SC: StarkGenericConfig> {
+    /// The main trace
+    pub main_trace: RowMajorMatrix<Val<SC>>,
+    /// Public input/output values
+    pub public_values: Vec<Val<SC>>,
+    /// Challenges of each aux phase sampled so far, in phase order; the last phase's are
+    /// pending when it has one more entry than `aux_traces`
+    pub aux_challenges: Vec<Vec<Challenge<SC>>>,
+    /// The trace committed in each aux phase so far, empty for phases without columns
+    pub aux_traces: Vec<RowMajorMatrix<Challenge<SC>>>,
+}
 
-            tracing::info!("Sampled {} challenges for auxiliary trace", num_challenges);
+impl<'a, SC, A> ProverState<'a, SC, A>
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'b> Air<ProverFolder<'b, SC>>
+        + for<'b> Air<crate::VerifierFolder<'b, SC>>,
+{
+    /// Start proving `main_trace`, failing on any condition under which [`prove`] rejects it.
+    pub fn new(
+        config: &'a SC,
+        air: &'a A,
+        pk: &'a ProvingKey<SC>,
+        main_trace: RowMajorMatrix<Val<SC>>,
+        public_values: &'a [Val<SC>],
+    ) -> Result<Self, ProverError> {
+        if config.is_zk() != 0 {
+            return Err(ProverError::UnsupportedConfig(
+                "zero-knowledge configs need randomness, use ProverState::new_zk",
+            ));
+        }
+        Self::start(
+            config,
+            air,
+            pk,
+            main_trace,
+            public_values,
+            config.initialise_challenger(),
+            None,
+            Box::new(NoTelemetry),
+        )
+    }
 
-            // Build auxiliary trace using challenges
-            // Pass the original main_trace (not LDE) to build_aux_trace
-            let aux_trace = air.build_aux_trace(&main_trace, &challenges);
+    /// Start a zero-knowledge proof of `main_trace`, randomizing the traces from `rng` like
+    /// [`prove_zk`].
+    pub fn new_zk<R>(
+        config: &'a SC,
+        air: &'a A,
+        pk: &'a ProvingKey<SC>,
+        main_trace: RowMajorMatrix<Val<SC>>,
+        public_values: &'a [Val<SC>],
+        rng: &'a mut R,
+    ) -> Result<Self, ProverError>
+    where
+        StandardUniform: Distribution<Val<SC>>,
+        R: Rng,
+    {
+        if config.is_zk() != 1 {
+            return Err(ProverError::UnsupportedConfig(
+                "new_zk needs a config with hiding commitments",
+            ));
+        }
+        Self::start(
+            config,
+            air,
+            pk,
+            main_trace,
+            public_values,
+            config.initialise_challenger(),
+            Some(Box::new(move || rng.random::<Val<SC>>())),
+            Box::new(NoTelemetry),
+        )
+    }
 
+    /// Resume the proof saved in `checkpoint`, replaying its commitments and challenges.
+    ///
+    /// The state continues with the step that followed the checkpoint: committing the aux
+    /// trace of the pending phase, see [`pending_challenges`](Self::pending_challenges),
+    /// or sampling the challenges of the next one.
+    ///
+    /// # Returns
+    /// The state, or a [`ProverError`]
+    /// - If `public_values` differ from the checkpoint's
+    /// - If the replayed challenges differ from the checkpoint's, i.e. it was taken under
+    ///   another config, AIR or key
+    /// - On any condition under which [`ProverState::new`] and
+    ///   [`commit_aux`](Self::commit_aux) fail
+    pub fn resume(
+        config: &'a SC,
+        air: &'a A,
+        pk: &'a ProvingKey<SC>,
+        public_values: &'a [Val<SC>],
+        checkpoint: ProverCheckpoint<SC>,
+    ) -> Result<Self, ProverError> {
+        let ProverCheckpoint {
+            main_trace,
+            public_values: checkpoint_public_values,
+            aux_challenges,
+            aux_traces,
+        } = checkpoint;
+        if checkpoint_public_values != public_values {
+            return Err(ProverError::CheckpointMismatch("public values"));
+        }
+        if aux_challenges.len() != aux_traces.len() && aux_challenges.len() != aux_traces.len() + 1
+        {
+            return Err(ProverError::CheckpointMismatch("aux phase counts"));
+        }
+
+        let mut state = Self::new(config, air, pk, main_trace, public_values)?;
+        state.commit_main();
+        let mut aux_traces = aux_traces.into_iter();
+        for challenges in aux_challenges {
+            if state.next_aux_phase().is_none() || state.sample_challenges() != challenges {
+                return Err(ProverError::CheckpointMismatch("aux challenges"));
+            }
+            if let Some(aux_trace) = aux_traces.next() {
+                state.commit_aux(aux_trace)?;
+            }
+        }
+        Ok(state)
+    }
+
+    /// Report the progress of the remaining steps to `telemetry`.
+    pub fn with_telemetry(mut self, telemetry: impl ProverTelemetry + 'a) -> Self {
+        self.telemetry = Box::new(telemetry);
+        self
+    }
+
+    fn start(
+        config: &'a SC,
+        air: &'a A,
+        pk: &'a ProvingKey<SC>,
+        main_trace: RowMajorMatrix<Val<SC>>,
+        public_values: &'a [Val<SC>],
+        mut challenger: SC::Challenger,
+        random_value: Option<Box<dyn FnMut() -> Val<SC> + 'a>>,
+        telemetry: Box<dyn ProverTelemetry + 'a>,
+    ) -> Result<Self, ProverError> {
+        let log_degree = check_main_trace(config, air, pk, &main_trace)?;
+
+        let pcs = config.pcs();
+
+        // Bind the AIR instance parameters before anything else
+        let instance_parameters = air.instance_parameters();
+        challenger.observe(Val::<SC>::from_usize(instance_parameters.len()));
+        challenger.observe_slice(&instance_parameters);
+
+        // Trace dimensions
+        // In ZK mode traces are committed with a random row after every row
+        let zk = config.is_zk();
+        let height = main_trace.height();
+        observe_trace_shape::<SC, A>(&mut challenger, air, log_degree, public_values.len());
+        let trace_domain = pcs.natural_domain_for_degree(height);
+        let ext_trace_domain = pcs.natural_domain_for_degree(height << zk);
+
+        // The preprocessed trace was committed once in the proving key
+        if let Some(preprocessed) = &pk.vk.preprocessed {
+            challenger.observe(preprocessed.commit.clone());
+        }
+
+        // Create larger domain for quotient evaluation
+        // Randomized traces have twice the degree, and so does the quotient
+        let quotient_domain =
+            ext_trace_domain.create_disjoint_domain((height << zk) * pk.vk.quotient_degree());
+        if config.quotient_block_size().is_some() {
+            check_quotient_blocks(
+                config,
+                pk.vk.quotient_degree(),
+                pk.vk.quotient_degree() << zk,
+            )?;
+        }
+
+        Ok(Self {
+            config,
+            air,
+            pk,
+            public_values,
+            random_value,
+            telemetry,
+            pool: WorkerPool::new(config.num_threads())?,
+            challenger,
+            log_degree,
+            trace_domain,
+            ext_trace_domain,
+            quotient_domain,
+            main_trace,
+            main: None,
+            main_lde: None,
+            phase_alphas: Vec::new(),
+            pending_challenges: None,
+            aux_commits: Vec::new(),
+            aux_data: Vec::new(),
+            aux_traces: Vec::new(),
+            aux_challenges: Vec::new(),
+            aux_exposed_values: Vec::new(),
+        })
+    }
+
+    /// Commit the main trace and observe it together with the public values.
+    ///
+    /// # Panics
+    /// - If the main trace is already committed
+    /// - If `pk` was set up under a config without
+    ///   [`StarkGenericConfig::per_phase_alpha`] and the config has it
+    pub fn commit_main(&mut self) -> Commitment<SC> {
+        assert!(self.main.is_none(), "the main trace is already committed");
+        let (config, air) = (self.config, self.air);
+        let pcs = config.pcs();
+        let zk = config.is_zk();
+        let height = self.height();
+
+        // ==================== PHASE 1: Main Trace ====================
+        info_span!("commit main trace").in_scope(|| {
+            tracing::info!("Committing main trace (height={})", height);
+        });
+
+        self.telemetry
+            .phase_started(ProverPhase::MainCommit, height << zk);
+        // Aux builders read the main trace after it is committed; without them the PCS takes
+        // it over. A randomized commitment is a new matrix either way, so only an
+        // unrandomized trace read by aux builders is copied
+        let width = self.main_trace.width();
+        let released = committed_aux_phases(air).is_empty().then(|| {
+            core::mem::replace(&mut self.main_trace, RowMajorMatrix::new(Vec::new(), width))
+        });
+        let committed_main = match (self.random_value.as_mut(), released) {
+            (Some(random_value), released) => randomize_rows(
+                released.as_ref().unwrap_or(&self.main_trace),
+                &mut **random_value,
+            ),
+            (None, Some(main_trace)) => main_trace,
+            (None, None) => self.main_trace.clone(),
+        };
+        let ext_trace_domain = self.ext_trace_domain;
+        let (main_commit, main_data) = info_span!("pcs_commit_main").in_scope(|| {
+            self.pool
+                .install(|| pcs.commit([(ext_trace_domain, committed_main)]))
+        });
+        self.telemetry.phase_finished(ProverPhase::MainCommit);
+
+        self.observe_main(main_commit.clone(), main_data);
+        main_commit
+    }
+
+    /// Take `main_commit` and `main_data`, made earlier by [`commit_main_trace`], as the
+    /// commitment to the main trace, and observe it together with the public values.
+    ///
+    /// The proof is bound to that commitment and only verifies if it commits to the main
+    /// trace this state was started with.
+    ///
+    /// # Panics
+    /// - If the main trace is already committed
+    /// - If the config is zero-knowledge
+    /// - In the cases of [`commit_main`](Self::commit_main)
+    pub fn use_committed_main(
+        &mut self,
+        main_commit: Commitment<SC>,
+        main_data: PcsProverData<SC>,
+    ) {
+        assert!(self.main.is_none(), "the main trace is already committed");
+        assert_eq!(
+            self.config.is_zk(),
+            0,
+            "zero-knowledge main commitments are randomized by the prover"
+        );
+        if committed_aux_phases(self.air).is_empty() {
+            let width = self.main_trace.width();
+            self.main_trace = RowMajorMatrix::new(Vec::new(), width);
+        }
+        self.observe_main(main_commit, main_data);
+    }
+
+    /// Observe the main commitment and the public values, then get ready for the aux phases.
+    fn observe_main(&mut self, main_commit: Commitment<SC>, main_data: PcsProverData<SC>) {
+        let (config, air, pk) = (self.config, self.air, self.pk);
+        let pcs = config.pcs();
+        let zk = config.is_zk();
+
+        // Observe main trace commitment
+        self.challenger.observe(main_commit.clone());
+        self.challenger.observe_slice(self.public_values);
+
+        // With per-phase folding, the main trace constraints get their alpha now
+        if config.per_phase_alpha() {
             assert_eq!(
-                aux_trace.width,
-                air.aux_width(),
-                "Auxiliary trace width mismatch"
-            );
-            assert_eq!(
-                aux_trace.height(),
-                height,
-                "Auxiliary trace height mismatch"
+                pk.vk.constraint_phases.len(),
+                pk.vk.num_constraints,
+                "the proving key was not set up for per-phase alphas"
             );
+            self.phase_alphas.push(self.challenger.sample());
+        }
 
-            tracing::info!(
-                "Built auxiliary trace ({}x{})",
-                aux_trace.height(),
-                aux_trace.width
-            );
+        // Advanced aux builders may read the main trace over the quotient domain
+        if !committed_aux_phases(air).is_empty() && air.uses_main_trace_lde() {
+            let (log_degree, quotient_domain) = (self.log_degree, self.quotient_domain);
+            let pool = &self.pool;
+            self.main_lde = Some(info_span!("main trace lde").in_scope(|| MainTraceLde {
+                log_trace_height: log_degree as usize,
+                log_blowup: pk.vk.log_quotient_degree + zk,
+                first_point: quotient_domain.first_point(),
+                evaluations: pool.install(|| {
+                    pcs.get_evaluations_on_domain(&main_data, 0, quotient_domain)
+                        .to_row_major_matrix()
+                }),
+            }));
+        }
 
-            // Commit auxiliary trace (flatten to base field first)
-            let aux_trace_flat = aux_trace.clone().flatten_to_base();
-            let (aux_commit, aux_data) = info_span!("pcs_commit_aux")
-                .in_scope(|| pcs.commit([(trace_domain, aux_trace_flat)]));
+        self.main = Some((main_commit, main_data));
+    }
+
+    /// The aux phase whose challenges are sampled next, or `None` once every phase is
+    /// committed.
+    pub fn next_aux_phase(&self) -> Option<usize> {
+        let phase = self.aux_traces.len();
+        (phase < self.air.num_aux_phases()).then_some(phase)
+    }
+
+    /// Sample the challenges of the next aux phase.
+    ///
+    /// # Panics
+    /// - If the main trace is not committed yet
+    /// - If the challenges of this phase were already sampled, or every phase is committed
+    pub fn sample_challenges(&mut self) -> Vec<Challenge<SC>> {
+        assert!(
+            self.main.is_some(),
+            "the main trace must be committed before sampling aux challenges"
+        );
+        assert!(
+            self.pending_challenges.is_none(),
+            "the challenges of this aux phase were already sampled"
+        );
+        let phase = self
+            .next_aux_phase()
+            .expect("every aux phase is already committed");
+        self.telemetry
+            .phase_started(ProverPhase::AuxPhase(phase), self.height());
+
+        let num_challenges = self.air.aux_phase_num_challenges(phase);
+        let challenges: Vec<Challenge<SC>> = (0..num_challenges)
+            .map(|_| self.challenger.sample())
+            .collect();
+
+        tracing::info!(
+            "Sampled {} challenges for auxiliary phase {}",
+            num_challenges,
+            phase
+        );
+
+        self.pending_challenges = Some(challenges.clone());
+        challenges
+    }
+
+    /// Commit `aux_trace` as the trace of the phase whose challenges were just sampled,
+    /// returning its commitment.
+    ///
+    /// A phase without columns takes an empty matrix and commits nothing.
+    ///
+    /// # Returns
+    /// The commitment, `None` for a phase without columns, or a [`ProverError`] on any
+    /// condition under which [`prove`] rejects an aux trace
+    ///
+    /// # Panics
+    /// - If the phase's challenges were not sampled
+    pub fn commit_aux(
+        &mut self,
+        aux_trace: RowMajorMatrix<Challenge<SC>>,
+    ) -> Result<Option<Commitment<SC>>, ProverError> {
+        let challenges = self
+            .pending_challenges
+            .take()
+            .expect("the challenges of the aux phase must be sampled before committing it");
+        let (config, air) = (self.config, self.air);
+        let phase = self.aux_traces.len();
+
+        let (aux_trace, aux_commit) = if air.aux_phase_width(phase) == 0 {
+            let empty = check_challenge_phase(air, phase)?;
+            if aux_trace.width != 0 || !aux_trace.values.is_empty() {
+                return Err(ProverError::AuxTraceShapeMismatch {
+                    phase,
+                    expected: (0, 0),
+                    actual: (aux_trace.width, aux_trace.height()),
+                });
+            }
+            (empty, None)
+        } else {
+            check_aux_trace(air, phase, &aux_trace, self.height())?;
+
+            // Commit auxiliary trace (flatten to base field first), one matrix at a time
+            let mut aux_trace_flat = committed_aux_trace(air, phase, &aux_trace)?;
+            if let Some(random_value) = self.random_value.as_mut() {
+                aux_trace_flat = randomize_rows(&aux_trace_flat, &mut **random_value);
+            }
+            let aux_matrices = split_aux_matrices(air, phase, aux_trace_flat);
+            let (pcs, ext_trace_domain) = (config.pcs(), self.ext_trace_domain);
+            let (aux_commit, data) = info_span!("pcs_commit_aux").in_scope(|| {
+                self.pool.install(|| {
+                    pcs.commit(
+                        aux_matrices
+                            .into_iter()
+                            .map(|matrix| (ext_trace_domain, matrix))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+            });
 
             // Observe auxiliary commitment
-            challenger.observe(aux_commit.clone());
+            self.challenger.observe(aux_commit.clone());
+            self.aux_commits.push(aux_commit.clone());
+            self.aux_data.push(data);
+            (aux_trace, Some(aux_commit))
+        };
+
+        // Observe the values the phase exposes
+        let exposed_values = phase_exposed_values(air, phase, &aux_trace, &challenges)?;
+        for value in &exposed_values {
+            self.challenger
+                .observe_slice(value.as_basis_coefficients_slice());
+        }
+        if config.per_phase_alpha() {
+            self.phase_alphas.push(self.challenger.sample());
+        }
+
+        self.aux_exposed_values.push(exposed_values);
+        self.aux_traces.push(aux_trace);
+        self.aux_challenges.extend(challenges);
+        self.telemetry.phase_finished(ProverPhase::AuxPhase(phase));
+        Ok(aux_commit)
+    }
+
+    /// The challenges sampled for the aux phase to be committed next, if any.
+    pub fn pending_challenges(&self) -> Option<&[Challenge<SC>]> {
+        self.pending_challenges.as_deref()
+    }
+
+    /// Save the state for [`resume`](Self::resume).
+    ///
+    /// # Returns
+    /// The checkpoint, or a [`ProverError`]
+    /// - If the config is zero-knowledge, whose committed traces hold random rows
+    /// - If no aux phase has columns, in which case the main trace was handed over to the
+    ///   PCS; such a proof has no costly step left to save
+    ///
+    /// # Panics
+    /// - If the main trace is not committed yet
+    pub fn checkpoint(&self) -> Result<ProverCheckpoint<SC>, ProverError> {
+        assert!(
+            self.main.is_some(),
+            "the main trace must be committed before checkpointing"
+        );
+        if self.config.is_zk() != 0 {
+            return Err(ProverError::CheckpointUnavailable(
+                "zero-knowledge traces are committed with random rows",
+            ));
+        }
+        if committed_aux_phases(self.air).is_empty() {
+            return Err(ProverError::CheckpointUnavailable(
+                "the main trace was handed over to the PCS",
+            ));
+        }
+
+        let mut aux_challenges = Vec::with_capacity(self.aux_traces.len() + 1);
+        let mut sampled = self.aux_challenges.as_slice();
+        for phase in 0..self.aux_traces.len() {
+            let (challenges, rest) = sampled.split_at(self.air.aux_phase_num_challenges(phase));
+            aux_challenges.push(challenges.to_vec());
+            sampled = rest;
+        }
+        aux_challenges.extend(self.pending_challenges.clone());
 
-            (Some(aux_commit), Some(aux_data), Some(aux_trace))
+        Ok(ProverCheckpoint {
+            main_trace: self.main_trace.clone(),
+            public_values: self.public_values.to_vec(),
+            aux_challenges,
+            aux_traces: self.aux_traces.clone(),
         })
-    } else {
-        (None, None, None)
-    };
+    }
 
-    // ==================== PHASE 3: Quotient Polynomial ====================
-    info_span!("quotient computation").in_scope(|| {
-        tracing::info!("Computing quotient polynomial");
-    });
+    /// The main trace, for building aux traces; empty when no aux phase has columns.
+    pub const fn main_trace(&self) -> &RowMajorMatrix<Val<SC>> {
+        &self.main_trace
+    }
 
-    // Sample challenge for combining constraints
-    let alpha: Challenge<SC> = challenger.sample();
+    /// The main trace over the quotient domain, once committed, if `air` reads it, see
+    /// [`crate::AuxTraceBuilder::uses_main_trace_lde`].
+    pub const fn main_trace_lde(&self) -> Option<&MainTraceLde<Val<SC>>> {
+        self.main_lde.as_ref()
+    }
 
-    // Compute constraint polynomial degree
-    // TODO: For now using a simple heuristic; should compute symbolically
-    let constraint_degree = 2; // Most common case
-    let quotient_degree = 1 << constraint_degree;
+    /// The traces of the aux phases committed so far, in phase order.
+    pub fn aux_traces(&self) -> &[RowMajorMatrix<Challenge<SC>>] {
+        &self.aux_traces
+    }
 
-    // Create larger domain for quotient evaluation
-    let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+    /// Compute and commit the quotient, then open every commitment.
+    ///
+    /// # Panics
+    /// - If the main trace or an aux phase is not committed
+    pub fn finish(self) -> Result<Proof<SC>, ProverError> {
+        self.finish_with_backend(&DagBackend::from_proving_key(self.pk))
+    }
 
-    // Get trace evaluations on quotient domain
-    let main_on_quotient = pcs.get_evaluations_on_domain(&main_data, 0, quotient_domain);
-    let aux_on_quotient = aux_data
-        .as_ref()
-        .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+    /// [`finish`](Self::finish), evaluating the quotient with `backend`.
+    pub fn finish_with_backend<B: QuotientBackend<SC>>(
+        self,
+        backend: &B,
+    ) -> Result<Proof<SC>, ProverError> {
+        Ok(self.conclude(backend).0)
+    }
 
-    // Compute quotient values
-    let quotient_values = compute_quotient_values(
-        air,
-        trace_domain,
-        quotient_domain,
-        &main_on_quotient,
-        aux_on_quotient.as_ref(),
-        alpha,
-        public_values,
-    );
+    /// The proof, and the challenger after it observed the whole proof.
+    fn conclude<B: QuotientBackend<SC>>(mut self, backend: &B) -> (Proof<SC>, SC::Challenger) {
+        assert!(
+            self.next_aux_phase().is_none(),
+            "every aux phase must be committed before finishing"
+        );
+        let (main_commit, main_data) = self
+            .main
+            .take()
+            .expect("the main trace must be committed before finishing");
+        let (config, air, pk, public_values) = (self.config, self.air, self.pk, self.public_values);
+        let pcs = config.pcs();
+        let (trace_domain, quotient_domain) = (self.trace_domain, self.quotient_domain);
+        let num_quotient_chunks = pk.vk.quotient_degree() << config.is_zk();
+        let preprocessed = pk
+            .vk
+            .preprocessed
+            .as_ref()
+            .zip(pk.preprocessed_data.as_ref());
+        let committed_phases = committed_aux_phases(air);
+        let mut challenger = self.challenger;
+        let telemetry = &mut *self.telemetry;
+        let pool = &self.pool;
 
-    // Commit to quotient polynomial chunks
-    let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
-    let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
-    let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
+        // ==================== PHASE 3: Quotient Polynomial ====================
+        info_span!("quotient computation").in_scope(|| {
+            tracing::info!("Computing quotient polynomial");
+        });
 
-    // Commit all chunks together (not separately)
-    let (quotient_commit, quotient_data) = info_span!("pcs_commit_quotient").in_scope(|| {
-        pcs.commit(
-            quotient_chunk_domains
-                .iter()
-                .copied()
-                .zip(quotient_chunks.into_iter())
-                .collect::<Vec<_>>(),
-        )
-    });
+        // Sample challenge for combining constraints, unless each phase sampled its own
+        let alpha_powers = if config.per_phase_alpha() {
+            ConstraintFolding::PerPhase {
+                alphas: &self.phase_alphas,
+                constraint_phases: &pk.vk.constraint_phases,
+            }
+            .coefficients(pk.vk.num_constraints)
+        } else {
+            let alpha: Challenge<SC> = challenger.sample();
+            ConstraintFolding::Single(alpha).coefficients(pk.vk.num_constraints)
+        };
+        telemetry.phase_started(ProverPhase::Quotient, quotient_domain.size());
 
-    // Observe quotient commitment
-    challenger.observe(quotient_commit.clone());
+        // Get trace evaluations on quotient domain
+        let main_on_quotient = pcs.get_evaluations_on_domain(&main_data, 0, quotient_domain);
+        let preprocessed_on_quotient =
+            preprocessed.map(|(_, data)| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+        let aux_on_quotient: Vec<_> = self
+            .aux_data
+            .iter()
+            .zip(&committed_phases)
+            .flat_map(|(data, &phase)| {
+                (0..air.aux_phase_matrix_widths(phase).len())
+                    .map(move |matrix| pcs.get_evaluations_on_domain(data, matrix, quotient_domain))
+            })
+            .collect();
 
-    // ==================== PHASE 4: Opening ====================
-    info_span!("opening").in_scope(|| {
-        tracing::info!("Computing opening proofs");
-    });
+        // Compute the quotient, split into the chunks to commit
+        let aux_exposed_values = self.aux_exposed_values.concat();
+        let block_size = config.quotient_block_size();
+        let points_in_order = quotient_points_in_order(config, pk.vk.quotient_degree());
+        let quotient_chunks = pool.install(|| {
+            quotient_chunks(
+                backend,
+                air,
+                trace_domain,
+                quotient_domain,
+                num_quotient_chunks,
+                block_size,
+                points_in_order,
+                |rows, selectors| QuotientInputs {
+                    trace_domain,
+                    quotient_domain,
+                    rows,
+                    main_on_quotient: &main_on_quotient,
+                    preprocessed_on_quotient: preprocessed_on_quotient.as_ref(),
+                    aux_on_quotient: &aux_on_quotient,
+                    selectors,
+                    aux_challenges: &self.aux_challenges,
+                    aux_exposed_values: &aux_exposed_values,
+                    alpha_powers: &alpha_powers,
+                    public_values,
+                },
+                telemetry,
+            )
+        });
 
-    // Sample out-of-domain evaluation point
-    let zeta: Challenge<SC> = challenger.sample();
-    let zeta_next = trace_domain
-        .next_point(zeta)
-        .expect("domain must support next_point");
+        // Commit to quotient polynomial chunks
+        let quotient_chunk_domains = quotient_domain.split_domains(num_quotient_chunks);
 
-    // Open all committed polynomials
-    let mut opening_points = vec![(&main_data, vec![vec![zeta, zeta_next]])];
+        // Commit the chunks together, or in column groups if the MMCS limits leaf width
+        let quotient_groups = quotient_column_groups(
+            num_quotient_chunks,
+            Challenge::<SC>::DIMENSION,
+            config.max_leaf_width(),
+        );
+        let (quotient_commits, quotient_data) = pool.install(|| {
+            commit_quotient_groups::<SC>(
+                pcs,
+                &quotient_chunk_domains,
+                &quotient_chunks,
+                &quotient_groups,
+            )
+        });
 
-    if let Some(ref aux_data) = aux_data {
-        opening_points.push((aux_data, vec![vec![zeta, zeta_next]]));
+        // Observe quotient commitments
+        for commit in &quotient_commits {
+            challenger.observe(commit.clone());
+        }
+        let folding_ops = (2 * pk.vk.num_constraints as u64 + 1) * quotient_domain.size() as u64;
+        telemetry.field_ops(ProverPhase::Quotient, folding_ops);
+        telemetry.phase_finished(ProverPhase::Quotient);
+
+        // ==================== PHASE 4: Opening ====================
+        info_span!("opening").in_scope(|| {
+            tracing::info!("Computing opening proofs");
+        });
+
+        // Sample the out-of-domain evaluation points
+        let zetas: Vec<Challenge<SC>> = (0..config.num_ood_points())
+            .map(|_| challenger.sample())
+            .collect();
+
+        // Traces are opened at every zeta and the point one row after it
+        let trace_points: Vec<Challenge<SC>> = zetas
+            .iter()
+            .flat_map(|&zeta| {
+                let zeta_next = trace_domain
+                    .next_point(zeta)
+                    .expect("domain must support next_point");
+                [zeta, zeta_next]
+            })
+            .collect();
+
+        // Open all committed polynomials
+        let mut opening_points = vec![(&main_data, vec![trace_points.clone()])];
+
+        if let Some((_, data)) = preprocessed {
+            opening_points.push((data, vec![trace_points.clone()]));
+        }
+
+        for (data, &phase) in self.aux_data.iter().zip(&committed_phases) {
+            let num_matrices = air.aux_phase_matrix_widths(phase).len();
+            opening_points.push((data, vec![trace_points.clone(); num_matrices]));
+        }
+
+        // Open every piece of every quotient chunk at every zeta
+        for (data, group) in quotient_data.iter().zip(&quotient_groups) {
+            opening_points.push((data, vec![zetas.clone(); group.len()]));
+        }
+
+        // The opening covers the committed traces over the quotient domain
+        telemetry.phase_started(ProverPhase::Opening, quotient_domain.size());
+        let (opened_values, opening_proof) =
+            pool.install(|| pcs.open(opening_points, &mut challenger));
+        telemetry.phase_finished(ProverPhase::Opening);
+
+        // Extract opened values, in commitment order
+        let mut values_iter = opened_values.into_iter();
+        let main_openings = values_iter.next().unwrap();
+        let preprocessed_openings = preprocessed.map(|_| values_iter.next().unwrap());
+        let aux_openings: Vec<_> = (0..self.aux_data.len())
+            .map(|_| values_iter.next().unwrap())
+            .collect();
+        let quotient_openings = regroup_quotient_openings(
+            zetas.len(),
+            num_quotient_chunks,
+            &quotient_groups,
+            values_iter,
+        );
+
+        // Regroup the values by out-of-domain point
+        let mut openings = (0..zetas.len()).map(|j| {
+            let (local, next) = (2 * j, 2 * j + 1);
+            OpenedValues {
+                main_local: main_openings[0][local].clone(),
+                main_next: main_openings[0][next].clone(),
+                preprocessed_local: preprocessed_openings
+                    .as_ref()
+                    .map_or_else(Vec::new, |pp| pp[0][local].clone()),
+                preprocessed_next: preprocessed_openings
+                    .as_ref()
+                    .map_or_else(Vec::new, |pp| pp[0][next].clone()),
+                aux_local: (!aux_openings.is_empty()).then(|| {
+                    aux_openings
+                        .iter()
+                        .map(|aux| aux.iter().flat_map(|m| m[local].iter().copied()).collect())
+                        .collect()
+                }),
+                aux_next: (!aux_openings.is_empty()).then(|| {
+                    aux_openings
+                        .iter()
+                        .map(|aux| aux.iter().flat_map(|m| m[next].iter().copied()).collect())
+                        .collect()
+                }),
+                quotient_chunks: quotient_openings[j].clone(),
+            }
+        });
+        let first = openings.next().unwrap();
+        let additional_openings = openings.collect();
+
+        let proof = Proof {
+            version: PROOF_VERSION,
+            main_commit,
+            aux_commits: self.aux_commits,
+            aux_exposed_values: self.aux_exposed_values,
+            quotient_commits,
+            main_local: first.main_local,
+            main_next: first.main_next,
+            preprocessed_local: first.preprocessed_local,
+            preprocessed_next: first.preprocessed_next,
+            aux_local: first.aux_local,
+            aux_next: first.aux_next,
+            quotient_chunks: first.quotient_chunks,
+            opening_proof,
+            additional_openings,
+            log_degree: self.log_degree,
+            public_values_digest: None,
+            aux_challenge_counts: None,
+        };
+        warn_if_oversized(config, &proof);
+        (proof, challenger)
     }
 
-    // Open all quotient chunks at zeta (they're all in one commitment now)
-    let quotient_opening_points: Vec<Vec<Challenge<SC>>> =
-        quotient_chunk_domains.iter().map(|_| vec![zeta]).collect();
-    opening_points.push((&quotient_data, quotient_opening_points));
+    const fn height(&self) -> usize {
+        1 << self.log_degree
+    }
+}
 
-    let (opened_values, opening_proof) = pcs.open(opening_points, &mut challenger);
+/// Prove several AIRs ("tables") in one proof.
+///
+/// Each table has its own trace, possibly of a different height, and its own commitments;
+/// all tables share one challenger and one PCS opening proof. Aux challenges are sampled
+/// once per phase and shared by every table with that phase, each table taking as many as
+/// it asks for, so lookup arguments can span tables. `alpha` and `zeta` are shared too.
+///
+/// # Arguments
+/// - `config`: STARK configuration (PCS, challenger)
+/// - `airs`: The AIR of each table
+/// - `pks`: Proving key of each table, from [`crate::setup`]
+/// - `main_traces`: Main trace of each table
+/// - `public_values`: Public values of each table
+///
+/// # Returns
+/// A proof that can be verified with [`crate::verify_multi`], or a [`ProverError`]
+/// - If the number of keys, traces or public value vectors differs from the number of AIRs
+/// - If the config opens at more than one out-of-domain point or is zero-knowledge
+/// - On any condition under which [`prove`] fails for one of the tables
+///
+/// # Panics
+/// - If an aux builder panics
+#[instrument(skip_all, fields(num_tables = airs.len()))]
+pub fn prove_multi<SC, A>(
+    config: &SC,
+    airs: &[A],
+    pks: &[ProvingKey<SC>],
+    main_traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<MultiProof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    let airs: Vec<&A> = airs.iter().collect();
+    let pks: Vec<&ProvingKey<SC>> = pks.iter().collect();
+    prove_tables(config, &airs, &pks, main_traces, public_values)
+}
 
-    // Extract opened values
-    let mut values_iter = opened_values.into_iter();
+/// One statement of a batch proven by [`prove_batch`].
+pub struct BatchStatement<'a, SC: StarkGenericConfig, A> {
+    /// The AIR of the statement; several statements may share one
+    pub air: &'a A,
+    /// Proving key of `air`, from [`crate::setup`]
+    pub pk: &'a ProvingKey<SC>,
+    /// The main execution trace
+    pub main_trace: RowMajorMatrix<Val<SC>>,
+    /// Public input/output values
+    pub public_values: Vec<Val<SC>>,
+}
 
-    // Main trace openings
-    let main_openings = values_iter.next().unwrap();
-    let main_local = main_openings[0][0].clone();
-    let main_next = main_openings[0][1].clone();
-
-    // Auxiliary trace openings (if present)
-    let (aux_local, aux_next) = if aux_data.is_some() {
-        let aux_openings = values_iter.next().unwrap();
-        (aux_openings[0][0].clone(), aux_openings[0][1].clone())
-    } else {
-        (vec![], vec![])
-    };
+/// Prove several statements under one continuous transcript.
+///
+/// Every main trace is committed before the first shared challenge is sampled, so the
+/// resulting [`MultiProof`] binds all statements at once, as an aggregator expects. This is
+/// [`prove_multi`] over borrowed AIRs and keys, so that one AIR can be proven for many
+/// witnesses without cloning it; each statement is one table of the proof.
+///
+/// # Returns
+/// A proof that can be verified with [`crate::verify_batch`], or a [`ProverError`] on any
+/// condition under which [`prove_multi`] fails
+///
+/// # Panics
+/// - If an aux builder panics
+#[instrument(skip_all, fields(num_statements = statements.len()))]
+pub fn prove_batch<SC, A>(
+    config: &SC,
+    statements: Vec<BatchStatement<'_, SC, A>>,
+) -> Result<MultiProof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    let mut airs = Vec::with_capacity(statements.len());
+    let mut pks = Vec::with_capacity(statements.len());
+    let mut main_traces = Vec::with_capacity(statements.len());
+    let mut public_values = Vec::with_capacity(statements.len());
+    for statement in statements {
+        airs.push(statement.air);
+        pks.push(statement.pk);
+        main_traces.push(statement.main_trace);
+        public_values.push(statement.public_values);
+    }
+    prove_tables(config, &airs, &pks, main_traces, &public_values)
+}
 
-    // Quotient chunk openings
-    // All quotient chunks were in one commitment, opened at multiple rounds (one per chunk)
-    let quotient_openings = values_iter.next().unwrap();
-    let quotient_chunks: Vec<Vec<Challenge<SC>>> = quotient_openings
+/// Shared body of [`prove_multi`] and [`prove_batch`].
+fn prove_tables<SC, A>(
+    config: &SC,
+    airs: &[&A],
+    pks: &[&ProvingKey<SC>],
+    mut main_traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<MultiProof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+{
+    if airs.len() != pks.len() {
+        return Err(ProverError::TableCountMismatch("one proving key per AIR"));
+    }
+    if airs.len() != main_traces.len() {
+        return Err(ProverError::TableCountMismatch("one main trace per AIR"));
+    }
+    if airs.len() != public_values.len() {
+        return Err(ProverError::TableCountMismatch(
+            "one public values vector per AIR",
+        ));
+    }
+    if config.num_ood_points() != 1 {
+        return Err(ProverError::UnsupportedConfig(
+            "multi-table proofs open at a single out-of-domain point",
+        ));
+    }
+    if config.is_zk() != 0 {
+        return Err(ProverError::UnsupportedConfig(
+            "multi-table proofs do not support zero-knowledge configs",
+        ));
+    }
+
+    let pcs = config.pcs();
+    let pool = WorkerPool::new(config.num_threads())?;
+    let mut challenger = config.initialise_challenger();
+
+    // Bind the number of tables and the shape of each before anything else
+    challenger.observe(Val::<SC>::from_usize(airs.len()));
+    let mut log_degrees = Vec::with_capacity(airs.len());
+    for (((air, pk), main_trace), public_values) in airs
+        .iter()
+        .copied()
+        .zip(pks)
+        .zip(&main_traces)
+        .zip(public_values)
+    {
+        let log_degree = check_main_trace(config, air, pk, main_trace)?;
+        if config.quotient_block_size().is_some() {
+            check_quotient_blocks(config, pk.vk.quotient_degree(), pk.vk.quotient_degree())?;
+        }
+
+        let instance_parameters = air.instance_parameters();
+        challenger.observe(Val::<SC>::from_usize(instance_parameters.len()));
+        challenger.observe_slice(&instance_parameters);
+
+        observe_trace_shape::<SC, A>(&mut challenger, air, log_degree, public_values.len());
+
+        if let Some(preprocessed) = &pk.vk.preprocessed {
+            challenger.observe(preprocessed.commit.clone());
+        }
+        log_degrees.push(log_degree);
+    }
+
+    let trace_domains: Vec<_> = main_traces
+        .iter()
+        .map(|trace| pcs.natural_domain_for_degree(trace.height()))
+        .collect();
+    // Each table has the quotient degree measured from its AIR by `setup`
+    let quotient_domains: Vec<_> = trace_domains
         .iter()
-        .map(|round| round[0].clone())
+        .zip(&main_traces)
+        .zip(pks)
+        .map(|((domain, trace), pk)| {
+            domain.create_disjoint_domain(trace.height() * pk.vk.quotient_degree())
+        })
         .collect();
 
-    Proof {
-        main_commit,
-        aux_commit,
-        quotient_commit,
-        main_local,
-        main_next,
-        aux_local,
-        aux_next,
-        quotient_chunks,
+    // ==================== PHASE 1: Main Traces ====================
+    let mut main_commits = Vec::with_capacity(airs.len());
+    let mut main_data = Vec::with_capacity(airs.len());
+    for (((air, &trace_domain), main_trace), public_values) in airs
+        .iter()
+        .zip(&trace_domains)
+        .zip(&mut main_traces)
+        .zip(public_values)
+    {
+        // Only tables with aux builders read their main trace again; the PCS takes over the
+        // others instead of a copy
+        let committed_main = if committed_aux_phases(*air).is_empty() {
+            let width = main_trace.width();
+            core::mem::replace(main_trace, RowMajorMatrix::new(Vec::new(), width))
+        } else {
+            main_trace.clone()
+        };
+        let (commit, data) = info_span!("pcs_commit_main")
+            .in_scope(|| pool.install(|| pcs.commit([(trace_domain, committed_main)])));
+        challenger.observe(commit.clone());
+        challenger.observe_slice(public_values);
+        main_commits.push(commit);
+        main_data.push(data);
+    }
+
+    // ==================== PHASE 2: Auxiliary Traces ====================
+    let main_ldes: Vec<_> = airs
+        .iter()
+        .zip(pks)
+        .zip(&main_data)
+        .zip(&quotient_domains)
+        .zip(&log_degrees)
+        .map(|((((air, pk), data), &quotient_domain), &log_degree)| {
+            (!committed_aux_phases(*air).is_empty() && air.uses_main_trace_lde()).then(|| {
+                info_span!("main trace lde").in_scope(|| MainTraceLde {
+                    log_trace_height: log_degree as usize,
+                    log_blowup: pk.vk.log_quotient_degree,
+                    first_point: quotient_domain.first_point(),
+                    evaluations: pool.install(|| {
+                        pcs.get_evaluations_on_domain(data, 0, quotient_domain)
+                            .to_row_major_matrix()
+                    }),
+                })
+            })
+        })
+        .collect();
+
+    let num_aux_phases = airs
+        .iter()
+        .map(|air| air.num_aux_phases())
+        .max()
+        .unwrap_or(0);
+    let mut aux_commits: Vec<Vec<_>> = airs.iter().map(|_| Vec::new()).collect();
+    let mut aux_data: Vec<Vec<_>> = airs.iter().map(|_| Vec::new()).collect();
+    let mut aux_traces: Vec<Vec<RowMajorMatrix<Challenge<SC>>>> =
+        airs.iter().map(|_| Vec::new()).collect();
+    let mut aux_challenges: Vec<Vec<Challenge<SC>>> = airs.iter().map(|_| Vec::new()).collect();
+    let mut aux_exposed_values: Vec<Vec<Vec<Challenge<SC>>>> =
+        airs.iter().map(|_| Vec::new()).collect();
+    for phase in 0..num_aux_phases {
+        info_span!("auxiliary phase", phase).in_scope(|| -> Result<(), ProverError> {
+            // One set of challenges for every table with this phase
+            let num_challenges = airs
+                .iter()
+                .filter(|air| phase < air.num_aux_phases())
+                .map(|air| air.aux_phase_num_challenges(phase))
+                .max()
+                .unwrap_or(0);
+            let challenges: Vec<Challenge<SC>> =
+                (0..num_challenges).map(|_| challenger.sample()).collect();
+
+            for (t, air) in airs.iter().copied().enumerate() {
+                if phase >= air.num_aux_phases() {
+                    continue;
+                }
+                let table_challenges = &challenges[..air.aux_phase_num_challenges(phase)];
+
+                let aux_trace = if air.aux_phase_width(phase) == 0 {
+                    check_challenge_phase(air, phase)?
+                } else {
+                    let aux_trace = build_aux_phase_traced(
+                        air,
+                        phase,
+                        &main_traces[t],
+                        main_ldes[t].as_ref(),
+                        &aux_traces[t],
+                        table_challenges,
+                        &(),
+                    );
+
+                    check_aux_trace(air, phase, &aux_trace, main_traces[t].height())?;
+
+                    let aux_trace_flat = committed_aux_trace(air, phase, &aux_trace)?;
+                    let aux_matrices = split_aux_matrices(air, phase, aux_trace_flat);
+                    let (aux_commit, data) = info_span!("pcs_commit_aux").in_scope(|| {
+                        pool.install(|| {
+                            pcs.commit(
+                                aux_matrices
+                                    .into_iter()
+                                    .map(|matrix| (trace_domains[t], matrix))
+                                    .collect::<Vec<_>>(),
+                            )
+                        })
+                    });
+                    challenger.observe(aux_commit.clone());
+                    aux_commits[t].push(aux_commit);
+                    aux_data[t].push(data);
+                    aux_trace
+                };
+                let exposed_values =
+                    phase_exposed_values(air, phase, &aux_trace, table_challenges)?;
+                for value in &exposed_values {
+                    challenger.observe_slice(value.as_basis_coefficients_slice());
+                }
+
+                aux_exposed_values[t].push(exposed_values);
+                aux_traces[t].push(aux_trace);
+                aux_challenges[t].extend_from_slice(table_challenges);
+            }
+            Ok(())
+        })?;
+    }
+
+    // ==================== PHASE 3: Quotient Polynomials ====================
+    let alpha: Challenge<SC> = challenger.sample();
+
+    let quotient_groups: Vec<_> = pks
+        .iter()
+        .map(|pk| {
+            quotient_column_groups(
+                pk.vk.quotient_degree(),
+                Challenge::<SC>::DIMENSION,
+                config.max_leaf_width(),
+            )
+        })
+        .collect();
+    let mut quotient_commits = Vec::with_capacity(airs.len());
+    let mut quotient_data = Vec::with_capacity(airs.len());
+    for (t, air) in airs.iter().copied().enumerate() {
+        let quotient_domain = quotient_domains[t];
+        let main_on_quotient = pcs.get_evaluations_on_domain(&main_data[t], 0, quotient_domain);
+        let preprocessed_on_quotient = pks[t]
+            .preprocessed_data
+            .as_ref()
+            .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+        let aux_on_quotient: Vec<_> = aux_data[t]
+            .iter()
+            .zip(committed_aux_phases(air))
+            .flat_map(|(data, phase)| {
+                (0..air.aux_phase_matrix_widths(phase).len())
+                    .map(move |matrix| pcs.get_evaluations_on_domain(data, matrix, quotient_domain))
+            })
+            .collect();
+
+        let table_exposed_values = aux_exposed_values[t].concat();
+        let alpha_powers = ConstraintFolding::Single(alpha).coefficients(pks[t].vk.num_constraints);
+        let quotient_degree = pks[t].vk.quotient_degree();
+        let block_size = config.quotient_block_size();
+        let points_in_order = quotient_points_in_order(config, quotient_degree);
+        let quotient_chunks = pool.install(|| {
+            quotient_chunks(
+                &DagBackend::from_proving_key(pks[t]),
+                air,
+                trace_domains[t],
+                quotient_domain,
+                quotient_degree,
+                block_size,
+                points_in_order,
+                |rows, selectors| QuotientInputs {
+                    trace_domain: trace_domains[t],
+                    quotient_domain,
+                    rows,
+                    main_on_quotient: &main_on_quotient,
+                    preprocessed_on_quotient: preprocessed_on_quotient.as_ref(),
+                    aux_on_quotient: &aux_on_quotient,
+                    selectors,
+                    aux_challenges: &aux_challenges[t],
+                    aux_exposed_values: &table_exposed_values,
+                    alpha_powers: &alpha_powers,
+                    public_values: &public_values[t],
+                },
+                &mut NoTelemetry,
+            )
+        });
+
+        let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
+        let (commits, data) = pool.install(|| {
+            commit_quotient_groups::<SC>(
+                pcs,
+                &quotient_chunk_domains,
+                &quotient_chunks,
+                &quotient_groups[t],
+            )
+        });
+        for commit in &commits {
+            challenger.observe(commit.clone());
+        }
+        quotient_commits.push(commits);
+        quotient_data.push(data);
+    }
+
+    // ==================== PHASE 4: Opening ====================
+    let zeta: Challenge<SC> = challenger.sample();
+
+    // Open every table's commitments, in table order
+    let mut opening_points = Vec::new();
+    for (t, trace_domain) in trace_domains.iter().enumerate() {
+        let zeta_next = trace_domain
+            .next_point(zeta)
+            .expect("domain must support next_point");
+        opening_points.push((&main_data[t], vec![vec![zeta, zeta_next]]));
+        if let Some(data) = &pks[t].preprocessed_data {
+            opening_points.push((data, vec![vec![zeta, zeta_next]]));
+        }
+        for (data, phase) in aux_data[t].iter().zip(committed_aux_phases(airs[t])) {
+            let num_matrices = airs[t].aux_phase_matrix_widths(phase).len();
+            opening_points.push((data, vec![vec![zeta, zeta_next]; num_matrices]));
+        }
+        for (data, group) in quotient_data[t].iter().zip(&quotient_groups[t]) {
+            opening_points.push((data, vec![vec![zeta]; group.len()]));
+        }
+    }
+
+    let (opened_values, opening_proof) = pool.install(|| pcs.open(opening_points, &mut challenger));
+
+    // Extract opened values, in commitment order
+    let mut values_iter = opened_values.into_iter();
+    let tables = main_commits
+        .into_iter()
+        .zip(aux_commits)
+        .zip(aux_exposed_values)
+        .zip(quotient_commits)
+        .zip(pks)
+        .zip(log_degrees)
+        .zip(&quotient_groups)
+        .map(
+            |(
+                (
+                    ((((main_commit, aux_commits), aux_exposed_values), quotient_commits), pk),
+                    log_degree,
+                ),
+                quotient_groups,
+            )| {
+                let mut main = values_iter.next().unwrap().remove(0);
+                let mut preprocessed = pk.preprocessed_data.as_ref().map_or_else(
+                    || vec![vec![]; 2],
+                    |_| values_iter.next().unwrap().remove(0),
+                );
+                let (aux_local, aux_next): (Vec<_>, Vec<_>) = aux_commits
+                    .iter()
+                    .map(|_| {
+                        let aux = values_iter.next().unwrap();
+                        let local = aux.iter().flat_map(|m| m[0].iter().copied()).collect();
+                        let next = aux.iter().flat_map(|m| m[1].iter().copied()).collect();
+                        (local, next)
+                    })
+                    .unzip();
+                let quotient_chunks = regroup_quotient_openings(
+                    1,
+                    pk.vk.quotient_degree(),
+                    quotient_groups,
+                    values_iter.by_ref(),
+                )
+                .pop()
+                .unwrap();
+                let main_next = main.pop().unwrap();
+                let preprocessed_next = preprocessed.pop().unwrap();
+                TableProof {
+                    main_commit,
+                    aux_commits,
+                    aux_exposed_values,
+                    quotient_commits,
+                    opened_values: OpenedValues {
+                        main_local: main.pop().unwrap(),
+                        main_next,
+                        preprocessed_local: preprocessed.pop().unwrap(),
+                        preprocessed_next,
+                        aux_local: (!aux_local.is_empty()).then_some(aux_local),
+                        aux_next: (!aux_next.is_empty()).then_some(aux_next),
+                        quotient_chunks,
+                    },
+                    log_degree,
+                }
+            },
+        )
+        .collect();
+
+    let proof = MultiProof {
+        version: PROOF_VERSION,
+        tables,
         opening_proof,
-        log_degree,
+    };
+    warn_if_oversized(config, &proof);
+    Ok(proof)
+}
+
+/// Warn when `proof` exceeds [`StarkGenericConfig::max_proof_size`].
+///
+/// The proof is still returned: the size only depends on the config and the trace
+/// dimensions, so the operator has to fix the parameters rather than retry.
+fn warn_if_oversized<SC: StarkGenericConfig>(config: &SC, proof: &impl Serialize) {
+    if let Some(max_proof_size) = config.max_proof_size() {
+        let size = serialized_size(proof);
+        if size > max_proof_size {
+            tracing::warn!(
+                size,
+                max_proof_size,
+                "Proof exceeds the configured maximum size"
+            );
+        }
     }
 }
 
-/// Compute quotient polynomial values by evaluating constraints on the quotient domain.
-#[instrument(skip_all)]
-fn compute_quotient_values<SC, A, M>(
+/// Run the aux builder of `air` for `phase` inside a `build_aux_trace` span.
+///
+/// The span carries the AIR type and the phase, records the dimensions of the built
+/// trace, and is timed by any subscriber reporting span durations. The challenges are
+/// logged at debug level. Spans the builder opens itself, e.g. through the re-exported
+/// [`tracing`](crate::tracing), nest under this one.
+fn build_aux_phase_traced<F, EF, A>(
     air: &A,
-    trace_domain: crate::Domain<SC>,
-    quotient_domain: crate::Domain<SC>,
-    main_on_quotient: &M,
-    _aux_on_quotient: Option<&M>,
-    alpha: Challenge<SC>,
-    _public_values: &[Val<SC>],
-) -> Vec<Challenge<SC>>
+    phase: usize,
+    main_trace: &RowMajorMatrix<F>,
+    main_lde: Option<&MainTraceLde<F>>,
+    prior_phases: &[RowMajorMatrix<EF>],
+    challenges: &[EF],
+    hints: &dyn Any,
+) -> RowMajorMatrix<EF>
 where
-    SC: crate::StarkGenericConfig,
-    Val<SC>: PackedField,
-    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<ProverFolder<'a, SC>>,
-    M: p3_matrix::Matrix<Val<SC>> + Sync,
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
 {
-    let quotient_size = quotient_domain.size();
-    let width_main = main_on_quotient.width();
-    let _width_aux = 0; // TODO: Implement proper aux trace handling
-
-    // Compute selectors
-    let selectors = trace_domain.selectors_on_coset(quotient_domain);
-
-    // Calculate step size between consecutive trace points in quotient domain LDE
-    // quotient_domain is quotient_degree times larger than trace_domain
-    let log_quotient_degree =
-        p3_util::log2_strict_usize(quotient_size) - p3_util::log2_strict_usize(trace_domain.size());
-    let next_step = 1 << log_quotient_degree;
-
-    // Evaluate constraints at each point in quotient domain
-    // For simplicity, we'll do this in a single-threaded manner
-    // TODO: Add parallel evaluation
-    let mut quotient_values = Vec::with_capacity(quotient_size);
-
-    // First pass: count constraints by doing a dry run on first point
-    let main_local: Vec<_> = main_on_quotient.row_slice(0).unwrap().to_vec();
-    let main_next: Vec<_> = main_on_quotient
-        .row_slice(next_step % quotient_size)
-        .unwrap()
-        .to_vec();
-    let main_view =
-        p3_matrix::dense::RowMajorMatrix::new([main_local, main_next].concat(), width_main);
-    let aux_view = p3_matrix::dense::RowMajorMatrix::new(vec![], 0);
-
-    // Create dummy alpha powers for counting (won't be used, just need something)
-    let dummy_alpha_powers = vec![SC::Challenge::ZERO; 100];
-    let mut constraint_counter = ProverFolder {
-        main: main_view.as_view(),
-        aux: aux_view.as_view(),
-        is_first_row: selectors.is_first_row[0],
-        is_last_row: selectors.is_last_row[0],
-        is_transition: selectors.is_transition[0],
-        alpha_powers: &dummy_alpha_powers,
-        accumulator: SC::Challenge::ZERO,
-        constraint_index: 0,
-    };
-    air.eval(&mut constraint_counter);
-    let constraint_count = constraint_counter.constraint_index;
-
-    // Compute exact number of alpha powers and reverse
-    let mut alpha_powers: Vec<Challenge<SC>> = Vec::with_capacity(constraint_count);
-    let mut power = SC::Challenge::ONE;
-    for _ in 0..constraint_count {
-        alpha_powers.push(power);
-        power *= alpha;
-    }
-    alpha_powers.reverse();
-
-    for i in 0..quotient_size {
-        let is_first_row = selectors.is_first_row[i];
-        let is_last_row = selectors.is_last_row[i];
-        let is_transition = selectors.is_transition[i];
-        let inv_vanishing = selectors.inv_vanishing[i];
-
-        // Get local and next row values
-        // Next row is next_step away, not just i+1, because quotient domain LDE
-        // interleaves trace points with intermediate evaluation points
-        let main_local: Vec<_> = main_on_quotient.row_slice(i).unwrap().to_vec();
-        let main_next_idx = (i + next_step) % quotient_size;
-        let main_next: Vec<_> = main_on_quotient.row_slice(main_next_idx).unwrap().to_vec();
-
-        let main_view =
-            p3_matrix::dense::RowMajorMatrix::new([main_local, main_next].concat(), width_main);
-
-        // TODO: Implement proper aux trace handling
-        // For now, use empty aux view
-        let aux_view = p3_matrix::dense::RowMajorMatrix::new(vec![], 0);
-
-        // Evaluate constraints
-        let mut folder = ProverFolder {
-            main: main_view.as_view(),
-            aux: aux_view.as_view(),
-            is_first_row,
-            is_last_row,
-            is_transition,
-            alpha_powers: &alpha_powers,
-            accumulator: SC::Challenge::ZERO,
-            constraint_index: 0,
+    let span = info_span!(
+        "build_aux_trace",
+        air = core::any::type_name::<A>(),
+        phase,
+        rows = tracing::field::Empty,
+        width = tracing::field::Empty,
+    );
+    span.in_scope(|| {
+        tracing::debug!(?challenges, "auxiliary phase challenges");
+        let aux_trace = match main_lde {
+            Some(main_lde) => {
+                air.build_aux_phase_with_lde(phase, main_trace, main_lde, prior_phases, challenges)
+            }
+            None => {
+                air.build_aux_phase_with_hints(phase, main_trace, prior_phases, challenges, hints)
+            }
         };
+        span.record("rows", aux_trace.height());
+        span.record("width", aux_trace.width);
+        tracing::info!(
+            "Built auxiliary trace ({}x{})",
+            aux_trace.height(),
+            aux_trace.width
+        );
+        aux_trace
+    })
+}
 
-        air.eval(&mut folder);
+/// Check `main_trace` against `air`, the configuration and the preprocessed trace of `pk`,
+/// returning the log2 of its height.
+fn check_main_trace<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: &RowMajorMatrix<Val<SC>>,
+) -> Result<u8, ProverError>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if main_trace.width() != air.width() {
+        return Err(ProverError::MainTraceWidthMismatch {
+            expected: air.width(),
+            actual: main_trace.width(),
+        });
+    }
+    let height = main_trace.height();
+    if !height.is_power_of_two() {
+        return Err(ProverError::TraceHeightNotPowerOfTwo(height));
+    }
+    let log_degree = log2_strict_usize(height) as u8;
+    // Randomized traces are committed at twice their height
+    if let Some(max_log_degree) = config.max_supported_log_degree() {
+        if log_degree as usize + config.is_zk() > max_log_degree {
+            return Err(ProverError::TraceTooTall {
+                log_degree: log_degree as usize + config.is_zk(),
+                max_log_degree,
+            });
+        }
+    }
+    if let Some(preprocessed) = &pk.vk.preprocessed {
+        if preprocessed.log_degree != log_degree {
+            return Err(ProverError::PreprocessedHeightMismatch {
+                expected: preprocessed.log_degree,
+                actual: log_degree,
+            });
+        }
+    }
+    Ok(log_degree)
+}
 
-        // quotient(x) = constraints(x) / Z_H(x)
-        let quotient_value = folder.accumulator * inv_vanishing;
+/// Check that `phase`, which has no columns, samples challenges, returning the empty trace
+/// standing for it among the prior phases of later builders.
+fn check_challenge_phase<F, EF, A>(air: &A, phase: usize) -> Result<RowMajorMatrix<EF>, ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    if air.aux_phase_num_challenges(phase) == 0 {
+        return Err(ProverError::EmptyAuxPhase(phase));
+    }
+    if !air.aux_phase_matrix_widths(phase).is_empty() {
+        return Err(ProverError::AuxMatrixWidthsMismatch(phase));
+    }
+    Ok(RowMajorMatrix::new(Vec::new(), 0))
+}
 
-        // Debug: Check if we're getting reasonable values
-        if i < 3 {
-            tracing::debug!(
-                "Point {}: constraints={:?}, inv_van={:?}, quotient={:?}",
-                i,
-                folder.accumulator,
-                inv_vanishing,
-                quotient_value
-            );
+/// Check the trace built for `phase` against the declared width and the main trace height.
+fn check_aux_trace<F, EF, A>(
+    air: &A,
+    phase: usize,
+    aux_trace: &RowMajorMatrix<EF>,
+    height: usize,
+) -> Result<(), ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    let width = air.aux_phase_width(phase);
+    let matrix_widths = air.aux_phase_matrix_widths(phase);
+    if matrix_widths.contains(&0) || matrix_widths.iter().sum::<usize>() != width {
+        return Err(ProverError::AuxMatrixWidthsMismatch(phase));
+    }
+    if (aux_trace.width, aux_trace.height()) != (width, height) {
+        return Err(ProverError::AuxTraceShapeMismatch {
+            phase,
+            expected: (width, height),
+            actual: (aux_trace.width, aux_trace.height()),
+        });
+    }
+    Ok(())
+}
+
+/// The base field matrix committed for the trace built for `phase`: each extension column
+/// flattened to its coordinates or, in a base field phase, the base field column itself.
+///
+/// The coordinates are read off `aux_trace`, which later phases still need, so the phase
+/// holds the extension matrix and its flattening but never a second extension copy.
+fn committed_aux_trace<F, EF, A>(
+    air: &A,
+    phase: usize,
+    aux_trace: &RowMajorMatrix<EF>,
+) -> Result<RowMajorMatrix<F>, ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    if !air.aux_phase_in_base_field(phase) {
+        let dimension = <EF as BasedVectorSpace<F>>::DIMENSION;
+        let mut values = Vec::with_capacity(aux_trace.values.len() * dimension);
+        for value in &aux_trace.values {
+            values.extend_from_slice(value.as_basis_coefficients_slice());
+        }
+        return Ok(RowMajorMatrix::new(values, aux_trace.width * dimension));
+    }
+    let width = aux_trace.width;
+    let values = aux_trace
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let base = value.as_basis_coefficients_slice()[0];
+            if EF::from(base) == value {
+                Ok(base)
+            } else {
+                Err(ProverError::AuxTraceNotInBaseField {
+                    phase,
+                    row: i / width,
+                })
+            }
+        })
+        .collect::<Result<Vec<F>, _>>()?;
+    Ok(RowMajorMatrix::new(values, width))
+}
+
+/// The committed trace of `phase` cut into the matrices the AIR declares for it.
+fn split_aux_matrices<F, EF, A>(
+    air: &A,
+    phase: usize,
+    committed: RowMajorMatrix<F>,
+) -> Vec<RowMajorMatrix<F>>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    let widths = aux_matrix_committed_widths(air, phase);
+    if widths.len() == 1 {
+        return vec![committed];
+    }
+    let mut matrices: Vec<Vec<F>> = widths
+        .iter()
+        .map(|width| Vec::with_capacity(width * committed.height()))
+        .collect();
+    for row in committed.values.chunks_exact(committed.width) {
+        let mut start = 0;
+        for (values, &width) in matrices.iter_mut().zip(&widths) {
+            values.extend_from_slice(&row[start..start + width]);
+            start += width;
         }
+    }
+    matrices
+        .into_iter()
+        .zip(widths)
+        .map(|(values, width)| RowMajorMatrix::new(values, width))
+        .collect()
+}
 
-        quotient_values.push(quotient_value);
+/// The values `air` exposes for `phase`, checked against the declared count.
+fn phase_exposed_values<F, EF, A>(
+    air: &A,
+    phase: usize,
+    aux_trace: &RowMajorMatrix<EF>,
+    challenges: &[EF],
+) -> Result<Vec<EF>, ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    let exposed_values = air.aux_phase_exposed_values(phase, aux_trace, challenges);
+    let expected = air.aux_phase_num_exposed_values(phase);
+    if exposed_values.len() != expected {
+        return Err(ProverError::ExposedValueCountMismatch {
+            phase,
+            expected,
+            actual: exposed_values.len(),
+        });
     }
+    Ok(exposed_values)
+}
+
+/// Commit the quotient chunks in the column groups of [`quotient_column_groups`].
+///
+/// Each group is one commitment holding, per `(chunk, columns)` piece, those columns of
+/// the chunk over its domain.
+#[allow(clippy::type_complexity)]
+fn commit_quotient_groups<SC: StarkGenericConfig>(
+    pcs: &SC::Pcs,
+    chunk_domains: &[Domain<SC>],
+    chunks: &[RowMajorMatrix<Val<SC>>],
+    groups: &[Vec<(usize, Range<usize>)>],
+) -> (
+    Vec<<SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+    Vec<<SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::ProverData>,
+) {
+    info_span!("pcs_commit_quotient").in_scope(|| {
+        groups
+            .iter()
+            .map(|group| {
+                pcs.commit(
+                    group
+                        .iter()
+                        .map(|(chunk, columns)| {
+                            let matrix = &chunks[*chunk];
+                            let values = matrix
+                                .values
+                                .chunks_exact(matrix.width)
+                                .flat_map(|row| row[columns.clone()].iter().copied())
+                                .collect();
+                            (
+                                chunk_domains[*chunk],
+                                RowMajorMatrix::new(values, columns.len()),
+                            )
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .unzip()
+    })
+}
 
-    quotient_values
+/// Reassemble the opened quotient pieces of every group into whole chunks, per point.
+///
+/// Takes the openings of the groups' commitments from `openings`, in group order.
+fn regroup_quotient_openings<EF: Clone>(
+    num_points: usize,
+    num_chunks: usize,
+    groups: &[Vec<(usize, Range<usize>)>],
+    openings: impl Iterator<Item = Vec<Vec<Vec<EF>>>>,
+) -> Vec<Vec<Vec<EF>>> {
+    let mut chunks = vec![vec![Vec::new(); num_chunks]; num_points];
+    for (group, opened) in groups.iter().zip(openings) {
+        for ((chunk, _), piece) in group.iter().zip(opened) {
+            for (point, values) in piece.into_iter().enumerate() {
+                chunks[point][*chunk].extend(values);
+            }
+        }
+    }
+    chunks
 }
@@ -0,0 +1,155 @@
+//! Range-check chip
+//!
+//! [`RangeCheckChip`] is a table of the values `0..2^bits`, committed once as a
+//! preprocessed column, with a main column counting how often each value is looked up. It
+//! receives those values on a bus, so any AIR proving in the same [`crate::MultiProof`]
+//! range checks a column by sending it there, see [`RangeCheckChip::register`], and fills
+//! in the counts with a [`RangeCheckCounts`].
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::interaction::{build_table_receives, eval_table_receives};
+use crate::{AuxBuilder, AuxTraceBuilder, InteractionBuilder, Multiplicity};
+
+/// A lookup table of `0..2^bits`, receiving on `bus` the values other AIRs range check.
+///
+/// The main trace is one column: the multiplicity of each table value, in table order. The
+/// aux trace is one phase with the LogUp helper `m / (α - (bus + β·t))` and its running
+/// sum, whose last value is exposed as the chip's net
+/// [`interaction_sum`](crate::AuxTraceBuilder::interaction_sum). The chip draws the two
+/// challenges of [`crate::BusInteractions`], so the AIRs sending to it use that gadget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeCheckChip {
+    /// Values are checked to lie in `0..2^bits`
+    pub bits: usize,
+    /// The bus values are received on
+    pub bus: usize,
+}
+
+impl RangeCheckChip {
+    pub const fn new(bits: usize, bus: usize) -> Self {
+        Self { bits, bus }
+    }
+
+    /// Number of table rows, which is also the height of the chip's main trace.
+    pub const fn height(&self) -> usize {
+        1 << self.bits
+    }
+
+    /// Send each of `columns` to this chip once per row, range checking them.
+    pub fn register(
+        &self,
+        interactions: InteractionBuilder,
+        columns: &[usize],
+    ) -> InteractionBuilder {
+        columns.iter().fold(interactions, |interactions, &column| {
+            interactions.send(self.bus, vec![column], Multiplicity::One)
+        })
+    }
+
+    /// The chip's net sum: the running sum on the last row of its aux trace.
+    pub fn cumulative_sum<EF: Field>(&self, aux_trace: &RowMajorMatrix<EF>) -> EF {
+        aux_trace.values[aux_trace.values.len() - 1]
+    }
+}
+
+impl<F: Field> BaseAir<F> for RangeCheckChip {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..self.height()).map(F::from_usize).collect(),
+        ))
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RangeCheckChip {
+    fn aux_width(&self) -> usize {
+        2
+    }
+
+    fn num_challenges(&self) -> usize {
+        crate::BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let table = BaseAir::<F>::preprocessed_trace(self).expect("the chip has a table");
+        build_table_receives(self.bus, &table, &main.values, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        vec![self.cumulative_sum(aux_phase)]
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for RangeCheckChip {
+    fn eval(&self, builder: &mut AB) {
+        eval_table_receives(builder, self.bus);
+    }
+}
+
+/// Counts the values an execution range checks, to fill in the main trace of a
+/// [`RangeCheckChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeCheckCounts {
+    counts: Vec<u32>,
+}
+
+impl RangeCheckCounts {
+    /// No values yet, for a chip of `bits` bits.
+    pub fn new(bits: usize) -> Self {
+        Self {
+            counts: vec![0; 1 << bits],
+        }
+    }
+
+    /// Count one check of `value`.
+    ///
+    /// # Panics
+    /// If `value` is out of range: the proof could not balance
+    pub fn record(&mut self, value: u64) {
+        let count = usize::try_from(value)
+            .ok()
+            .and_then(|value| self.counts.get_mut(value))
+            .unwrap_or_else(|| panic!("{value} is out of the range-check table"));
+        *count += 1;
+    }
+
+    /// Count a check of every value of `column` in `trace`, as
+    /// [`RangeCheckChip::register`] sends them.
+    pub fn record_column<F: PrimeField64>(&mut self, trace: &RowMajorMatrix<F>, column: usize) {
+        for row in trace.values.chunks_exact(trace.width) {
+            self.record(row[column].as_canonical_u64());
+        }
+    }
+
+    /// The main trace of the chip: one multiplicity per table value.
+    pub fn trace<F: Field>(&self) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(
+            self.counts
+                .iter()
+                .map(|&count| F::from_u32(count))
+                .collect(),
+        )
+    }
+}
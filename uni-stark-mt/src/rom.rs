@@ -0,0 +1,209 @@
+//! Program ROM lookup chip
+//!
+//! [`ProgramRom`] commits a program once as a preprocessed table of `(pc, instruction)`
+//! rows, with a main column counting how often each instruction is fetched. It receives the
+//! fetches on a bus, so an execution-trace AIR proving in the same [`crate::MultiProof`]
+//! shows it only runs instructions of the program by sending its `pc` and instruction
+//! columns there, see [`ProgramRom::register`]; [`ProgramRomCounts`] fills in the counts.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::interaction::{build_table_receives, eval_table_receives};
+use crate::{AuxBuilder, AuxTraceBuilder, InteractionBuilder, Multiplicity};
+
+/// A program, as a lookup table of `(pc, instruction)` receiving on `bus` the instructions
+/// an execution fetches.
+///
+/// Instruction `i` has `pc = i` and is `instruction_width` field elements, e.g. an opcode
+/// and its operands. The table is padded with zero instructions to a power-of-two height,
+/// at `pc`s past the program. The main trace is one column: the multiplicity of each row.
+/// The aux trace is one phase with the LogUp helper and its running sum, whose last value
+/// is exposed as the chip's net
+/// [`interaction_sum`](crate::AuxTraceBuilder::interaction_sum). The chip draws the two
+/// challenges of [`crate::BusInteractions`], so the AIRs sending to it use that gadget.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramRom {
+    /// The bus fetches are received on
+    pub bus: usize,
+    /// Field elements per instruction
+    pub instruction_width: usize,
+    /// The instructions, one after the other
+    pub instructions: Vec<u64>,
+}
+
+impl ProgramRom {
+    /// The ROM of `instructions`, each `instruction_width` values long.
+    ///
+    /// # Panics
+    /// If `instructions` does not hold a whole number of instructions
+    pub fn new(bus: usize, instruction_width: usize, instructions: Vec<u64>) -> Self {
+        assert!(
+            instruction_width > 0 && instructions.len() % instruction_width == 0,
+            "the program is not a whole number of {instruction_width}-value instructions"
+        );
+        Self {
+            bus,
+            instruction_width,
+            instructions,
+        }
+    }
+
+    /// Number of instructions in the program.
+    pub fn len(&self) -> usize {
+        self.instructions.len() / self.instruction_width
+    }
+
+    /// Whether the program has no instructions.
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Number of table rows, which is also the height of the chip's main trace.
+    pub fn height(&self) -> usize {
+        self.len().next_power_of_two().max(2)
+    }
+
+    /// Send the fetch of the instruction in columns `instruction` at the `pc` in column
+    /// `pc` to this chip, `multiplicity` times per row: 1 for every executed row, 0 for
+    /// padding.
+    pub fn register(
+        &self,
+        interactions: InteractionBuilder,
+        pc: usize,
+        instruction: &[usize],
+        multiplicity: Multiplicity,
+    ) -> InteractionBuilder {
+        let columns = core::iter::once(pc)
+            .chain(instruction.iter().copied())
+            .collect();
+        interactions.send(self.bus, columns, multiplicity)
+    }
+
+    /// The chip's net sum: the running sum on the last row of its aux trace.
+    pub fn cumulative_sum<EF: Field>(&self, aux_trace: &RowMajorMatrix<EF>) -> EF {
+        aux_trace.values[aux_trace.values.len() - 1]
+    }
+}
+
+impl<F: Field> BaseAir<F> for ProgramRom {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        let width = 1 + self.instruction_width;
+        let mut values = F::zero_vec(self.height() * width);
+        for (pc, row) in values.chunks_exact_mut(width).enumerate() {
+            row[0] = F::from_usize(pc);
+        }
+        for (row, instruction) in values
+            .chunks_exact_mut(width)
+            .zip(self.instructions.chunks_exact(self.instruction_width))
+        {
+            for (cell, &value) in row[1..].iter_mut().zip(instruction) {
+                *cell = F::from_u64(value);
+            }
+        }
+        Some(RowMajorMatrix::new(values, width))
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ProgramRom {
+    fn aux_width(&self) -> usize {
+        2
+    }
+
+    fn num_challenges(&self) -> usize {
+        crate::BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let table = BaseAir::<F>::preprocessed_trace(self).expect("the chip has a table");
+        build_table_receives(self.bus, &table, &main.values, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        vec![self.cumulative_sum(aux_phase)]
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for ProgramRom {
+    fn eval(&self, builder: &mut AB) {
+        eval_table_receives(builder, self.bus);
+    }
+}
+
+/// Counts the instructions an execution fetches, to fill in the main trace of a
+/// [`ProgramRom`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramRomCounts {
+    counts: Vec<u32>,
+}
+
+impl ProgramRomCounts {
+    /// No fetches yet, for `rom`.
+    pub fn new(rom: &ProgramRom) -> Self {
+        Self {
+            counts: vec![0; rom.height()],
+        }
+    }
+
+    /// Count one fetch at `pc`.
+    ///
+    /// # Panics
+    /// If `pc` is past the table
+    pub fn record(&mut self, pc: u64) {
+        let count = usize::try_from(pc)
+            .ok()
+            .and_then(|pc| self.counts.get_mut(pc))
+            .unwrap_or_else(|| panic!("pc {pc} is out of the program ROM"));
+        *count += 1;
+    }
+
+    /// Count the fetches of every row of `trace`, at the `pc` in column `pc` and as many
+    /// times as `multiplicity` says, as [`ProgramRom::register`] sends them.
+    pub fn record_column<F: PrimeField64>(
+        &mut self,
+        trace: &RowMajorMatrix<F>,
+        pc: usize,
+        multiplicity: Multiplicity,
+    ) {
+        for row in trace.values.chunks_exact(trace.width) {
+            let times = match multiplicity {
+                Multiplicity::One => 1,
+                Multiplicity::Column(c) => row[c].as_canonical_u64(),
+            };
+            for _ in 0..times {
+                self.record(row[pc].as_canonical_u64());
+            }
+        }
+    }
+
+    /// The main trace of the chip: one multiplicity per table row.
+    pub fn trace<F: Field>(&self) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(
+            self.counts
+                .iter()
+                .map(|&count| F::from_u32(count))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,178 @@
+//! Typed views of trace rows
+//!
+//! AIRs usually name their columns with a `#[repr(C)]` row struct and reinterpret a row
+//! slice as that struct. [`trace_row!`] declares such a struct and implements [`TraceRow`]
+//! for it, and [`RowSlice`] does the reinterpretation, so the casts stay inside this module:
+//!
+//! ```
+//! use p3_uni_stark_mt::{trace_row, RowSlice};
+//!
+//! trace_row! {
+//!     pub struct PairRow<F> {
+//!         pub left: F,
+//!         pub right: [F; 2],
+//!     }
+//! }
+//!
+//! let mut values = [1u32, 2, 3, 4, 5, 6];
+//! values.rows_mut_as::<PairRow<u32>>()[1].left = 7;
+//! assert_eq!(values[3..].row_as::<PairRow<u32>>().right, [5, 6]);
+//! assert_eq!(values[3], 7);
+//! ```
+
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::slice;
+
+/// A row struct whose fields are all `T` or arrays of `T`, laid out as `WIDTH` consecutive
+/// `T` values.
+///
+/// Implement it with [`trace_row!`], which checks the field types. Its size and alignment
+/// are further checked at compile time when it is first used.
+///
+/// # Safety
+///
+/// The type must be `#[repr(C)]` with only fields of type `T` or `[T; N]`, `WIDTH` `T`
+/// values in all, as [`RowSlice`] casts slices of `T` to it. A plain `impl` is rejected:
+///
+/// ```compile_fail
+/// use p3_uni_stark_mt::TraceRow;
+///
+/// struct Wide(u64);
+///
+/// impl TraceRow<u32> for Wide {
+///     const WIDTH: usize = 2;
+/// }
+/// ```
+pub unsafe trait TraceRow<T>: Sized {
+    /// Number of `T` columns in the row
+    const WIDTH: usize;
+}
+
+/// A type a [`TraceRow`] field can have: `T` itself or an array of `T`.
+///
+/// # Safety
+///
+/// The type must be laid out as `WIDTH` consecutive `T` values.
+pub unsafe trait RowColumn<T> {
+    /// Number of `T` columns in the field
+    const WIDTH: usize;
+}
+
+// SAFETY: a `T` is one `T` value
+unsafe impl<T> RowColumn<T> for T {
+    const WIDTH: usize = 1;
+}
+
+// SAFETY: arrays are laid out as `N` consecutive elements
+unsafe impl<T, const N: usize> RowColumn<T> for [T; N] {
+    const WIDTH: usize = N;
+}
+
+/// Compile-time layout check of a [`TraceRow`].
+struct Layout<T, R>(PhantomData<(T, R)>);
+
+impl<T, R: TraceRow<T>> Layout<T, R> {
+    const CHECK: () = assert!(
+        R::WIDTH > 0
+            && size_of::<T>() > 0
+            && size_of::<R>() == R::WIDTH * size_of::<T>()
+            && align_of::<R>() == align_of::<T>(),
+        "row layout does not match its columns"
+    );
+}
+
+/// Reinterpretation of a slice of `T` as [`TraceRow`]s.
+pub trait RowSlice<T> {
+    /// View a single row as `R`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice does not hold exactly `R::WIDTH` values.
+    fn row_as<R: TraceRow<T>>(&self) -> &R;
+
+    /// Mutable version of [`RowSlice::row_as`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice does not hold exactly `R::WIDTH` values.
+    fn row_mut_as<R: TraceRow<T>>(&mut self) -> &mut R;
+
+    /// View consecutive rows, such as a row-major trace, as `R`s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice length is not a multiple of `R::WIDTH`.
+    fn rows_as<R: TraceRow<T>>(&self) -> &[R];
+
+    /// Mutable version of [`RowSlice::rows_as`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice length is not a multiple of `R::WIDTH`.
+    fn rows_mut_as<R: TraceRow<T>>(&mut self) -> &mut [R];
+}
+
+impl<T> RowSlice<T> for [T] {
+    fn row_as<R: TraceRow<T>>(&self) -> &R {
+        let rows = self.rows_as::<R>();
+        assert_eq!(rows.len(), 1, "slice is not a single row");
+        &rows[0]
+    }
+
+    fn row_mut_as<R: TraceRow<T>>(&mut self) -> &mut R {
+        let rows = self.rows_mut_as::<R>();
+        assert_eq!(rows.len(), 1, "slice is not a single row");
+        &mut rows[0]
+    }
+
+    fn rows_as<R: TraceRow<T>>(&self) -> &[R] {
+        let () = Layout::<T, R>::CHECK;
+        assert_eq!(
+            self.len() % R::WIDTH,
+            0,
+            "slice is not a whole number of rows"
+        );
+        // SAFETY: `R` consists of `R::WIDTH` fields of type `T` with no padding and the
+        // alignment of `T`, so `len / R::WIDTH` of them cover exactly the slice's values
+        unsafe { slice::from_raw_parts(self.as_ptr().cast::<R>(), self.len() / R::WIDTH) }
+    }
+
+    fn rows_mut_as<R: TraceRow<T>>(&mut self) -> &mut [R] {
+        let () = Layout::<T, R>::CHECK;
+        assert_eq!(
+            self.len() % R::WIDTH,
+            0,
+            "slice is not a whole number of rows"
+        );
+        // SAFETY: as in `rows_as`, and the borrow is exclusive
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr().cast::<R>(), self.len() / R::WIDTH) }
+    }
+}
+
+/// Declare a `#[repr(C)]` row struct generic over its column type and implement
+/// [`TraceRow`] for it.
+///
+/// Every field must have the column type or be an array of it; anything else fails to
+/// compile.
+#[macro_export]
+macro_rules! trace_row {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident<$t:ident> {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $field_ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[repr(C)]
+        $vis struct $name<$t> {
+            $($(#[$field_meta])* $field_vis $field: $field_ty),*
+        }
+
+        // SAFETY: the struct is `#[repr(C)]` and every field implements `RowColumn<$t>`,
+        // so it is laid out as `WIDTH` consecutive `$t` values
+        unsafe impl<$t> $crate::TraceRow<$t> for $name<$t> {
+            const WIDTH: usize = 0 $(+ <$field_ty as $crate::RowColumn<$t>>::WIDTH)*;
+        }
+    };
+}
@@ -0,0 +1,118 @@
+//! Segmented executions
+//!
+//! A long execution is split into segments, each proven on its own with [`crate::prove`]
+//! for the same AIR. Every segment's public values carry the state it starts from and the
+//! state it ends in, as laid out by a [`SegmentLayout`]; [`verify_segments`] checks the
+//! proofs and that each segment starts where the previous one ended, giving one answer for
+//! the whole execution. Checking the chain inside a recursion AIR is left to the
+//! `recursion` feature.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_air::Air;
+use tracing::instrument;
+
+use crate::{
+    verify_proofs, Challenge, MultiTraceAir, Proof, ProofInstance, StarkGenericConfig, Val,
+    VerificationError, VerifierFolder, VerifyingKey,
+};
+
+/// Where the state a segment starts from and the state it ends in sit among its public
+/// values.
+///
+/// Both ranges must have the same length: the end state of a segment is compared value by
+/// value with the start state of the next.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentLayout {
+    /// Public values holding the state before the segment's first step
+    pub start: Range<usize>,
+    /// Public values holding the state after the segment's last step
+    pub end: Range<usize>,
+}
+
+impl SegmentLayout {
+    /// State a segment with `public_values` starts from, `None` if they are too short.
+    pub fn start_state<'a, F>(&self, public_values: &'a [F]) -> Option<&'a [F]> {
+        public_values.get(self.start.clone())
+    }
+
+    /// State a segment with `public_values` ends in, `None` if they are too short.
+    pub fn end_state<'a, F>(&self, public_values: &'a [F]) -> Option<&'a [F]> {
+        public_values.get(self.end.clone())
+    }
+}
+
+/// Proof of one segment of an execution, checked by [`verify_segments`].
+pub struct SegmentProof<'a, SC: StarkGenericConfig> {
+    /// The proof from [`crate::prove`]
+    pub proof: &'a Proof<SC>,
+    /// Public values of the segment, including its start and end states
+    pub public_values: &'a [Val<SC>],
+}
+
+/// Verify the proofs of all segments of one execution of `air`, in execution order.
+///
+/// The execution is accepted if every proof verifies under `vk`, the first segment starts
+/// from `initial_state`, each following segment starts from the end state of the one
+/// before, and the last one ends in `final_state`. The chain is checked before any proof,
+/// since it is much cheaper; the proofs are then checked as by [`verify_proofs`],
+/// concurrently with the `parallel` feature.
+///
+/// # Returns
+/// - `Ok(())` if the whole execution is valid
+/// - `Err((index, error))` with the index in `segments` of a failing segment and its error:
+///   [`VerificationError::SegmentChainBroken`] if it does not start where it should, or
+///   does not end in `final_state` for the last one, and
+///   [`VerificationError::InvalidProof`] if its public values do not fit `layout`, or at
+///   index 0 if there are no segments
+#[instrument(skip_all, fields(num_segments = segments.len()))]
+pub fn verify_segments<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    layout: &SegmentLayout,
+    segments: &[SegmentProof<'_, SC>],
+    initial_state: &[Val<SC>],
+    final_state: &[Val<SC>],
+) -> Result<(), (usize, VerificationError)>
+where
+    SC: StarkGenericConfig + Sync,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+    Proof<SC>: Sync,
+    VerifyingKey<SC>: Sync,
+{
+    if segments.is_empty() {
+        return Err((0, VerificationError::InvalidProof("no segments")));
+    }
+    let mut expected_start = initial_state;
+    for (index, segment) in segments.iter().enumerate() {
+        let (Some(start), Some(end)) = (
+            layout.start_state(segment.public_values),
+            layout.end_state(segment.public_values),
+        ) else {
+            return Err((
+                index,
+                VerificationError::InvalidProof("public values do not fit the segment layout"),
+            ));
+        };
+        if start != expected_start {
+            return Err((index, VerificationError::SegmentChainBroken));
+        }
+        expected_start = end;
+    }
+    if expected_start != final_state {
+        return Err((segments.len() - 1, VerificationError::SegmentChainBroken));
+    }
+
+    let instances: Vec<_> = segments
+        .iter()
+        .map(|segment| ProofInstance {
+            air,
+            vk,
+            proof: segment.proof,
+            public_values: segment.public_values,
+        })
+        .collect();
+    verify_proofs(config, &instances)
+}
@@ -0,0 +1,104 @@
+//! Proving with a verifier self-check
+
+use core::fmt;
+use core::time::Duration;
+use std::time::Instant;
+
+use p3_air::Air;
+use p3_field::PackedField;
+use p3_matrix::dense::RowMajorMatrix;
+use tracing::instrument;
+
+use crate::{
+    prove, verify, Challenge, MultiTraceAir, Proof, ProverError, ProverFolder, ProvingKey, Val,
+    VerificationError, VerifierFolder,
+};
+
+/// Why [`prove_and_check`] returned no proof.
+#[derive(Debug)]
+pub enum SelfCheckError {
+    /// The prover rejected the witness or the configuration
+    Prover(ProverError),
+    /// The fresh proof failed verification
+    Verification(VerificationError),
+}
+
+impl fmt::Display for SelfCheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Prover(err) => write!(f, "proving failed: {err}"),
+            Self::Verification(err) => write!(f, "fresh proof failed verification: {err:?}"),
+        }
+    }
+}
+
+impl std::error::Error for SelfCheckError {}
+
+impl From<ProverError> for SelfCheckError {
+    fn from(err: ProverError) -> Self {
+        Self::Prover(err)
+    }
+}
+
+impl From<VerificationError> for SelfCheckError {
+    fn from(err: VerificationError) -> Self {
+        Self::Verification(err)
+    }
+}
+
+/// Wall-clock timings reported by [`prove_and_check`].
+#[derive(Clone, Copy, Debug)]
+pub struct SelfCheckTimings {
+    /// Time spent in [`prove`]
+    pub prove: Duration,
+    /// Time spent in [`verify`] on the fresh proof
+    pub verify: Duration,
+}
+
+/// Prove, then verify the fresh proof with the same configuration before returning it.
+///
+/// Services that ship proofs to third parties can use this to make sure a config drift or
+/// prover bug never produces a proof that fails verification downstream.
+///
+/// # Returns
+/// - `Ok((proof, timings))` if the proof verifies
+/// - `Err(SelfCheckError::Prover)` if the prover rejects the witness
+/// - `Err(SelfCheckError::Verification)` with the verifier's error otherwise
+#[instrument(skip_all)]
+pub fn prove_and_check<SC, A>(
+    config: &SC,
+    air: &A,
+    pk: &ProvingKey<SC>,
+    main_trace: RowMajorMatrix<Val<SC>>,
+    public_values: &[Val<SC>],
+) -> Result<(Proof<SC>, SelfCheckTimings), SelfCheckError>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let start = Instant::now();
+    let proof = prove(config, air, pk, main_trace, public_values)?;
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    let result = verify(config, air, &pk.vk, &proof, public_values);
+    let verify_time = start.elapsed();
+
+    tracing::info!(
+        "Self-checked proof: prove {:?}, verify {:?}",
+        prove_time,
+        verify_time
+    );
+    result?;
+
+    Ok((
+        proof,
+        SelfCheckTimings {
+            prove: prove_time,
+            verify: verify_time,
+        },
+    ))
+}
@@ -0,0 +1,325 @@
+//! Serialized proof size
+//!
+//! The prover and verifier do not depend on a wire format, so proof sizes are measured in a
+//! fixed reference layout: bincode's default encoding, with fixed-width integers, `u64`
+//! length prefixes, one-byte option tags and `u32` enum variant indices. Other binary formats
+//! land within a few percent of it, so a [`crate::StarkGenericConfig::max_proof_size`] set
+//! from it should leave some headroom.
+
+use core::fmt;
+
+use serde::ser::{self, Serialize};
+
+/// Number of bytes `value` serializes to in the reference layout.
+pub fn serialized_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut counter = SizeCounter { size: 0 };
+    match value.serialize(&mut counter) {
+        Ok(()) => counter.size,
+        Err(SizeError) => usize::MAX,
+    }
+}
+
+/// A [`ser::Serializer`] that adds up the bytes it would write.
+struct SizeCounter {
+    size: usize,
+}
+
+impl SizeCounter {
+    fn add(&mut self, bytes: usize) -> Result<(), SizeError> {
+        self.size = self.size.saturating_add(bytes);
+        Ok(())
+    }
+}
+
+/// Raised by a `Serialize` impl that fails on its own; the size is then unbounded.
+#[derive(Debug)]
+struct SizeError;
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("value could not be serialized")
+    }
+}
+
+impl core::error::Error for SizeError {}
+
+impl ser::Error for SizeError {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        SizeError
+    }
+}
+
+const LEN: usize = 8;
+const VARIANT: usize = 4;
+
+impl ser::Serializer for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), SizeError> {
+        self.add(2)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_i128(self, _v: i128) -> Result<(), SizeError> {
+        self.add(16)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), SizeError> {
+        self.add(2)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_u128(self, _v: u128) -> Result<(), SizeError> {
+        self.add(16)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SizeError> {
+        self.add(v.len_utf8())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SizeError> {
+        self.add(LEN + v.len())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SizeError> {
+        self.add(LEN + v.len())
+    }
+
+    fn serialize_none(self) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SizeError> {
+        self.add(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SizeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<(), SizeError> {
+        self.add(VARIANT)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        self.add(VARIANT)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, SizeError> {
+        self.add(LEN)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SizeError> {
+        self.add(VARIANT)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, SizeError> {
+        self.add(LEN)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SizeError> {
+        self.add(VARIANT)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SizeError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut SizeCounter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+//! The semver-committed API
+//!
+//! Everything re-exported here keeps its signature and behaviour across minor releases:
+//! describing an AIR, setting up its keys, proving and verifying single-table proofs, and
+//! the proof and error types they exchange. Production users who import from this module
+//! only can upgrade without following the faster-moving parts of the crate, which are
+//! grouped under [`crate::unstable`].
+//!
+//! The crate root still re-exports every item, stable or not.
+
+pub use crate::{
+    keygen, setup, trace_row, verify, AuxTraceBuilder, Challenge, ChallengeGroup, ChallengePlan,
+    Domain, MultiTraceAir, Proof, ProvingKey, RowSlice, StarkConfig, StarkGenericConfig, Val,
+    VerificationError, VerifyingKey, PROOF_VERSION,
+};
+
+#[cfg(feature = "prover")]
+pub use crate::{prove, prove_zk, ProverError};
@@ -0,0 +1,143 @@
+//! Proptest strategies for property-based tests
+//!
+//! [`SyntheticAir`] is a family of AIRs parameterized by width and constraint degree whose
+//! traces are fully determined by their first row, so any first row gives a valid trace.
+//! [`synthetic_instance`] draws an AIR and a valid trace for it, for tests asserting that
+//! every such instance proves and verifies under [`crate::fixtures::baby_bear_config`]:
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn round_trip((air, trace) in synthetic_instance()) {
+//!         let (pk, vk) = setup(&config, &air);
+//!         let proof = prove(&config, &air, &pk, trace, &[])?;
+//!         prop_assert!(verify(&config, &air, &vk, &proof, &[]).is_ok());
+//!     }
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, PrimeField32};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use proptest::prelude::*;
+
+use crate::fixtures::Val;
+use crate::{AuxTraceBuilder, TraceGenerator};
+
+/// Widths drawn by [`synthetic_air`] are at most this.
+pub const MAX_WIDTH: usize = 8;
+
+/// Constraint degrees drawn by [`synthetic_air`] are at most this, before the transition
+/// selector.
+pub const MAX_DEGREE: usize = 3;
+
+/// Log2 of the trace heights drawn by [`valid_trace`].
+pub const LOG_HEIGHTS: core::ops::RangeInclusive<usize> = 3..=6;
+
+/// A synthetic AIR of `width` columns with one transition constraint per column:
+/// `next[i] = local[i]^degree + local[(i + 1) % width]`.
+///
+/// Its constraints have degree `degree + 1` with the transition selector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyntheticAir {
+    /// Number of main trace columns, at least 1
+    pub width: usize,
+    /// Degree of the constraints in the trace values, at least 1
+    pub degree: usize,
+}
+
+impl SyntheticAir {
+    /// The row following `row`.
+    pub fn next_row<F: Field>(&self, row: &[F]) -> Vec<F> {
+        (0..self.width)
+            .map(|i| row[i].exp_u64(self.degree as u64) + row[(i + 1) % self.width])
+            .collect()
+    }
+}
+
+impl<F> BaseAir<F> for SyntheticAir {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SyntheticAir {}
+
+impl<AB: AirBuilder> Air<AB> for SyntheticAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+
+        let mut when_transition = builder.when_transition();
+        for i in 0..self.width {
+            let local_i: AB::Expr = local[i].clone().into();
+            when_transition.assert_eq(
+                local_i.exp_u64(self.degree as u64) + local[(i + 1) % self.width].clone(),
+                next[i].clone(),
+            );
+        }
+    }
+}
+
+/// Generates the trace of `1 << log_n` rows starting from `first_row`.
+impl<F: Field> TraceGenerator<F> for SyntheticAir {
+    type Inputs = (Vec<F>, usize);
+
+    fn generate(&self, (first_row, log_n): &(Vec<F>, usize)) -> RowMajorMatrix<F> {
+        let n = 1 << log_n;
+        let mut values = Vec::with_capacity(n * self.width);
+        let mut row = first_row.clone();
+        for _ in 0..n {
+            let next = self.next_row(&row);
+            values.extend(row);
+            row = next;
+        }
+        RowMajorMatrix::new(values, self.width)
+    }
+}
+
+/// Any field element.
+pub fn field_element() -> impl Strategy<Value = Val> {
+    any::<u32>().prop_map(Val::from_u32)
+}
+
+/// A [`SyntheticAir`] of width `1..=MAX_WIDTH` and degree `1..=MAX_DEGREE`.
+pub fn synthetic_air() -> impl Strategy<Value = SyntheticAir> {
+    (1..=MAX_WIDTH, 1..=MAX_DEGREE).prop_map(|(width, degree)| SyntheticAir { width, degree })
+}
+
+/// A valid trace of `air` with a random first row and a height of `2^k` rows, `k` in
+/// [`LOG_HEIGHTS`].
+pub fn valid_trace(air: SyntheticAir) -> impl Strategy<Value = RowMajorMatrix<Val>> {
+    (
+        proptest::collection::vec(field_element(), air.width),
+        LOG_HEIGHTS,
+    )
+        .prop_map(move |inputs| air.generate(&inputs))
+}
+
+/// A [`SyntheticAir`] together with a valid trace for it.
+pub fn synthetic_instance() -> impl Strategy<Value = (SyntheticAir, RowMajorMatrix<Val>)> {
+    synthetic_air().prop_flat_map(|air| (Just(air), valid_trace(air)))
+}
+
+/// A valid trace of `air` as above, with the cell at `(row, column)` changed by a nonzero
+/// amount, `row` never being the first: the transition into that row no longer holds.
+pub fn corrupted_trace(air: SyntheticAir) -> impl Strategy<Value = RowMajorMatrix<Val>> {
+    (
+        valid_trace(air),
+        any::<prop::sample::Index>(),
+        0..air.width,
+        1..Val::ORDER_U32,
+    )
+        .prop_map(|(mut trace, row, column, delta)| {
+            let row = 1 + row.index(trace.height() - 1);
+            let width = trace.width();
+            trace.values[row * width + column] += Val::from_u32(delta);
+            trace
+        })
+}
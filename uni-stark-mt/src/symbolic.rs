@@ -0,0 +1,1240 @@
+//! Constraint evaluation from an expression DAG
+//!
+//! [`CpuBackend`](crate::CpuBackend) runs `air.eval` at every packed point of the quotient
+//! domain, rebuilding each constraint expression as it goes. [`crate::setup`] runs it once
+//! instead, on a [`SymbolicBuilder`] whose expressions record how they were built, and
+//! compiles the constraints into a [`ConstraintDag`] kept in the proving key: one node per
+//! distinct subexpression, shared by every constraint that uses it. The provers evaluate
+//! the quotient with a [`DagBackend`] over that DAG, walking the nodes in order at each
+//! packed point, into buffers reused from point to point.
+//!
+//! The proof is the same as with [`CpuBackend`](crate::CpuBackend), which stays available
+//! through [`crate::prove_with_backend`]:
+//!
+//! ```ignore
+//! let proof = prove_with_backend(&config, &air, &pk, trace, &public_values, &CpuBackend)?;
+//! ```
+//!
+//! The same DAG is [`crate::setup`]'s analysis of the constraints: their number, their
+//! degrees and the aux phase each reads are read off its nodes.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "prover")]
+use core::fmt::Debug;
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PairBuilder};
+#[cfg(feature = "prover")]
+use p3_field::PackedField;
+use p3_field::{Algebra, ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+#[cfg(feature = "prover")]
+use p3_matrix::Matrix;
+
+use crate::air::aux_matrix_widths;
+#[cfg(feature = "prover")]
+use crate::backend::evaluate_quotient;
+use crate::keys::MAX_PUBLIC_VALUES;
+use crate::{AuxBuilder, MultiTraceAir};
+#[cfg(feature = "prover")]
+use crate::{
+    Challenge, PackedChallenge, PackedVal, ProverFolder, ProverTelemetry, ProvingKey,
+    QuotientBackend, QuotientInputs, StarkGenericConfig, Val, VerifyingKey,
+};
+
+/// A base field value the constraints read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SymbolicEntry {
+    /// Main trace column `column` in the local (`offset` 0) or next (`offset` 1) row
+    Main {
+        /// Row offset
+        offset: usize,
+        /// Column index
+        column: usize,
+    },
+    /// Preprocessed trace column `column` in the local or next row
+    Preprocessed {
+        /// Row offset
+        offset: usize,
+        /// Column index
+        column: usize,
+    },
+    /// Public value `index`
+    Public(usize),
+    /// The first row selector
+    IsFirstRow,
+    /// The last row selector
+    IsLastRow,
+    /// The transition selector
+    IsTransition,
+}
+
+/// An extension field value the constraints read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SymbolicExtEntry {
+    /// Aux trace column `column` in the local (`offset` 0) or next (`offset` 1) row
+    Aux {
+        /// Row offset
+        offset: usize,
+        /// Column index, counting the columns of all phases
+        column: usize,
+    },
+    /// Aux challenge `index`, counting the challenges of all phases
+    Challenge(usize),
+    /// Exposed value `index`, counting the values of all phases
+    Exposed(usize),
+}
+
+/// A base field variable of a [`SymbolicBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SymbolicVariable<F> {
+    /// The value it stands for
+    pub entry: SymbolicEntry,
+    _phantom: PhantomData<F>,
+}
+
+impl<F> SymbolicVariable<F> {
+    const fn new(entry: SymbolicEntry) -> Self {
+        Self {
+            entry,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// An extension field variable of a [`SymbolicBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SymbolicExtVariable<F, EF> {
+    /// The value it stands for
+    pub entry: SymbolicExtEntry,
+    _phantom: PhantomData<(F, EF)>,
+}
+
+impl<F, EF> SymbolicExtVariable<F, EF> {
+    const fn new(entry: SymbolicExtEntry) -> Self {
+        Self {
+            entry,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// A base field expression, as built by the AIR.
+#[derive(Clone, Debug)]
+pub enum SymbolicExpression<F> {
+    /// A value the constraints read
+    Entry(SymbolicEntry),
+    /// A constant
+    Constant(F),
+    /// Sum of two expressions
+    Add(Arc<Self>, Arc<Self>),
+    /// Difference of two expressions
+    Sub(Arc<Self>, Arc<Self>),
+    /// Negation of an expression
+    Neg(Arc<Self>),
+    /// Product of two expressions
+    Mul(Arc<Self>, Arc<Self>),
+}
+
+/// An extension field expression, as built by the AIR.
+#[derive(Clone, Debug)]
+pub enum SymbolicExtExpression<F, EF> {
+    /// A base field expression
+    Base(Arc<SymbolicExpression<F>>),
+    /// A value the constraints read
+    Entry(SymbolicExtEntry),
+    /// A constant
+    Constant(EF),
+    /// Sum of two expressions
+    Add(Arc<Self>, Arc<Self>),
+    /// Difference of two expressions
+    Sub(Arc<Self>, Arc<Self>),
+    /// Negation of an expression
+    Neg(Arc<Self>),
+    /// Product of two expressions
+    Mul(Arc<Self>, Arc<Self>),
+}
+
+impl<F: Field> SymbolicExpression<F> {
+    fn sum(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a + b),
+            (Self::Constant(a), x) | (x, Self::Constant(a)) if a.is_zero() => x,
+            (a, b) => Self::Add(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn difference(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a - b),
+            (x, Self::Constant(b)) if b.is_zero() => x,
+            (a, b) => Self::Sub(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn product(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a * b),
+            (Self::Constant(a), _) | (_, Self::Constant(a)) if a.is_zero() => {
+                Self::Constant(F::ZERO)
+            }
+            (Self::Constant(a), x) | (x, Self::Constant(a)) if a.is_one() => x,
+            (a, b) => Self::Mul(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn negation(self) -> Self {
+        match self {
+            Self::Constant(a) => Self::Constant(-a),
+            x => Self::Neg(Arc::new(x)),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> SymbolicExtExpression<F, EF> {
+    fn sum(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a + b),
+            (Self::Constant(a), x) | (x, Self::Constant(a)) if a.is_zero() => x,
+            (a, b) => Self::Add(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn difference(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a - b),
+            (x, Self::Constant(b)) if b.is_zero() => x,
+            (a, b) => Self::Sub(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn product(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a * b),
+            (Self::Constant(a), _) | (_, Self::Constant(a)) if a.is_zero() => {
+                Self::Constant(EF::ZERO)
+            }
+            (Self::Constant(a), x) | (x, Self::Constant(a)) if a.is_one() => x,
+            (a, b) => Self::Mul(Arc::new(a), Arc::new(b)),
+        }
+    }
+
+    fn negation(self) -> Self {
+        match self {
+            Self::Constant(a) => Self::Constant(-a),
+            x => Self::Neg(Arc::new(x)),
+        }
+    }
+}
+
+impl<F: Field> From<F> for SymbolicExpression<F> {
+    fn from(value: F) -> Self {
+        Self::Constant(value)
+    }
+}
+
+impl<F: Field> From<SymbolicVariable<F>> for SymbolicExpression<F> {
+    fn from(variable: SymbolicVariable<F>) -> Self {
+        Self::Entry(variable.entry)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> From<EF> for SymbolicExtExpression<F, EF> {
+    fn from(value: EF) -> Self {
+        Self::Constant(value)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> From<SymbolicExpression<F>> for SymbolicExtExpression<F, EF> {
+    fn from(expression: SymbolicExpression<F>) -> Self {
+        match expression {
+            SymbolicExpression::Constant(value) => Self::Constant(value.into()),
+            expression => Self::Base(Arc::new(expression)),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> From<SymbolicExtVariable<F, EF>>
+    for SymbolicExtExpression<F, EF>
+{
+    fn from(variable: SymbolicExtVariable<F, EF>) -> Self {
+        Self::Entry(variable.entry)
+    }
+}
+
+/// `Add`, `Sub` and `Mul` of `$lhs` by `$rhs`, converting both into `$out`.
+macro_rules! binary_ops {
+    ([$($generics:tt)*] $lhs:ty, $rhs:ty => $out:ty) => {
+        impl<$($generics)*> Add<$rhs> for $lhs {
+            type Output = $out;
+
+            fn add(self, rhs: $rhs) -> $out {
+                <$out>::from(self).sum(<$out>::from(rhs))
+            }
+        }
+
+        impl<$($generics)*> Sub<$rhs> for $lhs {
+            type Output = $out;
+
+            fn sub(self, rhs: $rhs) -> $out {
+                <$out>::from(self).difference(<$out>::from(rhs))
+            }
+        }
+
+        impl<$($generics)*> Mul<$rhs> for $lhs {
+            type Output = $out;
+
+            fn mul(self, rhs: $rhs) -> $out {
+                <$out>::from(self).product(<$out>::from(rhs))
+            }
+        }
+    };
+}
+
+/// `AddAssign`, `SubAssign` and `MulAssign` of `$lhs` by `$rhs`.
+macro_rules! assign_ops {
+    ([$($generics:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<$($generics)*> AddAssign<$rhs> for $lhs {
+            fn add_assign(&mut self, rhs: $rhs) {
+                *self = core::mem::take(self) + rhs;
+            }
+        }
+
+        impl<$($generics)*> SubAssign<$rhs> for $lhs {
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self = core::mem::take(self) - rhs;
+            }
+        }
+
+        impl<$($generics)*> MulAssign<$rhs> for $lhs {
+            fn mul_assign(&mut self, rhs: $rhs) {
+                *self = core::mem::take(self) * rhs;
+            }
+        }
+    };
+}
+
+binary_ops!([F: Field] SymbolicExpression<F>, SymbolicExpression<F> => SymbolicExpression<F>);
+binary_ops!([F: Field] SymbolicExpression<F>, F => SymbolicExpression<F>);
+binary_ops!([F: Field] SymbolicExpression<F>, SymbolicVariable<F> => SymbolicExpression<F>);
+binary_ops!([F: Field] SymbolicVariable<F>, SymbolicExpression<F> => SymbolicExpression<F>);
+binary_ops!([F: Field] SymbolicVariable<F>, SymbolicVariable<F> => SymbolicExpression<F>);
+binary_ops!([F: Field] SymbolicVariable<F>, F => SymbolicExpression<F>);
+assign_ops!([F: Field] SymbolicExpression<F>, SymbolicExpression<F>);
+assign_ops!([F: Field] SymbolicExpression<F>, F);
+assign_ops!([F: Field] SymbolicExpression<F>, SymbolicVariable<F>);
+
+binary_ops!(
+    [F: Field, EF: ExtensionField<F>]
+    SymbolicExtExpression<F, EF>, SymbolicExtExpression<F, EF> => SymbolicExtExpression<F, EF>
+);
+binary_ops!(
+    [F: Field, EF: ExtensionField<F>]
+    SymbolicExtExpression<F, EF>, EF => SymbolicExtExpression<F, EF>
+);
+binary_ops!(
+    [F: Field, EF: ExtensionField<F>]
+    SymbolicExtExpression<F, EF>, SymbolicExpression<F> => SymbolicExtExpression<F, EF>
+);
+assign_ops!([F: Field, EF: ExtensionField<F>] SymbolicExtExpression<F, EF>, SymbolicExtExpression<F, EF>);
+assign_ops!([F: Field, EF: ExtensionField<F>] SymbolicExtExpression<F, EF>, EF);
+assign_ops!([F: Field, EF: ExtensionField<F>] SymbolicExtExpression<F, EF>, SymbolicExpression<F>);
+
+impl<F: Field> Neg for SymbolicExpression<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.negation()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> Neg for SymbolicExtExpression<F, EF> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.negation()
+    }
+}
+
+impl<F: Field> Default for SymbolicExpression<F> {
+    fn default() -> Self {
+        Self::Constant(F::ZERO)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> Default for SymbolicExtExpression<F, EF> {
+    fn default() -> Self {
+        Self::Constant(EF::ZERO)
+    }
+}
+
+impl<F: Field> Sum for SymbolicExpression<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: Field> Product for SymbolicExpression<F> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> Sum for SymbolicExtExpression<F, EF> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> Product for SymbolicExtExpression<F, EF> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+impl<F: Field> PrimeCharacteristicRing for SymbolicExpression<F> {
+    type PrimeSubfield = F::PrimeSubfield;
+
+    const ZERO: Self = Self::Constant(F::ZERO);
+    const ONE: Self = Self::Constant(F::ONE);
+    const TWO: Self = Self::Constant(F::TWO);
+    const NEG_ONE: Self = Self::Constant(F::NEG_ONE);
+
+    fn from_prime_subfield(f: Self::PrimeSubfield) -> Self {
+        F::from_prime_subfield(f).into()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> PrimeCharacteristicRing for SymbolicExtExpression<F, EF> {
+    type PrimeSubfield = EF::PrimeSubfield;
+
+    const ZERO: Self = Self::Constant(EF::ZERO);
+    const ONE: Self = Self::Constant(EF::ONE);
+    const TWO: Self = Self::Constant(EF::TWO);
+    const NEG_ONE: Self = Self::Constant(EF::NEG_ONE);
+
+    fn from_prime_subfield(f: Self::PrimeSubfield) -> Self {
+        EF::from_prime_subfield(f).into()
+    }
+}
+
+impl<F: Field> Algebra<F> for SymbolicExpression<F> {}
+
+impl<F: Field> Algebra<SymbolicVariable<F>> for SymbolicExpression<F> {}
+
+impl<F: Field, EF: ExtensionField<F>> Algebra<EF> for SymbolicExtExpression<F, EF> {}
+
+impl<F: Field, EF: ExtensionField<F>> Algebra<SymbolicExpression<F>>
+    for SymbolicExtExpression<F, EF>
+{
+}
+
+/// A constraint recorded by a [`SymbolicBuilder`].
+#[derive(Clone, Debug)]
+pub enum SymbolicConstraint<F, EF> {
+    /// A base field constraint
+    Base(SymbolicExpression<F>),
+    /// An extension field constraint
+    Ext(SymbolicExtExpression<F, EF>),
+}
+
+/// An [`AirBuilder`] recording the constraints of an AIR as expressions.
+pub struct SymbolicBuilder<F, EF> {
+    main: RowMajorMatrix<SymbolicVariable<F>>,
+    preprocessed: RowMajorMatrix<SymbolicVariable<F>>,
+    aux: RowMajorMatrix<SymbolicExtVariable<F, EF>>,
+    aux_matrix_widths: Vec<usize>,
+    public_values: Vec<SymbolicVariable<F>>,
+    aux_challenges: Vec<SymbolicExtVariable<F, EF>>,
+    aux_exposed_values: Vec<SymbolicExtVariable<F, EF>>,
+    /// The constraints asserted so far, in assertion order
+    pub constraints: Vec<SymbolicConstraint<F, EF>>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> SymbolicBuilder<F, EF> {
+    /// A builder for `air`, whose preprocessed trace has `preprocessed_width` columns.
+    ///
+    /// Public values are available up to the number `setup` accepts.
+    pub fn new<A: MultiTraceAir<F, EF>>(air: &A, preprocessed_width: usize) -> Self {
+        let rows = |width: usize, entry: fn(usize, usize) -> SymbolicEntry| {
+            let values = (0..2)
+                .flat_map(|offset| (0..width).map(move |column| entry(offset, column)))
+                .map(SymbolicVariable::new)
+                .collect();
+            RowMajorMatrix::new(values, width)
+        };
+        let aux_width = air.total_aux_width();
+        let aux_values = (0..2)
+            .flat_map(|offset| {
+                (0..aux_width).map(move |column| {
+                    SymbolicExtVariable::new(SymbolicExtEntry::Aux { offset, column })
+                })
+            })
+            .collect();
+        let num_challenges = (0..air.num_aux_phases())
+            .map(|phase| air.aux_phase_num_challenges(phase))
+            .sum();
+        let num_exposed_values = (0..air.num_aux_phases())
+            .map(|phase| air.aux_phase_num_exposed_values(phase))
+            .sum();
+        Self {
+            main: rows(air.width(), |offset, column| SymbolicEntry::Main {
+                offset,
+                column,
+            }),
+            preprocessed: rows(preprocessed_width, |offset, column| {
+                SymbolicEntry::Preprocessed { offset, column }
+            }),
+            aux: RowMajorMatrix::new(aux_values, aux_width),
+            aux_matrix_widths: aux_matrix_widths(air),
+            public_values: (0..MAX_PUBLIC_VALUES)
+                .map(|index| SymbolicVariable::new(SymbolicEntry::Public(index)))
+                .collect(),
+            aux_challenges: (0..num_challenges)
+                .map(|index| SymbolicExtVariable::new(SymbolicExtEntry::Challenge(index)))
+                .collect(),
+            aux_exposed_values: (0..num_exposed_values)
+                .map(|index| SymbolicExtVariable::new(SymbolicExtEntry::Exposed(index)))
+                .collect(),
+            constraints: Vec::new(),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AirBuilder for SymbolicBuilder<F, EF> {
+    type F = F;
+    type Expr = SymbolicExpression<F>;
+    type Var = SymbolicVariable<F>;
+    type M = RowMajorMatrix<SymbolicVariable<F>>;
+
+    fn main(&self) -> Self::M {
+        self.main.clone()
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        SymbolicExpression::Entry(SymbolicEntry::IsFirstRow)
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        SymbolicExpression::Entry(SymbolicEntry::IsLastRow)
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        assert_eq!(size, 2, "Only window size 2 is supported");
+        SymbolicExpression::Entry(SymbolicEntry::IsTransition)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.constraints.push(SymbolicConstraint::Base(x.into()));
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> PairBuilder for SymbolicBuilder<F, EF> {
+    fn preprocessed(&self) -> Self::M {
+        self.preprocessed.clone()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AirBuilderWithPublicValues for SymbolicBuilder<F, EF> {
+    type PublicVar = SymbolicVariable<F>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        &self.public_values
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> ExtensionBuilder for SymbolicBuilder<F, EF> {
+    type EF = EF;
+    type ExprEF = SymbolicExtExpression<F, EF>;
+    type VarEF = SymbolicExtVariable<F, EF>;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.constraints.push(SymbolicConstraint::Ext(x.into()));
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxBuilder for SymbolicBuilder<F, EF> {
+    type MAux = RowMajorMatrix<SymbolicExtVariable<F, EF>>;
+
+    fn aux(&self) -> Self::MAux {
+        self.aux.clone()
+    }
+
+    fn aux_matrix_widths(&self) -> &[usize] {
+        &self.aux_matrix_widths
+    }
+
+    fn aux_challenges(&self) -> &[Self::VarEF] {
+        &self.aux_challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        &self.aux_exposed_values
+    }
+}
+
+/// A base field node of a [`ConstraintDag`]; operands are earlier base field nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum BaseNode {
+    Entry(SymbolicEntry),
+    /// Index into the base field constants
+    Constant(usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+}
+
+/// An extension field node of a [`ConstraintDag`]; operands are earlier extension field
+/// nodes, except for `Base`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ExtNode {
+    Base(usize),
+    Entry(SymbolicExtEntry),
+    /// Index into the extension field constants
+    Constant(usize),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Neg(usize),
+    Mul(usize, usize),
+}
+
+impl BaseNode {
+    /// This node with each operand `i` replaced by `node(i)` and each constant index `c`
+    /// by `constant(c)`.
+    fn map(
+        self,
+        mut node: impl FnMut(usize) -> usize,
+        constant: impl FnOnce(usize) -> usize,
+    ) -> Self {
+        match self {
+            Self::Entry(_) => self,
+            Self::Constant(c) => Self::Constant(constant(c)),
+            Self::Add(a, b) => Self::Add(node(a), node(b)),
+            Self::Sub(a, b) => Self::Sub(node(a), node(b)),
+            Self::Neg(a) => Self::Neg(node(a)),
+            Self::Mul(a, b) => Self::Mul(node(a), node(b)),
+        }
+    }
+}
+
+impl ExtNode {
+    /// This node with each base field operand `i` replaced by `base(i)`, each extension
+    /// field operand `i` by `node(i)` and each constant index `c` by `constant(c)`.
+    fn map(
+        self,
+        base: impl FnOnce(usize) -> usize,
+        mut node: impl FnMut(usize) -> usize,
+        constant: impl FnOnce(usize) -> usize,
+    ) -> Self {
+        match self {
+            Self::Base(a) => Self::Base(base(a)),
+            Self::Entry(_) => self,
+            Self::Constant(c) => Self::Constant(constant(c)),
+            Self::Add(a, b) => Self::Add(node(a), node(b)),
+            Self::Sub(a, b) => Self::Sub(node(a), node(b)),
+            Self::Neg(a) => Self::Neg(node(a)),
+            Self::Mul(a, b) => Self::Mul(node(a), node(b)),
+        }
+    }
+}
+
+/// The root node of a constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DagConstraint {
+    Base(usize),
+    Ext(usize),
+}
+
+/// The constraints of an AIR compiled into a DAG with shared subexpressions.
+///
+/// Nodes are stored in evaluation order, each after its operands, and two nodes are never
+/// equal: a subexpression the AIR builds several times, or that several constraints
+/// share, is evaluated once per point. Operations on constants are folded as they are
+/// compiled, like the operators of [`SymbolicExpression`] fold them, so no node has only
+/// constant operands.
+#[derive(Clone, Debug)]
+pub struct ConstraintDag<F, EF> {
+    base_nodes: Vec<BaseNode>,
+    ext_nodes: Vec<ExtNode>,
+    base_constants: Vec<F>,
+    ext_constants: Vec<EF>,
+    constraints: Vec<DagConstraint>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> ConstraintDag<F, EF> {
+    /// Compile the constraints of `air`, whose preprocessed trace has `preprocessed_width`
+    /// columns.
+    pub fn new<A>(air: &A, preprocessed_width: usize) -> Self
+    where
+        A: MultiTraceAir<F, EF> + Air<SymbolicBuilder<F, EF>>,
+    {
+        let mut builder = SymbolicBuilder::new(air, preprocessed_width);
+        air.eval(&mut builder);
+        Self::from_constraints(&builder.constraints)
+    }
+
+    /// Compile `constraints`, folded in this order.
+    pub fn from_constraints(constraints: &[SymbolicConstraint<F, EF>]) -> Self {
+        let mut compiler = DagCompiler {
+            dag: Self {
+                base_nodes: Vec::new(),
+                ext_nodes: Vec::new(),
+                base_constants: Vec::new(),
+                ext_constants: Vec::new(),
+                constraints: Vec::with_capacity(constraints.len()),
+            },
+            base_index: BTreeMap::new(),
+            ext_index: BTreeMap::new(),
+            base_seen: BTreeMap::new(),
+            ext_seen: BTreeMap::new(),
+        };
+        for constraint in constraints {
+            let root = match constraint {
+                SymbolicConstraint::Base(expression) => {
+                    DagConstraint::Base(compiler.base(expression))
+                }
+                SymbolicConstraint::Ext(expression) => DagConstraint::Ext(compiler.ext(expression)),
+            };
+            compiler.dag.constraints.push(root);
+        }
+        compiler.dag.remove_unreachable();
+        compiler.dag
+    }
+
+    /// Drop the nodes and constants no constraint reaches, such as the operands of folded
+    /// nodes, keeping the others in order.
+    fn remove_unreachable(&mut self) {
+        let mut base_live = vec![false; self.base_nodes.len()];
+        let mut ext_live = vec![false; self.ext_nodes.len()];
+        let mut base_constant_live = vec![false; self.base_constants.len()];
+        let mut ext_constant_live = vec![false; self.ext_constants.len()];
+        for constraint in &self.constraints {
+            match *constraint {
+                DagConstraint::Base(root) => base_live[root] = true,
+                DagConstraint::Ext(root) => ext_live[root] = true,
+            }
+        }
+        // Operands come before the nodes using them, so a backward pass marks every node a
+        // live one reads
+        for (index, node) in self.ext_nodes.iter().enumerate().rev() {
+            if ext_live[index] {
+                node.map(
+                    |a| mark(&mut base_live, a),
+                    |a| mark(&mut ext_live, a),
+                    |c| mark(&mut ext_constant_live, c),
+                );
+            }
+        }
+        for (index, node) in self.base_nodes.iter().enumerate().rev() {
+            if base_live[index] {
+                node.map(
+                    |a| mark(&mut base_live, a),
+                    |c| mark(&mut base_constant_live, c),
+                );
+            }
+        }
+
+        let base_index = live_indices(&base_live);
+        let ext_index = live_indices(&ext_live);
+        let base_constant_index = live_indices(&base_constant_live);
+        let ext_constant_index = live_indices(&ext_constant_live);
+        self.base_nodes = retain_live(&self.base_nodes, &base_live)
+            .into_iter()
+            .map(|node| node.map(|a| base_index[a], |c| base_constant_index[c]))
+            .collect();
+        self.ext_nodes = retain_live(&self.ext_nodes, &ext_live)
+            .into_iter()
+            .map(|node| {
+                node.map(
+                    |a| base_index[a],
+                    |a| ext_index[a],
+                    |c| ext_constant_index[c],
+                )
+            })
+            .collect();
+        self.base_constants = retain_live(&self.base_constants, &base_constant_live);
+        self.ext_constants = retain_live(&self.ext_constants, &ext_constant_live);
+        for constraint in &mut self.constraints {
+            *constraint = match *constraint {
+                DagConstraint::Base(root) => DagConstraint::Base(base_index[root]),
+                DagConstraint::Ext(root) => DagConstraint::Ext(ext_index[root]),
+            };
+        }
+    }
+
+    /// Number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Number of distinct subexpressions, base and extension field nodes together.
+    pub fn num_nodes(&self) -> usize {
+        self.base_nodes.len() + self.ext_nodes.len()
+    }
+
+    /// Degree of each constraint in the trace values and selectors, in assertion order.
+    ///
+    /// Trace columns and selectors have degree one, and public values, challenges, exposed
+    /// values and constants degree zero; a sum has the degree of its highest operand and a
+    /// product the sum of its operands'. Terms cancelling each other are not noticed, so
+    /// this bounds the actual degree.
+    pub fn constraint_degrees(&self) -> Vec<usize> {
+        self.measure(
+            |entry| usize::from(!matches!(entry, SymbolicEntry::Public(_))),
+            |entry| usize::from(matches!(entry, SymbolicExtEntry::Aux { .. })),
+            |a, b| a + b,
+        )
+    }
+
+    /// Phase of each constraint of `air`, in assertion order, see
+    /// [`crate::VerifyingKey::constraint_phases`]: one past the latest aux phase whose
+    /// columns, challenges or exposed values it reads, or 0 if it reads none.
+    pub fn constraint_phases<A: MultiTraceAir<F, EF>>(&self, air: &A) -> Vec<usize> {
+        let phases = 0..air.num_aux_phases();
+        let widths: Vec<usize> = phases.clone().map(|p| air.aux_phase_width(p)).collect();
+        let challenges: Vec<usize> = phases
+            .clone()
+            .map(|p| air.aux_phase_num_challenges(p))
+            .collect();
+        let exposed: Vec<usize> = phases
+            .map(|p| air.aux_phase_num_exposed_values(p))
+            .collect();
+        self.measure(
+            |_| 0,
+            |entry| match entry {
+                SymbolicExtEntry::Aux { column, .. } => phase_of(&widths, column) + 1,
+                SymbolicExtEntry::Challenge(index) => phase_of(&challenges, index) + 1,
+                SymbolicExtEntry::Exposed(index) => phase_of(&exposed, index) + 1,
+            },
+            usize::max,
+        )
+    }
+
+    /// A measure of each constraint, computed over the nodes in order: `entry` and
+    /// `ext_entry` give it for the values read and constants measure 0; sums, differences
+    /// and negations take the largest measure of their operands and products combine them
+    /// with `product`.
+    fn measure(
+        &self,
+        entry: impl Fn(SymbolicEntry) -> usize,
+        ext_entry: impl Fn(SymbolicExtEntry) -> usize,
+        product: impl Fn(usize, usize) -> usize,
+    ) -> Vec<usize> {
+        let mut base = Vec::with_capacity(self.base_nodes.len());
+        for node in &self.base_nodes {
+            let measure = match *node {
+                BaseNode::Entry(e) => entry(e),
+                BaseNode::Constant(_) => 0,
+                BaseNode::Add(a, b) | BaseNode::Sub(a, b) => base[a].max(base[b]),
+                BaseNode::Neg(a) => base[a],
+                BaseNode::Mul(a, b) => product(base[a], base[b]),
+            };
+            base.push(measure);
+        }
+
+        let mut ext = Vec::with_capacity(self.ext_nodes.len());
+        for node in &self.ext_nodes {
+            let measure = match *node {
+                ExtNode::Base(a) => base[a],
+                ExtNode::Entry(e) => ext_entry(e),
+                ExtNode::Constant(_) => 0,
+                ExtNode::Add(a, b) | ExtNode::Sub(a, b) => ext[a].max(ext[b]),
+                ExtNode::Neg(a) => ext[a],
+                ExtNode::Mul(a, b) => product(ext[a], ext[b]),
+            };
+            ext.push(measure);
+        }
+
+        self.constraints
+            .iter()
+            .map(|constraint| match *constraint {
+                DagConstraint::Base(root) => base[root],
+                DagConstraint::Ext(root) => ext[root],
+            })
+            .collect()
+    }
+}
+
+/// Set `live[index]`, returning `index`.
+fn mark(live: &mut [bool], index: usize) -> usize {
+    live[index] = true;
+    index
+}
+
+/// Index of each item among the live ones (meaningless for the others).
+fn live_indices(live: &[bool]) -> Vec<usize> {
+    let mut next = 0;
+    live.iter()
+        .map(|&is_live| {
+            let index = next;
+            next += usize::from(is_live);
+            index
+        })
+        .collect()
+}
+
+/// The live items of `items`, in order.
+fn retain_live<T: Copy>(items: &[T], live: &[bool]) -> Vec<T> {
+    items
+        .iter()
+        .zip(live)
+        .filter(|&(_, &is_live)| is_live)
+        .map(|(&item, _)| item)
+        .collect()
+}
+
+/// The phase holding item `index`, when phase `p` holds the next `sizes[p]` items.
+fn phase_of(sizes: &[usize], index: usize) -> usize {
+    let mut end = 0;
+    sizes
+        .iter()
+        .position(|&size| {
+            end += size;
+            index < end
+        })
+        .expect("the AIR reads an aux value past its last phase")
+}
+
+/// Interns the nodes of expression trees into a [`ConstraintDag`].
+struct DagCompiler<F, EF> {
+    dag: ConstraintDag<F, EF>,
+    base_index: BTreeMap<BaseNode, usize>,
+    ext_index: BTreeMap<ExtNode, usize>,
+    /// Node of each shared subtree already compiled, by address, so that a subtree the AIR
+    /// cloned into several places is walked once
+    base_seen: BTreeMap<*const SymbolicExpression<F>, usize>,
+    ext_seen: BTreeMap<*const SymbolicExtExpression<F, EF>, usize>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> DagCompiler<F, EF> {
+    fn base(&mut self, expression: &SymbolicExpression<F>) -> usize {
+        let node = match expression {
+            SymbolicExpression::Entry(entry) => BaseNode::Entry(*entry),
+            SymbolicExpression::Constant(value) => {
+                BaseNode::Constant(intern_constant(&mut self.dag.base_constants, *value))
+            }
+            SymbolicExpression::Add(a, b) => {
+                let (a, b) = (self.base_shared(a), self.base_shared(b));
+                BaseNode::Add(a.min(b), a.max(b))
+            }
+            SymbolicExpression::Sub(a, b) => {
+                BaseNode::Sub(self.base_shared(a), self.base_shared(b))
+            }
+            SymbolicExpression::Neg(a) => BaseNode::Neg(self.base_shared(a)),
+            SymbolicExpression::Mul(a, b) => {
+                let (a, b) = (self.base_shared(a), self.base_shared(b));
+                BaseNode::Mul(a.min(b), a.max(b))
+            }
+        };
+        let node = self.fold_base(node);
+        let nodes = &mut self.dag.base_nodes;
+        *self.base_index.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    /// `node` with its operations on constants carried out, as the operators of
+    /// [`SymbolicExpression`] do: a constant if its operands are, zero for a product by zero,
+    /// or the node of the other operand when one is the identity.
+    fn fold_base(&mut self, node: BaseNode) -> BaseNode {
+        let nodes = &self.dag.base_nodes;
+        let constant = |index: usize| match nodes[index] {
+            BaseNode::Constant(c) => Some(self.dag.base_constants[c]),
+            _ => None,
+        };
+        let value = match node {
+            BaseNode::Add(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x + y,
+                (Some(x), _) if x.is_zero() => return nodes[b],
+                (_, Some(y)) if y.is_zero() => return nodes[a],
+                _ => return node,
+            },
+            BaseNode::Sub(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x - y,
+                (_, Some(y)) if y.is_zero() => return nodes[a],
+                _ => return node,
+            },
+            BaseNode::Neg(a) => match constant(a) {
+                Some(x) => -x,
+                None => return node,
+            },
+            BaseNode::Mul(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x * y,
+                (Some(x), _) | (_, Some(x)) if x.is_zero() => F::ZERO,
+                (Some(x), _) if x.is_one() => return nodes[b],
+                (_, Some(y)) if y.is_one() => return nodes[a],
+                _ => return node,
+            },
+            BaseNode::Entry(_) | BaseNode::Constant(_) => return node,
+        };
+        BaseNode::Constant(intern_constant(&mut self.dag.base_constants, value))
+    }
+
+    fn base_shared(&mut self, expression: &Arc<SymbolicExpression<F>>) -> usize {
+        let address = Arc::as_ptr(expression);
+        if let Some(&index) = self.base_seen.get(&address) {
+            return index;
+        }
+        let index = self.base(expression);
+        self.base_seen.insert(address, index);
+        index
+    }
+
+    fn ext(&mut self, expression: &SymbolicExtExpression<F, EF>) -> usize {
+        let node = match expression {
+            SymbolicExtExpression::Base(a) => ExtNode::Base(self.base_shared(a)),
+            SymbolicExtExpression::Entry(entry) => ExtNode::Entry(*entry),
+            SymbolicExtExpression::Constant(value) => {
+                ExtNode::Constant(intern_constant(&mut self.dag.ext_constants, *value))
+            }
+            SymbolicExtExpression::Add(a, b) => {
+                let (a, b) = (self.ext_shared(a), self.ext_shared(b));
+                ExtNode::Add(a.min(b), a.max(b))
+            }
+            SymbolicExtExpression::Sub(a, b) => {
+                ExtNode::Sub(self.ext_shared(a), self.ext_shared(b))
+            }
+            SymbolicExtExpression::Neg(a) => ExtNode::Neg(self.ext_shared(a)),
+            SymbolicExtExpression::Mul(a, b) => {
+                let (a, b) = (self.ext_shared(a), self.ext_shared(b));
+                ExtNode::Mul(a.min(b), a.max(b))
+            }
+        };
+        let node = self.fold_ext(node);
+        let nodes = &mut self.dag.ext_nodes;
+        *self.ext_index.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    /// `node` with its operations on constants carried out, like [`Self::fold_base`]; a
+    /// base field constant lifted to the extension field becomes an extension field
+    /// constant.
+    fn fold_ext(&mut self, node: ExtNode) -> ExtNode {
+        let nodes = &self.dag.ext_nodes;
+        let constant = |index: usize| match nodes[index] {
+            ExtNode::Constant(c) => Some(self.dag.ext_constants[c]),
+            _ => None,
+        };
+        let value = match node {
+            ExtNode::Base(a) => match self.dag.base_nodes[a] {
+                BaseNode::Constant(c) => EF::from(self.dag.base_constants[c]),
+                _ => return node,
+            },
+            ExtNode::Add(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x + y,
+                (Some(x), _) if x.is_zero() => return nodes[b],
+                (_, Some(y)) if y.is_zero() => return nodes[a],
+                _ => return node,
+            },
+            ExtNode::Sub(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x - y,
+                (_, Some(y)) if y.is_zero() => return nodes[a],
+                _ => return node,
+            },
+            ExtNode::Neg(a) => match constant(a) {
+                Some(x) => -x,
+                None => return node,
+            },
+            ExtNode::Mul(a, b) => match (constant(a), constant(b)) {
+                (Some(x), Some(y)) => x * y,
+                (Some(x), _) | (_, Some(x)) if x.is_zero() => EF::ZERO,
+                (Some(x), _) if x.is_one() => return nodes[b],
+                (_, Some(y)) if y.is_one() => return nodes[a],
+                _ => return node,
+            },
+            ExtNode::Entry(_) | ExtNode::Constant(_) => return node,
+        };
+        ExtNode::Constant(intern_constant(&mut self.dag.ext_constants, value))
+    }
+
+    fn ext_shared(&mut self, expression: &Arc<SymbolicExtExpression<F, EF>>) -> usize {
+        let address = Arc::as_ptr(expression);
+        if let Some(&index) = self.ext_seen.get(&address) {
+            return index;
+        }
+        let index = self.ext(expression);
+        self.ext_seen.insert(address, index);
+        index
+    }
+}
+
+/// Index of `value` in `constants`, appending it if new.
+fn intern_constant<T: PartialEq + Copy>(constants: &mut Vec<T>, value: T) -> usize {
+    constants
+        .iter()
+        .position(|&constant| constant == value)
+        .unwrap_or_else(|| {
+            constants.push(value);
+            constants.len() - 1
+        })
+}
+
+/// A [`QuotientBackend`] evaluating the constraints from a [`ConstraintDag`] rather than
+/// running the AIR at every point.
+#[cfg(feature = "prover")]
+pub struct DagBackend<SC: StarkGenericConfig> {
+    dag: Arc<ConstraintDag<Val<SC>, Challenge<SC>>>,
+}
+
+#[cfg(feature = "prover")]
+impl<SC: StarkGenericConfig> DagBackend<SC> {
+    /// Compile the constraints of `air`, set up as `vk`.
+    ///
+    /// # Panics
+    /// - If `air` does not assert as many constraints as `vk` records
+    pub fn new<A>(air: &A, vk: &VerifyingKey<SC>) -> Self
+    where
+        A: MultiTraceAir<Val<SC>, Challenge<SC>> + Air<SymbolicBuilder<Val<SC>, Challenge<SC>>>,
+    {
+        let dag = ConstraintDag::new(air, vk.preprocessed_width());
+        assert_eq!(
+            dag.num_constraints(),
+            vk.num_constraints,
+            "the AIR was not set up as this verifying key"
+        );
+        Self { dag: Arc::new(dag) }
+    }
+
+    /// The constraints [`crate::setup`] compiled into `pk`, shared rather than compiled
+    /// again. The provers evaluate the quotient with it unless given another backend.
+    pub fn from_proving_key(pk: &ProvingKey<SC>) -> Self {
+        Self {
+            dag: pk.dag.clone(),
+        }
+    }
+
+    /// The compiled constraints.
+    pub fn dag(&self) -> &ConstraintDag<Val<SC>, Challenge<SC>> {
+        &self.dag
+    }
+
+    /// The constraints folded with `folder.alpha_powers` at the points of `folder`: the
+    /// accumulator `folder` reaches running the AIR there.
+    ///
+    /// `base` and `ext` hold the value of every node; they are only buffers, reused from
+    /// point to point.
+    fn fold(
+        &self,
+        folder: &ProverFolder<'_, SC>,
+        base: &mut Vec<PackedVal<SC>>,
+        ext: &mut Vec<PackedChallenge<SC>>,
+    ) -> PackedChallenge<SC>
+    where
+        Val<SC>: PackedField,
+    {
+        let dag = &self.dag;
+        let row_value = |values: &[PackedVal<SC>], width: usize, offset: usize, column: usize| {
+            values[offset * width + column]
+        };
+
+        base.clear();
+        for node in &dag.base_nodes {
+            let value = match *node {
+                BaseNode::Entry(SymbolicEntry::Main { offset, column }) => {
+                    row_value(folder.main.values, folder.main.width, offset, column)
+                }
+                BaseNode::Entry(SymbolicEntry::Preprocessed { offset, column }) => row_value(
+                    folder.preprocessed.values,
+                    folder.preprocessed.width,
+                    offset,
+                    column,
+                ),
+                BaseNode::Entry(SymbolicEntry::Public(index)) => folder.public_values[index].into(),
+                BaseNode::Entry(SymbolicEntry::IsFirstRow) => folder.is_first_row,
+                BaseNode::Entry(SymbolicEntry::IsLastRow) => folder.is_last_row,
+                BaseNode::Entry(SymbolicEntry::IsTransition) => folder.is_transition,
+                BaseNode::Constant(index) => dag.base_constants[index].into(),
+                BaseNode::Add(a, b) => base[a] + base[b],
+                BaseNode::Sub(a, b) => base[a] - base[b],
+                BaseNode::Neg(a) => -base[a],
+                BaseNode::Mul(a, b) => base[a] * base[b],
+            };
+            base.push(value);
+        }
+
+        ext.clear();
+        for node in &dag.ext_nodes {
+            let value = match *node {
+                ExtNode::Base(a) => base[a].into(),
+                ExtNode::Entry(SymbolicExtEntry::Aux { offset, column }) => {
+                    folder.aux.values[offset * folder.aux.width + column]
+                }
+                ExtNode::Entry(SymbolicExtEntry::Challenge(index)) => folder.aux_challenges[index],
+                ExtNode::Entry(SymbolicExtEntry::Exposed(index)) => {
+                    folder.aux_exposed_values[index]
+                }
+                ExtNode::Constant(index) => dag.ext_constants[index].into(),
+                ExtNode::Add(a, b) => ext[a] + ext[b],
+                ExtNode::Sub(a, b) => ext[a] - ext[b],
+                ExtNode::Neg(a) => -ext[a],
+                ExtNode::Mul(a, b) => ext[a] * ext[b],
+            };
+            ext.push(value);
+        }
+
+        // Folded exactly like `ProverFolder` folds the constraints as the AIR asserts them
+        let mut accumulator = PackedChallenge::<SC>::ZERO;
+        for (constraint, &coefficient) in dag.constraints.iter().zip(folder.alpha_powers) {
+            let alpha = PackedChallenge::<SC>::from(coefficient);
+            match *constraint {
+                DagConstraint::Base(root) => accumulator += alpha * base[root],
+                DagConstraint::Ext(root) => accumulator += alpha * ext[root],
+            }
+        }
+        accumulator
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<SC: StarkGenericConfig> QuotientBackend<SC> for DagBackend<SC> {
+    #[tracing::instrument(skip_all)]
+    fn quotient_values<A, M>(
+        &self,
+        air: &A,
+        inputs: QuotientInputs<'_, SC, M>,
+        telemetry: &mut dyn ProverTelemetry,
+    ) -> Vec<Challenge<SC>>
+    where
+        Val<SC>: PackedField,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<ProverFolder<'a, SC>>,
+        M: Matrix<Val<SC>> + Sync,
+    {
+        assert_eq!(
+            inputs.alpha_powers.len(),
+            self.dag.num_constraints(),
+            "the constraint DAG was compiled from another AIR"
+        );
+        let scratch = || {
+            (
+                vec![PackedVal::<SC>::ZERO; self.dag.base_nodes.len()],
+                vec![PackedChallenge::<SC>::ZERO; self.dag.ext_nodes.len()],
+            )
+        };
+        evaluate_quotient(air, inputs, telemetry, scratch, |folder, (base, ext)| {
+            folder.accumulator = self.fold(folder, base, ext);
+            folder.constraint_index = self.dag.num_constraints();
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<SC: StarkGenericConfig> Debug for DagBackend<SC> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DagBackend")
+            .field("num_constraints", &self.dag.num_constraints())
+            .field("num_nodes", &self.dag.num_nodes())
+            .finish()
+    }
+}
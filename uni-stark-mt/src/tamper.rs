@@ -0,0 +1,276 @@
+//! Proof tamper testing
+//!
+//! [`ProofMutator`] perturbs each component of a valid proof in turn: it flips bytes of the
+//! commitments and of the PCS opening proof, bumps every opened and exposed value, swaps
+//! the main openings at `ζ` and `ζ·g`, drops quotient chunks and aux commitments, and
+//! changes the version and degree. A sound verifier rejects every such mutant:
+//!
+//! ```ignore
+//! let accepted = ProofMutator::new(&proof)
+//!     .accepted_mutations(|mutant| verify(&config, &air, &vk, mutant, &public_values));
+//! assert!(accepted.is_empty(), "{accepted:?}");
+//! ```
+//!
+//! Byte flips go through the Borsh encoding of [`crate::borsh`], so they reach the bytes of
+//! commitments and opening proofs whose Plonky3 types are opaque here. A flip whose result
+//! no longer decodes, e.g. into a non-canonical field element, yields no mutant.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_field::PrimeCharacteristicRing;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::borsh::{from_reader, to_writer};
+use crate::{Proof, StarkGenericConfig, VerificationError};
+
+/// Opening proof bytes flipped by [`ProofMutator::mutations`] are spread evenly over its
+/// encoding, at most this many.
+pub const MAX_OPENING_PROOF_FLIPS: usize = 64;
+
+/// A commitment of a [`Proof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitmentSlot {
+    /// [`Proof::main_commit`]
+    Main,
+    /// The commitment of an aux phase in [`Proof::aux_commits`]
+    Aux(usize),
+    /// A quotient column group in [`Proof::quotient_commits`]
+    Quotient(usize),
+}
+
+/// A list of extension field values of a [`Proof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueSlot {
+    /// The values exposed by an aux phase
+    AuxExposed(usize),
+    /// [`Proof::main_local`]
+    MainLocal,
+    /// [`Proof::main_next`]
+    MainNext,
+    /// [`Proof::preprocessed_local`]
+    PreprocessedLocal,
+    /// [`Proof::preprocessed_next`]
+    PreprocessedNext,
+    /// The opened values of an aux phase at `ζ`
+    AuxLocal(usize),
+    /// The opened values of an aux phase at `ζ·g`
+    AuxNext(usize),
+    /// The opened values of a quotient chunk
+    QuotientChunk(usize),
+    /// [`Proof::public_values_digest`], as a list of zero or one value
+    PublicValuesDigest,
+}
+
+/// One perturbation of a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Increment [`Proof::version`]
+    Version,
+    /// Increment [`Proof::log_degree`]
+    LogDegree,
+    /// Flip the lowest bit of one byte of a commitment's encoding
+    CommitmentByte {
+        /// The commitment
+        commitment: CommitmentSlot,
+        /// Index of the byte in its encoding
+        byte: usize,
+    },
+    /// Flip the lowest bit of one byte of the opening proof's encoding
+    OpeningProofByte(usize),
+    /// Add one to a value
+    Value {
+        /// The list holding the value
+        slot: ValueSlot,
+        /// Index of the value in the list
+        index: usize,
+    },
+    /// Swap [`Proof::main_local`] and [`Proof::main_next`]
+    SwapMainOpenings,
+    /// Remove a quotient chunk from [`Proof::quotient_chunks`]
+    DropQuotientChunk(usize),
+    /// Remove a commitment from [`Proof::aux_commits`]
+    DropAuxCommit(usize),
+}
+
+/// Derives tampered copies of a valid proof.
+pub struct ProofMutator<'a, SC: StarkGenericConfig> {
+    proof: &'a Proof<SC>,
+}
+
+impl<'a, SC: StarkGenericConfig> ProofMutator<'a, SC> {
+    /// Mutate copies of `proof`, which should verify.
+    pub const fn new(proof: &'a Proof<SC>) -> Self {
+        Self { proof }
+    }
+
+    /// Every mutation that applies to the proof, component by component.
+    pub fn mutations(&self) -> Vec<Mutation> {
+        let proof = self.proof;
+        let mut mutations = vec![Mutation::Version, Mutation::LogDegree];
+
+        let commitments = core::iter::once((CommitmentSlot::Main, &proof.main_commit))
+            .chain((0..).map(CommitmentSlot::Aux).zip(&proof.aux_commits))
+            .chain(
+                (0..)
+                    .map(CommitmentSlot::Quotient)
+                    .zip(&proof.quotient_commits),
+            );
+        for (commitment, value) in commitments {
+            mutations.extend(
+                (0..encoded_len(value)).map(|byte| Mutation::CommitmentByte { commitment, byte }),
+            );
+        }
+
+        let len = encoded_len(&proof.opening_proof);
+        let step = len.div_ceil(MAX_OPENING_PROOF_FLIPS).max(1);
+        mutations.extend((0..len).step_by(step).map(Mutation::OpeningProofByte));
+
+        for (slot, len) in self.value_slots() {
+            mutations.extend((0..len).map(|index| Mutation::Value { slot, index }));
+        }
+        if proof.main_local != proof.main_next {
+            mutations.push(Mutation::SwapMainOpenings);
+        }
+        mutations.extend((0..proof.quotient_chunks.len()).map(Mutation::DropQuotientChunk));
+        mutations.extend((0..proof.aux_commits.len()).map(Mutation::DropAuxCommit));
+        mutations
+    }
+
+    /// A copy of the proof with `mutation` applied, `None` if it does not apply to this
+    /// proof or a flipped encoding no longer decodes.
+    pub fn apply(&self, mutation: Mutation) -> Option<Proof<SC>> {
+        let mut proof = self.proof.clone();
+        match mutation {
+            Mutation::Version => proof.version = proof.version.wrapping_add(1),
+            Mutation::LogDegree => proof.log_degree = proof.log_degree.wrapping_add(1),
+            Mutation::CommitmentByte { commitment, byte } => {
+                let commit = match commitment {
+                    CommitmentSlot::Main => &mut proof.main_commit,
+                    CommitmentSlot::Aux(phase) => proof.aux_commits.get_mut(phase)?,
+                    CommitmentSlot::Quotient(group) => proof.quotient_commits.get_mut(group)?,
+                };
+                *commit = flip_byte(commit, byte)?;
+            }
+            Mutation::OpeningProofByte(byte) => {
+                proof.opening_proof = flip_byte(&proof.opening_proof, byte)?;
+            }
+            Mutation::Value { slot, index } => {
+                *slot_values(&mut proof, slot)?.get_mut(index)? += SC::Challenge::ONE;
+            }
+            Mutation::SwapMainOpenings => {
+                core::mem::swap(&mut proof.main_local, &mut proof.main_next);
+            }
+            Mutation::DropQuotientChunk(chunk) => {
+                proof.quotient_chunks.get(chunk)?;
+                proof.quotient_chunks.remove(chunk);
+            }
+            Mutation::DropAuxCommit(phase) => {
+                proof.aux_commits.get(phase)?;
+                proof.aux_commits.remove(phase);
+            }
+        }
+        Some(proof)
+    }
+
+    /// Every mutant of [`ProofMutator::mutations`] with the mutation that made it.
+    pub fn mutants(&self) -> impl Iterator<Item = (Mutation, Proof<SC>)> + '_ {
+        self.mutations()
+            .into_iter()
+            .filter_map(|mutation| Some((mutation, self.apply(mutation)?)))
+    }
+
+    /// The mutations whose mutant `verify` accepts, in the order of
+    /// [`ProofMutator::mutations`]; empty for a sound verifier.
+    pub fn accepted_mutations(
+        &self,
+        mut verify: impl FnMut(&Proof<SC>) -> Result<(), VerificationError>,
+    ) -> Vec<Mutation> {
+        self.mutants()
+            .filter(|(_, mutant)| verify(mutant).is_ok())
+            .map(|(mutation, _)| mutation)
+            .collect()
+    }
+
+    /// Each list of values of the proof with its length.
+    fn value_slots(&self) -> Vec<(ValueSlot, usize)> {
+        let proof = self.proof;
+        let mut slots = vec![
+            (ValueSlot::MainLocal, proof.main_local.len()),
+            (ValueSlot::MainNext, proof.main_next.len()),
+            (ValueSlot::PreprocessedLocal, proof.preprocessed_local.len()),
+            (ValueSlot::PreprocessedNext, proof.preprocessed_next.len()),
+            (
+                ValueSlot::PublicValuesDigest,
+                usize::from(proof.public_values_digest.is_some()),
+            ),
+        ];
+        slots.extend(
+            proof
+                .aux_exposed_values
+                .iter()
+                .enumerate()
+                .map(|(phase, values)| (ValueSlot::AuxExposed(phase), values.len())),
+        );
+        let aux_local = proof.aux_local.iter().flatten();
+        slots.extend(
+            aux_local
+                .enumerate()
+                .map(|(phase, values)| (ValueSlot::AuxLocal(phase), values.len())),
+        );
+        let aux_next = proof.aux_next.iter().flatten();
+        slots.extend(
+            aux_next
+                .enumerate()
+                .map(|(phase, values)| (ValueSlot::AuxNext(phase), values.len())),
+        );
+        slots.extend(
+            proof
+                .quotient_chunks
+                .iter()
+                .enumerate()
+                .map(|(chunk, values)| (ValueSlot::QuotientChunk(chunk), values.len())),
+        );
+        slots
+    }
+}
+
+/// The values of `proof` in `slot`, `None` if it has no such list.
+fn slot_values<SC: StarkGenericConfig>(
+    proof: &mut Proof<SC>,
+    slot: ValueSlot,
+) -> Option<&mut [SC::Challenge]> {
+    let values = match slot {
+        ValueSlot::AuxExposed(phase) => proof.aux_exposed_values.get_mut(phase)?,
+        ValueSlot::MainLocal => &mut proof.main_local,
+        ValueSlot::MainNext => &mut proof.main_next,
+        ValueSlot::PreprocessedLocal => &mut proof.preprocessed_local,
+        ValueSlot::PreprocessedNext => &mut proof.preprocessed_next,
+        ValueSlot::AuxLocal(phase) => proof.aux_local.as_mut()?.get_mut(phase)?,
+        ValueSlot::AuxNext(phase) => proof.aux_next.as_mut()?.get_mut(phase)?,
+        ValueSlot::QuotientChunk(chunk) => proof.quotient_chunks.get_mut(chunk)?,
+        ValueSlot::PublicValuesDigest => {
+            return proof
+                .public_values_digest
+                .as_mut()
+                .map(core::slice::from_mut);
+        }
+    };
+    Some(values)
+}
+
+fn encoded_len<T: Serialize>(value: &T) -> usize {
+    let mut bytes = Vec::new();
+    to_writer(value, &mut bytes).map_or(0, |()| bytes.len())
+}
+
+/// `value` with the lowest bit of byte `byte` of its encoding flipped.
+fn flip_byte<T: Serialize + DeserializeOwned>(value: &T, byte: usize) -> Option<T> {
+    let mut bytes = Vec::new();
+    to_writer(value, &mut bytes).ok()?;
+    *bytes.get_mut(byte)? ^= 1;
+    let mut reader = bytes.as_slice();
+    let flipped = from_reader(&mut reader).ok()?;
+    reader.is_empty().then_some(flipped)
+}
@@ -0,0 +1,83 @@
+//! Prover telemetry
+//!
+//! Long proofs can be monitored by passing a [`ProverTelemetry`] to
+//! [`crate::prove_with_telemetry`]. The prover reports when each phase starts and
+//! finishes and how many rows it covers, and reports progress through the quotient loop
+//! as it goes, and the field operations it performs itself. The PCS opening (FRI) runs
+//! inside the PCS, so it is only reported as a whole.
+//!
+//! With the `std` feature, [`crate::Watchdog`] turns these reports into periodic heartbeat
+//! events carrying the throughput in rows per second, and flags the proof as stuck when a
+//! phase runs longer than a configured duration.
+
+use serde::{Deserialize, Serialize};
+
+/// A phase of proving reported to [`ProverTelemetry`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProverPhase {
+    /// Committing the main trace
+    MainCommit,
+    /// Building and committing the trace of an auxiliary phase
+    AuxPhase(usize),
+    /// Evaluating the constraints over the quotient domain and committing the quotient
+    Quotient,
+    /// The PCS opening proof, FRI included
+    Opening,
+}
+
+/// Receives progress reports from the prover.
+///
+/// Every method defaults to doing nothing. The prover reports from its worker pool while
+/// evaluating the quotient, hence the `Send` bound.
+pub trait ProverTelemetry: Send {
+    /// `phase` starts and will cover `rows` rows.
+    fn phase_started(&mut self, phase: ProverPhase, rows: usize) {
+        let _ = (phase, rows);
+    }
+
+    /// `rows_done` rows of `phase` are done.
+    fn progress(&mut self, phase: ProverPhase, rows_done: usize) {
+        let _ = (phase, rows_done);
+    }
+
+    /// `phase` performed about `ops` extension field operations outside the PCS, reported
+    /// before it finishes.
+    ///
+    /// The count is estimated from the dimensions of the phase, like
+    /// [`crate::VerificationCost::field_ops`]: the quotient phase reports one
+    /// multiplication and one addition per constraint and point for folding, and one
+    /// multiplication per point for the division by the vanishing polynomial. The AIR's own
+    /// arithmetic, aux builders, and the LDEs, hashing and FRI of the PCS are not counted.
+    fn field_ops(&mut self, phase: ProverPhase, ops: u64) {
+        let _ = (phase, ops);
+    }
+
+    /// `phase` is done.
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        let _ = phase;
+    }
+}
+
+/// Telemetry that ignores every report.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoTelemetry;
+
+impl ProverTelemetry for NoTelemetry {}
+
+impl ProverTelemetry for &mut dyn ProverTelemetry {
+    fn phase_started(&mut self, phase: ProverPhase, rows: usize) {
+        (**self).phase_started(phase, rows);
+    }
+
+    fn progress(&mut self, phase: ProverPhase, rows_done: usize) {
+        (**self).progress(phase, rows_done);
+    }
+
+    fn field_ops(&mut self, phase: ProverPhase, ops: u64) {
+        (**self).field_ops(phase, ops);
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        (**self).phase_finished(phase);
+    }
+}
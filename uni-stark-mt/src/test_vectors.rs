@@ -0,0 +1,202 @@
+//! Conformance test vectors for alternative verifiers
+//!
+//! [`generate_vectors`] proves every case of [`TEST_VECTOR_CASES`] on the fixtures config
+//! and records the proof together with the values a verifier derives from it: the
+//! transcript schedule, every challenge, the out-of-domain point `ζ` and its next-row
+//! point, the opened values and the quotient recomposed at `ζ`. The same seed always
+//! yields the same vector, so an implementation in another language or inside a circuit
+//! (a Solidity verifier, a recursion AIR) replays each vector and compares every
+//! intermediate value, not only the final accept or reject.
+//!
+//! Field elements are canonical `u32` BabyBear values; extension elements are their basis
+//! coefficients, lowest degree first. The vectors are serde-serializable:
+//!
+//! ```text
+//! cargo test -p p3-uni-stark-mt --features test-vectors --test test_vectors
+//! ```
+//!
+//! compares against `test_vectors/fibonacci.json` and writes it when it is missing or when
+//! `UPDATE_TEST_VECTORS` is set.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::{BasedVectorSpace, PrimeField32};
+use serde::{Deserialize, Serialize};
+
+use crate::fixtures::{fibonacci_proof, Challenge, FibonacciFixture, FixtureConfig, FIXTURE_SEED};
+use crate::{
+    export_transcript, recompose_quotient_from_chunks, OpenedValues, Proof, StarkGenericConfig,
+    TranscriptSpec,
+};
+
+/// An extension field element as its canonical basis coefficients.
+pub type Element = Vec<u32>;
+
+/// A proof recorded as a test vector.
+#[derive(Clone, Copy, Debug)]
+pub struct TestVectorCase {
+    /// Name of the vector
+    pub name: &'static str,
+    /// Seed of [`crate::fixtures::baby_bear_config`]
+    pub seed: u64,
+    /// Log2 of the Fibonacci trace height
+    pub log_n: usize,
+}
+
+/// The recorded cases: Fibonacci traces of two heights, and one under another permutation.
+pub const TEST_VECTOR_CASES: &[TestVectorCase] = &[
+    TestVectorCase {
+        name: "fibonacci_2^3",
+        seed: FIXTURE_SEED,
+        log_n: 3,
+    },
+    TestVectorCase {
+        name: "fibonacci_2^6",
+        seed: FIXTURE_SEED,
+        log_n: 6,
+    },
+    TestVectorCase {
+        name: "fibonacci_2^3_seed_1",
+        seed: 1,
+        log_n: 3,
+    },
+];
+
+/// The challenges of a test vector, in the order they are sampled.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorChallenges {
+    /// Challenges of each auxiliary phase (empty without aux trace)
+    pub aux_challenges: Vec<Vec<Element>>,
+    /// Challenge combining the constraints
+    pub alpha: Element,
+    /// Out-of-domain evaluation point `ζ`
+    pub zeta: Element,
+    /// Next-row point `ζ·g`
+    pub zeta_next: Element,
+}
+
+/// A proof of one [`TestVectorCase`] and the intermediate values of its verification.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TestVector {
+    /// Name of the case
+    pub name: String,
+    /// Seed of the config the proof was made under
+    pub seed: u64,
+    /// Log2 of the trace height
+    pub log_n: usize,
+    /// Log2 of the number of quotient chunks
+    pub log_quotient_degree: usize,
+    /// Public values, canonical
+    pub public_values: Vec<u32>,
+    /// The order in which the verifier observes commitments and samples challenges
+    pub transcript: TranscriptSpec,
+    /// Every challenge sampled before the PCS opening
+    pub challenges: VectorChallenges,
+    /// Values opened at `ζ` and `ζ·g`
+    pub openings: OpenedValues<Element>,
+    /// The quotient recomposed from its chunks at `ζ`
+    pub quotient_at_zeta: Element,
+    /// The trace domain's vanishing polynomial at `ζ`; the constraints folded by `alpha`
+    /// at `ζ` equal `quotient_at_zeta · vanishing_at_zeta`
+    pub vanishing_at_zeta: Element,
+    /// The proof itself
+    pub proof: Proof<FixtureConfig>,
+}
+
+/// Prove `case` and record its intermediate values.
+///
+/// # Panics
+/// If the fixture proof cannot be replayed, which would be a prover bug
+pub fn generate_vector(case: &TestVectorCase) -> TestVector {
+    let FibonacciFixture {
+        config,
+        air,
+        vk,
+        public_values,
+        proof,
+    } = fibonacci_proof(case.seed, case.log_n);
+    let export = export_transcript(&config, &air, &vk, &proof, &public_values)
+        .expect("fixture proof is well formed");
+    let (zeta, zeta_next) = export.opening_points[0];
+
+    // The fixtures config is not zero-knowledge: one quotient chunk per quotient degree
+    let height = 1 << proof.log_degree;
+    let quotient_degree = vk.quotient_degree();
+    let trace_domain = config.pcs().natural_domain_for_degree(height);
+    let quotient_chunk_domains = trace_domain
+        .create_disjoint_domain(height * quotient_degree)
+        .split_domains(quotient_degree);
+    let quotient_at_zeta = recompose_quotient_from_chunks::<FixtureConfig>(
+        &quotient_chunk_domains,
+        &proof.quotient_chunks,
+        zeta,
+    )
+    .expect("fixture proof is well formed");
+
+    TestVector {
+        name: case.name.into(),
+        seed: case.seed,
+        log_n: case.log_n,
+        log_quotient_degree: vk.log_quotient_degree,
+        public_values: public_values
+            .iter()
+            .map(PrimeField32::as_canonical_u32)
+            .collect(),
+        transcript: export.spec,
+        challenges: VectorChallenges {
+            aux_challenges: export
+                .challenges
+                .aux_challenges
+                .iter()
+                .map(|phase| elements(phase))
+                .collect(),
+            alpha: element(&export.challenges.alpha),
+            zeta: element(&zeta),
+            zeta_next: element(&zeta_next),
+        },
+        openings: opened_elements(&export.openings[0]),
+        quotient_at_zeta: element(&quotient_at_zeta),
+        vanishing_at_zeta: element(&trace_domain.vanishing_poly_at_point(zeta)),
+        proof,
+    }
+}
+
+/// Record every case of [`TEST_VECTOR_CASES`].
+pub fn generate_vectors() -> Vec<TestVector> {
+    TEST_VECTOR_CASES.iter().map(generate_vector).collect()
+}
+
+fn element(value: &Challenge) -> Element {
+    value
+        .as_basis_coefficients_slice()
+        .iter()
+        .map(PrimeField32::as_canonical_u32)
+        .collect()
+}
+
+fn elements(values: &[Challenge]) -> Vec<Element> {
+    values.iter().map(element).collect()
+}
+
+fn opened_elements(values: &OpenedValues<Challenge>) -> OpenedValues<Element> {
+    let phases = |phases: &Option<Vec<Vec<Challenge>>>| {
+        phases
+            .as_ref()
+            .map(|phases| phases.iter().map(|phase| elements(phase)).collect())
+    };
+    OpenedValues {
+        main_local: elements(&values.main_local),
+        main_next: elements(&values.main_next),
+        preprocessed_local: elements(&values.preprocessed_local),
+        preprocessed_next: elements(&values.preprocessed_next),
+        aux_local: phases(&values.aux_local),
+        aux_next: phases(&values.aux_next),
+        quotient_chunks: values
+            .quotient_chunks
+            .iter()
+            .map(|chunk| elements(chunk))
+            .collect(),
+    }
+}
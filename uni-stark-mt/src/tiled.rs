@@ -0,0 +1,241 @@
+//! Merkle leaves holding several rows
+//!
+//! A Merkle tree over a skinny matrix, such as the 2-column Fibonacci trace, spends most
+//! of its hashing on compressions: each leaf absorbs a couple of elements and every one of
+//! them needs its own path. [`TiledMmcs`] wraps the config's input MMCS and commits each
+//! matrix with `rows_per_leaf` consecutive rows per leaf, so the inner tree has that many
+//! times fewer leaves and its paths are `log2(rows_per_leaf)` levels shorter. An opening
+//! still returns the one queried row to the PCS; the proof carries the other rows of its
+//! leaf so the verifier can rebuild and hash the whole leaf.
+//!
+//! Tiling is chosen per config by building the PCS over `TiledMmcs::new(mmcs, k)` instead
+//! of `mmcs`, and pays off while a leaf stays within the hash rate. A config limiting leaf
+//! width with [`crate::StarkConfig::with_max_leaf_width`] counts the tiled width.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use p3_commit::{BatchOpening, BatchOpeningRef, Mmcs};
+use p3_matrix::{Dimensions, Matrix};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A matrix viewed with `rows` consecutive rows side by side in each row.
+#[derive(Clone, Debug)]
+pub struct TiledMatrix<M> {
+    inner: M,
+    rows: usize,
+}
+
+impl<M> TiledMatrix<M> {
+    /// The untiled matrix.
+    pub const fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+impl<T: Send + Sync + Clone, M: Matrix<T>> Matrix<T> for TiledMatrix<M> {
+    fn width(&self) -> usize {
+        self.inner.width() * self.rows
+    }
+
+    fn height(&self) -> usize {
+        self.inner.height() / self.rows
+    }
+
+    unsafe fn get_unchecked(&self, row: usize, col: usize) -> T {
+        let width = self.inner.width();
+        self.inner
+            .get_unchecked(row * self.rows + col / width, col % width)
+    }
+}
+
+/// An MMCS committing `rows_per_leaf` consecutive rows of each matrix per leaf of `Inner`.
+#[derive(Clone, Debug)]
+pub struct TiledMmcs<Inner> {
+    inner: Inner,
+    log_rows_per_leaf: usize,
+}
+
+impl<Inner> TiledMmcs<Inner> {
+    /// Tile the leaves of `inner` with `rows_per_leaf` rows each.
+    ///
+    /// # Panics
+    /// If `rows_per_leaf` is not a power of two
+    pub fn new(inner: Inner, rows_per_leaf: usize) -> Self {
+        assert!(
+            rows_per_leaf.is_power_of_two(),
+            "rows per leaf must be a power of two"
+        );
+        Self {
+            inner,
+            log_rows_per_leaf: rows_per_leaf.trailing_zeros() as usize,
+        }
+    }
+
+    /// Number of rows in each leaf.
+    pub const fn rows_per_leaf(&self) -> usize {
+        1 << self.log_rows_per_leaf
+    }
+
+    /// Position in its leaf of the row of a matrix of `height` rows opened at `index` of
+    /// a batch whose tallest matrix has `max_height` rows.
+    const fn offset(&self, index: usize, height: usize, max_height: usize) -> usize {
+        let row = index >> (max_height.ilog2() - height.ilog2());
+        row & (self.rows_per_leaf() - 1)
+    }
+}
+
+/// Opening proof of a [`TiledMmcs`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize, P: Serialize"))]
+#[serde(bound(deserialize = "T: DeserializeOwned, P: DeserializeOwned"))]
+pub struct TiledProof<T, P> {
+    /// For each matrix, the rows of the opened leaf other than the opened row, in order
+    pub siblings: Vec<Vec<T>>,
+    /// Opening of the leaf in the inner MMCS
+    pub inner: P,
+}
+
+/// Why a [`TiledMmcs`] opening was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TiledMmcsError<E> {
+    /// The opening does not have one row and one set of sibling rows per matrix
+    WrongBatchSize,
+    /// An opened row or its siblings do not match the matrix width
+    WrongWidth,
+    /// A matrix height is not a multiple of the rows per leaf
+    WrongHeight,
+    /// The rebuilt leaves do not open against the commitment
+    Inner(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for TiledMmcsError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongBatchSize => write!(f, "opening does not cover every matrix"),
+            Self::WrongWidth => write!(f, "opened rows do not match the matrix widths"),
+            Self::WrongHeight => write!(f, "matrix height is not a multiple of the tile"),
+            Self::Inner(err) => write!(f, "leaf opening rejected: {err:?}"),
+        }
+    }
+}
+
+impl<E: fmt::Debug> core::error::Error for TiledMmcsError<E> {}
+
+impl<T, Inner> Mmcs<T> for TiledMmcs<Inner>
+where
+    T: Send + Sync + Clone + Serialize + DeserializeOwned,
+    Inner: Mmcs<T>,
+{
+    type ProverData<M> = Inner::ProverData<TiledMatrix<M>>;
+    type Commitment = Inner::Commitment;
+    type Proof = TiledProof<T, Inner::Proof>;
+    type Error = TiledMmcsError<Inner::Error>;
+
+    fn commit<M: Matrix<T>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        let rows = self.rows_per_leaf();
+        let tiled = inputs
+            .into_iter()
+            .map(|inner| {
+                assert_eq!(
+                    inner.height() % rows,
+                    0,
+                    "matrix height must be a multiple of the rows per leaf"
+                );
+                TiledMatrix { inner, rows }
+            })
+            .collect();
+        self.inner.commit(tiled)
+    }
+
+    fn open_batch<M: Matrix<T>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> BatchOpening<T, Self> {
+        let heights: Vec<usize> = self
+            .get_matrices(prover_data)
+            .iter()
+            .map(|matrix| matrix.height())
+            .collect();
+        let max_height = heights.iter().copied().max().unwrap_or(0);
+        let BatchOpening {
+            opened_values: tiles,
+            opening_proof: inner,
+        } = self
+            .inner
+            .open_batch(index >> self.log_rows_per_leaf, prover_data);
+
+        // Cut the opened row out of each leaf
+        let (opened_values, siblings) = tiles
+            .into_iter()
+            .zip(heights)
+            .map(|(mut tile, height)| {
+                let offset = self.offset(index, height, max_height);
+                let width = tile.len() >> self.log_rows_per_leaf;
+                let rest = tile.split_off((offset + 1) * width);
+                let row = tile.split_off(offset * width);
+                tile.extend(rest);
+                (row, tile)
+            })
+            .unzip();
+
+        BatchOpening::new(opened_values, TiledProof { siblings, inner })
+    }
+
+    fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        self.inner
+            .get_matrices(prover_data)
+            .into_iter()
+            .map(TiledMatrix::inner)
+            .collect()
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        batch_opening: BatchOpeningRef<'_, T, Self>,
+    ) -> Result<(), Self::Error> {
+        let rows = self.rows_per_leaf();
+        let opened_values = batch_opening.opened_values;
+        let proof = batch_opening.opening_proof;
+        if opened_values.len() != dimensions.len() || proof.siblings.len() != dimensions.len() {
+            return Err(TiledMmcsError::WrongBatchSize);
+        }
+        if dimensions
+            .iter()
+            .any(|dims| dims.height < rows || dims.height % rows != 0)
+        {
+            return Err(TiledMmcsError::WrongHeight);
+        }
+        let max_height = dimensions.iter().map(|dims| dims.height).max().unwrap_or(0);
+
+        // Put each opened row back into its leaf
+        let mut tiles = Vec::with_capacity(dimensions.len());
+        let mut tiled_dimensions = Vec::with_capacity(dimensions.len());
+        for ((dims, row), siblings) in dimensions.iter().zip(opened_values).zip(&proof.siblings) {
+            if row.len() != dims.width || siblings.len() != (rows - 1) * dims.width {
+                return Err(TiledMmcsError::WrongWidth);
+            }
+            let offset = self.offset(index, dims.height, max_height);
+            let (before, after) = siblings.split_at(offset * dims.width);
+            tiles.push([before, row, after].concat());
+            tiled_dimensions.push(Dimensions {
+                width: dims.width * rows,
+                height: dims.height / rows,
+            });
+        }
+
+        self.inner
+            .verify_batch(
+                commit,
+                &tiled_dimensions,
+                index >> self.log_rows_per_leaf,
+                BatchOpeningRef::new(&tiles, &proof.inner),
+            )
+            .map_err(TiledMmcsError::Inner)
+    }
+}
@@ -0,0 +1,216 @@
+//! Transcript specification
+//!
+//! [`VerifyingKey::transcript_spec`] lists every step of the Fiat-Shamir transcript of a
+//! single-AIR proof, in order: what is observed or sampled and how many elements. The
+//! verifier charges its transcript replay from the same list, so the specification is
+//! exercised on every metered verification. It serializes with serde for auditors and
+//! reimplementers.
+
+use alloc::vec::Vec;
+
+use p3_field::BasedVectorSpace;
+use serde::{Deserialize, Serialize};
+
+use crate::proof::quotient_column_groups;
+use crate::{Challenge, MultiTraceAir, StarkGenericConfig, Val, VerifyingKey};
+
+/// What a transcript step observes or samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptItem {
+    /// Number of AIR instance parameters
+    InstanceParameterCount,
+    /// The AIR instance parameters
+    InstanceParameters,
+    /// Log2 of the trace height
+    LogDegree,
+    /// Number of main trace columns
+    MainWidth,
+    /// Number of auxiliary phases
+    NumAuxPhases,
+    /// Number of columns of an auxiliary phase
+    AuxPhaseWidth(usize),
+    /// Number of public values
+    NumPublicValues,
+    /// Commitment to the preprocessed trace
+    PreprocessedCommitment,
+    /// Commitment to the main trace
+    MainCommitment,
+    /// The public values
+    PublicValues,
+    /// Challenges of an auxiliary phase
+    AuxChallenges(usize),
+    /// Commitment to the trace of an auxiliary phase
+    AuxCommitment(usize),
+    /// Values exposed by an auxiliary phase, as base field coordinates
+    AuxExposedValues(usize),
+    /// Constraint combination challenge
+    Alpha,
+    /// Combination challenge of the constraints of a phase, `0` for those reading only the
+    /// main trace and `p + 1` for those reading auxiliary phase `p`
+    PhaseAlpha(usize),
+    /// Commitment to a column group of the quotient chunks
+    QuotientCommitment(usize),
+    /// An out-of-domain point
+    Zeta(usize),
+    /// The PCS opening proof, which drives the challenger itself
+    PcsOpening,
+}
+
+/// How a transcript step uses the challenger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptOp {
+    /// Observe this many base field elements
+    ObserveField(usize),
+    /// Observe one PCS commitment
+    ObserveCommitment,
+    /// Sample this many challenge field elements
+    Sample(usize),
+    /// Hand the challenger to the PCS opening proof
+    Pcs,
+}
+
+/// One step of the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptStep {
+    /// What is observed or sampled
+    pub item: TranscriptItem,
+    /// How the challenger is used
+    pub op: TranscriptOp,
+}
+
+impl TranscriptStep {
+    /// Elements and commitments observed or sampled; the PCS opening counts for none.
+    pub const fn num_elements(&self) -> usize {
+        match self.op {
+            TranscriptOp::ObserveField(n) | TranscriptOp::Sample(n) => n,
+            TranscriptOp::ObserveCommitment => 1,
+            TranscriptOp::Pcs => 0,
+        }
+    }
+}
+
+/// The transcript schedule of proofs of one AIR, see [`VerifyingKey::transcript_spec`].
+///
+/// A multi-table proof of [`crate::prove_multi`] runs one transcript over its tables, in the
+/// order the AIRs are given, and observes the public values of each table on its own. The
+/// number of tables comes first, then the instance parameters, shape and preprocessed
+/// commitment of each table, its [`TranscriptItem::NumPublicValues`] included. Then, table
+/// by table, the [`TranscriptItem::MainCommitment`] is followed by that table's
+/// [`TranscriptItem::PublicValues`], before the next table's commitment: each set of public
+/// values is bound to the trace it belongs to, and moving values between tables changes
+/// every later challenge.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptSpec {
+    /// The steps, in transcript order
+    pub steps: Vec<TranscriptStep>,
+}
+
+impl TranscriptSpec {
+    /// Total elements and commitments observed or sampled before the PCS opening.
+    pub fn num_elements(&self) -> usize {
+        self.steps.iter().map(TranscriptStep::num_elements).sum()
+    }
+}
+
+impl<SC: StarkGenericConfig> VerifyingKey<SC> {
+    /// The transcript schedule of [`crate::prove`] and [`crate::verify`] for proofs of `air`
+    /// under `config` and this key, with `num_public_values` public values.
+    ///
+    /// Steps observing or sampling nothing are left out.
+    pub fn transcript_spec<A>(
+        &self,
+        config: &SC,
+        air: &A,
+        num_public_values: usize,
+    ) -> TranscriptSpec
+    where
+        A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+    {
+        let mut steps = Vec::new();
+        let mut push = |item, op| {
+            let step = TranscriptStep { item, op };
+            if step.num_elements() > 0 || op == TranscriptOp::Pcs {
+                steps.push(step);
+            }
+        };
+        let observe = TranscriptOp::ObserveField;
+
+        push(TranscriptItem::InstanceParameterCount, observe(1));
+        push(
+            TranscriptItem::InstanceParameters,
+            observe(air.instance_parameters().len()),
+        );
+
+        push(TranscriptItem::LogDegree, observe(1));
+        push(TranscriptItem::MainWidth, observe(1));
+        push(TranscriptItem::NumAuxPhases, observe(1));
+        for phase in 0..air.num_aux_phases() {
+            push(TranscriptItem::AuxPhaseWidth(phase), observe(1));
+        }
+        push(TranscriptItem::NumPublicValues, observe(1));
+
+        if self.preprocessed.is_some() {
+            push(
+                TranscriptItem::PreprocessedCommitment,
+                TranscriptOp::ObserveCommitment,
+            );
+        }
+        push(
+            TranscriptItem::MainCommitment,
+            TranscriptOp::ObserveCommitment,
+        );
+        push(TranscriptItem::PublicValues, observe(num_public_values));
+        if config.per_phase_alpha() {
+            push(TranscriptItem::PhaseAlpha(0), TranscriptOp::Sample(1));
+        }
+
+        for phase in 0..air.num_aux_phases() {
+            push(
+                TranscriptItem::AuxChallenges(phase),
+                TranscriptOp::Sample(air.aux_phase_num_challenges(phase)),
+            );
+            // A phase without columns commits nothing
+            if air.aux_phase_width(phase) > 0 {
+                push(
+                    TranscriptItem::AuxCommitment(phase),
+                    TranscriptOp::ObserveCommitment,
+                );
+            }
+            push(
+                TranscriptItem::AuxExposedValues(phase),
+                observe(air.aux_phase_num_exposed_values(phase) * Challenge::<SC>::DIMENSION),
+            );
+            if config.per_phase_alpha() {
+                push(
+                    TranscriptItem::PhaseAlpha(phase + 1),
+                    TranscriptOp::Sample(1),
+                );
+            }
+        }
+
+        if !config.per_phase_alpha() {
+            push(TranscriptItem::Alpha, TranscriptOp::Sample(1));
+        }
+
+        let num_quotient_chunks = self.quotient_degree() << config.is_zk();
+        let num_quotient_commits = quotient_column_groups(
+            num_quotient_chunks,
+            Challenge::<SC>::DIMENSION,
+            config.max_leaf_width(),
+        )
+        .len();
+        for i in 0..num_quotient_commits {
+            push(
+                TranscriptItem::QuotientCommitment(i),
+                TranscriptOp::ObserveCommitment,
+            );
+        }
+
+        for i in 0..config.num_ood_points() {
+            push(TranscriptItem::Zeta(i), TranscriptOp::Sample(1));
+        }
+        push(TranscriptItem::PcsOpening, TranscriptOp::Pcs);
+
+        TranscriptSpec { steps }
+    }
+}
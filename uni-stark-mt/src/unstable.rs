@@ -0,0 +1,49 @@
+//! Experimental subsystems
+//!
+//! Items here may change in any release, including minor ones: gadgets, multi-table and
+//! aggregated proofs, pluggable quotient backends, prover telemetry, cost models and
+//! commitment layouts. Code that depends on them should pin the crate version. The
+//! semver-committed subset is [`crate::stable`].
+//!
+//! The `recursion` feature does not add items yet; what it grows into will be re-exported
+//! here first.
+
+pub use crate::{
+    export_transcript, replay_challenges, verify_batch, verify_constraints_only, verify_metered,
+    verify_multi, verify_proofs, verify_segments, verify_with_challenger, verify_with_witness,
+    AirShape, BatchInstance, ConstraintDag, ConstraintWitness, CostEstimate, CostModel, MultiProof,
+    ProofInstance, QueryIndices, SegmentLayout, SegmentProof, SymbolicBuilder, SymbolicConstraint,
+    SymbolicEntry, SymbolicExpression, SymbolicExtEntry, SymbolicExtExpression,
+    SymbolicExtVariable, SymbolicVariable, TableProof, TiledMatrix, TiledMmcs, TiledMmcsError,
+    TiledProof, TraceGenerator, TranscriptExport, TranscriptSpec, VerificationCost,
+    VerificationMeter, WorkBudget,
+};
+
+#[cfg(feature = "prover")]
+pub use crate::{
+    commit_main_trace, prove_batch, prove_from_inputs, prove_multi, prove_with_aux_trace,
+    prove_with_backend, prove_with_challenger, prove_with_committed_main, prove_with_hints,
+    prove_with_telemetry, BatchStatement, ChunkedTrace, CpuBackend, DagBackend, NoTelemetry,
+    ProverCheckpoint, ProverPhase, ProverState, ProverTelemetry, QuotientBackend, QuotientInputs,
+    QuotientSelectors,
+};
+
+#[cfg(all(feature = "std", feature = "prover"))]
+pub use crate::{
+    prove_with_metrics, verify_aggregated, verify_bundle, AggregatedProof, CountingAllocator,
+    DagError, Link, MetricsRecorder, PhaseMetrics, ProofBundle, ProofDag, ProverMetrics,
+    StatementId, StatementWitness, Watchdog,
+};
+
+#[cfg(feature = "lookups")]
+pub use crate::{
+    BusInteraction, BusInteractions, ByteOp, ByteOpChip, ByteOpCounts, InteractionBuilder,
+    LogUpLookup, LookupInteraction, LookupKind, Multiplicity, ProgramRom, ProgramRomCounts,
+    RangeCheckChip, RangeCheckCounts,
+};
+
+#[cfg(feature = "memory")]
+pub use crate::MemoryChecker;
+
+#[cfg(feature = "machine")]
+pub use crate::{Chip, Machine};
@@ -1,40 +1,137 @@
 //! Verifier implementation for multi-trace STARK
+//!
+//! Everything in this module must be panic-free on adversarial proofs: malformed
+//! shapes are reported as [`VerificationError::InvalidProof`] instead.
 
+#![deny(
+    clippy::indexing_slicing,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic
+)]
+
+use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
 
-use itertools::Itertools;
 use p3_air::Air;
 use p3_challenger::{CanObserve, CanSample};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{BasedVectorSpace, Field, PrimeCharacteristicRing};
+use p3_maybe_rayon::prelude::*;
+use serde::Serialize;
 use tracing::instrument;
 
-use crate::{Challenge, Domain, MultiTraceAir, Proof, Val, VerifierFolder};
+use crate::air::{aux_matrix_committed_widths, aux_matrix_widths, committed_aux_phases};
+use crate::proof::{aux_challenge_counts, observe_trace_shape, quotient_column_groups};
+use crate::{
+    public_values_digest, serialized_size, Challenge, ConstraintFolding, Domain, MultiProof,
+    MultiTraceAir, OpenedValues, Proof, StarkGenericConfig, Unmetered, Val, VerificationCost,
+    VerificationMeter, VerificationStep, VerifierFolder, VerifyingKey, PROOF_VERSION,
+};
 
 /// Verification error types
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum VerificationError {
     /// PCS verification failed
     PcsVerificationFailed,
-    /// Constraint evaluation failed
+    /// Constraint evaluation failed, see [`verify_with_witness`] for the values involved
     ConstraintVerificationFailed,
     /// Invalid proof structure
     InvalidProof(&'static str),
+    /// The proof's recorded public values digest does not match the supplied public values
+    PublicValuesMismatch,
+    /// The aux challenge counts recorded in the proof differ from the AIR's declaration
+    AuxChallengeMismatch {
+        /// First aux phase on which the record and the AIR disagree
+        phase: usize,
+        /// Challenges the prover sampled in that phase, `None` past its last phase
+        recorded: Option<usize>,
+        /// Challenges the AIR declares for that phase, `None` past its last phase
+        declared: Option<usize>,
+    },
+    /// An aux phase exposes values other than those the AIR derives from the public values,
+    /// see [`crate::AuxTraceBuilder::expected_aux_exposed_values`]
+    ExposedValueMismatch {
+        /// The aux phase
+        phase: usize,
+    },
+    /// The bus interaction sums of the proven tables do not add up to zero, see
+    /// [`crate::AuxTraceBuilder::interaction_sum`]
+    UnbalancedInteractions,
+    /// A segment does not start in the state the previous segment ended in, see
+    /// [`crate::verify_segments`]
+    SegmentChainBroken,
+    /// The verifying key was generated for an AIR of a different shape
+    VerifyingKeyMismatch(&'static str),
+    /// The proof was produced under another protocol revision, see [`crate::PROOF_VERSION`]
+    UnsupportedVersion {
+        /// Version recorded in the proof
+        found: u16,
+        /// Version this verifier implements
+        expected: u16,
+    },
+    /// The verification meter refused to pay for a step
+    BudgetExceeded(VerificationStep),
+}
+
+/// Values the verifier computed when the constraint identity failed.
+///
+/// Lets a failed verification be analysed off-line without rerunning it under a debugger;
+/// see [`verify_with_witness`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintWitness<EF> {
+    /// Out-of-domain evaluation point
+    pub zeta: EF,
+    /// Folded constraints evaluated at `zeta`
+    pub constraints_at_zeta: EF,
+    /// Quotient recomposed from its opened chunks
+    pub quotient_at_zeta: EF,
+    /// First-row selector at `zeta`
+    pub is_first_row: EF,
+    /// Last-row selector at `zeta`
+    pub is_last_row: EF,
+    /// Transition selector at `zeta`
+    pub is_transition: EF,
+    /// Inverse of the trace domain's vanishing polynomial at `zeta`
+    pub inv_vanishing: EF,
+    /// Opened main trace row at `zeta`
+    pub main_local: Vec<EF>,
+    /// Opened main trace row at the next point
+    pub main_next: Vec<EF>,
+    /// Opened preprocessed row at `zeta`
+    pub preprocessed_local: Vec<EF>,
+    /// Opened preprocessed row at the next point
+    pub preprocessed_next: Vec<EF>,
+    /// Recombined auxiliary row at `zeta`, all phases concatenated
+    pub aux_local: Vec<EF>,
+    /// Recombined auxiliary row at the next point, all phases concatenated
+    pub aux_next: Vec<EF>,
 }
 
 /// Recomposes the quotient polynomial from its chunks evaluated at a point.
 ///
 /// Given quotient chunks and their domains, this computes the Lagrange
 /// interpolation coefficients (zps) and reconstructs quotient(zeta).
+///
+/// Returns [`VerificationError::InvalidProof`] if the number of chunks does not match the
+/// number of domains or a chunk does not hold exactly one extension element's worth of
+/// base field coordinates.
 pub fn recompose_quotient_from_chunks<SC>(
     quotient_chunks_domains: &[Domain<SC>],
     quotient_chunks: &[Vec<Challenge<SC>>],
     zeta: Challenge<SC>,
-) -> Challenge<SC>
+) -> Result<Challenge<SC>, VerificationError>
 where
     SC: crate::StarkGenericConfig,
 {
+    if quotient_chunks.len() != quotient_chunks_domains.len() {
+        return Err(VerificationError::InvalidProof(
+            "quotient chunk count does not match quotient degree",
+        ));
+    }
+
     let zps = quotient_chunks_domains
         .iter()
         .enumerate()
@@ -44,176 +141,1790 @@ where
                 .enumerate()
                 .filter(|(j, _)| *j != i)
                 .map(|(_, other_domain)| {
-                    other_domain.vanishing_poly_at_point(zeta)
-                        * other_domain
-                            .vanishing_poly_at_point(domain.first_point())
-                            .inverse()
+                    other_domain
+                        .vanishing_poly_at_point(domain.first_point())
+                        .try_inverse()
+                        .map(|inv| other_domain.vanishing_poly_at_point(zeta) * inv)
+                        .ok_or(VerificationError::InvalidProof(
+                            "quotient chunk domains are not disjoint",
+                        ))
                 })
-                .product::<Challenge<SC>>()
+                .product::<Result<Challenge<SC>, _>>()
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Each chunk is a Vec<Challenge> representing the columns at that point
     // After flattening to base, each extension field element becomes
     // DIMENSION base field elements (e.g., 4 for degree-4 extension)
     quotient_chunks
         .iter()
-        .enumerate()
-        .map(|(ch_i, chunk_vals)| {
+        .zip(zps)
+        .map(|(chunk_vals, zp)| {
+            if chunk_vals.len() != Challenge::<SC>::DIMENSION {
+                return Err(VerificationError::InvalidProof(
+                    "quotient chunk has the wrong number of coordinates",
+                ));
+            }
             // Reconstruct the Challenge from its base field components
-            zps[ch_i]
-                * chunk_vals
-                    .iter()
-                    .enumerate()
-                    .map(|(e_i, &c)| Challenge::<SC>::ith_basis_element(e_i).unwrap() * c)
-                    .sum::<Challenge<SC>>()
+            Ok(zp * recompose_from_coordinates::<SC>(chunk_vals)?)
         })
-        .sum::<Challenge<SC>>()
+        .sum()
+}
+
+/// Recombines the openings of flattened base field columns into one extension element.
+///
+/// A column of extension elements is committed as `DIMENSION` base field columns; opening
+/// them at an extension point yields one value per basis coordinate.
+fn recompose_from_coordinates<SC>(
+    coordinates: &[Challenge<SC>],
+) -> Result<Challenge<SC>, VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+{
+    coordinates
+        .iter()
+        .enumerate()
+        .map(|(e_i, &c)| {
+            Challenge::<SC>::ith_basis_element(e_i)
+                .map(|basis| basis * c)
+                .ok_or(VerificationError::InvalidProof(
+                    "too many coordinates for an extension element",
+                ))
+        })
+        .sum()
+}
+
+/// Recombines the flattened openings of every committed auxiliary phase into extension
+/// field columns, concatenated in phase order.
+///
+/// A phase declared over the base field was committed one column per column, so its
+/// openings are its column values.
+fn recompose_aux_rows<SC, A>(
+    air: &A,
+    phases: &[Vec<Challenge<SC>>],
+) -> Result<Vec<Challenge<SC>>, VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let mut columns = Vec::new();
+    for (phase, flat) in committed_aux_phases(air).into_iter().zip(phases) {
+        if air.aux_phase_in_base_field(phase) {
+            columns.extend_from_slice(flat);
+        } else {
+            for coordinates in flat.chunks_exact(Challenge::<SC>::DIMENSION) {
+                columns.push(recompose_from_coordinates::<SC>(coordinates)?);
+            }
+        }
+    }
+    Ok(columns)
+}
+
+/// The verifier challenges a proof was generated with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofChallenges<EF> {
+    /// Challenges sampled for building each auxiliary phase (empty without aux trace)
+    pub aux_challenges: Vec<Vec<EF>>,
+    /// Challenge used to combine the constraints; with per-phase alphas, the one of the
+    /// last phase
+    pub alpha: EF,
+    /// Challenges folding the constraints of each phase, sampled after the main trace and
+    /// after each aux phase (empty unless
+    /// [`crate::StarkGenericConfig::per_phase_alpha`])
+    pub phase_alphas: Vec<EF>,
+    /// Out-of-domain evaluation point
+    pub zeta: EF,
+    /// Further out-of-domain points, when the config opens at more than one
+    pub additional_zetas: Vec<EF>,
+}
+
+/// Recompute the challenges a proof used by replaying its transcript.
+///
+/// This performs no verification: it walks the same Fiat-Shamir schedule as [`verify`],
+/// so external tools can recompute auxiliary traces or audit the transcript without
+/// reimplementing the challenger walk.
+pub fn replay_challenges<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> ProofChallenges<Challenge<SC>>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    replay_transcript(
+        config,
+        air,
+        vk,
+        proof,
+        public_values,
+        &mut config.initialise_challenger(),
+    )
+}
+
+type Commitment<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+
+/// The claims a PCS opening proof is checked against: each commitment with the domain of
+/// each of its matrices, the points they are opened at and the values opened there.
+pub(crate) type OpeningClaims<SC> = Vec<(
+    Commitment<SC>,
+    Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)>,
+)>;
+
+/// A configuration whose PCS checks its opening proof at query indices drawn from the
+/// transcript, such as FRI's, see [`TranscriptExport::query_indices`].
+///
+/// With the `fri` feature, [`crate::StarkConfig`] implements it over `TwoAdicFriPcs`.
+pub trait QueryIndices: StarkGenericConfig {
+    /// The query indices of the opening proof `proof` of `claims`, sampled from
+    /// `challenger`, in the state the opening is checked against, as the PCS verifier
+    /// samples them; `None` if the configuration does not know how its PCS samples them.
+    #[allow(clippy::type_complexity)]
+    fn query_indices(
+        &self,
+        claims: &OpeningClaims<Self>,
+        proof: &<Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::Proof,
+        challenger: &mut Self::Challenger,
+    ) -> Option<Vec<usize>>;
+}
+
+/// Everything an outer SNARK wrapping a proof needs to re-derive its transcript, see
+/// [`export_transcript`].
+#[derive(Clone)]
+pub struct TranscriptExport<SC: StarkGenericConfig> {
+    /// The order in which the items below are observed and the challenges sampled
+    pub spec: crate::TranscriptSpec,
+    /// Commitment to the preprocessed trace, from the verifying key
+    pub preprocessed_commit: Option<Commitment<SC>>,
+    /// Commitment to the main trace
+    pub main_commit: Commitment<SC>,
+    /// Commitment of each auxiliary phase with columns, in phase order
+    pub aux_commits: Vec<Commitment<SC>>,
+    /// Commitments to the quotient chunks, one per column group
+    pub quotient_commits: Vec<Commitment<SC>>,
+    /// Public input/output values
+    pub public_values: Vec<Val<SC>>,
+    /// Values exposed by each auxiliary phase
+    pub aux_exposed_values: Vec<Vec<Challenge<SC>>>,
+    /// Every challenge sampled before the PCS opening
+    pub challenges: ProofChallenges<Challenge<SC>>,
+    /// Each out-of-domain point `ζ` with the next-row point `ζ·g`, in sampling order
+    pub opening_points: Vec<(Challenge<SC>, Challenge<SC>)>,
+    /// Values opened at each of `opening_points`
+    pub openings: Vec<OpenedValues<Challenge<SC>>>,
+    /// The positions the PCS opening proof is queried at, in sampling order, if the
+    /// configuration knows them, see [`QueryIndices`]
+    ///
+    /// FRI samples them once it has observed its commit-phase commitments and final
+    /// polynomial and checked the proof-of-work witness; the wrapper circuit opens its
+    /// Merkle paths there.
+    pub query_indices: Option<Vec<usize>>,
+}
+
+/// Export the transcript of `proof` for an outer SNARK wrapper: the commitment digests,
+/// the challenge derivation order, every challenge, the opened values and the positions
+/// the PCS opening is queried at.
+///
+/// Like [`replay_challenges`], this performs no verification; wrap only proofs that
+/// [`verify`] accepts.
+///
+/// # Returns
+/// The export, or [`VerificationError::InvalidProof`] if the trace domain of `proof` has no
+/// next-row point, its degree is out of range or its openings do not match the AIR and
+/// the configuration
+pub fn export_transcript<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> Result<TranscriptExport<SC>, VerificationError>
+where
+    SC: QueryIndices,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if proof.additional_openings.len() + 1 != config.num_ood_points() {
+        return Err(VerificationError::InvalidProof(
+            "number of opened points does not match the configuration",
+        ));
+    }
+    let domains = ProofDomains::new(config, vk, proof.log_degree)?;
+
+    let mut pcs_challenger = config.initialise_challenger();
+    let challenges = replay_transcript(config, air, vk, proof, public_values, &mut pcs_challenger);
+    let zetas: Vec<Challenge<SC>> = core::iter::once(challenges.zeta)
+        .chain(challenges.additional_zetas.iter().copied())
+        .collect();
+    let zeta_nexts = zetas
+        .iter()
+        .map(|&zeta| {
+            domains
+                .trace_domain
+                .next_point(zeta)
+                .ok_or(VerificationError::InvalidProof(
+                    "trace domain does not support next_point",
+                ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The PCS samples its query indices after observing what the claims hold
+    let openings: Vec<OpenedRows<'_, Challenge<SC>>> = core::iter::once(OpenedRows::first(proof))
+        .chain(proof.additional_openings.iter().map(OpenedRows::from))
+        .collect();
+    let quotient_groups = quotient_column_groups(
+        vk.quotient_degree() << config.is_zk(),
+        Challenge::<SC>::DIMENSION,
+        config.max_leaf_width(),
+    );
+    let claims = opening_claims::<SC, A>(
+        air,
+        vk,
+        proof,
+        domains.ext_trace_domain,
+        &domains.quotient_chunk_domains,
+        &quotient_groups,
+        &openings,
+        &zetas,
+        &zeta_nexts,
+    )?;
+    let query_indices = config.query_indices(&claims, &proof.opening_proof, &mut pcs_challenger);
+    let first = OpenedValues {
+        main_local: proof.main_local.clone(),
+        main_next: proof.main_next.clone(),
+        preprocessed_local: proof.preprocessed_local.clone(),
+        preprocessed_next: proof.preprocessed_next.clone(),
+        aux_local: proof.aux_local.clone(),
+        aux_next: proof.aux_next.clone(),
+        quotient_chunks: proof.quotient_chunks.clone(),
+    };
+
+    Ok(TranscriptExport {
+        spec: vk.transcript_spec(config, air, public_values.len()),
+        preprocessed_commit: vk
+            .preprocessed
+            .as_ref()
+            .map(|preprocessed| preprocessed.commit.clone()),
+        main_commit: proof.main_commit.clone(),
+        aux_commits: proof.aux_commits.clone(),
+        quotient_commits: proof.quotient_commits.clone(),
+        public_values: public_values.to_vec(),
+        aux_exposed_values: proof.aux_exposed_values.clone(),
+        challenges,
+        opening_points: zetas.into_iter().zip(zeta_nexts).collect(),
+        openings: core::iter::once(first)
+            .chain(proof.additional_openings.iter().cloned())
+            .collect(),
+        query_indices,
+    })
+}
+
+/// Replays the transcript up to the out-of-domain point on `challenger`, returning the
+/// sampled challenges and leaving `challenger` in the state the PCS opening is verified
+/// against.
+fn replay_transcript<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+    challenger: &mut SC::Challenger,
+) -> ProofChallenges<Challenge<SC>>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    // Bind the AIR instance parameters before anything else
+    let instance_parameters = air.instance_parameters();
+    challenger.observe(Val::<SC>::from_usize(instance_parameters.len()));
+    challenger.observe_slice(&instance_parameters);
+    observe_trace_shape::<SC, A>(challenger, air, proof.log_degree, public_values.len());
+
+    if let Some(preprocessed) = &vk.preprocessed {
+        challenger.observe(preprocessed.commit.clone());
+    }
+
+    // Observe main trace commitment (same as prover)
+    challenger.observe(proof.main_commit.clone());
+    challenger.observe_slice(public_values);
+
+    // With per-phase folding, each phase's alpha follows its commitment (same as prover)
+    let per_phase_alpha = config.per_phase_alpha();
+    let mut phase_alphas: Vec<Challenge<SC>> = Vec::new();
+    if per_phase_alpha {
+        phase_alphas.push(challenger.sample());
+    }
+
+    // Sample each phase's challenges, then observe its commitment, if it has columns, and
+    // its exposed values (same as prover)
+    let mut aux_commits = proof.aux_commits.iter();
+    let aux_challenges: Vec<Vec<Challenge<SC>>> = (0..air.num_aux_phases())
+        .map(|phase| {
+            let challenges: Vec<Challenge<SC>> = (0..air.aux_phase_num_challenges(phase))
+                .map(|_| challenger.sample())
+                .collect();
+            if air.aux_phase_width(phase) > 0 {
+                if let Some(aux_commit) = aux_commits.next() {
+                    challenger.observe(aux_commit.clone());
+                }
+            }
+            for value in proof.aux_exposed_values.get(phase).into_iter().flatten() {
+                challenger.observe_slice(value.as_basis_coefficients_slice());
+            }
+            if per_phase_alpha {
+                phase_alphas.push(challenger.sample());
+            }
+            challenges
+        })
+        .collect();
+
+    // Sample alpha for constraint combination (same as prover - must be BEFORE quotient commits)
+    let alpha: Challenge<SC> = match phase_alphas.last() {
+        Some(&alpha) => alpha,
+        None => challenger.sample(),
+    };
+
+    // Observe quotient commitments
+    for quotient_commit in &proof.quotient_commits {
+        challenger.observe(quotient_commit.clone());
+    }
+
+    // Sample out-of-domain points (same as prover)
+    let zeta: Challenge<SC> = challenger.sample();
+    let additional_zetas = (1..config.num_ood_points())
+        .map(|_| challenger.sample())
+        .collect();
+
+    ProofChallenges {
+        aux_challenges,
+        alpha,
+        phase_alphas,
+        zeta,
+        additional_zetas,
+    }
 }
 
 /// Verify a multi-trace STARK proof.
 ///
+/// The shape of the proof (opened row widths, aux phase and quotient chunk counts, trace
+/// height) is checked against `air` and `vk` before anything is hashed, so a malformed proof
+/// is rejected with [`VerificationError::InvalidProof`] and never indexed out of bounds.
+///
 /// # Arguments
 /// - `config`: STARK configuration (must match prover's config)
 /// - `air`: The AIR defining the computation (must match prover's AIR)
+/// - `vk`: Verifying key from [`crate::setup`] for `air`
 /// - `proof`: The proof to verify
 /// - `public_values`: Public input/output values (must match prover's)
 ///
 /// # Returns
 /// - `Ok(())` if the proof is valid
 /// - `Err(VerificationError)` if verification fails
-#[instrument(skip_all, fields(log_degree = proof.log_degree))]
 pub fn verify<SC, A>(
     config: &SC,
     air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    verify_metered(config, air, vk, proof, public_values, &mut Unmetered)
+}
+
+/// Verify a proof produced by [`crate::prove_with_challenger`], continuing the transcript of
+/// `challenger`.
+///
+/// `challenger` must be in the state the prover's was in. On success it has observed the
+/// whole proof, like the prover's, so an outer Fiat-Shamir protocol carries on from there;
+/// on failure it is left untouched.
+#[instrument(skip_all, fields(log_degree = proof.log_degree))]
+pub fn verify_with_challenger<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+    challenger: &mut SC::Challenger,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    verify_with(
+        config,
+        air,
+        vk,
+        proof,
+        public_values,
+        challenger,
+        &mut Unmetered,
+        true,
+        None,
+        None,
+    )
+}
+
+/// Verify a multi-trace STARK proof, charging each step to `meter`.
+///
+/// Each step's estimated cost is charged before the step runs, constraint evaluation from
+/// [`VerifyingKey::num_constraints`], so verification stops with
+/// [`VerificationError::BudgetExceeded`] once the meter refuses a charge. Hashing is not
+/// metered, see [`VerificationCost`].
+#[instrument(skip_all, fields(log_degree = proof.log_degree))]
+pub fn verify_metered<SC, A, M>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+    meter: &mut M,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+    M: VerificationMeter,
+{
+    verify_with(
+        config,
+        air,
+        vk,
+        proof,
+        public_values,
+        &mut config.initialise_challenger(),
+        meter,
+        true,
+        None,
+        None,
+    )
+}
+
+/// Check the constraint identity of a proof whose PCS openings were verified elsewhere.
+///
+/// Meant for outer protocols that already checked the opened values of `proof` against
+/// its commitments, e.g. through one FRI instance batched across several proofs. The
+/// transcript is replayed to recover the challenges and the constraints are checked
+/// against the opened values; `proof.opening_proof` is not looked at.
+///
+/// Without that outer check this accepts forged proofs: the opened values are trusted.
+#[instrument(skip_all, fields(log_degree = proof.log_degree))]
+pub fn verify_constraints_only<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    verify_with(
+        config,
+        air,
+        vk,
+        proof,
+        public_values,
+        &mut config.initialise_challenger(),
+        &mut Unmetered,
+        false,
+        None,
+        None,
+    )
+}
+
+/// Verify like [`verify`], recording the values behind a failed constraint check.
+///
+/// When verification fails with [`VerificationError::ConstraintVerificationFailed`],
+/// `witness` is set to what the verifier computed at the failing out-of-domain point;
+/// otherwise it is left untouched.
+#[instrument(skip_all, fields(log_degree = proof.log_degree))]
+pub fn verify_with_witness<SC, A>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
     proof: &Proof<SC>,
     public_values: &[Val<SC>],
+    witness: &mut Option<ConstraintWitness<Challenge<SC>>>,
 ) -> Result<(), VerificationError>
 where
     SC: crate::StarkGenericConfig,
     A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
 {
+    verify_with(
+        config,
+        air,
+        vk,
+        proof,
+        public_values,
+        &mut config.initialise_challenger(),
+        &mut Unmetered,
+        true,
+        None,
+        Some(witness),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_with<SC, A, M>(
+    config: &SC,
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+    challenger: &mut SC::Challenger,
+    meter: &mut M,
+    verify_openings: bool,
+    domains: Option<&ProofDomains<SC>>,
+    mut witness: Option<&mut Option<ConstraintWitness<Challenge<SC>>>>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+    M: VerificationMeter,
+{
+    let mut charge = |step: VerificationStep, cost: VerificationCost| {
+        if meter.charge(step, cost) {
+            Ok(())
+        } else {
+            Err(VerificationError::BudgetExceeded(step))
+        }
+    };
+
     // Check basic proof structure
-    if air.aux_width() > 0 && proof.aux_commit.is_none() {
+    if proof.version != PROOF_VERSION {
+        return Err(VerificationError::UnsupportedVersion {
+            found: proof.version,
+            expected: PROOF_VERSION,
+        });
+    }
+    check_verifying_key(air, vk)?;
+    check_proof_size(config, proof)?;
+    if let Some(recorded) = &proof.aux_challenge_counts {
+        check_aux_challenge_counts(
+            recorded,
+            &aux_challenge_counts::<Val<SC>, Challenge<SC>, A>(air),
+        )?;
+    }
+    let committed_phases = committed_aux_phases(air);
+    if proof.aux_commits.len() != committed_phases.len() {
+        return Err(VerificationError::InvalidProof(
+            "auxiliary commitment count does not match AIR aux phases",
+        ));
+    }
+    check_exposed_shape::<SC, A>(air, &proof.aux_exposed_values)?;
+    if proof.additional_openings.len() + 1 != config.num_ood_points() {
         return Err(VerificationError::InvalidProof(
-            "AIR requires auxiliary trace but proof has none",
+            "number of opened points does not match the configuration",
         ));
     }
+    if vk
+        .preprocessed
+        .as_ref()
+        .is_some_and(|preprocessed| preprocessed.log_degree != proof.log_degree)
+    {
+        return Err(VerificationError::InvalidProof(
+            "log_degree does not match the preprocessed trace height",
+        ));
+    }
+
+    // The quotient degree was measured from the AIR by `setup`
+    let quotient_degree = vk.quotient_degree();
 
-    if air.aux_width() == 0 && proof.aux_commit.is_some() {
+    // In ZK mode traces are committed with a random row after every row
+    let zk = config.is_zk();
+
+    // Check the shape of the opened values before any of them reaches the transcript, the
+    // PCS or the AIR
+    let openings: Vec<OpenedRows<'_, Challenge<SC>>> = core::iter::once(OpenedRows::first(proof))
+        .chain(proof.additional_openings.iter().map(OpenedRows::from))
+        .collect();
+    let preprocessed_width = vk.preprocessed_width();
+    let num_quotient_chunks = quotient_degree << zk;
+    for rows in &openings {
+        check_opened_shape(air, preprocessed_width, num_quotient_chunks, rows)?;
+    }
+    let quotient_groups = quotient_column_groups(
+        num_quotient_chunks,
+        Challenge::<SC>::DIMENSION,
+        config.max_leaf_width(),
+    );
+    if proof.quotient_commits.len() != quotient_groups.len() {
         return Err(VerificationError::InvalidProof(
-            "AIR has no auxiliary trace but proof includes one",
+            "quotient commitment count does not match the leaf width",
         ));
     }
 
-    let pcs = config.pcs();
-    let mut challenger = config.initialise_challenger();
+    // Proofs of one verifying key and degree share their domains, see `verify_proofs`
+    let own_domains;
+    let ProofDomains {
+        trace_domain,
+        ext_trace_domain,
+        quotient_chunk_domains,
+    } = match domains {
+        Some(domains) => domains,
+        None => {
+            own_domains = ProofDomains::new(config, vk, proof.log_degree)?;
+            &own_domains
+        }
+    };
+    let (trace_domain, ext_trace_domain) = (*trace_domain, *ext_trace_domain);
+
+    if let Some(digest) = proof.public_values_digest {
+        if digest != public_values_digest(config, public_values) {
+            return Err(VerificationError::PublicValuesMismatch);
+        }
+    }
+
+    // Everything the transcript observes and samples before the PCS opening
+    let transcript_ops = vk
+        .transcript_spec(config, air, public_values.len())
+        .num_elements();
+    charge(
+        VerificationStep::Transcript,
+        VerificationCost {
+            transcript_ops: transcript_ops as u64,
+            ..Default::default()
+        },
+    )?;
 
     // Reconstruct the verifier's view of the protocol
-    let height = 1 << proof.log_degree;
-    let trace_domain = pcs.natural_domain_for_degree(height);
+    let pcs = config.pcs();
+    let mut transcript = challenger.clone();
+    let challenges = replay_transcript(config, air, vk, proof, public_values, &mut transcript);
+    let ProofChallenges {
+        aux_challenges,
+        alpha,
+        phase_alphas,
+        zeta,
+        additional_zetas,
+    } = challenges;
+    let folding = if config.per_phase_alpha() {
+        if vk.constraint_phases.len() != vk.num_constraints {
+            return Err(VerificationError::VerifyingKeyMismatch(
+                "constraint phases were not measured for per-phase alphas",
+            ));
+        }
+        ConstraintFolding::PerPhase {
+            alphas: &phase_alphas,
+            constraint_phases: &vk.constraint_phases,
+        }
+    } else {
+        ConstraintFolding::Single(alpha)
+    };
+    let aux_challenges = aux_challenges.concat();
+    check_expected_exposed_values::<SC, A>(
+        air,
+        public_values,
+        &aux_challenges,
+        &proof.aux_exposed_values,
+    )?;
+    check_interaction_balance::<SC, A>([(air, proof.aux_exposed_values.as_slice())])?;
+    let aux_exposed_values = proof.aux_exposed_values.concat();
 
-    // Observe main trace commitment (same as prover)
-    challenger.observe(proof.main_commit.clone());
-    challenger.observe_slice(public_values);
+    let zetas: Vec<Challenge<SC>> = core::iter::once(zeta).chain(additional_zetas).collect();
+    let zeta_nexts = zetas
+        .iter()
+        .map(|&zeta| {
+            trace_domain
+                .next_point(zeta)
+                .ok_or(VerificationError::InvalidProof(
+                    "trace domain does not support next_point",
+                ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let coms_to_verify = opening_claims::<SC, A>(
+        air,
+        vk,
+        proof,
+        ext_trace_domain,
+        quotient_chunk_domains,
+        &quotient_groups,
+        &openings,
+        &zetas,
+        &zeta_nexts,
+    )?;
+
+    // The openings may already have been checked by an outer protocol
+    if verify_openings {
+        let aux_opened: usize = proof.aux_local.iter().flatten().map(Vec::len).sum();
+        let opened_per_point = 2 * (proof.main_local.len() + preprocessed_width + aux_opened)
+            + proof.quotient_chunks.len() * Challenge::<SC>::DIMENSION;
+        charge(
+            VerificationStep::PcsVerification,
+            VerificationCost {
+                opened_values: (opened_per_point * openings.len()) as u64,
+                ..Default::default()
+            },
+        )?;
+
+        // Verify PCS opening proofs
+        pcs.verify(coms_to_verify, &proof.opening_proof, &mut transcript)
+            .map_err(|_| VerificationError::PcsVerificationFailed)?;
+    }
+
+    // Check the constraint identity at every out-of-domain point
+    for (&zeta, rows) in zetas.iter().zip(&openings) {
+        // One multiplication and one addition per constraint, charged from the verifying
+        // key before the AIR runs
+        charge(
+            VerificationStep::ConstraintEvaluation,
+            VerificationCost {
+                field_ops: 2 * vk.num_constraints as u64,
+                ..Default::default()
+            },
+        )?;
+        check_constraints_at::<SC, A>(
+            air,
+            trace_domain,
+            quotient_chunk_domains,
+            zeta,
+            rows,
+            public_values,
+            &aux_challenges,
+            &aux_exposed_values,
+            folding,
+            &mut charge,
+            witness.as_deref_mut(),
+        )?;
+    }
+
+    *challenger = transcript;
+    Ok(())
+}
+
+/// Verify a proof of several AIRs produced by [`crate::prove_multi`].
+///
+/// # Arguments
+/// - `config`: STARK configuration (must match prover's config)
+/// - `airs`: The AIR of each table, in the prover's order
+/// - `vks`: Verifying key of each table
+/// - `proof`: The proof to verify
+/// - `public_values`: Public values of each table
+///
+/// # Returns
+/// - `Ok(())` if the proof is valid
+/// - `Err(VerificationError)` if verification fails
+#[instrument(skip_all, fields(num_tables = airs.len()))]
+pub fn verify_multi<SC, A>(
+    config: &SC,
+    airs: &[A],
+    vks: &[VerifyingKey<SC>],
+    proof: &MultiProof<SC>,
+    public_values: &[Vec<Val<SC>>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let airs: Vec<&A> = airs.iter().collect();
+    let vks: Vec<&VerifyingKey<SC>> = vks.iter().collect();
+    let public_values: Vec<&[Val<SC>]> = public_values.iter().map(Vec::as_slice).collect();
+    verify_tables(config, &airs, &vks, proof, &public_values, true)
+}
+
+/// One statement of a batch checked by [`verify_batch`].
+pub struct BatchInstance<'a, SC: StarkGenericConfig, A> {
+    /// The AIR of the statement
+    pub air: &'a A,
+    /// Verifying key of `air`
+    pub vk: &'a VerifyingKey<SC>,
+    /// Public input/output values (must match prover's)
+    pub public_values: &'a [Val<SC>],
+}
+
+/// Verify a proof of several statements produced by [`crate::prove_batch`].
+///
+/// `instances` must list the statements in the prover's order.
+#[instrument(skip_all, fields(num_statements = instances.len()))]
+pub fn verify_batch<SC, A>(
+    config: &SC,
+    instances: &[BatchInstance<'_, SC, A>],
+    proof: &MultiProof<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let airs: Vec<&A> = instances.iter().map(|instance| instance.air).collect();
+    let vks: Vec<&VerifyingKey<SC>> = instances.iter().map(|instance| instance.vk).collect();
+    let public_values: Vec<&[Val<SC>]> = instances
+        .iter()
+        .map(|instance| instance.public_values)
+        .collect();
+    verify_tables(config, &airs, &vks, proof, &public_values, false)
+}
+
+/// One independent proof checked by [`verify_proofs`].
+pub struct ProofInstance<'a, SC: StarkGenericConfig, A> {
+    /// The AIR the proof is for
+    pub air: &'a A,
+    /// Verifying key of `air`
+    pub vk: &'a VerifyingKey<SC>,
+    /// The proof to verify
+    pub proof: &'a Proof<SC>,
+    /// Public input/output values (must match prover's)
+    pub public_values: &'a [Val<SC>],
+}
+
+/// Verify many independent proofs, e.g. all the proofs a sequencer receives in a block.
+///
+/// Unlike [`verify_batch`], which checks one [`MultiProof`] binding several statements,
+/// every instance carries its own [`Proof`] from [`crate::prove`] and is checked exactly as
+/// [`verify`] would. Proofs sharing a verifying key and a trace height share their trace and
+/// quotient domains, built once per group. The PCS interface verifies one opening proof at a
+/// time, so openings are not batched across proofs; with the `parallel` feature the proofs
+/// are verified concurrently instead.
+///
+/// # Returns
+/// - `Ok(())` if every proof is valid
+/// - `Err((index, error))` with the index in `instances` of a failing proof and its error;
+///   with the `parallel` feature this need not be the first failing one
+#[instrument(skip_all, fields(num_proofs = instances.len()))]
+pub fn verify_proofs<SC, A>(
+    config: &SC,
+    instances: &[ProofInstance<'_, SC, A>],
+) -> Result<(), (usize, VerificationError)>
+where
+    SC: crate::StarkGenericConfig + Sync,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+    Proof<SC>: Sync,
+    VerifyingKey<SC>: Sync,
+{
+    // Group the proofs by verifying key, told apart by address, and degree. A group whose
+    // degree is out of range gets no domains; its proofs then fail as they would alone.
+    let mut group_index = BTreeMap::new();
+    let mut groups: Vec<Option<ProofDomains<SC>>> = Vec::new();
+    let group_of: Vec<usize> = instances
+        .iter()
+        .map(|instance| {
+            let key = (instance.proof.log_degree, core::ptr::from_ref(instance.vk));
+            *group_index.entry(key).or_insert_with(|| {
+                groups.push(ProofDomains::new(config, instance.vk, instance.proof.log_degree).ok());
+                groups.len() - 1
+            })
+        })
+        .collect();
+
+    instances
+        .par_iter()
+        .zip(&group_of)
+        .enumerate()
+        .try_for_each(|(index, (instance, &group))| {
+            verify_with(
+                config,
+                instance.air,
+                instance.vk,
+                instance.proof,
+                instance.public_values,
+                &mut config.initialise_challenger(),
+                &mut Unmetered,
+                true,
+                groups.get(group).and_then(Option::as_ref),
+                None,
+            )
+            .map_err(|error| (index, error))
+        })
+}
 
-    // Observe auxiliary commitment if present
-    if let Some(ref aux_commit) = proof.aux_commit {
-        // Sample challenges (same as prover)
-        let num_challenges = air.num_challenges();
-        for _ in 0..num_challenges {
-            let _: Challenge<SC> = challenger.sample();
+/// Shared body of [`verify_multi`] and [`verify_batch`].
+///
+/// With `joint_interactions`, the bus interactions of all tables must balance together, as
+/// the tables of one machine; otherwise each table's must balance on its own.
+fn verify_tables<SC, A>(
+    config: &SC,
+    airs: &[&A],
+    vks: &[&VerifyingKey<SC>],
+    proof: &MultiProof<SC>,
+    public_values: &[&[Val<SC>]],
+    joint_interactions: bool,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    if proof.version != PROOF_VERSION {
+        return Err(VerificationError::UnsupportedVersion {
+            found: proof.version,
+            expected: PROOF_VERSION,
+        });
+    }
+    if vks.len() != airs.len() || public_values.len() != airs.len() {
+        return Err(VerificationError::InvalidProof(
+            "one verifying key and public values vector is needed per AIR",
+        ));
+    }
+    if proof.tables.len() != airs.len() {
+        return Err(VerificationError::InvalidProof(
+            "table count does not match the number of AIRs",
+        ));
+    }
+    check_proof_size(config, proof)?;
+    if config.num_ood_points() != 1 {
+        return Err(VerificationError::InvalidProof(
+            "multi-table proofs open at a single out-of-domain point",
+        ));
+    }
+    if config.is_zk() != 0 {
+        return Err(VerificationError::InvalidProof(
+            "multi-table proofs do not support zero-knowledge configs",
+        ));
+    }
+
+    let pcs = config.pcs();
+
+    // Check the shape of every table before any opened value reaches an AIR
+    let mut trace_domains = Vec::with_capacity(airs.len());
+    for ((air, vk), table) in airs.iter().copied().zip(vks).zip(&proof.tables) {
+        check_verifying_key(air, vk)?;
+        let quotient_degree = vk.quotient_degree();
+        if table.aux_commits.len() != committed_aux_phases(air).len() {
+            return Err(VerificationError::InvalidProof(
+                "auxiliary commitment count does not match AIR aux phases",
+            ));
+        }
+        check_exposed_shape::<SC, A>(air, &table.aux_exposed_values)?;
+        if vk
+            .preprocessed
+            .as_ref()
+            .is_some_and(|preprocessed| preprocessed.log_degree != table.log_degree)
+        {
+            return Err(VerificationError::InvalidProof(
+                "log_degree does not match the preprocessed trace height",
+            ));
         }
+        let rows = OpenedRows::from(&table.opened_values);
+        check_opened_shape::<SC, A>(air, vk.preprocessed_width(), quotient_degree, &rows)?;
+        if config
+            .max_supported_log_degree()
+            .is_some_and(|max_log_degree| table.log_degree as usize > max_log_degree)
+        {
+            return Err(VerificationError::InvalidProof(
+                "log_degree exceeds the configuration's maximum",
+            ));
+        }
+        let height = 1usize
+            .checked_shl(table.log_degree.into())
+            .filter(|height| height.checked_mul(quotient_degree).is_some())
+            .ok_or(VerificationError::InvalidProof("log_degree too large"))?;
+        trace_domains.push(pcs.natural_domain_for_degree(height));
+    }
 
-        challenger.observe(aux_commit.clone());
+    // Replay the transcript (same order as prover)
+    let mut challenger = config.initialise_challenger();
+    challenger.observe(Val::<SC>::from_usize(airs.len()));
+    for (((air, vk), table), &public_values) in airs
+        .iter()
+        .copied()
+        .zip(vks)
+        .zip(&proof.tables)
+        .zip(public_values)
+    {
+        let instance_parameters = air.instance_parameters();
+        challenger.observe(Val::<SC>::from_usize(instance_parameters.len()));
+        challenger.observe_slice(&instance_parameters);
+        observe_trace_shape::<SC, A>(&mut challenger, air, table.log_degree, public_values.len());
+        if let Some(preprocessed) = &vk.preprocessed {
+            challenger.observe(preprocessed.commit.clone());
+        }
+    }
+    for (table, &public_values) in proof.tables.iter().zip(public_values) {
+        challenger.observe(table.main_commit.clone());
+        challenger.observe_slice(public_values);
     }
 
-    // Sample alpha for constraint combination (same as prover - must be BEFORE quotient commits)
-    let alpha: Challenge<SC> = challenger.sample();
+    let num_aux_phases = airs
+        .iter()
+        .map(|air| air.num_aux_phases())
+        .max()
+        .unwrap_or(0);
+    let mut aux_challenges: Vec<Vec<Challenge<SC>>> = airs.iter().map(|_| Vec::new()).collect();
+    let mut aux_commits: Vec<_> = proof
+        .tables
+        .iter()
+        .map(|table| table.aux_commits.iter())
+        .collect();
+    for phase in 0..num_aux_phases {
+        let num_challenges = airs
+            .iter()
+            .filter(|air| phase < air.num_aux_phases())
+            .map(|air| air.aux_phase_num_challenges(phase))
+            .max()
+            .unwrap_or(0);
+        let challenges: Vec<Challenge<SC>> =
+            (0..num_challenges).map(|_| challenger.sample()).collect();
+
+        for (((air, table), table_challenges), table_commits) in airs
+            .iter()
+            .zip(&proof.tables)
+            .zip(&mut aux_challenges)
+            .zip(&mut aux_commits)
+        {
+            if phase >= air.num_aux_phases() {
+                continue;
+            }
+            table_challenges.extend(challenges.iter().take(air.aux_phase_num_challenges(phase)));
+            if air.aux_phase_width(phase) > 0 {
+                if let Some(aux_commit) = table_commits.next() {
+                    challenger.observe(aux_commit.clone());
+                }
+            }
+            for value in table.aux_exposed_values.get(phase).into_iter().flatten() {
+                challenger.observe_slice(value.as_basis_coefficients_slice());
+            }
+        }
+    }
 
-    // Observe quotient commitment
-    challenger.observe(proof.quotient_commit.clone());
+    for (((air, &public_values), table_challenges), table) in airs
+        .iter()
+        .copied()
+        .zip(public_values)
+        .zip(&aux_challenges)
+        .zip(&proof.tables)
+    {
+        check_expected_exposed_values::<SC, A>(
+            air,
+            public_values,
+            table_challenges,
+            &table.aux_exposed_values,
+        )?;
+    }
+    let tables = airs.iter().copied().zip(
+        proof
+            .tables
+            .iter()
+            .map(|table| table.aux_exposed_values.as_slice()),
+    );
+    if joint_interactions {
+        check_interaction_balance::<SC, A>(tables)?;
+    } else {
+        for table in tables {
+            check_interaction_balance::<SC, A>([table])?;
+        }
+    }
 
-    // Sample out-of-domain point (same as prover)
+    let alpha: Challenge<SC> = challenger.sample();
+    for table in &proof.tables {
+        for quotient_commit in &table.quotient_commits {
+            challenger.observe(quotient_commit.clone());
+        }
+    }
     let zeta: Challenge<SC> = challenger.sample();
-    let _zeta_next = trace_domain
-        .next_point(zeta)
-        .expect("domain must support next_point");
-
-    // Compute quotient degree and domains (must match prover)
-    let constraint_degree = 2; // Must match prover's heuristic
-    let quotient_degree = 1 << constraint_degree;
-    let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
-    let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
-
-    // Build PCS opening verification data
-    // Format: Vec<(Commitment, Vec<(Domain, Vec<(Point, Values)>)>)>
+
+    // Build PCS opening verification data, in the prover's commitment order
+    let mut coms_to_verify = Vec::new();
+    let mut table_checks = Vec::with_capacity(airs.len());
+    for (((vk, table), &trace_domain), air) in vks
+        .iter()
+        .zip(&proof.tables)
+        .zip(&trace_domains)
+        .zip(airs.iter().copied())
+    {
+        let rows = OpenedRows::from(&table.opened_values);
+        let zeta_next = trace_domain
+            .next_point(zeta)
+            .ok_or(VerificationError::InvalidProof(
+                "trace domain does not support next_point",
+            ))?;
+        let zetas = [zeta];
+        let zeta_nexts = [zeta_next];
+
+        coms_to_verify.push((
+            table.main_commit.clone(),
+            vec![(
+                trace_domain,
+                trace_points(
+                    &zetas,
+                    &zeta_nexts,
+                    core::iter::once((rows.main_local, rows.main_next)),
+                ),
+            )],
+        ));
+        if let Some(preprocessed) = &vk.preprocessed {
+            coms_to_verify.push((
+                preprocessed.commit.clone(),
+                vec![(
+                    trace_domain,
+                    trace_points(
+                        &zetas,
+                        &zeta_nexts,
+                        core::iter::once((rows.preprocessed_local, rows.preprocessed_next)),
+                    ),
+                )],
+            ));
+        }
+        for (((aux_commit, local), next), phase) in table
+            .aux_commits
+            .iter()
+            .zip(rows.aux_local)
+            .zip(rows.aux_next)
+            .zip(committed_aux_phases(air))
+        {
+            coms_to_verify.push((
+                aux_commit.clone(),
+                aux_phase_claims::<SC, A>(
+                    air,
+                    phase,
+                    trace_domain,
+                    &zetas,
+                    &zeta_nexts,
+                    &[(local.as_slice(), next.as_slice())],
+                )?,
+            ));
+        }
+
+        let height = trace_domain.size();
+        let quotient_degree = vk.quotient_degree();
+        let quotient_groups = quotient_column_groups(
+            quotient_degree,
+            Challenge::<SC>::DIMENSION,
+            config.max_leaf_width(),
+        );
+        let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+        let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
+        coms_to_verify.extend(quotient_claims::<SC>(
+            &table.quotient_commits,
+            &quotient_groups,
+            &quotient_chunk_domains,
+            &zetas,
+            &[rows.quotient_chunks],
+        )?);
+
+        table_checks.push((trace_domain, quotient_chunk_domains, rows));
+    }
+
+    // Verify PCS opening proofs
+    pcs.verify(coms_to_verify, &proof.opening_proof, &mut challenger)
+        .map_err(|_| VerificationError::PcsVerificationFailed)?;
+
+    // Check the constraint identity of every table
+    for (
+        (((air, (trace_domain, quotient_chunk_domains, rows)), &public_values), aux_challenges),
+        table,
+    ) in airs
+        .iter()
+        .copied()
+        .zip(&table_checks)
+        .zip(public_values)
+        .zip(&aux_challenges)
+        .zip(&proof.tables)
+    {
+        check_constraints_at::<SC, A>(
+            air,
+            *trace_domain,
+            quotient_chunk_domains,
+            zeta,
+            rows,
+            public_values,
+            aux_challenges,
+            &table.aux_exposed_values.concat(),
+            ConstraintFolding::Single(alpha),
+            &mut |_, _| Ok(()),
+            None,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Reject a proof larger than [`crate::StarkGenericConfig::max_proof_size`].
+fn check_proof_size<SC: StarkGenericConfig>(
+    config: &SC,
+    proof: &impl Serialize,
+) -> Result<(), VerificationError> {
+    if config
+        .max_proof_size()
+        .is_some_and(|max_proof_size| serialized_size(proof) > max_proof_size)
+    {
+        return Err(VerificationError::InvalidProof(
+            "proof exceeds the configured maximum size",
+        ));
+    }
+    Ok(())
+}
+
+/// Check that every auxiliary phase exposes as many values as `air` declares.
+fn check_exposed_shape<SC, A>(
+    air: &A,
+    aux_exposed_values: &[Vec<Challenge<SC>>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if aux_exposed_values.len() != air.num_aux_phases()
+        || aux_exposed_values
+            .iter()
+            .enumerate()
+            .any(|(phase, values)| values.len() != air.aux_phase_num_exposed_values(phase))
+    {
+        return Err(VerificationError::InvalidProof(
+            "exposed value count does not match AIR aux phases",
+        ));
+    }
+    Ok(())
+}
+
+/// Compare the values exposed by each aux phase with those `air` derives from the public
+/// values, where it can.
+///
+/// `aux_challenges` are the challenges of all phases, concatenated in phase order.
+fn check_expected_exposed_values<SC, A>(
+    air: &A,
+    public_values: &[Val<SC>],
+    aux_challenges: &[Challenge<SC>],
+    aux_exposed_values: &[Vec<Challenge<SC>>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let mut offset = 0;
+    for (phase, exposed) in aux_exposed_values.iter().enumerate() {
+        let num_challenges = air.aux_phase_num_challenges(phase);
+        let challenges = aux_challenges.get(offset..offset + num_challenges).ok_or(
+            VerificationError::InvalidProof("aux challenge count does not match AIR aux phases"),
+        )?;
+        offset += num_challenges;
+        if air
+            .expected_aux_exposed_values(phase, public_values, challenges)
+            .is_some_and(|expected| expected != *exposed)
+        {
+            return Err(VerificationError::ExposedValueMismatch { phase });
+        }
+    }
+    Ok(())
+}
+
+/// Check that the bus interaction sums of `tables`, each an AIR and the values exposed by
+/// its aux phases, add up to zero.
+fn check_interaction_balance<'t, SC, A>(
+    tables: impl IntoIterator<Item = (&'t A, &'t [Vec<Challenge<SC>>])>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + 't,
+{
+    let mut total = Challenge::<SC>::ZERO;
+    for (air, exposed) in tables {
+        if let Some((phase, index)) = air.interaction_sum() {
+            total += *exposed
+                .get(phase)
+                .and_then(|values| values.get(index))
+                .ok_or(VerificationError::InvalidProof(
+                    "interaction sum is not among the exposed values",
+                ))?;
+        }
+    }
+    if total == Challenge::<SC>::ZERO {
+        Ok(())
+    } else {
+        Err(VerificationError::UnbalancedInteractions)
+    }
+}
+
+/// Check that `vk` was generated for an AIR of the shape of `air`.
+fn check_verifying_key<SC, A>(air: &A, vk: &VerifyingKey<SC>) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if vk.main_width != air.width() {
+        return Err(VerificationError::VerifyingKeyMismatch(
+            "main width does not match the AIR",
+        ));
+    }
+    if !vk
+        .aux_widths
+        .iter()
+        .copied()
+        .eq((0..air.num_aux_phases()).map(|phase| air.aux_phase_width(phase)))
+    {
+        return Err(VerificationError::VerifyingKeyMismatch(
+            "aux phase widths do not match the AIR",
+        ));
+    }
+    if vk.instance_parameters != air.instance_parameters() {
+        return Err(VerificationError::VerifyingKeyMismatch(
+            "instance parameters do not match the AIR",
+        ));
+    }
+    Ok(())
+}
+
+/// Check the aux challenge counts recorded by the prover against those `air` declares.
+fn check_aux_challenge_counts(
+    recorded: &[usize],
+    declared: &[usize],
+) -> Result<(), VerificationError> {
+    let phases = recorded.len().max(declared.len());
+    match (0..phases).find(|&phase| recorded.get(phase) != declared.get(phase)) {
+        Some(phase) => Err(VerificationError::AuxChallengeMismatch {
+            phase,
+            recorded: recorded.get(phase).copied(),
+            declared: declared.get(phase).copied(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// PCS claims of `proof`: each commitment with the domain of each of its matrices, the
+/// points they are opened at and the values opened there, in the prover's order.
+#[allow(clippy::too_many_arguments)]
+fn opening_claims<SC, A>(
+    air: &A,
+    vk: &VerifyingKey<SC>,
+    proof: &Proof<SC>,
+    ext_trace_domain: Domain<SC>,
+    quotient_chunk_domains: &[Domain<SC>],
+    quotient_groups: &[Vec<(usize, core::ops::Range<usize>)>],
+    openings: &[OpenedRows<'_, Challenge<SC>>],
+    zetas: &[Challenge<SC>],
+    zeta_nexts: &[Challenge<SC>],
+) -> Result<OpeningClaims<SC>, VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
     let mut coms_to_verify = vec![(
         proof.main_commit.clone(),
         vec![(
-            trace_domain,
-            vec![
-                (zeta, proof.main_local.clone()),
-                (_zeta_next, proof.main_next.clone()),
-            ],
+            ext_trace_domain,
+            trace_points(
+                zetas,
+                zeta_nexts,
+                openings
+                    .iter()
+                    .map(|rows| (rows.main_local, rows.main_next)),
+            ),
         )],
     )];
 
-    if let Some(ref aux_commit) = proof.aux_commit {
+    if let Some(preprocessed) = &vk.preprocessed {
         coms_to_verify.push((
-            aux_commit.clone(),
+            preprocessed.commit.clone(),
             vec![(
-                trace_domain,
-                vec![
-                    (zeta, proof.aux_local.clone()),
-                    (_zeta_next, proof.aux_next.clone()),
-                ],
+                ext_trace_domain,
+                trace_points(
+                    zetas,
+                    zeta_nexts,
+                    openings
+                        .iter()
+                        .map(|rows| (rows.preprocessed_local, rows.preprocessed_next)),
+                ),
             )],
         ));
     }
 
-    // Add quotient commitment with all chunks
-    // Each chunk is opened at zeta on its own domain
-    let quotient_openings: Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)> =
-        quotient_chunk_domains
+    let committed_phases = committed_aux_phases(air);
+    for (i, (aux_commit, &phase)) in proof.aux_commits.iter().zip(&committed_phases).enumerate() {
+        let rows = openings
             .iter()
-            .enumerate()
-            .map(|(i, &domain)| (domain, vec![(zeta, proof.quotient_chunks[i].clone())]))
-            .collect();
+            .map(|rows| match (rows.aux_local.get(i), rows.aux_next.get(i)) {
+                (Some(local), Some(next)) => Ok((local.as_slice(), next.as_slice())),
+                _ => Err(VerificationError::InvalidProof(
+                    "auxiliary openings count does not match AIR aux phases",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        coms_to_verify.push((
+            aux_commit.clone(),
+            aux_phase_claims::<SC, A>(air, phase, ext_trace_domain, zetas, zeta_nexts, &rows)?,
+        ));
+    }
 
-    coms_to_verify.push((proof.quotient_commit.clone(), quotient_openings));
+    // Add the quotient commitments; each piece of each chunk is opened at every zeta on the
+    // chunk's domain
+    let quotient_rows: Vec<_> = openings.iter().map(|rows| rows.quotient_chunks).collect();
+    coms_to_verify.extend(quotient_claims::<SC>(
+        &proof.quotient_commits,
+        quotient_groups,
+        quotient_chunk_domains,
+        zetas,
+        &quotient_rows,
+    )?);
 
-    // Verify PCS opening proofs
-    pcs.verify(coms_to_verify, &proof.opening_proof, &mut challenger)
-        .map_err(|_| VerificationError::PcsVerificationFailed)?;
+    Ok(coms_to_verify)
+}
+
+/// Pair each `(local, next)` opening with its `(zeta, zeta_next)` points.
+fn trace_points<'a, EF: Copy + 'a>(
+    zetas: &[EF],
+    zeta_nexts: &[EF],
+    rows: impl Iterator<Item = (&'a [EF], &'a [EF])>,
+) -> Vec<(EF, Vec<EF>)> {
+    zetas
+        .iter()
+        .zip(zeta_nexts)
+        .zip(rows)
+        .flat_map(|((&zeta, &zeta_next), (local, next))| {
+            [(zeta, local.to_vec()), (zeta_next, next.to_vec())]
+        })
+        .collect()
+}
+
+/// PCS claims for the commitment of aux phase `phase`: each of its matrices opened at
+/// every point, its columns cut out of the phase's openings at each of `zetas`.
+#[allow(clippy::type_complexity)]
+fn aux_phase_claims<SC, A>(
+    air: &A,
+    phase: usize,
+    domain: Domain<SC>,
+    zetas: &[Challenge<SC>],
+    zeta_nexts: &[Challenge<SC>],
+    rows: &[(&[Challenge<SC>], &[Challenge<SC>])],
+) -> Result<Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)>, VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let mut start = 0;
+    aux_matrix_committed_widths(air, phase)
+        .into_iter()
+        .map(|width| {
+            let columns = start..start + width;
+            start += width;
+            let matrix_rows = rows
+                .iter()
+                .map(|&(local, next)| local.get(columns.clone()).zip(next.get(columns.clone())))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(VerificationError::InvalidProof(
+                    "auxiliary openings do not match AIR aux matrix widths",
+                ))?;
+            Ok((
+                domain,
+                trace_points(zetas, zeta_nexts, matrix_rows.into_iter()),
+            ))
+        })
+        .collect()
+}
+
+/// PCS claims for the quotient commitments, one per column group.
+///
+/// `quotient_chunks` holds the opened chunks at each of `zetas`; every `(chunk, columns)`
+/// piece of a group is claimed at every zeta on the chunk's domain.
+#[allow(clippy::type_complexity)]
+fn quotient_claims<SC>(
+    commits: &[<SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::Commitment],
+    groups: &[Vec<(usize, core::ops::Range<usize>)>],
+    chunk_domains: &[Domain<SC>],
+    zetas: &[Challenge<SC>],
+    quotient_chunks: &[&[Vec<Challenge<SC>>]],
+) -> Result<
+    Vec<(
+        <SC::Pcs as Pcs<SC::Challenge, SC::Challenger>>::Commitment,
+        Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)>,
+    )>,
+    VerificationError,
+>
+where
+    SC: crate::StarkGenericConfig,
+{
+    if commits.len() != groups.len() {
+        return Err(VerificationError::InvalidProof(
+            "quotient commitment count does not match the leaf width",
+        ));
+    }
+    commits
+        .iter()
+        .zip(groups)
+        .map(|(commit, group)| {
+            let claims = group
+                .iter()
+                .map(|(chunk, columns)| {
+                    let domain = chunk_domains.get(*chunk).copied().ok_or(
+                        VerificationError::InvalidProof(
+                            "quotient chunk count does not match quotient degree",
+                        ),
+                    )?;
+                    let points = zetas
+                        .iter()
+                        .zip(quotient_chunks)
+                        .map(|(&zeta, chunks)| {
+                            chunks
+                                .get(*chunk)
+                                .and_then(|values| values.get(columns.clone()))
+                                .map(|values| (zeta, values.to_vec()))
+                                .ok_or(VerificationError::InvalidProof(
+                                    "quotient chunk shape does not match the extension degree",
+                                ))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((domain, points))
+                })
+                .collect::<Result<Vec<_>, VerificationError>>()?;
+            Ok((commit.clone(), claims))
+        })
+        .collect()
+}
+
+/// The domains a proof is checked on, fixed by its verifying key and degree.
+struct ProofDomains<SC: StarkGenericConfig> {
+    trace_domain: Domain<SC>,
+    /// The trace domain with room for the random rows of zero knowledge
+    ext_trace_domain: Domain<SC>,
+    quotient_chunk_domains: Vec<Domain<SC>>,
+}
+
+impl<SC: StarkGenericConfig> ProofDomains<SC> {
+    /// The domains of a proof under `vk` over `1 << log_degree` rows (must match prover).
+    fn new(config: &SC, vk: &VerifyingKey<SC>, log_degree: u8) -> Result<Self, VerificationError> {
+        let quotient_degree = vk.quotient_degree();
+        let zk = config.is_zk();
+        if config
+            .max_supported_log_degree()
+            .is_some_and(|max_log_degree| log_degree as usize + zk > max_log_degree)
+        {
+            return Err(VerificationError::InvalidProof(
+                "log_degree exceeds the configuration's maximum",
+            ));
+        }
+        let height = 1usize
+            .checked_shl(log_degree.into())
+            .filter(|height| height.checked_mul(quotient_degree << 1).is_some())
+            .ok_or(VerificationError::InvalidProof("log_degree too large"))?;
 
+        let pcs = config.pcs();
+        let ext_trace_domain = pcs.natural_domain_for_degree(height << zk);
+        let quotient_domain =
+            ext_trace_domain.create_disjoint_domain((height << zk) * quotient_degree);
+        Ok(Self {
+            trace_domain: pcs.natural_domain_for_degree(height),
+            ext_trace_domain,
+            quotient_chunk_domains: quotient_domain.split_domains(quotient_degree << zk),
+        })
+    }
+}
+
+/// Opened values at one out-of-domain point, borrowed from a [`Proof`].
+///
+/// Absent aux openings read as empty, so a proof without aux phases verifies whether it
+/// omits them or carries empty lists.
+struct OpenedRows<'a, EF> {
+    main_local: &'a [EF],
+    main_next: &'a [EF],
+    preprocessed_local: &'a [EF],
+    preprocessed_next: &'a [EF],
+    aux_local: &'a [Vec<EF>],
+    aux_next: &'a [Vec<EF>],
+    quotient_chunks: &'a [Vec<EF>],
+}
+
+impl<'a, EF> OpenedRows<'a, EF> {
+    /// The values opened at the first out-of-domain point.
+    fn first<SC>(proof: &'a Proof<SC>) -> Self
+    where
+        SC: crate::StarkGenericConfig<Challenge = EF>,
+    {
+        Self {
+            main_local: &proof.main_local,
+            main_next: &proof.main_next,
+            preprocessed_local: &proof.preprocessed_local,
+            preprocessed_next: &proof.preprocessed_next,
+            aux_local: proof.aux_local.as_deref().unwrap_or_default(),
+            aux_next: proof.aux_next.as_deref().unwrap_or_default(),
+            quotient_chunks: &proof.quotient_chunks,
+        }
+    }
+}
+
+impl<'a, EF> From<&'a OpenedValues<EF>> for OpenedRows<'a, EF> {
+    fn from(opened: &'a OpenedValues<EF>) -> Self {
+        Self {
+            main_local: &opened.main_local,
+            main_next: &opened.main_next,
+            preprocessed_local: &opened.preprocessed_local,
+            preprocessed_next: &opened.preprocessed_next,
+            aux_local: opened.aux_local.as_deref().unwrap_or_default(),
+            aux_next: opened.aux_next.as_deref().unwrap_or_default(),
+            quotient_chunks: &opened.quotient_chunks,
+        }
+    }
+}
+
+/// Checks that the values opened at one point have the shape the AIR and key expect.
+fn check_opened_shape<SC, A>(
+    air: &A,
+    preprocessed_width: usize,
+    num_quotient_chunks: usize,
+    rows: &OpenedRows<'_, Challenge<SC>>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if rows.main_local.len() != air.width() || rows.main_next.len() != air.width() {
+        return Err(VerificationError::InvalidProof(
+            "main trace openings do not match AIR width",
+        ));
+    }
+
+    if rows.preprocessed_local.len() != preprocessed_width
+        || rows.preprocessed_next.len() != preprocessed_width
+    {
+        return Err(VerificationError::InvalidProof(
+            "preprocessed openings do not match verifying key width",
+        ));
+    }
+
+    // Aux columns are committed flattened to the base field, and only by phases with columns
+    let committed_phases = committed_aux_phases(air);
+    if rows.aux_local.len() != committed_phases.len()
+        || rows.aux_next.len() != committed_phases.len()
+    {
+        return Err(VerificationError::InvalidProof(
+            "auxiliary openings count does not match AIR aux phases",
+        ));
+    }
+    for (phase, (local, next)) in committed_phases
+        .into_iter()
+        .zip(rows.aux_local.iter().zip(rows.aux_next))
+    {
+        let aux_opened_width = air.aux_phase_committed_width(phase);
+        if local.len() != aux_opened_width || next.len() != aux_opened_width {
+            return Err(VerificationError::InvalidProof(
+                "auxiliary trace openings do not match AIR aux width",
+            ));
+        }
+    }
+
+    if rows.quotient_chunks.len() != num_quotient_chunks {
+        return Err(VerificationError::InvalidProof(
+            "quotient chunk count does not match quotient degree",
+        ));
+    }
+    if rows
+        .quotient_chunks
+        .iter()
+        .any(|chunk| chunk.len() != Challenge::<SC>::DIMENSION)
+    {
+        return Err(VerificationError::InvalidProof(
+            "quotient chunk has the wrong number of coordinates",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Evaluates the constraints on the values opened at `zeta` and checks them against the
+/// recomposed quotient.
+#[allow(clippy::too_many_arguments)]
+fn check_constraints_at<SC, A>(
+    air: &A,
+    trace_domain: Domain<SC>,
+    quotient_chunk_domains: &[Domain<SC>],
+    zeta: Challenge<SC>,
+    rows: &OpenedRows<'_, Challenge<SC>>,
+    public_values: &[Val<SC>],
+    aux_challenges: &[Challenge<SC>],
+    aux_exposed_values: &[Challenge<SC>],
+    folding: ConstraintFolding<'_, Challenge<SC>>,
+    charge: &mut impl FnMut(VerificationStep, VerificationCost) -> Result<(), VerificationError>,
+    witness: Option<&mut Option<ConstraintWitness<Challenge<SC>>>>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+{
     // Compute selectors at zeta
     let selectors = trace_domain.selectors_at_point(zeta);
 
+    // Aux columns were committed flattened to the base field; constraints see all phases
+    // concatenated in phase order
+    let aux_local = recompose_aux_rows::<SC, A>(air, rows.aux_local)?;
+    let aux_next = recompose_aux_rows::<SC, A>(air, rows.aux_next)?;
+    let aux_matrix_widths = aux_matrix_widths(air);
+
+    let (alpha, phase_alphas, constraint_phases) = match folding {
+        ConstraintFolding::Single(alpha) => (alpha, &[][..], &[][..]),
+        ConstraintFolding::PerPhase {
+            alphas,
+            constraint_phases,
+        } => (Challenge::<SC>::ZERO, alphas, constraint_phases),
+    };
+
     // Evaluate constraints at zeta
     let mut folder = VerifierFolder {
-        main_local: &proof.main_local,
-        main_next: &proof.main_next,
-        aux_local: &proof.aux_local,
-        aux_next: &proof.aux_next,
+        main_local: rows.main_local,
+        main_next: rows.main_next,
+        preprocessed_local: rows.preprocessed_local,
+        preprocessed_next: rows.preprocessed_next,
+        aux_local: &aux_local,
+        aux_next: &aux_next,
+        aux_matrix_widths: &aux_matrix_widths,
+        public_values,
+        aux_challenges,
+        aux_exposed_values,
         is_first_row: selectors.is_first_row,
         is_last_row: selectors.is_last_row,
         is_transition: selectors.is_transition,
         alpha,
+        phase_alphas,
+        constraint_phases,
+        phase_accumulators: vec![Challenge::<SC>::ZERO; phase_alphas.len()],
         accumulator: SC::Challenge::ZERO,
+        constraint_index: 0,
+        constraint_labels: Vec::new(),
     };
 
     air.eval(&mut folder);
-    let constraints_at_zeta = folder.accumulator;
+    let constraints_at_zeta = if phase_alphas.is_empty() {
+        folder.accumulator
+    } else {
+        // Every constraint must have been attributed to a phase by the verifying key
+        if folder.constraint_index != constraint_phases.len() {
+            return Err(VerificationError::VerifyingKeyMismatch(
+                "constraint count differs from the measured constraint phases",
+            ));
+        }
+        ConstraintFolding::combine(&folder.phase_accumulators)
+    };
+
+    // Lagrange coefficients (two vanishing evaluations, an inversion and a product per pair)
+    // followed by basis recombination of each chunk
+    let num_chunks = rows.quotient_chunks.len();
+    let recomposition_ops =
+        4 * num_chunks * num_chunks + num_chunks * (2 * Challenge::<SC>::DIMENSION + 1);
+    charge(
+        VerificationStep::QuotientRecomposition,
+        VerificationCost {
+            field_ops: recomposition_ops as u64,
+            ..Default::default()
+        },
+    )?;
 
     // Reconstruct quotient value from chunks using Lagrange interpolation
     let quotient_at_zeta =
-        recompose_quotient_from_chunks::<SC>(&quotient_chunk_domains, &proof.quotient_chunks, zeta);
+        recompose_quotient_from_chunks::<SC>(quotient_chunk_domains, rows.quotient_chunks, zeta)?;
 
     // Check: C(zeta) / Z_H(zeta) == Q(zeta)
     // Equivalently: C(zeta) * inv_Z_H(zeta) == Q(zeta)
     // The selector provides inv_vanishing = 1/Z_H(zeta)
     if constraints_at_zeta * selectors.inv_vanishing != quotient_at_zeta {
+        if let Some(witness) = witness {
+            *witness = Some(ConstraintWitness {
+                zeta,
+                constraints_at_zeta,
+                quotient_at_zeta,
+                is_first_row: selectors.is_first_row,
+                is_last_row: selectors.is_last_row,
+                is_transition: selectors.is_transition,
+                inv_vanishing: selectors.inv_vanishing,
+                main_local: rows.main_local.to_vec(),
+                main_next: rows.main_next.to_vec(),
+                preprocessed_local: rows.preprocessed_local.to_vec(),
+                preprocessed_next: rows.preprocessed_next.to_vec(),
+                aux_local,
+                aux_next,
+            });
+        }
         return Err(VerificationError::ConstraintVerificationFailed);
     }
 
@@ -0,0 +1,156 @@
+//! Heartbeats and a stuck-phase watchdog for long proofs
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::{ProverPhase, ProverTelemetry};
+
+/// Emits heartbeat events while proving and flags phases that run too long.
+///
+/// A background thread wakes every `heartbeat_interval` and emits a `tracing` event
+/// with target `p3_uni_stark_mt::telemetry` for the running phase: its rows done, its
+/// elapsed time and its throughput in rows per second. Once a phase has run longer
+/// than `stuck_after`, a warning is emitted and [`Watchdog::stuck_phase`] reports it,
+/// even if the prover never reports again. A last heartbeat is emitted when each phase
+/// finishes.
+#[derive(Debug)]
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    wake: Condvar,
+    stuck_after: Duration,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    running: Option<RunningPhase>,
+    stuck: Option<ProverPhase>,
+    shutdown: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RunningPhase {
+    phase: ProverPhase,
+    started: Instant,
+    rows: usize,
+    rows_done: usize,
+}
+
+impl Watchdog {
+    /// Start the watchdog thread.
+    pub fn new(heartbeat_interval: Duration, stuck_after: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            wake: Condvar::new(),
+            stuck_after,
+        });
+        let thread = thread::spawn({
+            let shared = Arc::clone(&shared);
+            move || shared.run(heartbeat_interval)
+        });
+        Self {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// The first phase that ran longer than `stuck_after`, if any.
+    pub fn stuck_phase(&self) -> Option<ProverPhase> {
+        self.shared.lock().stuck
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state stays consistent even if a holder panicked
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn run(&self, heartbeat_interval: Duration) {
+        let mut state = self.lock();
+        while !state.shutdown {
+            state = self
+                .wake
+                .wait_timeout(state, heartbeat_interval)
+                .unwrap_or_else(|err| err.into_inner())
+                .0;
+            if let Some(running) = state.running {
+                heartbeat(&running);
+                self.check_stuck(&mut state, &running);
+            }
+        }
+    }
+
+    fn check_stuck(&self, state: &mut State, running: &RunningPhase) {
+        let elapsed = running.started.elapsed();
+        if state.stuck.is_none() && elapsed > self.stuck_after {
+            state.stuck = Some(running.phase);
+            tracing::warn!(
+                target: "p3_uni_stark_mt::telemetry",
+                phase = ?running.phase,
+                elapsed_ms = elapsed.as_millis() as u64,
+                stuck_after_ms = self.stuck_after.as_millis() as u64,
+                "Prover phase is stuck"
+            );
+        }
+    }
+}
+
+fn heartbeat(running: &RunningPhase) {
+    let elapsed = running.started.elapsed();
+    let rows_per_sec = running.rows_done as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    tracing::info!(
+        target: "p3_uni_stark_mt::telemetry",
+        phase = ?running.phase,
+        rows = running.rows,
+        rows_done = running.rows_done,
+        elapsed_ms = elapsed.as_millis() as u64,
+        rows_per_sec,
+        "Prover heartbeat"
+    );
+}
+
+impl ProverTelemetry for &Watchdog {
+    fn phase_started(&mut self, phase: ProverPhase, rows: usize) {
+        self.shared.lock().running = Some(RunningPhase {
+            phase,
+            started: Instant::now(),
+            rows,
+            rows_done: 0,
+        });
+    }
+
+    fn progress(&mut self, phase: ProverPhase, rows_done: usize) {
+        let mut state = self.shared.lock();
+        if let Some(running) = state.running.as_mut().filter(|r| r.phase == phase) {
+            running.rows_done = rows_done;
+        }
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        let mut state = self.shared.lock();
+        let Some(mut running) = state.running.filter(|r| r.phase == phase) else {
+            return;
+        };
+        state.running = None;
+        running.rows_done = running.rows;
+        heartbeat(&running);
+        self.shared.check_stuck(&mut state, &running);
+    }
+}
@@ -0,0 +1,1449 @@
+//! Test for an AIR whose constraints read the auxiliary trace
+//!
+//! The main trace is a single column `a`; the auxiliary trace is a single extension
+//! column `z` holding the running sum of `a`. The constraints tie `z` to `a`, so a wrong
+//! auxiliary trace must make verification fail. A second AIR adds a later phase `w = 2z`
+//! built from the first one, a third reads the main trace LDE while building `z`, and a
+//! fourth exposes the final value of `z`, which a fifth lets the verifier recompute from
+//! the public values. A sixth commits `z`, whose values lie in the base field, as a base
+//! field column, another samples a challenge for its constraints without any aux column,
+//! and a last one builds its aux column from hints supplied at proving time. The two-phase
+//! AIR is also proven step by step through a `ProverState`, resumed from checkpoints, and
+//! from aux traces built outside the prover.
+
+use core::any::Any;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{
+    CanObserve, CanSample, CanSampleBits, DuplexChallenger, FieldChallenger, GrindingChallenger,
+};
+use p3_commit::{ExtensionMmcs, Mmcs};
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+#[cfg(feature = "fri")]
+use p3_uni_stark_mt::export_transcript;
+use p3_uni_stark_mt::tracing::span::{Attributes, Id, Record};
+use p3_uni_stark_mt::tracing::{self, Event, Metadata, Subscriber};
+use p3_uni_stark_mt::{
+    prove, prove_with_aux_trace, prove_with_challenger, prove_with_hints, replay_challenges, setup,
+    verify, verify_with_challenger, AuxBuilder, AuxTraceBuilder, ChallengePlan, MainTraceLde,
+    MultiTraceAir, ProverCheckpoint, ProverError, ProverFolder, ProverState, StarkConfig,
+    SymbolicBuilder, TranscriptItem, TranscriptOp, TranscriptSpec, TranscriptStep,
+    VerificationError, VerifierFolder,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// Running-sum AIR: `z[0] = a[0]`, `z[i+1] = z[i] + a[i+1]`
+pub struct RunningSumAir {
+    /// Build a deliberately wrong auxiliary trace
+    pub corrupt_aux: bool,
+}
+
+impl<F> BaseAir<F> for RunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, _challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut sum = EF::ZERO;
+        let mut values: Vec<EF> = main
+            .values
+            .iter()
+            .map(|&a| {
+                sum += a;
+                sum
+            })
+            .collect();
+        if self.corrupt_aux {
+            values[1] += EF::ONE;
+        }
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for RunningSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let a_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), AB::ExprEF::from(a));
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z + AB::ExprEF::from(a_next));
+    }
+}
+
+/// Two-phase AIR: phase 0 is the running sum `z`, phase 1 is `w = 2z`
+pub struct TwoPhaseAir;
+
+impl<F> BaseAir<F> for TwoPhaseAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for TwoPhaseAir {
+    fn num_aux_phases(&self) -> usize {
+        2
+    }
+
+    fn aux_phase_width(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn challenge_plan(&self) -> ChallengePlan {
+        // Phase 1 draws a challenge it does not use, so each phase has its own round
+        ChallengePlan::new()
+            .with_group(0, "running_sum", &["r"])
+            .with_group(1, "doubling", &["unused"])
+    }
+
+    fn build_aux_phase(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        match phase {
+            0 => RunningSumAir { corrupt_aux: false }.build_aux_trace(main, challenges),
+            _ => RowMajorMatrix::new(
+                prior_phases[0].values.iter().map(|&z| z.double()).collect(),
+                1,
+            ),
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for TwoPhaseAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+
+        let aux = builder.aux();
+        let row = aux.row_slice(0).expect("Matrix is empty?");
+        let z: AB::ExprEF = row[0].into();
+        let w: AB::ExprEF = row[1].into();
+        builder.assert_zero_ext_labeled(w - (z.clone() + z), "w = 2z");
+    }
+}
+
+/// Running-sum AIR whose aux builder opts into the main trace LDE
+#[derive(Default)]
+pub struct LdeRunningSumAir {
+    /// Set once the prover handed the LDE to the aux builder
+    pub saw_lde: AtomicBool,
+}
+
+impl<F> BaseAir<F> for LdeRunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for LdeRunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn uses_main_trace_lde(&self) -> bool {
+        true
+    }
+
+    fn build_aux_phase_with_lde(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        main_lde: &MainTraceLde<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        assert_eq!(main_lde.size(), main.height() << main_lde.log_blowup);
+        assert_eq!(main_lde.evaluations.height(), main_lde.size());
+        assert_eq!(main_lde.row(0).len(), main.width());
+        assert_eq!(
+            main_lde.next_row_index(main_lde.size() - 1),
+            (1 << main_lde.log_blowup) - 1
+        );
+        self.saw_lde.store(true, Ordering::Relaxed);
+
+        RunningSumAir { corrupt_aux: false }.build_aux_phase(phase, main, prior_phases, challenges)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for LdeRunningSumAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+    }
+}
+
+/// Running-sum AIR exposing the last value of `z`
+pub struct ExposedSumAir;
+
+impl<F> BaseAir<F> for ExposedSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ExposedSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        RunningSumAir { corrupt_aux: false }.build_aux_trace(main, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        vec![*aux_phase.values.last().expect("aux trace is empty")]
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ExposedSumAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+
+        let total: AB::ExprEF = builder.aux_exposed_values()[0].into();
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        builder.when_last_row().assert_eq_ext(z, total);
+    }
+}
+
+/// [`ExposedSumAir`] whose exposed sum is claimed as the public value
+pub struct PublicSumAir;
+
+impl<F> BaseAir<F> for PublicSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PublicSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        ExposedSumAir.build_aux_trace(main, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        challenges: &[EF],
+    ) -> Vec<EF> {
+        ExposedSumAir.aux_phase_exposed_values(phase, aux_phase, challenges)
+    }
+
+    fn expected_aux_exposed_values(
+        &self,
+        _phase: usize,
+        public_values: &[F],
+        _challenges: &[EF],
+    ) -> Option<Vec<EF>> {
+        Some(public_values.iter().map(|&v| EF::from(v)).collect())
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for PublicSumAir {
+    fn eval(&self, builder: &mut AB) {
+        ExposedSumAir.eval(builder);
+    }
+}
+
+/// [`RunningSumAir`] with `z` committed over the base field
+pub struct BaseFieldSumAir {
+    /// Add the challenge to `z[2]`, taking it out of the base field
+    pub leak_challenge: bool,
+}
+
+impl<F> BaseAir<F> for BaseFieldSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for BaseFieldSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn aux_phase_in_base_field(&self, _phase: usize) -> bool {
+        true
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut trace = RunningSumAir { corrupt_aux: false }.build_aux_trace(main, challenges);
+        if self.leak_challenge {
+            trace.values[2] += challenges[0];
+        }
+        trace
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for BaseFieldSumAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+    }
+}
+
+/// Running sum `z` committed as one aux matrix, and `(2z, 3z)` as a second matrix of the
+/// same phase
+pub struct SplitSumAir {
+    /// Declared matrix widths of the phase
+    pub matrix_widths: Vec<usize>,
+}
+
+impl<F> BaseAir<F> for SplitSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SplitSumAir {
+    fn aux_width(&self) -> usize {
+        3
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn aux_phase_matrix_widths(&self, _phase: usize) -> Vec<usize> {
+        self.matrix_widths.clone()
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let sums = RunningSumAir { corrupt_aux: false }.build_aux_trace(main, challenges);
+        let values = sums
+            .values
+            .iter()
+            .flat_map(|&z| [z, z.double(), z * EF::from_u8(3)])
+            .collect();
+        RowMajorMatrix::new(values, 3)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for SplitSumAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+
+        let z_col = builder.aux_matrix_columns(0).start;
+        let multiples = builder.aux_matrix_columns(1);
+        let aux = builder.aux();
+        let local = aux.row_slice(0).expect("Matrix is empty?");
+        let z: AB::ExprEF = local[z_col].into();
+        let w: AB::ExprEF = local[multiples.start].into();
+        let u: AB::ExprEF = local[multiples.start + 1].into();
+        builder.assert_eq_ext(w, z.clone() + z.clone());
+        builder.assert_eq_ext(u, z.clone() + z.clone() + z);
+    }
+}
+
+/// Columns `(a, b, c, d)` with `(a, b) = (c, d)`, checked by one constraint on a random
+/// linear combination: `a + r·b = c + r·d` for a challenge `r`, without aux columns
+pub struct RandomCombinationAir;
+
+impl<F> BaseAir<F> for RandomCombinationAir {
+    fn width(&self) -> usize {
+        4
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RandomCombinationAir {
+    fn num_challenges(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for RandomCombinationAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let column = |i: usize| {
+            let value: AB::Expr = local[i].clone().into();
+            AB::ExprEF::from(value)
+        };
+        let [a, b, c, d] = [0, 1, 2, 3].map(column);
+        let r: AB::ExprEF = builder.aux_challenges()[0].into();
+        builder.assert_eq_ext(a + b * r.clone(), c + d * r);
+    }
+}
+
+/// Main column `a` and a base field aux column `inv` with `a·inv = 1`, where the inverses
+/// are handed to the prover as hints rather than computed by the builder
+pub struct HintedInverseAir;
+
+impl<F> BaseAir<F> for HintedInverseAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for HintedInverseAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn aux_phase_in_base_field(&self, _phase: usize) -> bool {
+        true
+    }
+
+    fn build_aux_phase_with_hints(
+        &self,
+        _phase: usize,
+        _main_trace: &RowMajorMatrix<F>,
+        _prior_phases: &[RowMajorMatrix<EF>],
+        _challenges: &[EF],
+        hints: &dyn Any,
+    ) -> RowMajorMatrix<EF> {
+        let inverses: &Vec<F> = hints.downcast_ref().expect("inverses are passed as hints");
+        RowMajorMatrix::new(inverses.iter().map(|&inv| EF::from(inv)).collect(), 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for HintedInverseAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let aux = builder.aux();
+        let inv: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        builder.assert_eq_ext(AB::ExprEF::from(a) * inv, AB::ExprEF::ONE);
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new((0..n).map(|i| F::from_usize(3 * i + 1)).collect(), 1)
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_running_sum_aux() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_running_sum_corrupt_aux_rejected() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: true };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_running_sum_recorded_challenges() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let mut proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    proof.record_aux_challenges(&air);
+    assert_eq!(proof.aux_challenge_counts, Some(vec![1]));
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // A prover build sampling two challenges is reported as such
+    proof.aux_challenge_counts = Some(vec![2]);
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &[]),
+        Err(VerificationError::AuxChallengeMismatch {
+            phase: 0,
+            recorded: Some(2),
+            declared: Some(1),
+        })
+    ));
+
+    // So is one with an extra phase
+    proof.aux_challenge_counts = Some(vec![1, 1]);
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &[]),
+        Err(VerificationError::AuxChallengeMismatch {
+            phase: 1,
+            recorded: Some(1),
+            declared: None,
+        })
+    ));
+}
+
+#[test]
+fn test_running_sum_replay_challenges() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    let challenges = replay_challenges(&config, &air, &vk, &proof, &[]);
+    assert_eq!(challenges.aux_challenges.len(), 1);
+    assert_eq!(challenges.aux_challenges[0].len(), 1);
+    assert_eq!(
+        challenges,
+        replay_challenges(&config, &air, &vk, &proof, &[])
+    );
+
+    // Different public values lead to a different transcript
+    let other = replay_challenges(&config, &air, &vk, &proof, &[Val::ONE]);
+    assert_ne!(challenges.aux_challenges, other.aux_challenges);
+    assert_ne!(challenges.zeta, other.zeta);
+
+    // So does a different claimed trace height, before any commitment is observed
+    let mut resized = proof;
+    resized.log_degree += 1;
+    let other = replay_challenges(&config, &air, &vk, &resized, &[]);
+    assert_ne!(challenges.aux_challenges, other.aux_challenges);
+}
+
+#[test]
+#[cfg(feature = "fri")]
+fn test_running_sum_export_transcript() {
+    let fri_params = create_test_fri_params((), 2);
+    let config = create_config().with_fri_params(&fri_params);
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    let export = export_transcript(&config, &air, &vk, &proof, &[]).expect("export failed");
+
+    assert_eq!(export.spec, vk.transcript_spec(&config, &air, 0));
+    assert_eq!(
+        export.challenges,
+        replay_challenges(&config, &air, &vk, &proof, &[])
+    );
+    assert!(export.preprocessed_commit.is_none());
+    assert_eq!(export.main_commit, proof.main_commit);
+    assert_eq!(export.aux_commits, proof.aux_commits);
+    assert_eq!(export.quotient_commits, proof.quotient_commits);
+    assert_eq!(export.aux_exposed_values, proof.aux_exposed_values);
+
+    assert_eq!(export.opening_points.len(), 1);
+    let (zeta, zeta_next) = export.opening_points[0];
+    assert_eq!(zeta, export.challenges.zeta);
+    assert_ne!(zeta_next, zeta);
+    assert_eq!(export.openings.len(), 1);
+    assert_eq!(export.openings[0].main_local, proof.main_local);
+    assert_eq!(export.openings[0].aux_next, proof.aux_next);
+
+    // One index per FRI query, drawn from the state the whole transcript leaves
+    let query_indices = export
+        .query_indices
+        .expect("the config records its FRI parameters");
+    assert_eq!(query_indices.len(), fri_params.num_queries);
+    let again = export_transcript(&config, &air, &vk, &proof, &[]).expect("export failed");
+    let other = export_transcript(&config, &air, &vk, &proof, &[Val::ONE]).expect("export failed");
+    assert_eq!(again.query_indices.as_ref(), Some(&query_indices));
+    assert_ne!(other.query_indices.as_ref(), Some(&query_indices));
+
+    // A config without its FRI parameters cannot tell them
+    let export =
+        export_transcript(&create_config(), &air, &vk, &proof, &[]).expect("export failed");
+    assert!(export.query_indices.is_none());
+}
+
+#[test]
+fn test_running_sum_transcript_spec() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: false };
+    let (_, vk) = setup(&config, &air);
+
+    let spec = vk.transcript_spec(&config, &air, 0);
+    let step = |item, op| TranscriptStep { item, op };
+    assert_eq!(
+        spec.steps,
+        [
+            step(
+                TranscriptItem::InstanceParameterCount,
+                TranscriptOp::ObserveField(1)
+            ),
+            step(TranscriptItem::LogDegree, TranscriptOp::ObserveField(1)),
+            step(TranscriptItem::MainWidth, TranscriptOp::ObserveField(1)),
+            step(TranscriptItem::NumAuxPhases, TranscriptOp::ObserveField(1)),
+            step(
+                TranscriptItem::AuxPhaseWidth(0),
+                TranscriptOp::ObserveField(1)
+            ),
+            step(
+                TranscriptItem::NumPublicValues,
+                TranscriptOp::ObserveField(1)
+            ),
+            step(
+                TranscriptItem::MainCommitment,
+                TranscriptOp::ObserveCommitment
+            ),
+            step(TranscriptItem::AuxChallenges(0), TranscriptOp::Sample(1)),
+            step(
+                TranscriptItem::AuxCommitment(0),
+                TranscriptOp::ObserveCommitment
+            ),
+            step(TranscriptItem::Alpha, TranscriptOp::Sample(1)),
+            step(
+                TranscriptItem::QuotientCommitment(0),
+                TranscriptOp::ObserveCommitment
+            ),
+            step(TranscriptItem::Zeta(0), TranscriptOp::Sample(1)),
+            step(TranscriptItem::PcsOpening, TranscriptOp::Pcs),
+        ]
+    );
+    assert_eq!(spec.num_elements(), 12);
+
+    // Public values and narrower quotient leaves add steps
+    let config = create_config().with_max_leaf_width(3);
+    let spec = vk.transcript_spec(&config, &air, 3);
+    assert!(spec.steps.contains(&step(
+        TranscriptItem::PublicValues,
+        TranscriptOp::ObserveField(3)
+    )));
+    assert!(spec.steps.contains(&step(
+        TranscriptItem::QuotientCommitment(1),
+        TranscriptOp::ObserveCommitment
+    )));
+
+    // The specification is machine-readable
+    let json = serde_json::to_string(&spec).expect("serialization failed");
+    assert_eq!(
+        serde_json::from_str::<TranscriptSpec>(&json).expect("invalid JSON"),
+        spec
+    );
+}
+
+/// A call a [`RecordingChallenger`] received
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ChallengerCall {
+    Observe,
+    ObserveCommitment,
+    Sample,
+    SampleChallenge,
+    SampleBits(usize),
+    Grind,
+}
+
+/// [`Challenger`] recording the calls the prover and verifier make on it
+#[derive(Clone)]
+struct RecordingChallenger {
+    inner: Challenger,
+    calls: Vec<ChallengerCall>,
+}
+
+impl CanObserve<Val> for RecordingChallenger {
+    fn observe(&mut self, value: Val) {
+        self.calls.push(ChallengerCall::Observe);
+        self.inner.observe(value);
+    }
+}
+
+impl CanObserve<<ValMmcs as Mmcs<Val>>::Commitment> for RecordingChallenger {
+    fn observe(&mut self, commitment: <ValMmcs as Mmcs<Val>>::Commitment) {
+        self.calls.push(ChallengerCall::ObserveCommitment);
+        self.inner.observe(commitment);
+    }
+}
+
+impl CanSample<Val> for RecordingChallenger {
+    fn sample(&mut self) -> Val {
+        self.calls.push(ChallengerCall::Sample);
+        self.inner.sample()
+    }
+}
+
+impl CanSample<Challenge> for RecordingChallenger {
+    fn sample(&mut self) -> Challenge {
+        self.calls.push(ChallengerCall::SampleChallenge);
+        self.inner.sample()
+    }
+}
+
+impl CanSampleBits<usize> for RecordingChallenger {
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        let index = self.inner.sample_bits(bits);
+        self.calls.push(ChallengerCall::SampleBits(index));
+        index
+    }
+}
+
+impl FieldChallenger<Val> for RecordingChallenger {}
+
+impl GrindingChallenger for RecordingChallenger {
+    type Witness = Val;
+
+    fn grind(&mut self, bits: usize) -> Val {
+        self.calls.push(ChallengerCall::Grind);
+        self.inner.grind(bits)
+    }
+}
+
+type RecordingConfig = StarkConfig<Pcs, Challenge, RecordingChallenger>;
+
+/// The config of [`create_config`] with a [`RecordingChallenger`].
+fn recording_config() -> RecordingConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = Pcs::new(
+        Dft::default(),
+        val_mmcs,
+        create_test_fri_params(challenge_mmcs, 2),
+    );
+    let challenger = RecordingChallenger {
+        inner: Challenger::new(perm),
+        calls: Vec::new(),
+    };
+    RecordingConfig::new(pcs, challenger)
+}
+
+/// The indices sampled among `calls`, in order.
+fn sampled_indices(calls: &[ChallengerCall]) -> Vec<usize> {
+    calls
+        .iter()
+        .filter_map(|call| match *call {
+            ChallengerCall::SampleBits(index) => Some(index),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The calls `spec` prescribes before the PCS opening takes over the challenger.
+fn spec_calls(spec: &TranscriptSpec) -> Vec<ChallengerCall> {
+    spec.steps
+        .iter()
+        .take_while(|step| step.op != TranscriptOp::Pcs)
+        .flat_map(|step| {
+            let call = match step.op {
+                TranscriptOp::ObserveField(_) => ChallengerCall::Observe,
+                TranscriptOp::ObserveCommitment => ChallengerCall::ObserveCommitment,
+                TranscriptOp::Sample(_) => ChallengerCall::SampleChallenge,
+                TranscriptOp::Pcs => unreachable!("the PCS opening ends the prefix"),
+            };
+            core::iter::repeat_n(call, step.num_elements())
+        })
+        .collect()
+}
+
+/// Prove and verify `air` through recording challengers and check both follow the
+/// transcript specification up to the PCS opening.
+fn check_transcript_spec<A>(air: &A, public_values: &[Val])
+where
+    A: MultiTraceAir<Val, Challenge>
+        + for<'a> Air<ProverFolder<'a, RecordingConfig>>
+        + for<'a> Air<VerifierFolder<'a, RecordingConfig>>
+        + Air<SymbolicBuilder<Val, Challenge>>,
+{
+    let config = recording_config();
+    let (pk, vk) = setup(&config, air);
+    let expected = spec_calls(&vk.transcript_spec(&config, air, public_values.len()));
+
+    let mut prover_challenger = config.challenger.clone();
+    let trace = generate_trace::<Val>(1 << 4);
+    let proof = prove_with_challenger(
+        &config,
+        air,
+        &pk,
+        trace,
+        public_values,
+        &mut prover_challenger,
+    )
+    .expect("proving failed");
+    let mut verifier_challenger = config.challenger.clone();
+    verify_with_challenger(
+        &config,
+        air,
+        &vk,
+        &proof,
+        public_values,
+        &mut verifier_challenger,
+    )
+    .expect("verification failed");
+
+    // The PCS drives the challenger further, in its own way on each side, but queries the
+    // same indices; the verifier may sample for its proof-of-work check before them
+    let queried = sampled_indices(&prover_challenger.calls);
+    assert!(!queried.is_empty());
+    assert!(sampled_indices(&verifier_challenger.calls).ends_with(&queried));
+    for calls in [prover_challenger.calls, verifier_challenger.calls] {
+        assert!(calls.len() > expected.len());
+        assert_eq!(calls[..expected.len()], expected);
+    }
+}
+
+#[test]
+fn test_transcript_spec_matches_challenger_calls() {
+    check_transcript_spec(&RunningSumAir { corrupt_aux: false }, &[]);
+    check_transcript_spec(&TwoPhaseAir, &[]);
+    check_transcript_spec(&ExposedSumAir, &[]);
+}
+
+#[test]
+#[cfg(feature = "fri")]
+fn test_export_transcript_query_indices() {
+    let config = recording_config().with_fri_params(&create_test_fri_params((), 2));
+    let air = RunningSumAir { corrupt_aux: false };
+    let (pk, vk) = setup(&config, &air);
+    let trace = generate_trace::<Val>(1 << 4);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+
+    // The exported indices are the last ones the verifier samples
+    let mut challenger = config.challenger.clone();
+    verify_with_challenger(&config, &air, &vk, &proof, &[], &mut challenger)
+        .expect("verification failed");
+    let export = export_transcript(&config, &air, &vk, &proof, &[]).expect("export failed");
+    let query_indices = export
+        .query_indices
+        .expect("the config records its FRI parameters");
+    assert!(sampled_indices(&challenger.calls).ends_with(&query_indices));
+}
+
+#[test]
+fn test_two_phase_aux() {
+    let config = create_config();
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &TwoPhaseAir);
+    let proof = prove(&config, &TwoPhaseAir, &pk, trace, &[]).expect("proving failed");
+    assert_eq!(proof.aux_commits.len(), 2);
+    verify(&config, &TwoPhaseAir, &vk, &proof, &[]).expect("verification failed");
+
+    let challenges = replay_challenges(&config, &TwoPhaseAir, &vk, &proof, &[]);
+    assert_eq!(challenges.aux_challenges.len(), 2);
+    assert_ne!(challenges.aux_challenges[0], challenges.aux_challenges[1]);
+}
+
+#[test]
+fn test_two_phase_prover_state() {
+    let config = create_config();
+    let air = TwoPhaseAir;
+    let trace = generate_trace::<Val>(1 << 4);
+    let (pk, vk) = setup(&config, &air);
+
+    // Drive the phases by hand, building each aux trace outside the prover
+    let mut state =
+        ProverState::new(&config, &air, &pk, trace.clone(), &[]).expect("invalid trace");
+    let main_commit = state.commit_main();
+    let mut sampled = Vec::new();
+    while let Some(phase) = state.next_aux_phase() {
+        let challenges = state.sample_challenges();
+        let aux_trace = AuxTraceBuilder::<Val, Challenge>::build_aux_phase(
+            &air,
+            phase,
+            state.main_trace(),
+            state.aux_traces(),
+            &challenges,
+        );
+        let aux_commit = state.commit_aux(aux_trace).expect("invalid aux trace");
+        assert!(aux_commit.is_some());
+        sampled.push(challenges);
+    }
+    let proof = state.finish().expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The transcript is the one the monolithic prover writes
+    let expected = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert_eq!(main_commit, expected.main_commit);
+    assert_eq!(
+        serde_json::to_string(&proof).expect("serialization failed"),
+        serde_json::to_string(&expected).expect("serialization failed")
+    );
+    let challenges = replay_challenges(&config, &air, &vk, &proof, &[]);
+    assert_eq!(challenges.aux_challenges, sampled);
+}
+
+#[test]
+fn test_prover_state_checkpoint() {
+    let config = create_config();
+    let air = TwoPhaseAir;
+    let trace = generate_trace::<Val>(1 << 4);
+    let (pk, vk) = setup(&config, &air);
+
+    // Checkpoint while the first aux trace is being built elsewhere
+    let mut state =
+        ProverState::new(&config, &air, &pk, trace.clone(), &[]).expect("invalid trace");
+    state.commit_main();
+    let challenges = state.sample_challenges();
+    let checkpoint = state.checkpoint().expect("checkpoint failed");
+    drop(state);
+    let json = serde_json::to_string(&checkpoint).expect("serialization failed");
+    let checkpoint: ProverCheckpoint<MyConfig> =
+        serde_json::from_str(&json).expect("deserialization failed");
+
+    let mut state =
+        ProverState::resume(&config, &air, &pk, &[], checkpoint.clone()).expect("resume failed");
+    assert_eq!(state.pending_challenges(), Some(challenges.as_slice()));
+    let aux_trace = AuxTraceBuilder::<Val, Challenge>::build_aux_phase(
+        &air,
+        0,
+        state.main_trace(),
+        state.aux_traces(),
+        &challenges,
+    );
+    state.commit_aux(aux_trace).expect("invalid aux trace");
+
+    // And again after the first phase is committed
+    let resumed = state.checkpoint().expect("checkpoint failed");
+    assert_eq!(resumed.aux_traces.len(), 1);
+    let mut state = ProverState::resume(&config, &air, &pk, &[], resumed).expect("resume failed");
+    assert_eq!(state.pending_challenges(), None);
+    let challenges = state.sample_challenges();
+    let aux_trace = AuxTraceBuilder::<Val, Challenge>::build_aux_phase(
+        &air,
+        1,
+        state.main_trace(),
+        state.aux_traces(),
+        &challenges,
+    );
+    state.commit_aux(aux_trace).expect("invalid aux trace");
+    let proof = state.finish().expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The resumed proof is the one proven in one go
+    let expected = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert_eq!(
+        serde_json::to_string(&proof).expect("serialization failed"),
+        serde_json::to_string(&expected).expect("serialization failed")
+    );
+
+    // A checkpoint only resumes under the statement it was taken for
+    assert!(matches!(
+        ProverState::resume(&config, &air, &pk, &[Val::ONE], checkpoint.clone()),
+        Err(ProverError::CheckpointMismatch(_))
+    ));
+    let mut other_trace = checkpoint;
+    other_trace.main_trace.values[0] += Val::ONE;
+    assert!(matches!(
+        ProverState::resume(&config, &air, &pk, &[], other_trace),
+        Err(ProverError::CheckpointMismatch(_))
+    ));
+}
+
+#[test]
+fn test_prover_state_rejects_bad_aux_trace() {
+    let config = create_config();
+    let air = TwoPhaseAir;
+    let (pk, _) = setup(&config, &air);
+
+    let mut state = ProverState::new(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[])
+        .expect("invalid trace");
+    state.commit_main();
+    state.sample_challenges();
+    assert_eq!(
+        state.commit_aux(RowMajorMatrix::new(vec![Challenge::ZERO; 8], 1)),
+        Err(ProverError::AuxTraceShapeMismatch {
+            phase: 0,
+            expected: (1, 16),
+            actual: (1, 8),
+        })
+    );
+}
+
+#[test]
+fn test_two_phase_external_aux_trace() {
+    let config = create_config();
+    let air = TwoPhaseAir;
+    let trace = generate_trace::<Val>(1 << 4);
+    let (pk, vk) = setup(&config, &air);
+
+    // The caller keeps the phases it built, like an accelerator holding them in its memory
+    let mut built: Vec<RowMajorMatrix<Challenge>> = Vec::new();
+    let proof = prove_with_aux_trace(
+        &config,
+        &air,
+        &pk,
+        trace.clone(),
+        &[],
+        |phase, challenges| {
+            let aux_trace = AuxTraceBuilder::<Val, Challenge>::build_aux_phase(
+                &air, phase, &trace, &built, challenges,
+            );
+            built.push(aux_trace.clone());
+            aux_trace
+        },
+    )
+    .expect("proving failed");
+    assert_eq!(built.len(), 2);
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // Supplied traces are checked like built ones
+    let result = prove_with_aux_trace(&config, &air, &pk, trace, &[], |_, _| {
+        RowMajorMatrix::new(vec![Challenge::ZERO; 8], 1)
+    });
+    assert_eq!(
+        result.err(),
+        Some(ProverError::AuxTraceShapeMismatch {
+            phase: 0,
+            expected: (1, 16),
+            actual: (1, 8),
+        })
+    );
+}
+
+#[cfg(feature = "debug")]
+#[test]
+fn test_check_constraints_finds_bad_row() {
+    use p3_uni_stark_mt::debug::check_constraints;
+
+    let main = generate_trace::<Val>(1 << 4);
+    let challenges = vec![vec![Challenge::from_u32(7)]];
+
+    let good = RunningSumAir { corrupt_aux: false };
+    let aux = vec![good.build_aux_trace(&main, &challenges[0])];
+    assert_eq!(
+        check_constraints::<MyConfig, _>(&good, &main, &aux, &challenges, &[]),
+        Ok(())
+    );
+
+    // The running sum is off by one on row 1, so the transition from row 0 fails first
+    let bad = RunningSumAir { corrupt_aux: true };
+    let aux = vec![bad.build_aux_trace(&main, &challenges[0])];
+    let violation = check_constraints::<MyConfig, _>(&bad, &main, &aux, &challenges, &[])
+        .expect_err("the corrupted running sum passed");
+    assert_eq!((violation.row, violation.constraint), (0, 1));
+    assert_eq!(violation.value, Challenge::ONE);
+    assert_eq!(violation.label, None);
+
+    // Both phases of the two-phase AIR are read
+    let mut aux = vec![good.build_aux_trace(&main, &challenges[0])];
+    aux.push(RowMajorMatrix::new(
+        aux[0].values.iter().map(|&z| z.double()).collect(),
+        1,
+    ));
+    let challenges = vec![challenges[0].clone(), vec![Challenge::from_u32(9)]];
+    assert_eq!(
+        check_constraints::<MyConfig, _>(&TwoPhaseAir, &main, &aux, &challenges, &[]),
+        Ok(())
+    );
+    aux[1].values[5] += Challenge::ONE;
+    let violation = check_constraints::<MyConfig, _>(&TwoPhaseAir, &main, &aux, &challenges, &[])
+        .expect_err("the corrupted second phase passed");
+    assert_eq!((violation.row, violation.constraint), (5, 2));
+
+    // The constraint asserted with a label is reported by it
+    assert_eq!(violation.label, Some("w = 2z"));
+    assert!(violation
+        .to_string()
+        .starts_with("constraint 2 (w = 2z) evaluates to"));
+}
+
+#[test]
+fn test_two_phase_per_phase_alpha() {
+    let config = create_config().with_per_phase_alpha();
+    let trace = generate_trace::<Val>(1 << 4);
+
+    // The running sum reads phase 0 and `w = 2z` reads phase 1
+    let (pk, vk) = setup(&config, &TwoPhaseAir);
+    assert_eq!(vk.constraint_phases, vec![1, 1, 2]);
+    let proof = prove(&config, &TwoPhaseAir, &pk, trace, &[]).expect("proving failed");
+    verify(&config, &TwoPhaseAir, &vk, &proof, &[]).expect("verification failed");
+
+    // An alpha is sampled after the main trace and after each aux phase, instead of once
+    let spec = vk.transcript_spec(&config, &TwoPhaseAir, 0);
+    let items: Vec<_> = spec.steps.iter().map(|step| step.item).collect();
+    assert!(!items.contains(&TranscriptItem::Alpha));
+    let phase_alphas: Vec<_> = items
+        .iter()
+        .filter(|item| matches!(item, TranscriptItem::PhaseAlpha(_)))
+        .collect();
+    assert_eq!(
+        phase_alphas,
+        [0, 1, 2]
+            .map(TranscriptItem::PhaseAlpha)
+            .iter()
+            .collect::<Vec<_>>()
+    );
+
+    // The single-alpha setting does not measure phases and rejects the proof
+    let single = create_config();
+    let (_, single_vk) = setup(&single, &TwoPhaseAir);
+    assert!(single_vk.constraint_phases.is_empty());
+    assert!(verify(&single, &TwoPhaseAir, &single_vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_running_sum_with_main_lde() {
+    let config = create_config();
+    let air = LdeRunningSumAir::default();
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(air.saw_lde.load(Ordering::Relaxed));
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_exposed_running_sum() {
+    let config = create_config();
+    let air = ExposedSumAir;
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    // 1 + 4 + ... + 46
+    assert_eq!(
+        proof.aux_exposed_values,
+        vec![vec![Challenge::from_u32(376)]]
+    );
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The constraints tie the exposed sum to the committed column
+    let mut tampered = proof.clone();
+    tampered.aux_exposed_values[0][0] += Challenge::ONE;
+    assert!(verify(&config, &air, &vk, &tampered, &[]).is_err());
+
+    let mut missing = proof;
+    missing.aux_exposed_values[0].clear();
+    assert!(verify(&config, &air, &vk, &missing, &[]).is_err());
+}
+
+#[test]
+fn test_exposed_sum_recomputed_from_public_values() {
+    let config = create_config();
+    let air = PublicSumAir;
+    let (pk, vk) = setup(&config, &air);
+
+    let public_values = [Val::from_u32(376)];
+    let proof = prove(
+        &config,
+        &air,
+        &pk,
+        generate_trace::<Val>(1 << 4),
+        &public_values,
+    )
+    .expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // The constraints hold for the committed sum, but it is not the claimed one
+    let wrong_public_values = [Val::from_u32(377)];
+    let proof = prove(
+        &config,
+        &air,
+        &pk,
+        generate_trace::<Val>(1 << 4),
+        &wrong_public_values,
+    )
+    .expect("proving failed");
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &wrong_public_values),
+        Err(VerificationError::ExposedValueMismatch { phase: 0 })
+    ));
+}
+
+/// Running-sum AIR whose aux builder opens its own span
+pub struct SpannedRunningSumAir;
+
+impl<F> BaseAir<F> for SpannedRunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SpannedRunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        tracing::info_span!("running_sum")
+            .in_scope(|| RunningSumAir { corrupt_aux: false }.build_aux_trace(main, challenges))
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for SpannedRunningSumAir {
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir { corrupt_aux: false }.eval(builder);
+    }
+}
+
+/// Records every span's name and the name of the span it was opened in.
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    next_id: Arc<AtomicU64>,
+    names: Arc<Mutex<Vec<&'static str>>>,
+    stack: Arc<Mutex<Vec<u64>>>,
+    spans: Arc<Mutex<Vec<(&'static str, Option<&'static str>)>>>,
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut names = self.names.lock().unwrap();
+        let parent = self
+            .stack
+            .lock()
+            .unwrap()
+            .last()
+            .map(|&p| names[p as usize]);
+        names.push(span.metadata().name());
+        self.spans
+            .lock()
+            .unwrap()
+            .push((span.metadata().name(), parent));
+        Id::from_u64(id + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64() - 1);
+    }
+
+    fn exit(&self, _span: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+#[test]
+fn test_aux_builder_spans() {
+    let config = create_config();
+    let air = SpannedRunningSumAir;
+    let trace = generate_trace::<Val>(1 << 4);
+    let (pk, vk) = setup(&config, &air);
+
+    let recorder = SpanRecorder::default();
+    let proof = tracing::subscriber::with_default(recorder.clone(), || {
+        prove(&config, &air, &pk, trace, &[]).expect("proving failed")
+    });
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The builder's own span nests under the prover's
+    let spans = recorder.spans.lock().unwrap();
+    assert!(spans.contains(&("build_aux_trace", Some("auxiliary phase"))));
+    assert!(spans.contains(&("running_sum", Some("build_aux_trace"))));
+}
+
+#[test]
+fn test_base_field_aux_phase() {
+    let config = create_config();
+    let air = BaseFieldSumAir {
+        leak_challenge: false,
+    };
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // One committed column instead of one per extension coordinate
+    let aux_local = proof.aux_local.as_ref().expect("proof has aux openings");
+    assert_eq!(aux_local[0].len(), 1);
+
+    // The same proof read as an extension phase has too few openings
+    assert!(verify(
+        &config,
+        &RunningSumAir { corrupt_aux: false },
+        &vk,
+        &proof,
+        &[]
+    )
+    .is_err());
+
+    let air = BaseFieldSumAir {
+        leak_challenge: true,
+    };
+    assert_eq!(
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).err(),
+        Some(ProverError::AuxTraceNotInBaseField { phase: 0, row: 2 })
+    );
+}
+
+#[test]
+fn test_aux_phase_with_several_matrices() {
+    let config = create_config();
+    let air = SplitSumAir {
+        matrix_widths: vec![1, 2],
+    };
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The openings of both matrices, side by side in the phase
+    let aux_local = proof.aux_local.as_ref().expect("proof has aux openings");
+    assert_eq!(aux_local[0].len(), 3 * 4);
+
+    // The same openings checked as one matrix do not match the commitment
+    let single = SplitSumAir {
+        matrix_widths: vec![3],
+    };
+    assert!(verify(&config, &single, &vk, &proof, &[]).is_err());
+
+    let air = SplitSumAir {
+        matrix_widths: vec![1, 1],
+    };
+    assert_eq!(
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).err(),
+        Some(ProverError::AuxMatrixWidthsMismatch(0))
+    );
+}
+
+#[test]
+fn test_challenges_without_aux_columns() {
+    let config = create_config();
+    let air = RandomCombinationAir;
+    let trace = |corrupt: bool| {
+        let mut values: Vec<Val> = (0..1 << 4)
+            .flat_map(|i| {
+                let (a, b) = (Val::from_usize(i), Val::from_usize(2 * i + 1));
+                [a, b, a, b]
+            })
+            .collect();
+        if corrupt {
+            values[4 * 3 + 3] += Val::ONE;
+        }
+        RowMajorMatrix::new(values, 4)
+    };
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace(false), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The challenge is sampled, but nothing is committed or opened for the phase
+    assert!(proof.aux_commits.is_empty());
+    assert!(proof.aux_local.is_none() && proof.aux_next.is_none());
+    let challenges = replay_challenges(&config, &air, &vk, &proof, &[]);
+    assert_eq!(challenges.aux_challenges.len(), 1);
+    assert_eq!(challenges.aux_challenges[0].len(), 1);
+    let spec = vk.transcript_spec(&config, &air, 0);
+    assert!(spec.steps.contains(&TranscriptStep {
+        item: TranscriptItem::AuxChallenges(0),
+        op: TranscriptOp::Sample(1),
+    }));
+    assert!(!spec
+        .steps
+        .iter()
+        .any(|step| step.item == TranscriptItem::AuxCommitment(0)));
+
+    // A witness breaking one of the combined equalities is rejected
+    let proof = prove(&config, &air, &pk, trace(true), &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_aux_trace_from_hints() {
+    let config = create_config();
+    let air = HintedInverseAir;
+    let trace = generate_trace::<Val>(1 << 4);
+    let mut inverses: Vec<Val> = trace.values.iter().map(|a| a.inverse()).collect();
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove_with_hints(&config, &air, &pk, trace.clone(), &[], &inverses)
+        .expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // Hints are not trusted: a wrong inverse fails the constraint
+    inverses[5] = Val::ONE;
+    let proof =
+        prove_with_hints(&config, &air, &pk, trace, &[], &inverses).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
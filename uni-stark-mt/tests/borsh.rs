@@ -0,0 +1,69 @@
+//! Test for the Borsh encoding of proofs and verifying keys
+//!
+//! A fixture proof and its key round-trip through Borsh and still verify; malformed and
+//! oversized encodings are rejected.
+
+#![cfg(all(feature = "borsh", feature = "fixtures"))]
+
+use p3_uni_stark_mt::borsh::proof_from_borsh;
+use p3_uni_stark_mt::fixtures::{fibonacci_proof, FixtureConfig, FIXTURE_SEED};
+use p3_uni_stark_mt::{verify, Proof, ProofLimits, VerifyingKey, PROOF_VERSION};
+
+#[test]
+fn test_borsh_round_trip() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+
+    let proof_bytes = borsh::to_vec(&fixture.proof).expect("proof encoding failed");
+    let vk_bytes = borsh::to_vec(&fixture.vk).expect("key encoding failed");
+    let proof: Proof<FixtureConfig> =
+        borsh::from_slice(&proof_bytes).expect("proof decoding failed");
+    let vk: VerifyingKey<FixtureConfig> =
+        borsh::from_slice(&vk_bytes).expect("key decoding failed");
+    verify(
+        &fixture.config,
+        &fixture.air,
+        &vk,
+        &proof,
+        &fixture.public_values,
+    )
+    .expect("verification failed");
+
+    // The encoding is canonical and starts with the little-endian protocol version
+    assert_eq!(borsh::to_vec(&proof).unwrap(), proof_bytes);
+    assert_eq!(borsh::to_vec(&vk).unwrap(), vk_bytes);
+    assert_eq!(proof_bytes[..2], PROOF_VERSION.to_le_bytes());
+
+    // Truncated and extended encodings are rejected
+    let truncated = &proof_bytes[..proof_bytes.len() - 1];
+    assert!(borsh::from_slice::<Proof<FixtureConfig>>(truncated).is_err());
+    let mut extended = proof_bytes.clone();
+    extended.push(0);
+    assert!(borsh::from_slice::<Proof<FixtureConfig>>(&extended).is_err());
+}
+
+#[test]
+fn test_borsh_limits() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let limits = ProofLimits::for_air::<FixtureConfig, _>(&fixture.air, &fixture.vk);
+
+    let proof_bytes = borsh::to_vec(&fixture.proof).expect("proof encoding failed");
+    let proof = proof_from_borsh::<FixtureConfig>(&proof_bytes, limits).expect("decoding failed");
+    verify(
+        &fixture.config,
+        &fixture.air,
+        &fixture.vk,
+        &proof,
+        &fixture.public_values,
+    )
+    .expect("verification failed");
+
+    let mut extended = proof_bytes;
+    extended.push(0);
+    assert!(proof_from_borsh::<FixtureConfig>(&extended, limits).is_err());
+
+    let mut widened = fixture.proof;
+    widened.main_local.extend(widened.main_local.clone());
+    let widened = borsh::to_vec(&widened).expect("proof encoding failed");
+    assert!(borsh::from_slice::<Proof<FixtureConfig>>(&widened).is_ok());
+    assert!(proof_from_borsh::<FixtureConfig>(&widened, limits).is_err());
+}
@@ -0,0 +1,169 @@
+//! Test for the byte operation lookup chip
+//!
+//! A table computing `c = a ^ b` sends its triples to an XOR chip over 4-bit operands,
+//! whose 256-row preprocessed table keeps the test fast; the two are proven together.
+
+#![cfg(feature = "lookups")]
+
+use p3_air::{Air, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove_multi, setup, verify_multi, AuxBuilder, AuxTraceBuilder, BusInteractions, ByteOp,
+    ByteOpChip, ByteOpCounts, InteractionBuilder, VerificationError,
+};
+
+const XOR_BUS: usize = 2;
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// The tables of the test machine
+pub enum Table {
+    /// Rows `(a, b, c)` looking up `c = a ^ b`
+    Xors(BusInteractions),
+    /// The XOR chip
+    Xor(ByteOpChip),
+}
+
+fn xor_chip() -> ByteOpChip {
+    ByteOpChip {
+        op: ByteOp::Xor,
+        bits: 4,
+        bus: XOR_BUS,
+    }
+}
+
+fn tables() -> [Table; 2] {
+    let chip = xor_chip();
+    let xors = chip.register(InteractionBuilder::new(), A, B, C).build();
+    [Table::Xors(xors), Table::Xor(chip)]
+}
+
+impl<F: Field> BaseAir<F> for Table {
+    fn width(&self) -> usize {
+        match self {
+            Self::Xors(_) => 3,
+            Self::Xor(chip) => BaseAir::<F>::width(chip),
+        }
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        match self {
+            Self::Xors(_) => None,
+            Self::Xor(chip) => chip.preprocessed_trace(),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for Table {
+    fn aux_width(&self) -> usize {
+        match self {
+            Self::Xors(bus) => bus.aux_width(),
+            Self::Xor(chip) => AuxTraceBuilder::<F, EF>::aux_width(chip),
+        }
+    }
+
+    fn num_challenges(&self) -> usize {
+        BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        match self {
+            Self::Xors(bus) => bus.build_aux_trace(main, challenges),
+            Self::Xor(chip) => chip.build_aux_trace(main, challenges),
+        }
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        match self {
+            Self::Xors(bus) => vec![bus.cumulative_sum(aux_phase, 0)],
+            Self::Xor(chip) => vec![chip.cumulative_sum(aux_phase)],
+        }
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for Table {
+    fn eval(&self, builder: &mut AB) {
+        match self {
+            Self::Xors(bus) => bus.eval(builder, 0, 0),
+            Self::Xor(chip) => chip.eval(builder),
+        }
+    }
+}
+
+/// Rows `(a, b, a ^ b)` over 4-bit operands
+fn xors_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let values = (0..n)
+        .flat_map(|i| {
+            let (a, b) = ((3 * i + 1) as u8 & 0xf, (5 * i + 2) as u8 & 0xf);
+            [a, b, a ^ b].map(F::from_u8)
+        })
+        .collect();
+    RowMajorMatrix::new(values, 3)
+}
+
+#[test]
+fn test_xor_lookups() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = tables();
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let xors = xors_trace::<Val>(1 << 4);
+    let mut counts = ByteOpCounts::new(&xor_chip());
+    counts.record_columns(&xors, A, B);
+    let traces = vec![xors, counts.trace()];
+
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    verify_multi(&config, &airs, &vks, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_wrong_xor_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = tables();
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    // Row 5 claims a ^ b is one more than it is; the chip still counts the real triple
+    let mut xors = xors_trace::<Val>(1 << 4);
+    let mut counts = ByteOpCounts::new(&xor_chip());
+    counts.record_columns(&xors, A, B);
+    xors.values[5 * 3 + C] += Val::ONE;
+    let traces = vec![xors, counts.trace()];
+
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &proof, &public_values),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+}
+
+#[test]
+fn test_byte_op_table() {
+    let chip = ByteOpChip::new(ByteOp::And, XOR_BUS);
+    let table: RowMajorMatrix<Val> = chip.preprocessed_trace().expect("the chip has a table");
+    assert_eq!(table.values.len(), 3 << 16);
+
+    // Row a·256 + b holds (a, b, a & b)
+    let row = 0xa5 * 256 + 0x3c;
+    assert_eq!(
+        table.values[3 * row..3 * row + 3],
+        [0xa5, 0x3c, 0xa5 & 0x3c].map(Val::from_u8)
+    );
+}
@@ -0,0 +1,160 @@
+//! Test for proving over Mersenne31 with the circle PCS
+//!
+//! Mersenne31 has no large two-adic subgroup, so its traces live on circle domains and are
+//! committed by `CirclePcs`. The cube-sum AIR below has a degree-3 constraint, so its
+//! quotient is split into two chunks, and a running-sum aux phase, so the aux commitment
+//! and the extension field openings go through the circle PCS too.
+
+use core::marker::PhantomData;
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::CirclePcs;
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::create_test_fri_params;
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher};
+use p3_uni_stark_mt::{
+    prove, prove_multi, setup, verify, verify_multi, AuxBuilder, AuxTraceBuilder, StarkConfig,
+};
+
+/// Counter `a` with `b = a³` and an aux running sum `z` of `r·b`.
+pub struct CubeSumAir;
+
+impl<F> BaseAir<F> for CubeSumAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CubeSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut sum = EF::ZERO;
+        let values = main
+            .rows()
+            .map(|mut row| {
+                sum += challenges[0] * row.nth(1).expect("Row has two columns");
+                sum
+            })
+            .collect();
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for CubeSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let (a, b): (AB::Expr, AB::Expr) = (local[0].clone().into(), local[1].clone().into());
+        let (a_next, b_next): (AB::Expr, AB::Expr) =
+            (next[0].clone().into(), next[1].clone().into());
+
+        builder.when_first_row().assert_zero(a.clone());
+        builder
+            .when_transition()
+            .assert_eq(a_next, a.clone() + AB::Expr::ONE);
+        builder.assert_eq(b.clone(), a.cube());
+
+        // z[0] = r·b[0], z[i+1] = z[i] + r·b[i+1]
+        let r: AB::ExprEF = builder.aux_challenges()[0].into();
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), r.clone() * b);
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z + r * b_next);
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let values = (0..n)
+        .flat_map(|i| {
+            let a = F::from_usize(i);
+            [a, a.cube()]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+type Val = Mersenne31;
+type Challenge = BinomialExtensionField<Val, 3>;
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let byte_hash = ByteHash {};
+    let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let pcs = Pcs {
+        mmcs: val_mmcs,
+        fri_params: create_test_fri_params(challenge_mmcs, 0),
+        _phantom: PhantomData,
+    };
+    MyConfig::new(pcs, Challenger::from_hasher(vec![], byte_hash))
+}
+
+#[test]
+fn test_circle_cube_sum() {
+    let config = create_config();
+    let (pk, vk) = setup(&config, &CubeSumAir);
+    assert_eq!(vk.max_constraint_degree, 3);
+
+    let proof = prove(
+        &config,
+        &CubeSumAir,
+        &pk,
+        generate_trace::<Val>(1 << 5),
+        &[],
+    )
+    .expect("proving failed");
+    assert_eq!(proof.quotient_chunks.len(), 2);
+    verify(&config, &CubeSumAir, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_circle_bad_trace_rejected() {
+    let config = create_config();
+    let (pk, vk) = setup(&config, &CubeSumAir);
+
+    let mut trace = generate_trace::<Val>(1 << 5);
+    trace.values[7] += Val::ONE;
+    let proof = prove(&config, &CubeSumAir, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &CubeSumAir, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_circle_multi() {
+    let config = create_config();
+    let airs = [CubeSumAir, CubeSumAir];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    // Tables of different heights share one opening proof
+    let traces = vec![generate_trace::<Val>(1 << 3), generate_trace::<Val>(1 << 4)];
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    verify_multi(&config, &airs, &vks, &proof, &public_values).expect("verification failed");
+}
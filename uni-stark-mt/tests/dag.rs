@@ -0,0 +1,260 @@
+//! Test for proving a DAG of statements linked through their public values
+//!
+//! Each statement is a Fibonacci run from public starting values `(a, b)` to a public final
+//! value `x`. Two runs feed their final values into the starting values of a third.
+//!
+//! The constraint DAG of an AIR is checked too: it folds the constants of expressions built
+//! without folding them.
+
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    setup, verify_aggregated, verify_bundle, AuxTraceBuilder, ConstraintDag, DagError, ProofDag,
+    StarkConfig, StatementId, StatementWitness, SymbolicConstraint, SymbolicEntry,
+    SymbolicExpression,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// Fibonacci run with public values `[a, b, x]`: first row `(a, b)`, last `right = x`
+pub struct LinkedFibonacciAir;
+
+impl<F> BaseAir<F> for LinkedFibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for LinkedFibonacciAir {
+    fn aux_width(&self) -> usize {
+        0
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for LinkedFibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let (a, b, x) = (
+            builder.public_values()[0],
+            builder.public_values()[1],
+            builder.public_values()[2],
+        );
+        let (left, right) = (local[0].clone(), local[1].clone());
+
+        builder.when_first_row().assert_eq(left.clone(), a);
+        builder.when_first_row().assert_eq(right.clone(), b);
+        builder
+            .when_transition()
+            .assert_eq(right.clone(), next[0].clone());
+        builder
+            .when_transition()
+            .assert_eq(left + right.clone(), next[1].clone());
+        builder.when_last_row().assert_eq(right, x);
+    }
+}
+
+/// Witness generator for `n` rows from `(a, b)`, unless linked inputs override them.
+fn fibonacci_witness(
+    n: usize,
+    a: u64,
+    b: u64,
+) -> impl FnOnce(&[(usize, Val)]) -> StatementWitness<Val> {
+    move |inputs| {
+        let mut start = [Val::from_u64(a), Val::from_u64(b)];
+        for &(input, value) in inputs {
+            start[input] = value;
+        }
+        let [mut left, mut right] = start;
+        let mut values = Vec::with_capacity(2 * n);
+        for i in 0..n {
+            values.extend([left, right]);
+            if i + 1 < n {
+                (left, right) = (right, left + right);
+            }
+        }
+        StatementWitness {
+            main_trace: RowMajorMatrix::new(values, 2),
+            public_values: vec![start[0], start[1], right],
+        }
+    }
+}
+
+/// One `a^2 * b = c` operation of `MulAir`, with `a` stepping by 20 from row to row
+struct SingleMulAir;
+
+impl<F> BaseAir<F> for SingleMulAir {
+    fn width(&self) -> usize {
+        3
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SingleMulAir {}
+
+impl<AB: AirBuilder> Air<AB> for SingleMulAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let a: AB::Expr = local[0].clone().into();
+        builder.assert_zero(a.clone().exp_u64(2) * local[1].clone() - local[2].clone());
+        builder
+            .when_transition()
+            .assert_eq(a + AB::Expr::from_u8(20), next[0].clone());
+    }
+}
+
+/// The constraints of [`SingleMulAir`] as a code generator might emit them, without
+/// folding constants: `a^2` as `1 * a * a` and the step as `10 + 10`
+fn unfolded_mul_constraints() -> Vec<SymbolicConstraint<Val, Challenge>> {
+    type Expr = SymbolicExpression<Val>;
+    let entry = |entry| Arc::new(Expr::Entry(entry));
+    let main = |offset, column| entry(SymbolicEntry::Main { offset, column });
+    let constant = |value| Arc::new(Expr::Constant(Val::from_u8(value)));
+    let binary = |op: fn(Arc<Expr>, Arc<Expr>) -> Expr, a, b| Arc::new(op(a, b));
+
+    let a_squared = binary(
+        Expr::Mul,
+        binary(Expr::Mul, constant(1), main(0, 0)),
+        main(0, 0),
+    );
+    let mul = Expr::Sub(binary(Expr::Mul, a_squared, main(0, 1)), main(0, 2));
+    let step = binary(Expr::Add, constant(10), constant(10));
+    let transition = Expr::Mul(
+        entry(SymbolicEntry::IsTransition),
+        binary(Expr::Sub, binary(Expr::Add, main(0, 0), step), main(1, 0)),
+    );
+    vec![
+        SymbolicConstraint::Base(mul),
+        SymbolicConstraint::Base(transition),
+    ]
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+/// The consuming statement is added first, so proving order differs from statement order.
+fn build_dag(config: &MyConfig) -> ProofDag<'static, MyConfig, LinkedFibonacciAir> {
+    let mut dag = ProofDag::new();
+    let (pk, _) = setup(config, &LinkedFibonacciAir);
+    let joined = dag.add_statement(LinkedFibonacciAir, pk, fibonacci_witness(1 << 2, 0, 0));
+    let (pk, _) = setup(config, &LinkedFibonacciAir);
+    let first = dag.add_statement(LinkedFibonacciAir, pk, fibonacci_witness(1 << 3, 0, 1));
+    let (pk, _) = setup(config, &LinkedFibonacciAir);
+    let second = dag.add_statement(LinkedFibonacciAir, pk, fibonacci_witness(1 << 2, 1, 2));
+    dag.link(first, 2, joined, 0);
+    dag.link(second, 2, joined, 1);
+    dag
+}
+
+#[test]
+fn test_dag_bundle() {
+    let config = create_config();
+    let dag = build_dag(&config);
+    assert_eq!(
+        dag.proving_order(),
+        Ok(vec![StatementId(1), StatementId(2), StatementId(0)])
+    );
+    let links = dag.links().to_vec();
+    let bundle = dag.prove_bundle(&config).expect("proving failed");
+
+    // 0, 1, ..., 21 and 1, 2, ..., 8 feed 21, 8, 29, 37, 66
+    assert_eq!(
+        bundle.public_values,
+        [[21, 8, 66], [0, 1, 21], [1, 2, 8]].map(|values| values.map(Val::from_u64).to_vec())
+    );
+
+    let airs = [LinkedFibonacciAir, LinkedFibonacciAir, LinkedFibonacciAir];
+    let vks: Vec<_> = airs.iter().map(|air| setup(&config, air).1).collect();
+    verify_bundle(&config, &airs, &vks, &links, &bundle).expect("verification failed");
+
+    // A consumed value no longer matching its producer is rejected
+    let mut unlinked = bundle;
+    unlinked.public_values[0][0] += Val::ONE;
+    assert!(verify_bundle(&config, &airs, &vks, &links, &unlinked).is_err());
+}
+
+#[test]
+fn test_dag_aggregated() {
+    let config = create_config();
+    let dag = build_dag(&config);
+    let links = dag.links().to_vec();
+    let proof = dag.prove_aggregated(&config).expect("proving failed");
+    assert_eq!(proof.proof.tables.len(), 3);
+
+    let airs = [LinkedFibonacciAir, LinkedFibonacciAir, LinkedFibonacciAir];
+    let vks: Vec<_> = airs.iter().map(|air| setup(&config, air).1).collect();
+    verify_aggregated(&config, &airs, &vks, &links, &proof).expect("verification failed");
+
+    // Linking the starting values instead of the final ones does not hold
+    let mut miswired = links;
+    miswired.iter_mut().for_each(|link| link.output = 0);
+    assert!(verify_aggregated(&config, &airs, &vks, &miswired, &proof).is_err());
+}
+
+#[test]
+fn test_dag_cycle() {
+    let config = create_config();
+    let mut dag = build_dag(&config);
+    dag.link(StatementId(0), 2, StatementId(1), 0);
+    assert_eq!(dag.proving_order(), Err(DagError::Cycle));
+    assert!(matches!(dag.prove_bundle(&config), Err(DagError::Cycle)));
+}
+
+#[test]
+fn test_constraint_dag_folds_constants() {
+    // Unfolded, the constraints have 15 distinct subexpressions: `1`, `1 * a`, `10` and
+    // `10 + 10` fold into the node of `a` and a new constant `20`
+    let dag = ConstraintDag::from_constraints(&unfolded_mul_constraints());
+    assert_eq!(dag.num_nodes(), 12);
+
+    // The same DAG as the AIR's, whose expressions fold as they are built
+    let expected = ConstraintDag::<Val, Challenge>::new(&SingleMulAir, 0);
+    assert_eq!(dag.num_nodes(), expected.num_nodes());
+    assert_eq!(dag.constraint_degrees(), expected.constraint_degrees());
+}
@@ -0,0 +1,140 @@
+//! Differential tests against upstream p3-uni-stark
+//!
+//! For AIRs without aux columns, this crate proves the same statements as upstream over the
+//! same PCS. The transcripts are not byte-compatible: this crate also binds the AIR
+//! instance parameters and the trace shape before the main commitment, so the challenges
+//! differ and neither verifier accepts the other's proof. What must agree is everything
+//! committed before the first challenge, the proof shape, the order of the steps upstream
+//! has, and the verdicts of both verifiers.
+
+#![cfg(all(feature = "fixtures", feature = "prover"))]
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::{ProverConstraintFolder, SymbolicAirBuilder, VerifierConstraintFolder};
+use p3_uni_stark_mt::fixtures::{
+    baby_bear_config, baby_bear_pcs, fibonacci_trace, Challenge, Challenger, FibonacciAir,
+    FixtureConfig, Pcs, Val, FIXTURE_SEED,
+};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxTraceBuilder, MultiTraceAir, ProverFolder, SymbolicBuilder,
+    TranscriptItem, VerifierFolder,
+};
+
+type UpstreamConfig = p3_uni_stark::StarkConfig<Pcs, Challenge, Challenger>;
+
+/// The transcript steps upstream has, in its order.
+const UPSTREAM_ITEMS: [TranscriptItem; 6] = [
+    TranscriptItem::MainCommitment,
+    TranscriptItem::PublicValues,
+    TranscriptItem::Alpha,
+    TranscriptItem::QuotientCommitment(0),
+    TranscriptItem::Zeta(0),
+    TranscriptItem::PcsOpening,
+];
+
+/// Prove `trace` of `air` with both crates and check that they agree.
+fn check_against_upstream<A>(air: &A, trace: RowMajorMatrix<Val>, public_values: Vec<Val>)
+where
+    A: MultiTraceAir<Val, Challenge>
+        + for<'a> Air<ProverFolder<'a, FixtureConfig>>
+        + for<'a> Air<VerifierFolder<'a, FixtureConfig>>
+        + Air<SymbolicBuilder<Val, Challenge>>
+        + Air<SymbolicAirBuilder<Val>>
+        + for<'a> Air<ProverConstraintFolder<'a, UpstreamConfig>>
+        + for<'a> Air<VerifierConstraintFolder<'a, UpstreamConfig>>,
+{
+    assert_eq!(
+        AuxTraceBuilder::<Val, Challenge>::aux_width(air),
+        0,
+        "upstream has no aux trace"
+    );
+    let config = baby_bear_config(FIXTURE_SEED);
+    let (pcs, challenger) = baby_bear_pcs(FIXTURE_SEED);
+    let upstream_config = UpstreamConfig::new(pcs, challenger);
+
+    let (pk, vk) = setup(&config, air);
+    let proof = prove(&config, air, &pk, trace.clone(), &public_values).expect("trace is valid");
+    let upstream_proof = p3_uni_stark::prove(&upstream_config, air, trace, &public_values);
+    verify(&config, air, &vk, &proof, &public_values).expect("verification failed");
+    p3_uni_stark::verify(&upstream_config, air, &upstream_proof, &public_values)
+        .expect("upstream verification failed");
+
+    // Same trace and PCS: same main commitment and proof shape
+    assert_eq!(proof.main_commit, upstream_proof.commitments.trace);
+    assert_eq!(usize::from(proof.log_degree), upstream_proof.degree_bits);
+    assert_eq!(
+        proof.main_local.len(),
+        upstream_proof.opened_values.trace_local.len()
+    );
+    let upstream_chunks = &upstream_proof.opened_values.quotient_chunks;
+    assert_eq!(proof.quotient_chunks.len(), upstream_chunks.len());
+    for (chunk, upstream_chunk) in proof.quotient_chunks.iter().zip(upstream_chunks) {
+        assert_eq!(chunk.len(), upstream_chunk.len());
+    }
+
+    // Upstream's steps appear in this crate's transcript in the same order
+    let spec = vk.transcript_spec(&config, air, public_values.len());
+    let shared: Vec<_> = spec
+        .steps
+        .iter()
+        .map(|step| step.item)
+        .filter(|item| UPSTREAM_ITEMS.contains(item))
+        .collect();
+    assert_eq!(shared, UPSTREAM_ITEMS);
+
+    // Both reject their proof against other public values
+    if let Some((first, rest)) = public_values.split_first() {
+        let wrong: Vec<_> = core::iter::once(*first + Val::ONE)
+            .chain(rest.iter().copied())
+            .collect();
+        assert!(verify(&config, air, &vk, &proof, &wrong).is_err());
+        assert!(p3_uni_stark::verify(&upstream_config, air, &upstream_proof, &wrong).is_err());
+    }
+}
+
+#[test]
+fn test_fibonacci_matches_upstream() {
+    for log_n in [3, 6] {
+        let trace = fibonacci_trace::<Val>(log_n);
+        let public_values = vec![*trace.values.last().expect("trace is empty")];
+        check_against_upstream(&FibonacciAir, trace, public_values);
+    }
+}
+
+/// `next[0] = local[0]^3 + local[1]` with `local[1]` constant: degree 4 with the selector.
+struct CubeAir;
+
+impl<F> BaseAir<F> for CubeAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CubeAir {}
+
+impl<AB: AirBuilder> Air<AB> for CubeAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let x: AB::Expr = local[0].clone().into();
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(x.cube() + local[1].clone(), next[0].clone());
+        when_transition.assert_eq(local[1].clone(), next[1].clone());
+    }
+}
+
+#[test]
+fn test_high_degree_matches_upstream() {
+    let (mut x, step) = (Val::TWO, Val::from_u32(7));
+    let mut values = Vec::new();
+    for _ in 0..1 << 5 {
+        values.extend([x, step]);
+        x = x.cube() + step;
+    }
+    check_against_upstream(&CubeAir, RowMajorMatrix::new(values, 2), vec![]);
+}
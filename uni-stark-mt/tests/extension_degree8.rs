@@ -0,0 +1,167 @@
+//! Tests with a degree-8 extension challenge field
+//!
+//! Every extension value is committed and opened as `DIMENSION` base field columns: the
+//! quotient chunks, the auxiliary trace and their recomposition at the verifier. Running
+//! them over BabyBear⁸ checks that none of it assumes the degree-4 extension used by the
+//! other tests.
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxBuilder, AuxTraceBuilder, ProofLimits, StarkConfig,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use serde::de::DeserializeSeed;
+
+/// Running-sum AIR: `z[0] = a[0]`, `z[i+1] = z[i] + a[i+1]`
+pub struct RunningSumAir {
+    /// Build a deliberately wrong auxiliary trace
+    pub corrupt_aux: bool,
+}
+
+impl<F> BaseAir<F> for RunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, _challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut sum = EF::ZERO;
+        let mut values: Vec<EF> = main
+            .values
+            .iter()
+            .map(|&a| {
+                sum += a;
+                sum
+            })
+            .collect();
+        if self.corrupt_aux {
+            values[1] += EF::ONE;
+        }
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for RunningSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let a_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), AB::ExprEF::from(a));
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z + AB::ExprEF::from(a_next));
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new((0..n).map(|i| F::from_usize(3 * i + 1)).collect(), 1)
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 8>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_degree8_running_sum() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+
+    // One extension column is opened as 8 base field columns
+    assert_eq!(proof.aux_local.as_ref().map(|aux| aux[0].len()), Some(8));
+    assert_eq!(proof.quotient_chunks.len(), 1);
+    assert!(proof.quotient_chunks.iter().all(|chunk| chunk.len() == 8));
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The deserialization limits scale with the extension degree too
+    let json = serde_json::to_string(&proof).expect("serialization failed");
+    let decoded = ProofLimits::for_air::<MyConfig, _>(&air, &vk)
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .expect("deserialization failed");
+    verify(&config, &air, &vk, &decoded, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_degree8_corrupt_aux_rejected() {
+    let config = create_config();
+    let air = RunningSumAir { corrupt_aux: true };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_degree8_max_leaf_width() {
+    // 1 chunk of 8 base columns, 5 columns per leaf
+    let config = create_config().with_max_leaf_width(5);
+    let air = RunningSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert_eq!(proof.quotient_commits.len(), 2);
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
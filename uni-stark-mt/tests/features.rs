@@ -0,0 +1,75 @@
+//! Every combination of the gadget features and the prover builds on its own, the
+//! verifier builds for wasm32 and the embedded verifier's firmware links for riscv32
+//!
+//! Runs `cargo check` once per combination, so it is ignored by default:
+//! `cargo test -p p3-uni-stark-mt --test features -- --ignored`
+
+use std::process::Command;
+
+/// The gadget subsystems and the prover; the verifier is always built
+const OPTIONAL_FEATURES: [&str; 5] = ["lookups", "memory", "machine", "recursion", "prover"];
+
+#[test]
+#[ignore = "runs cargo check for every feature combination"]
+fn test_optional_feature_combinations_compile() {
+    for mask in 0..1usize << OPTIONAL_FEATURES.len() {
+        let features: Vec<&str> = OPTIONAL_FEATURES
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| mask & (1 << i) != 0)
+            .map(|(_, &feature)| feature)
+            .collect();
+        let status = Command::new(env!("CARGO"))
+            .args(["check", "--lib", "--no-default-features"])
+            .arg(format!("--features={}", features.join(",")))
+            .args([
+                "--manifest-path",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"),
+            ])
+            .args(["--target-dir", env!("CARGO_TARGET_TMPDIR")])
+            .status()
+            .expect("failed to run cargo");
+        assert!(status.success(), "features {features:?} do not build");
+    }
+}
+
+#[test]
+#[ignore = "runs cargo check for wasm32-unknown-unknown, whose target must be installed"]
+fn test_wasm32_builds() {
+    for features in ["", "lookups,memory,machine,prover", "borsh,json,fixtures"] {
+        let status = Command::new(env!("CARGO"))
+            .args(["check", "--lib", "--no-default-features"])
+            .arg(format!("--features={features}"))
+            .args(["--target", "wasm32-unknown-unknown"])
+            .args([
+                "--manifest-path",
+                concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"),
+            ])
+            .args(["--target-dir", env!("CARGO_TARGET_TMPDIR")])
+            .status()
+            .expect("failed to run cargo");
+        assert!(
+            status.success(),
+            "features {features:?} do not build for wasm32"
+        );
+    }
+}
+
+#[test]
+#[ignore = "runs cargo build for riscv32imac-unknown-none-elf, whose target must be installed"]
+fn test_riscv32_firmware_links() {
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--bin", "firmware"])
+        .args(["--target", "riscv32imac-unknown-none-elf"])
+        .args([
+            "--manifest-path",
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../embedded-verifier/Cargo.toml"
+            ),
+        ])
+        .args(["--target-dir", env!("CARGO_TARGET_TMPDIR")])
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "the firmware does not link for riscv32");
+}
@@ -1,31 +1,38 @@
 //! Basic Fibonacci AIR test for multi-trace STARK
 //!
 //! This is a simplified version that tests the core proving/verification without
-//! auxiliary traces or public values (to be added later).
+//! auxiliary traces. The final value is bound through a public value.
 
-use core::borrow::Borrow;
-
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-use p3_challenger::DuplexChallenger;
-use p3_commit::ExtensionMmcs;
+use p3_challenger::{CanObserve, CanSample, DuplexChallenger};
+use p3_commit::{ExtensionMmcs, PolynomialSpace};
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{Field, PrimeCharacteristicRing, PrimeField64};
+use p3_field::{Field, PackedValue, PrimeCharacteristicRing, PrimeField64, TwoAdicField};
 use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark_mt::{prove, verify, AuxTraceBuilder, StarkConfig};
+use p3_uni_stark_mt::{
+    commit_main_trace, prove, prove_from_inputs, prove_with_backend, prove_with_challenger,
+    prove_with_committed_main, prove_with_telemetry, prove_zk, serialized_size, setup, trace_row,
+    verify, verify_constraints_only, verify_metered, verify_proofs, verify_with_challenger,
+    verify_with_witness, AuxTraceBuilder, ChunkedTrace, CpuBackend, MultiTraceAir, Proof,
+    ProofInstance, ProofLimits, ProverError, ProverFolder, ProverPhase, ProverTelemetry,
+    QuotientBackend, QuotientInputs, QuotientSelectors, RowSlice, StarkConfig, StarkGenericConfig,
+    TiledMmcs, TraceGenerator, VerificationCost, VerificationError, VerificationStep, WorkBudget,
+};
 use rand::rngs::SmallRng;
 use rand::SeedableRng;
+use serde::de::DeserializeSeed;
+use serde::Serialize;
 
-/// Simple Fibonacci AIR without public values
-pub struct FibonacciAir {
-    /// Expected final value (hardcoded in constraints for now)
-    pub expected_final: u32,
-}
+/// Simple Fibonacci AIR starting from (0, 1)
+///
+/// Public values: `[x]`, the expected final value.
+pub struct FibonacciAir;
 
 impl<F> BaseAir<F> for FibonacciAir {
     fn width(&self) -> usize {
@@ -47,16 +54,17 @@ where
     }
 }
 
-impl<AB: AirBuilder> Air<AB> for FibonacciAir {
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
+        let x = builder.public_values()[0];
 
         let (local, next) = (
             main.row_slice(0).expect("Matrix is empty?"),
             main.row_slice(1).expect("Matrix only has 1 row?"),
         );
-        let local: &FibonacciRow<AB::Var> = (*local).borrow();
-        let next: &FibonacciRow<AB::Var> = (*next).borrow();
+        let local: &FibonacciRow<AB::Var> = local.row_as();
+        let next: &FibonacciRow<AB::Var> = next.row_as();
 
         let mut when_first_row = builder.when_first_row();
 
@@ -70,8 +78,8 @@ impl<AB: AirBuilder> Air<AB> for FibonacciAir {
         when_transition.assert_eq(local.right.clone(), next.left.clone());
         when_transition.assert_eq(local.left.clone() + local.right.clone(), next.right.clone());
 
-        // TODO: Add final value constraint when we support public values
-        // For now, we just verify the Fibonacci recurrence relation
+        // Constrain last row: b = x
+        builder.when_last_row().assert_eq(local.right.clone(), x);
     }
 }
 
@@ -80,9 +88,7 @@ pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajo
 
     let mut trace = RowMajorMatrix::new(F::zero_vec(n * NUM_FIBONACCI_COLS), NUM_FIBONACCI_COLS);
 
-    let (prefix, rows, suffix) = unsafe { trace.values.align_to_mut::<FibonacciRow<F>>() };
-    assert!(prefix.is_empty(), "Alignment should match");
-    assert!(suffix.is_empty(), "Alignment should match");
+    let rows = trace.values.rows_mut_as::<FibonacciRow<F>>();
     assert_eq!(rows.len(), n);
 
     rows[0] = FibonacciRow::new(F::from_u64(a), F::from_u64(b));
@@ -97,25 +103,31 @@ pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajo
 
 const NUM_FIBONACCI_COLS: usize = 2;
 
-pub struct FibonacciRow<F> {
-    pub left: F,
-    pub right: F,
+/// Generates the trace from its number of rows, which is not checked to be a power of two
+impl<F: PrimeField64> TraceGenerator<F> for FibonacciAir {
+    type Inputs = usize;
+
+    fn generate(&self, &n: &usize) -> RowMajorMatrix<F> {
+        let mut values = Vec::with_capacity(n * NUM_FIBONACCI_COLS);
+        let (mut left, mut right) = (F::ZERO, F::ONE);
+        for _ in 0..n {
+            values.extend([left, right]);
+            (left, right) = (right, left + right);
+        }
+        RowMajorMatrix::new(values, NUM_FIBONACCI_COLS)
+    }
 }
 
-impl<F> FibonacciRow<F> {
-    const fn new(left: F, right: F) -> Self {
-        Self { left, right }
+trace_row! {
+    pub struct FibonacciRow<F> {
+        pub left: F,
+        pub right: F,
     }
 }
 
-impl<F> Borrow<FibonacciRow<F>> for [F] {
-    fn borrow(&self) -> &FibonacciRow<F> {
-        debug_assert_eq!(self.len(), NUM_FIBONACCI_COLS);
-        let (prefix, shorts, suffix) = unsafe { self.align_to::<FibonacciRow<F>>() };
-        debug_assert!(prefix.is_empty(), "Alignment should match");
-        debug_assert!(suffix.is_empty(), "Alignment should match");
-        debug_assert_eq!(shorts.len(), 1);
-        &shorts[0]
+impl<F> FibonacciRow<F> {
+    const fn new(left: F, right: F) -> Self {
+        Self { left, right }
     }
 }
 
@@ -133,64 +145,1177 @@ type Dft = Radix2DitParallel<Val>;
 type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
-#[test]
-fn test_fibonacci_basic() {
-    let mut rng = SmallRng::seed_from_u64(1);
-    let perm = Perm::new_from_rng_128(&mut rng);
-    let hash = MyHash::new(perm.clone());
-    let compress = MyCompress::new(perm.clone());
-    let val_mmcs = ValMmcs::new(hash, compress);
+/// The seeded Poseidon2 permutation every config here hashes and samples with
+fn create_perm() -> Perm {
+    Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1))
+}
+
+/// Merkle commitments hashing and compressing with `perm`
+fn create_val_mmcs(perm: &Perm) -> ValMmcs {
+    ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()))
+}
+
+/// FRI over Poseidon2 Merkle commitments, folding down to final polynomials of
+/// `1 << log_final_poly_len` coefficients
+fn create_config_with_final_poly_len(log_final_poly_len: usize) -> MyConfig {
+    let perm = create_perm();
+    let val_mmcs = create_val_mmcs(&perm);
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-    let dft = Dft::default();
+    let fri_params = create_test_fri_params(challenge_mmcs, log_final_poly_len);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_params);
+    MyConfig::new(pcs, Challenger::new(perm))
+}
+
+/// The config of every test whose trace has more than one row
+fn create_config() -> MyConfig {
+    create_config_with_final_poly_len(2)
+}
 
+#[test]
+fn test_fibonacci_basic() {
     let n = 1 << 3; // 8 rows
     let trace = generate_trace_rows::<Val>(0, 1, n);
 
-    let fri_params = create_test_fri_params(challenge_mmcs, 2);
-    let pcs = Pcs::new(dft, val_mmcs, fri_params);
-    let challenger = Challenger::new(perm);
-    let config = MyConfig::new(pcs, challenger);
+    let config = create_config();
 
-    let air = FibonacciAir {
-        expected_final: 21, // 8th Fibonacci number
-    };
+    let air = FibonacciAir;
 
-    // Empty public values for now
-    let public_values = vec![];
+    // 8th Fibonacci number
+    let public_values = vec![Val::from_u64(21)];
 
     println!("Generating proof...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
     );
+
+    // Degree-2 constraints need a quotient domain no larger than the trace domain
+    assert_eq!(vk.num_constraints, 5);
+    assert_eq!(vk.max_constraint_degree, 2);
+    assert_eq!(vk.quotient_degree(), 1);
+    assert_eq!(proof.quotient_chunks.len(), 1);
+
     println!("Verifying proof...");
-    verify(&config, &air, &proof, &public_values).expect("verification failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
 }
 
 #[test]
 fn test_fibonacci_one_row() {
-    let mut rng = SmallRng::seed_from_u64(1);
-    let perm = Perm::new_from_rng_128(&mut rng);
-    let hash = MyHash::new(perm.clone());
-    let compress = MyCompress::new(perm.clone());
-    let val_mmcs = ValMmcs::new(hash, compress);
-    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-    let dft = Dft::default();
-
     let n = 1; // Single row
     let trace = generate_trace_rows::<Val>(0, 1, n);
 
-    let fri_params = create_test_fri_params(challenge_mmcs, 0);
-    let pcs = Pcs::new(dft, val_mmcs, fri_params);
-    let challenger = Challenger::new(perm);
-    let config = MyConfig::new(pcs, challenger);
+    let config = create_config_with_final_poly_len(0);
+
+    let air = FibonacciAir;
+
+    let public_values = vec![Val::ONE];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_fibonacci_public_values_digest() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let mut proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    proof.commit_to_public_values(&config, &public_values);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    let wrong_public_values = vec![Val::from_u64(34)];
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &wrong_public_values),
+        Err(VerificationError::PublicValuesMismatch)
+    ));
+}
+
+#[test]
+fn test_fibonacci_malformed_proofs() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+
+    // Rejected before the transcript is charged, so even an empty budget reports the shape
+    let assert_invalid = |proof: &Proof<MyConfig>| {
+        let mut budget = WorkBudget::new(VerificationCost::default());
+        assert!(matches!(
+            verify_metered(&config, &air, &vk, proof, &public_values, &mut budget),
+            Err(VerificationError::InvalidProof(_))
+        ));
+    };
+
+    let mut truncated_main = proof.clone();
+    truncated_main.main_local.pop();
+    assert_invalid(&truncated_main);
+
+    let mut widened_next = proof.clone();
+    widened_next.main_next.push(Challenge::ZERO);
+    assert_invalid(&widened_next);
+
+    let mut spurious_aux = proof.clone();
+    spurious_aux.aux_local = Some(vec![vec![Challenge::ZERO]]);
+    assert_invalid(&spurious_aux);
+
+    let mut dropped_chunk = proof.clone();
+    dropped_chunk.quotient_chunks.pop();
+    assert_invalid(&dropped_chunk);
+
+    let mut extra_chunk = proof.clone();
+    extra_chunk.quotient_chunks.push(vec![]);
+    assert_invalid(&extra_chunk);
+
+    let mut short_chunk = proof.clone();
+    short_chunk.quotient_chunks[0].pop();
+    assert_invalid(&short_chunk);
+
+    let mut extra_quotient_commit = proof.clone();
+    extra_quotient_commit
+        .quotient_commits
+        .push(proof.main_commit.clone());
+    assert_invalid(&extra_quotient_commit);
+
+    let mut huge_degree = proof.clone();
+    huge_degree.log_degree = u8::MAX;
+    assert_invalid(&huge_degree);
+}
+
+/// Layout of [`Proof`] before it carried a protocol version and when the aux openings were
+/// always present.
+#[derive(Serialize)]
+struct LegacyProof<'a, C, EF, P, O> {
+    main_commit: &'a C,
+    aux_commits: &'a [C],
+    aux_exposed_values: &'a [Vec<EF>],
+    quotient_commits: &'a [C],
+    main_local: &'a [EF],
+    main_next: &'a [EF],
+    preprocessed_local: &'a [EF],
+    preprocessed_next: &'a [EF],
+    aux_local: Vec<Vec<EF>>,
+    aux_next: Vec<Vec<EF>>,
+    quotient_chunks: &'a [Vec<EF>],
+    opening_proof: &'a P,
+    additional_openings: &'a [O],
+    log_degree: u8,
+    public_values_digest: &'a Option<EF>,
+    aux_challenge_counts: &'a Option<Vec<usize>>,
+}
+
+#[test]
+fn test_fibonacci_bounded_deserialization() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    let limits = ProofLimits::for_air::<MyConfig, _>(&air, &vk);
+
+    let json = serde_json::to_string(&proof).expect("serialization failed");
+    let decoded = limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .expect("deserialization failed");
+    verify(&config, &air, &vk, &decoded, &public_values).expect("verification failed");
+
+    // Without aux phases the aux openings are omitted
+    assert!(proof.aux_local.is_none() && proof.aux_next.is_none());
+    assert!(json.contains(r#""aux_local":null"#));
+
+    // The same holds in a binary encoding
+    let bytes = postcard::to_allocvec(&proof).expect("serialization failed");
+    let decoded = limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut postcard::Deserializer::from_bytes(&bytes))
+        .expect("deserialization failed");
+    verify(&config, &air, &vk, &decoded, &public_values).expect("verification failed");
+
+    // Proofs from before the protocol version, with empty lists of aux openings, are not
+    // decoded as this revision: either decoding fails or verification does
+    let legacy = LegacyProof {
+        main_commit: &proof.main_commit,
+        aux_commits: &proof.aux_commits,
+        aux_exposed_values: &proof.aux_exposed_values,
+        quotient_commits: &proof.quotient_commits,
+        main_local: &proof.main_local,
+        main_next: &proof.main_next,
+        preprocessed_local: &proof.preprocessed_local,
+        preprocessed_next: &proof.preprocessed_next,
+        aux_local: Vec::new(),
+        aux_next: Vec::new(),
+        quotient_chunks: &proof.quotient_chunks,
+        opening_proof: &proof.opening_proof,
+        additional_openings: &proof.additional_openings,
+        log_degree: proof.log_degree,
+        public_values_digest: &proof.public_values_digest,
+        aux_challenge_counts: &proof.aux_challenge_counts,
+    };
+    let bytes = postcard::to_allocvec(&legacy).expect("serialization failed");
+    if let Ok(decoded) = limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut postcard::Deserializer::from_bytes(&bytes))
+    {
+        assert!(verify(&config, &air, &vk, &decoded, &public_values).is_err());
+    }
+
+    // A proof claiming more opened values than the AIR has columns is rejected
+    let mut widened = proof.clone();
+    widened.main_local.extend(vec![Challenge::ZERO; 1000]);
+    let json = serde_json::to_string(&widened).expect("serialization failed");
+    assert!(limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .is_err());
+
+    let mut padded_chunks = proof;
+    padded_chunks.quotient_chunks.push(vec![Challenge::ZERO; 4]);
+    let json = serde_json::to_string(&padded_chunks).expect("serialization failed");
+    assert!(limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .is_err());
+}
+
+#[test]
+fn test_fibonacci_max_leaf_width() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    // 1 chunk of 4 base columns, split unevenly across leaves
+    let config = create_config().with_max_leaf_width(3);
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    assert_eq!(proof.quotient_commits.len(), 2);
+    assert!(proof.quotient_chunks.iter().all(|chunk| chunk.len() == 4));
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    let mut dropped = proof.clone();
+    dropped.quotient_commits.pop();
+    assert!(verify(&config, &air, &vk, &dropped, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_num_threads() {
+    let config = |num_threads: Option<usize>| {
+        let config = create_config();
+        match num_threads {
+            Some(num_threads) => config.with_num_threads(num_threads),
+            None => config,
+        }
+    };
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(2178309)];
+    let prove_on = |num_threads| {
+        let config = config(num_threads);
+        let (pk, vk) = setup(&config, &air);
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 5);
+        let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+        verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+        serde_json::to_string(&proof).expect("serialization failed")
+    };
+
+    // The pool size changes where the work runs, never the proof
+    let proof = prove_on(None);
+    assert_eq!(prove_on(Some(1)), proof);
+    assert_eq!(prove_on(Some(3)), proof);
+}
+
+/// [`FibonacciAir`], recording the worker threads its constraints are evaluated on
+#[cfg(feature = "parallel")]
+#[derive(Default)]
+struct ThreadRecordingAir {
+    threads: std::sync::Mutex<std::collections::BTreeSet<String>>,
+}
+
+#[cfg(feature = "parallel")]
+impl<F> BaseAir<F> for ThreadRecordingAir {
+    fn width(&self) -> usize {
+        NUM_FIBONACCI_COLS
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<F: Field, EF: p3_field::ExtensionField<F>> AuxTraceBuilder<F, EF> for ThreadRecordingAir {}
+
+#[cfg(feature = "parallel")]
+impl<AB: AirBuilderWithPublicValues> Air<AB> for ThreadRecordingAir {
+    fn eval(&self, builder: &mut AB) {
+        if let Some(name) = std::thread::current().name() {
+            self.threads.lock().unwrap().insert(name.into());
+        }
+        // Long enough for idle workers to steal the other tasks
+        std::thread::sleep(std::time::Duration::from_micros(50));
+        FibonacciAir.eval(builder);
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_quotient_evaluated_on_pool() {
+    let config = create_config().with_num_threads(3);
+    let air = ThreadRecordingAir::default();
+    let n = 1 << 12;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+    let public_values = vec![trace.get(n - 1, 1).expect("trace is empty")];
+    let (pk, vk) = setup(&config, &air);
+    // Run the AIR at every point rather than the constraint DAG of `pk`
+    let proof = prove_with_backend(&config, &air, &pk, trace, &public_values, &CpuBackend)
+        .expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // Setup and verification run on the calling thread, the quotient on several workers
+    let threads = air.threads.lock().unwrap();
+    let workers = threads
+        .iter()
+        .filter(|name| name.starts_with("p3-uni-stark-mt-"))
+        .count();
+    assert!(workers > 1, "quotient evaluated on {threads:?}");
+}
+
+#[test]
+fn test_fibonacci_tiled_leaves() {
+    type TiledPcs = TwoAdicFriPcs<Val, Dft, TiledMmcs<ValMmcs>, ChallengeMmcs>;
+    type TiledConfig = StarkConfig<TiledPcs, Challenge, Challenger>;
+
+    let config = |rows_per_leaf: usize| {
+        let perm = create_perm();
+        let val_mmcs = create_val_mmcs(&perm);
+        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+        let pcs = TiledPcs::new(
+            Dft::default(),
+            TiledMmcs::new(val_mmcs, rows_per_leaf),
+            create_test_fri_params(challenge_mmcs, 2),
+        );
+        TiledConfig::new(pcs, Challenger::new(perm))
+    };
+
+    let n = 1 << 5;
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(2_178_309)];
+
+    // Four 2-column rows per leaf, with one row per leaf as the baseline
+    let tiled = config(4);
+    let (pk, tiled_vk) = setup(&tiled, &air);
+    let proof = prove(
+        &tiled,
+        &air,
+        &pk,
+        generate_trace_rows::<Val>(0, 1, n),
+        &public_values,
+    )
+    .expect("proving failed");
+    verify(&tiled, &air, &tiled_vk, &proof, &public_values).expect("verification failed");
+
+    let untiled = config(1);
+    let (pk, vk) = setup(&untiled, &air);
+    let baseline = prove(
+        &untiled,
+        &air,
+        &pk,
+        generate_trace_rows::<Val>(0, 1, n),
+        &public_values,
+    )
+    .expect("proving failed");
+    verify(&untiled, &air, &vk, &baseline, &public_values).expect("verification failed");
+
+    // Leaves are rebuilt with the verifier's own tiling
+    assert!(verify(&config(2), &air, &tiled_vk, &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_max_proof_size() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    let size = serialized_size(&proof);
+
+    // A proof exactly at the limit is accepted, one byte over it is not
+    let config = config.with_max_proof_size(size);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+    let config = config.with_max_proof_size(size - 1);
+    assert!(verify(&config, &air, &vk, &proof, &public_values).is_err());
+
+    let json = serde_json::to_string(&proof).expect("serialization failed");
+    let limits = ProofLimits::for_air::<MyConfig, _>(&air, &vk);
+    assert!(limits
+        .with_max_proof_size(size)
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .is_ok());
+    assert!(limits
+        .with_max_proof_size(size - 1)
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .is_err());
+}
+
+#[test]
+fn test_fibonacci_multiple_ood_points() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config().with_ood_points(3);
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    assert_eq!(proof.additional_openings.len(), 2);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
 
-    let air = FibonacciAir { expected_final: 1 };
+    // Bounded deserialization needs to know about the extra points
+    let json = serde_json::to_string(&proof).expect("serialization failed");
+    let limits = ProofLimits::for_air::<MyConfig, _>(&air, &vk);
+    assert!(limits
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .is_err());
+    let decoded = limits
+        .with_num_ood_points(3)
+        .proof_seed::<MyConfig>()
+        .deserialize(&mut serde_json::Deserializer::from_str(&json))
+        .expect("deserialization failed");
+    verify(&config, &air, &vk, &decoded, &public_values).expect("verification failed");
 
-    let public_values = vec![];
+    // Dropping a point or tampering with one is rejected
+    let mut dropped = proof.clone();
+    dropped.additional_openings.pop();
+    assert!(matches!(
+        verify(&config, &air, &vk, &dropped, &public_values),
+        Err(VerificationError::InvalidProof(_))
+    ));
 
-    let proof = prove(&config, &air, trace, &public_values);
-    verify(&config, &air, &proof, &public_values).expect("verification failed");
+    let mut tampered = proof;
+    tampered.additional_openings[1].main_next[0] += Challenge::ONE;
+    assert!(verify(&config, &air, &vk, &tampered, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_constraints_only() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    verify_constraints_only(&config, &air, &vk, &proof, &public_values)
+        .expect("constraint check failed");
+
+    // The constraint identity is still checked
+    let wrong_public_values = vec![Val::from_u64(22)];
+    assert!(verify_constraints_only(&config, &air, &vk, &proof, &wrong_public_values).is_err());
+
+    let mut tampered = proof;
+    tampered.main_next[0] += Challenge::ONE;
+    assert!(verify_constraints_only(&config, &air, &vk, &tampered, &public_values).is_err());
+}
+
+/// [`MyConfig`] claiming hiding commitments, to exercise the ZK code paths
+struct ZkConfig(MyConfig);
+
+impl StarkGenericConfig for ZkConfig {
+    type Pcs = Pcs;
+    type Challenge = Challenge;
+    type Challenger = Challenger;
+
+    fn pcs(&self) -> &Pcs {
+        self.0.pcs()
+    }
+
+    fn initialise_challenger(&self) -> Challenger {
+        self.0.initialise_challenger()
+    }
+
+    fn is_zk(&self) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_fibonacci_zk() {
+    let n = 1 << 3;
+
+    let config = ZkConfig(create_config());
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove_zk(
+        &config,
+        &air,
+        &pk,
+        generate_trace_rows::<Val>(0, 1, n),
+        &public_values,
+        &mut SmallRng::seed_from_u64(2),
+    )
+    .expect("proving failed");
+    assert_eq!(proof.quotient_chunks.len(), 4);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // Fresh randomness gives different openings of the same witness
+    let other = prove_zk(
+        &config,
+        &air,
+        &pk,
+        generate_trace_rows::<Val>(0, 1, n),
+        &public_values,
+        &mut SmallRng::seed_from_u64(3),
+    )
+    .expect("proving failed");
+    verify(&config, &air, &vk, &other, &public_values).expect("verification failed");
+    assert_ne!(proof.main_local, other.main_local);
+
+    // The constraints are still enforced
+    let wrong_public_values = vec![Val::from_u64(22)];
+    let wrong = prove_zk(
+        &config,
+        &air,
+        &pk,
+        generate_trace_rows::<Val>(0, 1, n),
+        &wrong_public_values,
+        &mut SmallRng::seed_from_u64(4),
+    )
+    .expect("proving failed");
+    assert!(verify(&config, &air, &vk, &wrong, &wrong_public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_metered_verification() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+
+    let mut budget = WorkBudget::new(VerificationCost {
+        transcript_ops: 1_000,
+        opened_values: 1_000,
+        field_ops: 1_000,
+    });
+    verify_metered(&config, &air, &vk, &proof, &public_values, &mut budget)
+        .expect("verification failed");
+    assert!(budget.used.opened_values > 0);
+    assert!(budget.used.field_ops > 0);
+
+    let mut tight = WorkBudget::new(VerificationCost {
+        transcript_ops: 1_000,
+        opened_values: 1,
+        field_ops: 1_000,
+    });
+    assert!(matches!(
+        verify_metered(&config, &air, &vk, &proof, &public_values, &mut tight),
+        Err(VerificationError::BudgetExceeded(
+            VerificationStep::PcsVerification
+        ))
+    ));
+
+    // The constraints are charged from the verifying key before they are evaluated
+    let mut tight = WorkBudget::new(VerificationCost {
+        transcript_ops: 1_000,
+        opened_values: 1_000,
+        field_ops: 2 * vk.num_constraints as u64 - 1,
+    });
+    assert!(matches!(
+        verify_metered(&config, &air, &vk, &proof, &public_values, &mut tight),
+        Err(VerificationError::BudgetExceeded(
+            VerificationStep::ConstraintEvaluation
+        ))
+    ));
+}
+
+#[test]
+fn test_fibonacci_bad_witness_errors() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, _) = setup(&config, &air);
+
+    // Three columns for a two-column AIR
+    let wide = RowMajorMatrix::new(Val::zero_vec(3 * 8), 3);
+    assert_eq!(
+        prove(&config, &air, &pk, wide, &public_values).err(),
+        Some(ProverError::MainTraceWidthMismatch {
+            expected: 2,
+            actual: 3
+        })
+    );
+
+    // Six rows
+    let short = RowMajorMatrix::new(Val::zero_vec(2 * 6), 2);
+    assert_eq!(
+        prove(&config, &air, &pk, short, &public_values).err(),
+        Some(ProverError::TraceHeightNotPowerOfTwo(6))
+    );
+}
+
+#[test]
+fn test_fibonacci_prove_from_inputs() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    let proof = prove_from_inputs(&config, &air, &pk, &8, &public_values).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // The generated trace goes through the same shape checks as a supplied one
+    assert_eq!(
+        prove_from_inputs(&config, &air, &pk, &6, &public_values).err(),
+        Some(ProverError::TraceHeightNotPowerOfTwo(6))
+    );
+}
+
+#[test]
+fn test_fibonacci_verify_proofs() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let (pk, vk) = setup(&config, &air);
+
+    // 8 and 16 rows end on the 8th and 16th Fibonacci numbers; the two proofs over 8 rows
+    // share their domains
+    let statements = [
+        (8, vec![Val::from_u64(21)]),
+        (16, vec![Val::from_u64(987)]),
+        (8, vec![Val::from_u64(21)]),
+    ];
+    let proofs: Vec<_> = statements
+        .iter()
+        .map(|(n, public_values)| {
+            let trace = generate_trace_rows::<Val>(0, 1, *n);
+            prove(&config, &air, &pk, trace, public_values).expect("proving failed")
+        })
+        .collect();
+    let instances: Vec<_> = proofs
+        .iter()
+        .zip(&statements)
+        .map(|(proof, (_, public_values))| ProofInstance {
+            air: &air,
+            vk: &vk,
+            proof,
+            public_values,
+        })
+        .collect();
+    verify_proofs(&config, &instances).expect("verification failed");
+
+    // A wrong claim is reported with its index
+    let wrong = [Val::from_u64(986)];
+    let mut instances = instances;
+    instances[1].public_values = &wrong;
+    let (index, _) = verify_proofs(&config, &instances).expect_err("wrong claim accepted");
+    assert_eq!(index, 1);
+}
+
+#[test]
+fn test_fibonacci_external_challenger() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    // An outer protocol has already written to the transcript
+    let outer_transcript = || {
+        let mut challenger = config.initialise_challenger();
+        challenger.observe(Val::from_u64(42));
+        challenger
+    };
+
+    let mut prover_challenger = outer_transcript();
+    let proof = prove_with_challenger(
+        &config,
+        &air,
+        &pk,
+        trace,
+        &public_values,
+        &mut prover_challenger,
+    )
+    .expect("proving failed");
+
+    let mut verifier_challenger = outer_transcript();
+    verify_with_challenger(
+        &config,
+        &air,
+        &vk,
+        &proof,
+        &public_values,
+        &mut verifier_challenger,
+    )
+    .expect("verification failed");
+
+    // Both sides carry on from the same state
+    let prover_next: Challenge = prover_challenger.sample();
+    let verifier_next: Challenge = verifier_challenger.sample();
+    assert_eq!(prover_next, verifier_next);
+
+    // The proof is bound to the outer transcript
+    assert!(verify(&config, &air, &vk, &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_committed_main() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    // The commitment is published before the proof exists
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let (main_commit, main_data) = commit_main_trace(&config, trace.clone());
+    let proof = prove_with_committed_main(
+        &config,
+        &air,
+        &pk,
+        trace.clone(),
+        main_commit.clone(),
+        main_data,
+        &public_values,
+    )
+    .expect("proving failed");
+    assert_eq!(proof.main_commit, main_commit);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // Committing inside the prover gives the same commitment
+    let fresh = prove(&config, &air, &pk, trace.clone(), &public_values).expect("proving failed");
+    assert_eq!(fresh.main_commit, main_commit);
+
+    // A commitment to another trace does not prove this one
+    let other = generate_trace_rows::<Val>(1, 1, 8);
+    let (other_commit, other_data) = commit_main_trace(&config, other);
+    let proof = prove_with_committed_main(
+        &config,
+        &air,
+        &pk,
+        trace,
+        other_commit,
+        other_data,
+        &public_values,
+    )
+    .expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_chunked_trace() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    // Feed the trace three rows at a time; the proof is the one of the whole trace
+    let full = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let mut chunked = ChunkedTrace::new(2, 1 << 3);
+    for chunk in full.values.chunks(3 * 2) {
+        chunked
+            .push_chunk(RowMajorMatrixView::new(chunk, 2))
+            .expect("chunk fits");
+    }
+    assert_eq!(chunked.rows(), 8);
+    let trace = chunked.finish().expect("trace is complete");
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+    let whole = prove(&config, &air, &pk, full.clone(), &public_values).expect("proving failed");
+    assert_eq!(
+        serde_json::to_string(&proof).expect("serialization failed"),
+        serde_json::to_string(&whole).expect("serialization failed")
+    );
+
+    // Chunks of the wrong width, too many rows and too few are rejected
+    let mut chunked = ChunkedTrace::new(2, 4);
+    assert_eq!(
+        chunked.push_chunk(RowMajorMatrixView::new(&full.values[..6], 3)),
+        Err(ProverError::MainTraceWidthMismatch {
+            expected: 2,
+            actual: 3
+        })
+    );
+    assert_eq!(
+        chunked.push_chunk(RowMajorMatrixView::new(&full.values[..10], 2)),
+        Err(ProverError::TraceRowCountMismatch {
+            expected: 4,
+            actual: 5
+        })
+    );
+    chunked
+        .push_chunk(RowMajorMatrixView::new(&full.values[..6], 2))
+        .expect("chunk fits");
+    assert_eq!(
+        chunked.finish().err(),
+        Some(ProverError::TraceRowCountMismatch {
+            expected: 4,
+            actual: 3
+        })
+    );
+}
+
+#[test]
+fn test_fibonacci_max_supported_log_degree() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let log_blowup = create_test_fri_params((), 2).log_blowup;
+    let config = create_config().with_log_blowup(log_blowup);
+
+    let max_log_degree = config
+        .max_supported_log_degree()
+        .expect("two-adic config should know its limit");
+    assert_eq!(
+        max_log_degree,
+        Val::TWO_ADICITY - log_blowup.max(2),
+        "limit should come from the two-adicity and the blowup"
+    );
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let mut proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    proof.log_degree = (max_log_degree + 1) as u8;
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &public_values),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fibonacci_prove_and_check() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+
+    let (pk, vk) = setup(&config, &air);
+    let (proof, timings) =
+        p3_uni_stark_mt::prove_and_check(&config, &air, &pk, trace, &public_values)
+            .expect("self-check failed");
+    println!("prove: {:?}, verify: {:?}", timings.prove, timings.verify);
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+}
+
+/// Records every telemetry report
+#[derive(Default)]
+struct RecordedTelemetry(Vec<(&'static str, ProverPhase, usize)>);
+
+impl ProverTelemetry for &mut RecordedTelemetry {
+    fn phase_started(&mut self, phase: ProverPhase, rows: usize) {
+        self.0.push(("start", phase, rows));
+    }
+
+    fn progress(&mut self, phase: ProverPhase, rows_done: usize) {
+        self.0.push(("progress", phase, rows_done));
+    }
+
+    fn phase_finished(&mut self, phase: ProverPhase) {
+        self.0.push(("finish", phase, 0));
+    }
+}
+
+#[test]
+fn test_fibonacci_telemetry() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    let mut telemetry = RecordedTelemetry::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let proof = prove_with_telemetry(&config, &air, &pk, trace, &public_values, &mut telemetry)
+        .expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    // 8 trace rows over a quotient domain of 8 points
+    let starts: Vec<_> = telemetry
+        .0
+        .iter()
+        .filter(|(event, ..)| *event == "start")
+        .map(|&(_, phase, rows)| (phase, rows))
+        .collect();
+    assert_eq!(
+        starts,
+        [
+            (ProverPhase::MainCommit, 8),
+            (ProverPhase::Quotient, 8),
+            (ProverPhase::Opening, 8),
+        ]
+    );
+    let progress: Vec<_> = telemetry
+        .0
+        .iter()
+        .filter(|(event, ..)| *event == "progress")
+        .map(|&(_, _, rows_done)| rows_done)
+        .collect();
+    // Reported after each batch of points the workers evaluate, however wide the packing
+    assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(progress.last(), Some(&8));
+    assert_eq!(
+        telemetry.0.last(),
+        Some(&("finish", ProverPhase::Opening, 0))
+    );
+}
+
+/// Delegates to [`CpuBackend`], recording the inputs and optionally corrupting the result
+#[derive(Default)]
+struct RecordingBackend {
+    /// Quotient domain size and number of constraint coefficients of each call
+    calls: std::sync::Mutex<Vec<(usize, usize)>>,
+    corrupt: bool,
+}
+
+impl QuotientBackend<MyConfig> for RecordingBackend {
+    fn quotient_values<A, M>(
+        &self,
+        air: &A,
+        inputs: QuotientInputs<'_, MyConfig, M>,
+        telemetry: &mut dyn ProverTelemetry,
+    ) -> Vec<Challenge>
+    where
+        A: MultiTraceAir<Val, Challenge> + for<'a> Air<ProverFolder<'a, MyConfig>>,
+        M: Matrix<Val> + Sync,
+    {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((inputs.selectors.len(), inputs.alpha_powers.len()));
+        let mut values = CpuBackend.quotient_values(air, inputs, telemetry);
+        if self.corrupt {
+            values[0] += Challenge::ONE;
+        }
+        values
+    }
+}
+
+#[test]
+fn test_fibonacci_quotient_backend() {
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, vk) = setup(&config, &air);
+
+    // The backend sees the whole quotient domain and every constraint coefficient
+    let backend = RecordingBackend::default();
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let proof = prove_with_backend(&config, &air, &pk, trace, &public_values, &backend)
+        .expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+    assert_eq!(
+        *backend.calls.lock().unwrap(),
+        [(8 << vk.log_quotient_degree, vk.num_constraints)]
+    );
+
+    // A backend returning a wrong quotient yields a proof that does not verify
+    let backend = RecordingBackend {
+        corrupt: true,
+        ..Default::default()
+    };
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    let proof = prove_with_backend(&config, &air, &pk, trace, &public_values, &backend)
+        .expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_fibonacci_quotient_block_size() {
+    let config = |quotient_block_size: Option<usize>| {
+        let config = create_config();
+        match quotient_block_size {
+            Some(quotient_block_size) => config.with_quotient_block_size(quotient_block_size),
+            None => config,
+        }
+    };
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let prove_in_blocks = |quotient_block_size| {
+        let config = config(quotient_block_size);
+        let (pk, vk) = setup(&config, &air);
+        let backend = RecordingBackend::default();
+        let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+        let proof = prove_with_backend(&config, &air, &pk, trace, &public_values, &backend)
+            .expect("proving failed");
+        verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+        let json = serde_json::to_string(&proof).expect("serialization failed");
+        (json, backend.calls.into_inner().unwrap(), vk)
+    };
+
+    // The backend sees one block at a time, which never changes the proof
+    let (proof, _, vk) = prove_in_blocks(None);
+    let quotient_size = 8 << vk.log_quotient_degree;
+    for quotient_block_size in [3, 8, quotient_size, 4 * quotient_size] {
+        let (blocked, calls, _) = prove_in_blocks(Some(quotient_block_size));
+        assert_eq!(blocked, proof);
+        let expected: Vec<_> = (0..quotient_size)
+            .step_by(quotient_block_size)
+            .map(|start| {
+                (
+                    quotient_block_size.min(quotient_size - start),
+                    vk.num_constraints,
+                )
+            })
+            .collect();
+        assert_eq!(calls, expected);
+    }
+}
+
+#[test]
+fn test_quotient_selectors_streamed() {
+    let config = create_config();
+
+    let trace_domain =
+        <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(config.pcs(), 8);
+    let quotient_domain = trace_domain.create_disjoint_domain(32);
+
+    // Walking the two-adic quotient domain gives the selectors computed over the coset
+    let streamed: Vec<_> = QuotientSelectors::<MyConfig>::from_point(
+        trace_domain,
+        quotient_domain,
+        quotient_domain.first_point(),
+        32,
+    )
+    .collect();
+    let on_coset: Vec<_> =
+        QuotientSelectors::<MyConfig>::on_coset(trace_domain, quotient_domain).collect();
+    assert_eq!(streamed.len(), 32);
+    assert_eq!(on_coset.len(), 32);
+    for (streamed, on_coset) in streamed.iter().zip(&on_coset) {
+        assert_eq!(streamed.is_first_row, on_coset.is_first_row);
+        assert_eq!(streamed.is_last_row, on_coset.is_last_row);
+        assert_eq!(streamed.is_transition, on_coset.is_transition);
+        assert_eq!(streamed.inv_vanishing, on_coset.inv_vanishing);
+    }
+
+    // Packed reads pad the lanes past the last point with zeros
+    let mut selectors = QuotientSelectors::<MyConfig>::from_point(
+        trace_domain,
+        quotient_domain,
+        quotient_domain.first_point(),
+        1,
+    );
+    let packed = selectors.next_packed();
+    assert_eq!(
+        packed.inv_vanishing.as_slice()[0],
+        on_coset[0].inv_vanishing
+    );
+    assert!(packed.inv_vanishing.as_slice()[1..]
+        .iter()
+        .all(|&lane| lane == Val::ZERO));
+    assert_eq!(selectors.len(), 0);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fibonacci_watchdog() {
+    use std::time::Duration;
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+    let public_values = vec![Val::from_u64(21)];
+    let (pk, _) = setup(&config, &air);
+
+    // Generous limits are never hit
+    let watchdog = p3_uni_stark_mt::Watchdog::new(Duration::from_secs(1), Duration::from_secs(600));
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    prove_with_telemetry(&config, &air, &pk, trace, &public_values, &watchdog)
+        .expect("proving failed");
+    assert_eq!(watchdog.stuck_phase(), None);
+
+    // Every phase takes longer than nothing, so the first one is flagged
+    let watchdog = p3_uni_stark_mt::Watchdog::new(Duration::from_secs(1), Duration::ZERO);
+    let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);
+    prove_with_telemetry(&config, &air, &pk, trace, &public_values, &watchdog)
+        .expect("proving failed");
+    assert_eq!(watchdog.stuck_phase(), Some(ProverPhase::MainCommit));
+}
+
+#[test]
+fn test_fibonacci_wrong_final_value() {
+    let n = 1 << 3;
+    let trace = generate_trace_rows::<Val>(0, 1, n);
+
+    let config = create_config();
+
+    let air = FibonacciAir;
+
+    // The trace ends in 21, so claiming 34 must not verify
+    let public_values = vec![Val::from_u64(34)];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &public_values),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+
+    // The same error, with the values the verifier computed
+    let mut witness = None;
+    assert!(matches!(
+        verify_with_witness(&config, &air, &vk, &proof, &public_values, &mut witness),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+    let witness = witness.expect("constraint failure records a witness");
+    assert_ne!(
+        witness.constraints_at_zeta * witness.inv_vanishing,
+        witness.quotient_at_zeta
+    );
+    assert_eq!(witness.main_local, proof.main_local);
+    assert!(witness.aux_local.is_empty());
 }
@@ -0,0 +1,79 @@
+//! The fixtures are deterministic and verify, at the cost their preset's model predicts
+
+#![cfg(feature = "fixtures")]
+
+use p3_uni_stark_mt::fixtures::{baby_bear_cost_model, fibonacci_proof, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    serialized_size, verify, verify_metered, AirShape, CostEstimate, WorkBudget,
+};
+
+#[test]
+fn test_fixture_verifies() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    verify(
+        &fixture.config,
+        &fixture.air,
+        &fixture.vk,
+        &fixture.proof,
+        &fixture.public_values,
+    )
+    .expect("verification failed");
+}
+
+#[test]
+fn test_fixture_is_deterministic() {
+    let encode = |seed| {
+        serde_json::to_string(&fibonacci_proof(seed, 3).proof).expect("serialization failed")
+    };
+
+    assert_eq!(encode(FIXTURE_SEED), encode(FIXTURE_SEED));
+    assert_ne!(encode(FIXTURE_SEED), encode(FIXTURE_SEED + 1));
+}
+
+#[test]
+fn test_cost_model_matches_fixture_proofs() {
+    let model = baby_bear_cost_model();
+    for log_n in 3..7 {
+        let fixture = fibonacci_proof(FIXTURE_SEED, log_n);
+        let shape = AirShape::new(&fixture.vk, &fixture.air, fixture.public_values.len());
+        let estimate = model.estimate(log_n, &shape);
+
+        // The metered cost is exact
+        let mut budget = WorkBudget::new(estimate.cost);
+        verify_metered(
+            &fixture.config,
+            &fixture.air,
+            &fixture.vk,
+            &fixture.proof,
+            &fixture.public_values,
+            &mut budget,
+        )
+        .expect("verification failed");
+        assert_eq!(budget.used, estimate.cost);
+
+        // The proof size is within a tenth of the real one
+        let size = serialized_size(&fixture.proof);
+        assert!(
+            estimate.proof_size.abs_diff(size) * 10 <= size,
+            "log_n {log_n}: estimated {} bytes, proof has {size}",
+            estimate.proof_size
+        );
+    }
+
+    // Taller traces cost more hashes and bytes, and the estimate is machine-readable
+    let shape = AirShape {
+        main_width: 8,
+        num_constraints: 8,
+        log_quotient_degree: 1,
+        ..Default::default()
+    };
+    let small = model.estimate(10, &shape);
+    let large = model.estimate(20, &shape);
+    assert!(large.proof_size > small.proof_size);
+    assert!(large.hashes > small.hashes);
+    let json = serde_json::to_string(&large).expect("serialization failed");
+    assert_eq!(
+        serde_json::from_str::<CostEstimate>(&json).expect("invalid JSON"),
+        large
+    );
+}
@@ -0,0 +1,194 @@
+//! The prover's and the verifier's folders fold constraints alike
+//!
+//! The verifier Horner-evaluates the constraints as the AIR asserts them, the prover
+//! applies precomputed coefficients; both must reach the same accumulator at any point.
+
+#![cfg(all(feature = "fixtures", feature = "prover"))]
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_field::{BasedVectorSpace, ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrixView;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{Challenge, FixtureConfig, Val};
+use p3_uni_stark_mt::{
+    AuxBuilder, AuxTraceBuilder, ConstraintFolding, PackedChallenge, PackedVal, ProverFolder,
+    VerifierFolder,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+const WIDTH: usize = 3;
+
+/// Base and extension constraints of several degrees, under each selector.
+struct FoldingAir;
+
+impl<F> BaseAir<F> for FoldingAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FoldingAir {}
+
+impl<AB: AuxBuilder> Air<AB> for FoldingAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+        let a: AB::Expr = local[0].clone().into();
+        let b: AB::Expr = local[1].clone().into();
+        let c: AB::Expr = local[2].clone().into();
+
+        builder.when_first_row().assert_one(a.clone());
+        builder
+            .when_transition()
+            .assert_eq(a.clone() * b.clone(), next[2].clone());
+        builder.assert_zero(c.cube() - a);
+        builder.assert_zero_ext(AB::ExprEF::from(b.clone()) * AB::ExprEF::from(c));
+        builder.when_last_row().assert_zero(b);
+    }
+}
+
+const NUM_CONSTRAINTS: usize = 5;
+
+/// Random values of the main rows and the selectors.
+struct Point {
+    main: Vec<Val>,
+    selectors: [Val; 3],
+}
+
+impl Point {
+    fn random(rng: &mut SmallRng) -> Self {
+        Self {
+            main: (0..2 * WIDTH)
+                .map(|_| Val::from_u32(rng.random()))
+                .collect(),
+            selectors: [(); 3].map(|_| Val::from_u32(rng.random())),
+        }
+    }
+
+    /// The verifier folder's accumulators and constraint count under `folding`.
+    fn verifier_accumulators(
+        &self,
+        folding: ConstraintFolding<'_, Challenge>,
+    ) -> (Vec<Challenge>, usize) {
+        let main: Vec<Challenge> = self.main.iter().map(|&v| v.into()).collect();
+        let (alpha, phase_alphas, constraint_phases) = match folding {
+            ConstraintFolding::Single(alpha) => (alpha, &[][..], &[][..]),
+            ConstraintFolding::PerPhase {
+                alphas,
+                constraint_phases,
+            } => (Challenge::ZERO, alphas, constraint_phases),
+        };
+        let mut folder = VerifierFolder::<FixtureConfig> {
+            main_local: &main[..WIDTH],
+            main_next: &main[WIDTH..],
+            preprocessed_local: &[],
+            preprocessed_next: &[],
+            aux_local: &[],
+            aux_next: &[],
+            aux_matrix_widths: &[],
+            public_values: &[],
+            aux_challenges: &[],
+            aux_exposed_values: &[],
+            is_first_row: self.selectors[0].into(),
+            is_last_row: self.selectors[1].into(),
+            is_transition: self.selectors[2].into(),
+            alpha,
+            phase_alphas,
+            constraint_phases,
+            phase_accumulators: vec![Challenge::ZERO; phase_alphas.len()],
+            accumulator: Challenge::ZERO,
+            constraint_index: 0,
+            constraint_labels: Vec::new(),
+        };
+        FoldingAir.eval(&mut folder);
+        let accumulators = if phase_alphas.is_empty() {
+            vec![folder.accumulator]
+        } else {
+            folder.phase_accumulators
+        };
+        (accumulators, folder.constraint_index)
+    }
+
+    /// The prover folder's accumulator, in every lane, with `coefficients`.
+    fn prover_accumulator(&self, coefficients: &[Challenge]) -> PackedChallenge<FixtureConfig> {
+        let main: Vec<PackedVal<FixtureConfig>> = self.main.iter().map(|&v| v.into()).collect();
+        let mut folder = ProverFolder::<FixtureConfig> {
+            main: RowMajorMatrixView::new(&main, WIDTH),
+            preprocessed: RowMajorMatrixView::new(&[], 0),
+            aux: RowMajorMatrixView::new(&[], 0),
+            aux_matrix_widths: &[],
+            public_values: &[],
+            aux_challenges: &[],
+            aux_exposed_values: &[],
+            is_first_row: self.selectors[0].into(),
+            is_last_row: self.selectors[1].into(),
+            is_transition: self.selectors[2].into(),
+            alpha_powers: coefficients,
+            accumulator: PackedChallenge::<FixtureConfig>::ZERO,
+            constraint_index: 0,
+        };
+        FoldingAir.eval(&mut folder);
+        assert_eq!(folder.constraint_index, coefficients.len());
+        folder.accumulator
+    }
+}
+
+/// Both folders agree under `folding` at random points.
+fn check_folders_agree(rng: &mut SmallRng, folding: ConstraintFolding<'_, Challenge>) {
+    let coefficients = folding.coefficients(NUM_CONSTRAINTS);
+    for _ in 0..8 {
+        let point = Point::random(rng);
+        let (accumulators, num_constraints) = point.verifier_accumulators(folding);
+        assert_eq!(num_constraints, NUM_CONSTRAINTS);
+        assert_eq!(accumulators.len(), folding.num_accumulators());
+
+        let folded = ConstraintFolding::combine(&accumulators);
+        assert_eq!(
+            point.prover_accumulator(&coefficients),
+            PackedChallenge::<FixtureConfig>::from(folded)
+        );
+    }
+}
+
+fn random_challenge(rng: &mut SmallRng) -> Challenge {
+    Challenge::from_basis_coefficients_fn(|_| Val::from_u32(rng.random()))
+}
+
+#[test]
+fn test_single_alpha_folding() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let alpha = random_challenge(&mut rng);
+    let folding = ConstraintFolding::Single(alpha);
+
+    // The coefficients are the powers of alpha, highest first
+    let coefficients = folding.coefficients(NUM_CONSTRAINTS);
+    for (i, &coefficient) in coefficients.iter().enumerate() {
+        assert_eq!(coefficient, alpha.exp_u64((NUM_CONSTRAINTS - 1 - i) as u64));
+    }
+    check_folders_agree(&mut rng, folding);
+}
+
+#[test]
+fn test_per_phase_folding() {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let alphas = [random_challenge(&mut rng), random_challenge(&mut rng)];
+    let constraint_phases = [1, 0, 1, 1, 0];
+    let folding = ConstraintFolding::PerPhase {
+        alphas: &alphas,
+        constraint_phases: &constraint_phases,
+    };
+
+    // Within each phase, the powers of its alpha, highest first
+    let coefficients = folding.coefficients(NUM_CONSTRAINTS);
+    let expected = [
+        alphas[1].square(),
+        alphas[0],
+        alphas[1],
+        Challenge::ONE,
+        Challenge::ONE,
+    ];
+    assert_eq!(coefficients, expected);
+    check_folders_agree(&mut rng, folding);
+}
@@ -0,0 +1,127 @@
+//! Golden-file regression tracking of proof sizes and prover phase timings
+//!
+//! The comparison against `golden/prover.json` times real proofs, so it is ignored by
+//! default and best run in release mode:
+//! `cargo test --release -p p3-uni-stark-mt --features golden --test golden -- --ignored`.
+//! Set `UPDATE_GOLDEN=1` to rewrite the golden file and `GOLDEN_THRESHOLD` to change the
+//! tolerated slowdown (a fraction, 0.25 by default).
+
+#![cfg(feature = "golden")]
+
+use std::fs;
+use std::path::Path;
+
+use p3_uni_stark_mt::golden::{
+    record_case, record_golden, regressions, GoldenRecord, PhaseTiming, Regression, GOLDEN_CASES,
+};
+use p3_uni_stark_mt::ProverPhase;
+
+const GOLDEN_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden/prover.json");
+
+#[test]
+#[ignore = "times real proofs against the golden file"]
+fn test_golden_regressions() {
+    let current = record_golden();
+    let path = Path::new(GOLDEN_FILE);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() || !path.exists() {
+        fs::create_dir_all(path.parent().expect("golden file has a directory"))
+            .expect("failed to create the golden directory");
+        let json = serde_json::to_string_pretty(&current).expect("serialization failed");
+        fs::write(path, json + "\n").expect("failed to write the golden file");
+        return;
+    }
+
+    let json = fs::read_to_string(path).expect("failed to read the golden file");
+    let golden: Vec<GoldenRecord> = serde_json::from_str(&json).expect("invalid golden file");
+    let threshold = std::env::var("GOLDEN_THRESHOLD").map_or(0.25, |threshold| {
+        threshold.parse().expect("invalid GOLDEN_THRESHOLD")
+    });
+    let found = regressions(&golden, &current, threshold);
+    assert!(
+        found.is_empty(),
+        "regressions beyond {:.0}%:\n{}",
+        threshold * 100.0,
+        found
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+}
+
+#[test]
+fn test_golden_record_phases() {
+    let record = record_case(&GOLDEN_CASES[0]);
+    assert_eq!(record.name, GOLDEN_CASES[0].name);
+    assert!(record.proof_size > 0);
+    let phases: Vec<_> = record.phases.iter().map(|timing| timing.phase).collect();
+    assert_eq!(
+        phases,
+        [
+            ProverPhase::MainCommit,
+            ProverPhase::Quotient,
+            ProverPhase::Opening
+        ]
+    );
+
+    // Proof sizes are deterministic
+    assert_eq!(record_case(&GOLDEN_CASES[0]).proof_size, record.proof_size);
+}
+
+#[test]
+fn test_golden_regressions_threshold() {
+    let record = |proof_size, quotient_micros| GoldenRecord {
+        name: "case".into(),
+        proof_size,
+        phases: vec![
+            PhaseTiming {
+                phase: ProverPhase::MainCommit,
+                micros: 10_000,
+            },
+            PhaseTiming {
+                phase: ProverPhase::Quotient,
+                micros: quotient_micros,
+            },
+        ],
+    };
+    let golden = [record(1000, 10_000)];
+
+    // Within the threshold, or slower by less than the noise floor
+    assert!(regressions(&golden, &[record(1100, 12_000)], 0.25).is_empty());
+    assert!(regressions(&[record(1000, 100)], &[record(1000, 900)], 0.25).is_empty());
+
+    assert_eq!(
+        regressions(&golden, &[record(1300, 13_000)], 0.25),
+        [
+            Regression::ProofSize {
+                case: "case".into(),
+                golden: 1000,
+                current: 1300,
+            },
+            Regression::Timing {
+                case: "case".into(),
+                phase: ProverPhase::Quotient,
+                golden: 10_000,
+                current: Some(13_000),
+            },
+        ]
+    );
+
+    // Missing cases and phases are regressions, new ones are not
+    let mut without_quotient = record(1000, 10_000);
+    without_quotient.phases.pop();
+    assert_eq!(
+        regressions(&golden, &[without_quotient], 0.25),
+        [Regression::Timing {
+            case: "case".into(),
+            phase: ProverPhase::Quotient,
+            golden: 10_000,
+            current: None,
+        }]
+    );
+    assert_eq!(
+        regressions(&golden, &[], 0.25),
+        [Regression::MissingCase("case".into())]
+    );
+    assert!(regressions(&[], &golden, 0.25).is_empty());
+}
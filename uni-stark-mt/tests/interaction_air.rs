@@ -0,0 +1,171 @@
+//! Test for bus interactions between tables
+//!
+//! A counter table sends each of its values `0..8` on a bus; a table of `0..16` receives
+//! them, counted by a multiplicity column. Neither table balances on its own, but their
+//! net sums cancel in a multi-table proof.
+
+#![cfg(feature = "lookups")]
+
+use p3_air::{Air, BaseAir};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Challenge, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove, prove_multi, setup, verify, verify_multi, AuxBuilder, AuxTraceBuilder, BusInteractions,
+    InteractionBuilder, Multiplicity, VerificationError,
+};
+
+const VALUE_BUS: usize = 3;
+
+/// A table whose only constraints are its bus interactions
+pub struct BusChip {
+    width: usize,
+    bus: BusInteractions,
+}
+
+impl BusChip {
+    /// Sends its single column once per row
+    fn sender(bus: usize) -> Self {
+        Self {
+            width: 1,
+            bus: InteractionBuilder::new()
+                .send(bus, vec![0], Multiplicity::One)
+                .build(),
+        }
+    }
+
+    /// Receives its first column as many times as its second says
+    fn receiver(bus: usize) -> Self {
+        Self {
+            width: 2,
+            bus: InteractionBuilder::new()
+                .receive(bus, vec![0], Multiplicity::Column(1))
+                .build(),
+        }
+    }
+}
+
+impl<F> BaseAir<F> for BusChip {
+    fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for BusChip {
+    fn aux_width(&self) -> usize {
+        self.bus.aux_width()
+    }
+
+    fn num_challenges(&self) -> usize {
+        BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        self.bus.build_aux_trace(main, challenges)
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        vec![self.bus.cumulative_sum(aux_phase, 0)]
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for BusChip {
+    fn eval(&self, builder: &mut AB) {
+        self.bus.eval(builder, 0, 0);
+    }
+}
+
+fn sender_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..n).map(F::from_usize).collect())
+}
+
+/// `0..n` with multiplicity 1 for the values below `used`
+fn receiver_trace<F: Field>(n: usize, used: usize) -> RowMajorMatrix<F> {
+    let values = (0..n)
+        .flat_map(|i| [F::from_usize(i), F::from_bool(i < used)])
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+#[test]
+fn test_bus_balances_across_tables() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = [BusChip::sender(VALUE_BUS), BusChip::receiver(VALUE_BUS)];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let traces = vec![
+        sender_trace::<Val>(1 << 3),
+        receiver_trace::<Val>(1 << 4, 1 << 3),
+    ];
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+
+    // Each table has a non-zero net sum; together they cancel
+    let sums: Vec<Challenge> = proof
+        .tables
+        .iter()
+        .map(|table| table.aux_exposed_values[0][0])
+        .collect();
+    assert_ne!(sums[0], Challenge::ZERO);
+    assert_eq!(sums[0] + sums[1], Challenge::ZERO);
+    verify_multi(&config, &airs, &vks, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_unbalanced_bus_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let public_values = vec![vec![], vec![]];
+
+    // The receiver takes one value fewer than is sent
+    let airs = [BusChip::sender(VALUE_BUS), BusChip::receiver(VALUE_BUS)];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+    let traces = vec![
+        sender_trace::<Val>(1 << 3),
+        receiver_trace::<Val>(1 << 4, 7),
+    ];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &proof, &public_values),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+
+    // The same values on another bus do not cancel
+    let airs = [BusChip::sender(VALUE_BUS), BusChip::receiver(VALUE_BUS + 1)];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+    let traces = vec![
+        sender_trace::<Val>(1 << 3),
+        receiver_trace::<Val>(1 << 4, 1 << 3),
+    ];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &proof, &public_values),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+}
+
+#[test]
+fn test_single_table_must_balance_alone() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = BusChip::sender(VALUE_BUS);
+    let (pk, vk) = setup(&config, &air);
+
+    let proof =
+        prove(&config, &air, &pk, sender_trace::<Val>(1 << 3), &[]).expect("proving failed");
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &[]),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+}
@@ -0,0 +1,69 @@
+//! Test for the JSON encoding of proofs and verifying keys
+//!
+//! A fixture proof and its key round-trip through JSON and still verify, and the encoded
+//! objects have exactly the documented keys.
+
+#![cfg(all(feature = "json", feature = "fixtures"))]
+
+use p3_uni_stark_mt::fixtures::{fibonacci_proof, FixtureConfig, FIXTURE_SEED};
+use p3_uni_stark_mt::json::{
+    proof_from_json, proof_to_json, vk_from_json, vk_to_json, PROOF_FIELDS, VERIFYING_KEY_FIELDS,
+};
+use p3_uni_stark_mt::{verify, Proof, ProofLimits, VerifyingKey};
+
+/// Keys of the JSON object `json`, sorted
+fn keys(json: &str) -> Vec<String> {
+    let value: serde_json::Value = serde_json::from_str(json).expect("invalid JSON");
+    let mut keys: Vec<String> = value
+        .as_object()
+        .expect("not an object")
+        .keys()
+        .cloned()
+        .collect();
+    keys.sort();
+    keys
+}
+
+fn sorted(fields: &[&str]) -> Vec<String> {
+    let mut fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+    fields.sort();
+    fields
+}
+
+#[test]
+fn test_json_round_trip() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let limits = ProofLimits::for_air::<FixtureConfig, _>(&fixture.air, &fixture.vk);
+
+    let proof_json = proof_to_json(&fixture.proof);
+    let vk_json = vk_to_json(&fixture.vk);
+    let proof: Proof<FixtureConfig> =
+        proof_from_json(&proof_json, limits).expect("proof decoding failed");
+    let vk: VerifyingKey<FixtureConfig> = vk_from_json(&vk_json).expect("key decoding failed");
+    verify(
+        &fixture.config,
+        &fixture.air,
+        &vk,
+        &proof,
+        &fixture.public_values,
+    )
+    .expect("verification failed");
+
+    // The encoding is canonical
+    assert_eq!(proof_to_json(&proof), proof_json);
+    assert_eq!(vk_to_json(&vk), vk_json);
+
+    // Trailing data and oversized proofs are rejected
+    assert!(proof_from_json::<FixtureConfig>(&format!("{proof_json} {{}}"), limits).is_err());
+    let mut widened = fixture.proof;
+    widened.main_local.extend(widened.main_local.clone());
+    assert!(proof_from_json::<FixtureConfig>(&proof_to_json(&widened), limits).is_err());
+}
+
+#[test]
+fn test_json_field_names() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    assert_eq!(keys(&proof_to_json(&fixture.proof)), sorted(PROOF_FIELDS));
+    assert_eq!(keys(&vk_to_json(&fixture.vk)), sorted(VERIFYING_KEY_FIELDS));
+    assert!(proof_to_json(&fixture.proof).starts_with(r#"{"version":"#));
+}
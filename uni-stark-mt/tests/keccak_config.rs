@@ -0,0 +1,161 @@
+//! Compatibility tests with the Keccak-based config stacks used across Plonky3's examples
+//!
+//! Fields are hashed into a `KeccakF` sponge MMCS and the transcript runs through a
+//! `SerializingChallenger32`/`SerializingChallenger64` over Keccak-256, as in the upstream
+//! examples, so proofs from those stacks work without any adapter.
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_baby_bear::BabyBear;
+use p3_challenger::{HashChallenger, SerializingChallenger32, SerializingChallenger64};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_goldilocks::Goldilocks;
+use p3_keccak::{Keccak256Hash, KeccakF};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher};
+use p3_uni_stark_mt::{prove, setup, verify, AuxBuilder, AuxTraceBuilder, StarkConfig};
+
+/// Counter AIR with a running-sum auxiliary column, so both the base field and the
+/// extension field MMCS are exercised.
+pub struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut sum = EF::ZERO;
+        let values = main
+            .values
+            .iter()
+            .map(|&a| {
+                sum += challenges[0] * a;
+                sum
+            })
+            .collect();
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let a_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+
+        builder.when_first_row().assert_zero(a.clone());
+        builder
+            .when_transition()
+            .assert_eq(a_next.clone(), a.clone() + AB::Expr::ONE);
+
+        // z[0] = r·a[0], z[i+1] = z[i] + r·a[i+1]
+        let r: AB::ExprEF = builder.aux_challenges()[0].into();
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), r.clone() * a);
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z + r * a_next);
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new((0..n).map(F::from_usize).collect(), 1)
+}
+
+type ByteHash = Keccak256Hash;
+type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+type FieldHash = SerializingHasher<U64Hash>;
+type MyCompress = CompressionFunctionFromHasher<U64Hash, 2, 4>;
+
+fn keccak_mmcs<F: Field>() -> KeccakMmcs<F> {
+    let u64_hash = U64Hash::new(KeccakF {});
+    let field_hash = FieldHash::new(u64_hash);
+    let compress = MyCompress::new(u64_hash);
+    KeccakMmcs::new(field_hash, compress)
+}
+
+type KeccakMmcs<F> = MerkleTreeMmcs<
+    [F; p3_keccak::VECTOR_LEN],
+    [u64; p3_keccak::VECTOR_LEN],
+    FieldHash,
+    MyCompress,
+    4,
+>;
+
+#[test]
+fn test_keccak_baby_bear() {
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type ValMmcs = KeccakMmcs<Val>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type Dft = Radix2DitParallel<Val>;
+    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    let val_mmcs = keccak_mmcs::<Val>();
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_params);
+    let challenger = Challenger::from_hasher(vec![], ByteHash {});
+    let config = MyConfig::new(pcs, challenger);
+
+    let trace = generate_trace::<Val>(1 << 5);
+    let (pk, vk) = setup(&config, &CounterAir);
+    let proof = prove(&config, &CounterAir, &pk, trace, &[]).expect("proving failed");
+    verify(&config, &CounterAir, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_keccak_goldilocks() {
+    type Val = Goldilocks;
+    type Challenge = BinomialExtensionField<Val, 2>;
+    type ValMmcs = KeccakMmcs<Val>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+    type Dft = Radix2DitParallel<Val>;
+    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+    let val_mmcs = keccak_mmcs::<Val>();
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_params);
+    let challenger = Challenger::from_hasher(vec![], ByteHash {});
+    let config = MyConfig::new(pcs, challenger);
+
+    let trace = generate_trace::<Val>(1 << 5);
+    let (pk, vk) = setup(&config, &CounterAir);
+    let proof = prove(&config, &CounterAir, &pk, trace, &[]).expect("proving failed");
+    verify(&config, &CounterAir, &vk, &proof, &[]).expect("verification failed");
+
+    // A trace breaking the counter must not verify
+    let mut bad_trace = generate_trace::<Val>(1 << 5);
+    bad_trace.values[3] = Val::ZERO;
+    let proof = prove(&config, &CounterAir, &pk, bad_trace, &[]).expect("proving failed");
+    assert!(verify(&config, &CounterAir, &vk, &proof, &[]).is_err());
+}
@@ -0,0 +1,182 @@
+//! Tests over KoalaBear and its degree-4 extension
+//!
+//! The main trace, the aux phases and the quotient are all committed over KoalaBear, with
+//! challenges drawn from its quartic extension, as in new Plonky3 deployments. A two-phase
+//! AIR checks that a later phase built from an earlier one proves too, reading its
+//! challenges by name from a `ChallengePlan`.
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxBuilder, AuxTraceBuilder, ChallengePlan, StarkConfig,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// Two-phase AIR: phase 0 is the running sum `z` of `a` weighted by a challenge `r`,
+/// phase 1 is `w = z + s` for a second challenge `s`
+pub struct WeightedSumAir {
+    /// Build a deliberately wrong second phase
+    pub corrupt_aux: bool,
+}
+
+/// The weight `r` of phase 0 and the shift `s` of phase 1
+fn weighted_sum_challenges() -> ChallengePlan {
+    ChallengePlan::new()
+        .with_group(0, "sum", &["r"])
+        .with_group(1, "shift", &["s"])
+}
+
+impl<F> BaseAir<F> for WeightedSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for WeightedSumAir {
+    fn num_aux_phases(&self) -> usize {
+        2
+    }
+
+    fn aux_phase_width(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn challenge_plan(&self) -> ChallengePlan {
+        weighted_sum_challenges()
+    }
+
+    fn build_aux_phase(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        let plan = weighted_sum_challenges();
+        let values = if phase == 0 {
+            let r = plan.phase_challenge(phase, challenges, "sum", "r");
+            let mut sum = EF::ZERO;
+            main.values
+                .iter()
+                .map(|&a| {
+                    sum += r * a;
+                    sum
+                })
+                .collect()
+        } else {
+            let s = plan.phase_challenge(phase, challenges, "shift", "s");
+            let mut values: Vec<EF> = prior_phases[0].values.iter().map(|&z| z + s).collect();
+            if self.corrupt_aux {
+                values[2] += EF::ONE;
+            }
+            values
+        };
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for WeightedSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let a_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+
+        let plan = weighted_sum_challenges();
+        let r: AB::ExprEF = builder.aux_challenge(&plan, "sum", "r").into();
+        let s: AB::ExprEF = builder.aux_challenge(&plan, "shift", "s").into();
+        let aux = builder.aux();
+        let local = aux.row_slice(0).expect("Matrix is empty?");
+        let next = aux.row_slice(1).expect("Matrix only has 1 row?");
+        let (z, w): (AB::ExprEF, AB::ExprEF) = (local[0].into(), local[1].into());
+        let z_next: AB::ExprEF = next[0].into();
+
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), r.clone() * AB::ExprEF::from(a));
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z.clone() + r * AB::ExprEF::from(a_next));
+        builder.assert_eq_ext(w, z + s);
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new((0..n).map(|i| F::from_usize(3 * i + 1)).collect(), 1)
+}
+
+// Type aliases for test configuration
+type Val = KoalaBear;
+type Perm = Poseidon2KoalaBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_koala_bear_two_phase() {
+    let config = create_config();
+    let air = WeightedSumAir { corrupt_aux: false };
+    let trace = generate_trace::<Val>(1 << 5);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+
+    // One challenge per phase, read back by name
+    let plan = AuxTraceBuilder::<Val, Challenge>::challenge_plan(&air);
+    assert_eq!(plan.num_challenges(), 2);
+    assert_eq!(plan.index("shift", "s"), Some(1));
+    assert_eq!(plan.phase_index(1, "shift", "s"), Some(0));
+    assert_eq!(plan.phase_index(0, "shift", "s"), None);
+
+    // One commitment per phase, each column opened as 4 base field columns
+    assert_eq!(proof.aux_commits.len(), 2);
+    assert_eq!(proof.aux_local.as_ref().map(|aux| aux[1].len()), Some(4));
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_koala_bear_corrupt_aux_rejected() {
+    let config = create_config();
+    let air = WeightedSumAir { corrupt_aux: true };
+    let trace = generate_trace::<Val>(1 << 5);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
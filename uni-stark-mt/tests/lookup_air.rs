@@ -0,0 +1,155 @@
+//! Test for the built-in LogUp lookup gadget
+//!
+//! The main trace has a `value` column looked up in a `table` column holding `0..n`, with
+//! a `multiplicity` column counting how often each table entry is used.
+
+#![cfg(feature = "lookups")]
+
+use p3_air::{Air, BaseAir};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxBuilder, AuxTraceBuilder, LogUpLookup, LookupInteraction, StarkConfig,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+const VALUE: usize = 0;
+const TABLE: usize = 1;
+const MULTIPLICITY: usize = 2;
+
+/// Looks up every `value` in the `table` column
+pub struct TableLookupAir {
+    lookup: LogUpLookup,
+}
+
+impl Default for TableLookupAir {
+    fn default() -> Self {
+        Self {
+            lookup: LogUpLookup::new(vec![
+                LookupInteraction::send(vec![VALUE]),
+                LookupInteraction::receive(vec![TABLE], MULTIPLICITY),
+            ]),
+        }
+    }
+}
+
+impl<F> BaseAir<F> for TableLookupAir {
+    fn width(&self) -> usize {
+        3
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for TableLookupAir {
+    fn aux_width(&self) -> usize {
+        self.lookup.aux_width()
+    }
+
+    fn num_challenges(&self) -> usize {
+        LogUpLookup::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        self.lookup.build_aux_trace(main, challenges)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for TableLookupAir {
+    fn eval(&self, builder: &mut AB) {
+        self.lookup.eval(builder, 0);
+    }
+}
+
+/// `value[i] = (3i + 1) mod 8` against the table `0..n`
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let values: Vec<usize> = (0..n).map(|i| (3 * i + 1) % 8).collect();
+    let mut multiplicities = vec![0; n];
+    for &v in &values {
+        multiplicities[v] += 1;
+    }
+    let rows = (0..n)
+        .flat_map(|i| {
+            [
+                F::from_usize(values[i]),
+                F::from_usize(i),
+                F::from_usize(multiplicities[i]),
+            ]
+        })
+        .collect();
+    RowMajorMatrix::new(rows, 3)
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_logup_lookup() {
+    let config = create_config();
+    let air = TableLookupAir::default();
+    let trace = generate_trace::<Val>(1 << 4);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_logup_lookup_missing_entry_rejected() {
+    let config = create_config();
+    let air = TableLookupAir::default();
+
+    // Look up a value that is not in the table
+    let mut trace = generate_trace::<Val>(1 << 4);
+    trace.values[VALUE] = Val::from_u64(100);
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_logup_lookup_wrong_multiplicity_rejected() {
+    let config = create_config();
+    let air = TableLookupAir::default();
+
+    let mut trace = generate_trace::<Val>(1 << 4);
+    trace.values[3 + MULTIPLICITY] += Val::ONE;
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
@@ -0,0 +1,201 @@
+//! Test for the chip/machine abstraction
+//!
+//! A machine with an addition chip and an 8-bit range-check chip proves a record of
+//! additions: each chip generates its trace from the record, and the addition chip range
+//! checks its operands and sums on the range chip's bus.
+
+#![cfg(feature = "machine")]
+
+use p3_air::{Air, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Challenge, FixtureConfig, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    AuxBuilder, AuxTraceBuilder, BusInteractions, Chip, InteractionBuilder, Machine,
+    RangeCheckChip, RangeCheckCounts, TraceGenerator,
+};
+
+const RANGE_BUS: usize = 0;
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+
+/// The chips of the test machine
+pub enum AddChip {
+    /// Rows `(a, b, c)` with `c = a + b`, all three range checked
+    Add(BusInteractions),
+    /// The range-check chip
+    Range(RangeCheckChip),
+}
+
+/// Rows `(a, b, a + b)` for each addition of `record`, padded with zeros to a power of two
+fn add_trace<F: Field>(record: &[(u64, u64)]) -> RowMajorMatrix<F> {
+    let height = record.len().next_power_of_two().max(2);
+    let values = record
+        .iter()
+        .map(|&(a, b)| [a, b, a + b])
+        .chain(core::iter::repeat([0; 3]))
+        .take(height)
+        .flat_map(|row| row.map(F::from_u64))
+        .collect();
+    RowMajorMatrix::new(values, 3)
+}
+
+impl<F: Field> BaseAir<F> for AddChip {
+    fn width(&self) -> usize {
+        match self {
+            Self::Add(_) => 3,
+            Self::Range(chip) => BaseAir::<F>::width(chip),
+        }
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        match self {
+            Self::Add(_) => None,
+            Self::Range(chip) => chip.preprocessed_trace(),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for AddChip {
+    fn aux_width(&self) -> usize {
+        match self {
+            Self::Add(bus) => bus.aux_width(),
+            Self::Range(chip) => AuxTraceBuilder::<F, EF>::aux_width(chip),
+        }
+    }
+
+    fn num_challenges(&self) -> usize {
+        BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        match self {
+            Self::Add(bus) => bus.build_aux_trace(main, challenges),
+            Self::Range(chip) => chip.build_aux_trace(main, challenges),
+        }
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        match self {
+            Self::Add(bus) => vec![bus.cumulative_sum(aux_phase, 0)],
+            Self::Range(chip) => vec![chip.cumulative_sum(aux_phase)],
+        }
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for AddChip {
+    fn eval(&self, builder: &mut AB) {
+        match self {
+            Self::Add(bus) => {
+                let main = builder.main();
+                let local = main.row_slice(0).expect("Matrix is empty?");
+                builder.assert_eq(local[A].clone() + local[B].clone(), local[C].clone());
+                bus.eval(builder, 0, 0);
+            }
+            Self::Range(chip) => chip.eval(builder),
+        }
+    }
+}
+
+impl TraceGenerator<Val> for AddChip {
+    type Inputs = [(u64, u64)];
+
+    fn generate(&self, record: &[(u64, u64)]) -> RowMajorMatrix<Val> {
+        match self {
+            Self::Add(_) => add_trace(record),
+            Self::Range(chip) => {
+                let adds = add_trace::<Val>(record);
+                let mut counts = RangeCheckCounts::new(chip.bits);
+                for column in [A, B, C] {
+                    counts.record_column(&adds, column);
+                }
+                counts.trace()
+            }
+        }
+    }
+}
+
+impl Chip<Val, Challenge> for AddChip {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Add(_) => "add",
+            Self::Range(_) => "range",
+        }
+    }
+}
+
+/// Additions of 8-bit values with 8-bit sums
+pub struct AddMachine {
+    chips: [AddChip; 2],
+}
+
+impl AddMachine {
+    fn new() -> Self {
+        let range = RangeCheckChip::new(8, RANGE_BUS);
+        let add = range
+            .register(InteractionBuilder::new(), &[A, B, C])
+            .build();
+        Self {
+            chips: [AddChip::Add(add), AddChip::Range(range)],
+        }
+    }
+}
+
+impl Machine<FixtureConfig> for AddMachine {
+    type Chip = AddChip;
+
+    fn chips(&self) -> &[AddChip] {
+        &self.chips
+    }
+}
+
+#[test]
+fn test_machine_proves_record() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let machine = AddMachine::new();
+    let (pks, vks) = machine.setup(&config);
+
+    let record = [(1, 2), (100, 155), (0, 0), (17, 38), (255, 0)];
+    let traces = machine.generate_traces(&record);
+    assert_eq!(traces[0].height(), 8);
+    assert_eq!(traces[0].row_slice(1).unwrap()[C], Val::from_u8(255));
+    assert_eq!(traces[1].height(), 256);
+
+    let public_values = machine.public_values(&record);
+    let proof = machine
+        .prove(&config, &pks, &record)
+        .expect("proving failed");
+    machine
+        .verify(&config, &vks, &proof, &public_values)
+        .expect("verification failed");
+}
+
+#[test]
+fn test_chip_index() {
+    let machine = AddMachine::new();
+    assert_eq!(machine.chip_index("add"), Some(0));
+    assert_eq!(machine.chip_index("range"), Some(1));
+    assert_eq!(machine.chip_index("cpu"), None);
+}
+
+#[test]
+#[should_panic(expected = "out of the range-check table")]
+fn test_overflowing_record_rejected() {
+    // 200 + 100 does not fit in a byte, so the range chip cannot count the sum
+    let _ = AddMachine::new().generate_traces(&[(200, 100)]);
+}
@@ -0,0 +1,287 @@
+//! Test for the offline memory checking gadget
+//!
+//! The main trace lists the memory accesses of a short program, one per row, out of
+//! address order; the gadget sorts them in its first aux phase and checks them against the
+//! accesses in its second. A forged sorted trace that splits an address into two blocks,
+//! to make a stale read look fresh, must be rejected.
+
+#![cfg(feature = "memory")]
+
+use p3_air::{Air, BaseAir};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxBuilder, AuxTraceBuilder, ChallengePlan, MemoryChecker,
+};
+
+/// Main columns: address, timestamp, value, is_write
+pub struct MemoryAir {
+    memory: MemoryChecker,
+}
+
+impl Default for MemoryAir {
+    fn default() -> Self {
+        Self {
+            memory: MemoryChecker {
+                address: 0,
+                timestamp: 1,
+                value: 2,
+                is_write: 3,
+                timestamp_bits: 4,
+                address_bits: 4,
+            },
+        }
+    }
+}
+
+impl<F> BaseAir<F> for MemoryAir {
+    fn width(&self) -> usize {
+        4
+    }
+}
+
+impl<F: PrimeField64, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for MemoryAir {
+    fn num_aux_phases(&self) -> usize {
+        MemoryChecker::NUM_PHASES
+    }
+
+    fn aux_phase_width(&self, phase: usize) -> usize {
+        self.memory.aux_phase_width(phase)
+    }
+
+    fn aux_phase_in_base_field(&self, phase: usize) -> bool {
+        self.memory.aux_phase_in_base_field(phase)
+    }
+
+    fn challenge_plan(&self) -> ChallengePlan {
+        self.memory.challenge_plan()
+    }
+
+    fn build_aux_phase(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        self.memory
+            .build_aux_phase(phase, main, prior_phases, challenges)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for MemoryAir {
+    fn eval(&self, builder: &mut AB) {
+        self.memory.eval(builder);
+    }
+}
+
+/// [`MemoryAir`] whose sorted trace lists the accesses in a given order rather than sorted.
+struct ForgedMemoryAir {
+    inner: MemoryAir,
+    /// Main rows in the order of the forged sorted trace
+    order: Vec<usize>,
+}
+
+impl<F> BaseAir<F> for ForgedMemoryAir {
+    fn width(&self) -> usize {
+        4
+    }
+}
+
+impl<F: PrimeField64, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ForgedMemoryAir {
+    fn num_aux_phases(&self) -> usize {
+        MemoryChecker::NUM_PHASES
+    }
+
+    fn aux_phase_width(&self, phase: usize) -> usize {
+        self.inner.memory.aux_phase_width(phase)
+    }
+
+    fn aux_phase_in_base_field(&self, phase: usize) -> bool {
+        self.inner.memory.aux_phase_in_base_field(phase)
+    }
+
+    fn challenge_plan(&self) -> ChallengePlan {
+        self.inner.memory.challenge_plan()
+    }
+
+    fn build_aux_phase(
+        &self,
+        phase: usize,
+        main: &RowMajorMatrix<F>,
+        prior_phases: &[RowMajorMatrix<EF>],
+        challenges: &[EF],
+    ) -> RowMajorMatrix<EF> {
+        match phase {
+            0 => self.forged_sorted(main),
+            _ => self
+                .inner
+                .memory
+                .build_aux_phase(phase, main, prior_phases, challenges),
+        }
+    }
+}
+
+impl ForgedMemoryAir {
+    /// The gadget's sorted trace over the accesses in `order`: address, timestamp, value,
+    /// is_write, same address flag, inverse of the address step, then the timestamp step
+    /// bits and the address step bits, filled as the gadget would.
+    fn forged_sorted<F: PrimeField64, EF: ExtensionField<F>>(
+        &self,
+        main: &RowMajorMatrix<F>,
+    ) -> RowMajorMatrix<EF> {
+        const SAME_ADDRESS: usize = 4;
+        const ADDRESS_STEP_INVERSE: usize = 5;
+        const TIMESTAMP_STEP_BITS: usize = 6;
+        let memory = &self.inner.memory;
+        let address_step_bits = TIMESTAMP_STEP_BITS + memory.timestamp_bits;
+        let width = memory.aux_phase_width(0);
+        let accesses: Vec<&[F]> = self
+            .order
+            .iter()
+            .map(|&i| &main.values[i * main.width..(i + 1) * main.width])
+            .collect();
+
+        let mut values = EF::zero_vec(accesses.len() * width);
+        for (i, row) in values.chunks_exact_mut(width).enumerate() {
+            let access = accesses[i];
+            for (column, &value) in access.iter().enumerate() {
+                row[column] = EF::from(value);
+            }
+            let Some(next) = accesses.get(i + 1) else {
+                continue;
+            };
+            let address_step = next[0] - access[0];
+            let (bits, gap) = if address_step == F::ZERO {
+                row[SAME_ADDRESS] = EF::ONE;
+                (
+                    TIMESTAMP_STEP_BITS..address_step_bits,
+                    next[1] - access[1] - F::ONE,
+                )
+            } else {
+                row[ADDRESS_STEP_INVERSE] = EF::from(address_step.inverse());
+                (address_step_bits..width, address_step - F::ONE)
+            };
+            let gap = gap.as_canonical_u64();
+            for (bit, column) in bits.enumerate() {
+                row[column] = EF::from_bool((gap >> bit) & 1 == 1);
+            }
+        }
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ForgedMemoryAir {
+    fn eval(&self, builder: &mut AB) {
+        self.inner.memory.eval(builder);
+    }
+}
+
+/// `(address, value, is_write)` per access, at timestamps 1, 2, …; padded with reads of
+/// address 2
+const PROGRAM: [(usize, usize, bool); 8] = [
+    (5, 10, true),
+    (2, 7, true),
+    (5, 10, false),
+    (5, 11, true),
+    (2, 7, false),
+    (9, 1, true),
+    (5, 11, false),
+    (9, 1, false),
+];
+
+fn memory_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let values = (0..n)
+        .flat_map(|i| {
+            let (address, value, is_write) = PROGRAM.get(i).copied().unwrap_or((2, 7, false));
+            [
+                F::from_usize(address),
+                F::from_usize(i + 1),
+                F::from_usize(value),
+                F::from_bool(is_write),
+            ]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 4)
+}
+
+#[test]
+fn test_memory_consistency() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = MemoryAir::default();
+    let (pk, vk) = setup(&config, &air);
+
+    let proof =
+        prove(&config, &air, &pk, memory_trace::<Val>(1 << 4), &[]).expect("proving failed");
+
+    // The sorted trace, then the permutation check
+    assert_eq!(proof.aux_commits.len(), 2);
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_stale_read_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = MemoryAir::default();
+    let (pk, vk) = setup(&config, &air);
+
+    // Address 5 is read at timestamp 7 with the value it held before timestamp 4
+    let mut trace = memory_trace::<Val>(1 << 4);
+    trace.values[6 * 4 + 2] = Val::from_usize(10);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_read_before_write_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = MemoryAir::default();
+    let (pk, vk) = setup(&config, &air);
+
+    // Address 9 is read before anything is written to it
+    let mut trace = memory_trace::<Val>(1 << 4);
+    trace.values[5 * 4 + 3] = Val::from_bool(false);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_split_address_block_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+
+    // Address 5 is written 5 at timestamp 1, then 7 at timestamp 5, and read at timestamp
+    // 10 with the stale value 5; address 2 is written once and read back
+    let accesses: [(usize, usize, usize, bool); 8] = [
+        (5, 1, 5, true),
+        (5, 5, 7, true),
+        (5, 10, 5, false),
+        (2, 11, 7, true),
+        (2, 12, 7, false),
+        (2, 13, 7, false),
+        (2, 14, 7, false),
+        (2, 15, 7, false),
+    ];
+    let values = accesses
+        .iter()
+        .flat_map(|&(address, timestamp, value, is_write)| {
+            [
+                Val::from_usize(address),
+                Val::from_usize(timestamp),
+                Val::from_usize(value),
+                Val::from_bool(is_write),
+            ]
+        })
+        .collect();
+    let trace = RowMajorMatrix::new(values, 4);
+
+    // Sorted as [5: write 5, stale read], [2: ...], [5: write 7], the stale read follows
+    // the write it returns and the later write starts a block of its own
+    let air = ForgedMemoryAir {
+        inner: MemoryAir::default(),
+        order: vec![0, 2, 3, 4, 5, 6, 7, 1],
+    };
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
@@ -0,0 +1,86 @@
+//! Structured prover metrics, with peak allocation read from a counting global allocator
+
+#![cfg(all(feature = "std", feature = "prover", feature = "fixtures"))]
+
+use p3_uni_stark_mt::fixtures::{
+    baby_bear_config, fibonacci_trace, FibonacciAir, Val, FIXTURE_SEED,
+};
+use p3_uni_stark_mt::{
+    prove_with_metrics, prove_with_telemetry, setup, verify, CountingAllocator, MetricsRecorder,
+    ProverPhase,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+#[test]
+fn test_prove_with_metrics() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = FibonacciAir;
+    let trace = fibonacci_trace::<Val>(6);
+    let public_values = [*trace.values.last().expect("trace is empty")];
+    let (pk, vk) = setup(&config, &air);
+
+    let (proof, metrics) =
+        prove_with_metrics(&config, &air, &pk, trace, &public_values, Some(&ALLOCATOR))
+            .expect("trace is valid");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    let phases: Vec<_> = metrics.phases.iter().map(|metrics| metrics.phase).collect();
+    assert_eq!(
+        phases,
+        [
+            ProverPhase::MainCommit,
+            ProverPhase::Quotient,
+            ProverPhase::Opening
+        ]
+    );
+
+    // Folding every constraint over the quotient domain, and dividing by the vanishing
+    // polynomial
+    let quotient_size = (64 << vk.log_quotient_degree) as u64;
+    let quotient = metrics.phase(ProverPhase::Quotient).expect("quotient ran");
+    assert_eq!(
+        quotient.field_ops,
+        (2 * vk.num_constraints as u64 + 1) * quotient_size
+    );
+    assert_eq!(metrics.field_ops(), quotient.field_ops);
+
+    // Committing allocates the LDE
+    for phase in &metrics.phases {
+        assert!(
+            phase.peak_allocation.is_some_and(|bytes| bytes > 0),
+            "{phase:?}"
+        );
+    }
+    assert_eq!(
+        metrics.wall_time(),
+        metrics.phases.iter().map(|phase| phase.wall_time).sum()
+    );
+
+    // The metrics serialize for storage
+    let json = serde_json::to_string(&metrics).expect("serialization failed");
+    assert_eq!(
+        serde_json::from_str::<p3_uni_stark_mt::ProverMetrics>(&json).unwrap(),
+        metrics
+    );
+}
+
+#[test]
+fn test_metrics_recorder_without_allocator() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = FibonacciAir;
+    let trace = fibonacci_trace::<Val>(3);
+    let public_values = [*trace.values.last().expect("trace is empty")];
+    let (pk, _) = setup(&config, &air);
+
+    let mut recorder = MetricsRecorder::new();
+    prove_with_telemetry(&config, &air, &pk, trace, &public_values, &mut recorder)
+        .expect("trace is valid");
+    let metrics = recorder.into_metrics();
+    assert_eq!(metrics.phases.len(), 3);
+    assert!(metrics
+        .phases
+        .iter()
+        .all(|phase| phase.peak_allocation.is_none()));
+}
@@ -0,0 +1,93 @@
+//! Tests for main traces read from memory-mapped files
+
+#![cfg(all(feature = "mmap", feature = "fixtures"))]
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{
+    baby_bear_config, fibonacci_trace, FibonacciAir, Val, FIXTURE_SEED,
+};
+use p3_uni_stark_mt::{prove, setup, verify, MmapTrace, MmapTraceError};
+
+/// Write `values` to a fresh temporary file as their in-memory representation.
+fn write_trace(name: &str, values: &[Val]) -> (PathBuf, File) {
+    let path = std::env::temp_dir().join(format!("p3-mmap-{}-{name}", std::process::id()));
+    let (prefix, bytes, suffix) = unsafe { values.align_to::<u8>() };
+    assert!(prefix.is_empty() && suffix.is_empty());
+    File::create(&path)
+        .and_then(|mut file| file.write_all(bytes))
+        .expect("failed to write trace file");
+    let file = File::open(&path).expect("failed to open trace file");
+    (path, file)
+}
+
+#[test]
+fn test_mmap_trace_proves() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let trace = fibonacci_trace::<Val>(4);
+    let (path, file) = write_trace("proves", &trace.values);
+
+    let mapped = unsafe { MmapTrace::<Val>::map(&file, 2) }.expect("failed to map trace");
+    assert_eq!((mapped.width(), mapped.height()), (2, 16));
+    assert_eq!(mapped.values(), &trace.values[..]);
+    assert_eq!(mapped.row_slice(15), Some(&trace.values[30..32]));
+    assert_eq!(mapped.row_slice(16), None);
+
+    let public_values = vec![mapped.values()[31]];
+    let (pk, vk) = setup(&config, &FibonacciAir);
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        &pk,
+        mapped.to_row_major_matrix(),
+        &public_values,
+    )
+    .expect("proving failed");
+    verify(&config, &FibonacciAir, &vk, &proof, &public_values).expect("verification failed");
+
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_mmap_trace_rejects_bad_shapes() {
+    let trace = fibonacci_trace::<Val>(2);
+
+    // Three rows of width 2
+    let (path, file) = write_trace("height", &trace.values[..6]);
+    let err = unsafe { MmapTrace::<Val>::map(&file, 2) }.unwrap_err();
+    assert!(matches!(err, MmapTraceError::HeightNotPowerOfTwo(3)));
+    std::fs::remove_file(path).ok();
+
+    // Eight elements do not fill rows of width 3
+    let (path, file) = write_trace("width", &trace.values);
+    let err = unsafe { MmapTrace::<Val>::map(&file, 3) }.unwrap_err();
+    assert!(matches!(
+        err,
+        MmapTraceError::PartialRow {
+            elements: 8,
+            width: 3
+        }
+    ));
+    assert!(matches!(
+        unsafe { MmapTrace::<Val>::map(&file, 0) },
+        Err(MmapTraceError::ZeroWidth)
+    ));
+    std::fs::remove_file(path).ok();
+
+    // A trailing byte is not a whole element
+    let path = std::env::temp_dir().join(format!("p3-mmap-{}-partial", std::process::id()));
+    std::fs::write(&path, [0u8; 9]).expect("failed to write trace file");
+    let file = File::open(&path).expect("failed to open trace file");
+    let err = unsafe { MmapTrace::<Val>::map(&file, 1) }.unwrap_err();
+    assert!(matches!(
+        err,
+        MmapTraceError::PartialElement {
+            len: 9,
+            element_size: 4
+        }
+    ));
+    std::fs::remove_file(path).ok();
+}
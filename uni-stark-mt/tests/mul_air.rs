@@ -13,7 +13,9 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark_mt::{prove, verify, AuxTraceBuilder, StarkConfig};
+use p3_uni_stark_mt::{
+    keygen, prove, setup, verify, AuxTraceBuilder, StarkConfig, VerificationError,
+};
 use rand::distr::{Distribution, StandardUniform};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
@@ -115,6 +117,14 @@ where
         // Return empty matrix
         RowMajorMatrix::new(vec![], 0)
     }
+
+    fn instance_parameters(&self) -> Vec<F> {
+        vec![
+            F::from_u64(self.degree),
+            F::from_bool(self.uses_boundary_constraints),
+            F::from_bool(self.uses_transition_constraints),
+        ]
+    }
 }
 
 fn create_test_config(
@@ -195,14 +205,15 @@ fn test_mul_air_deg2() {
     let public_values = vec![];
 
     println!("Generating proof for degree 2...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
     );
 
     println!("Verifying proof...");
-    verify(&config, &air, &proof, &public_values).expect("verification failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
 }
 
@@ -220,14 +231,15 @@ fn test_mul_air_deg3() {
     let public_values = vec![];
 
     println!("Generating proof for degree 3...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
     );
 
     println!("Verifying proof...");
-    verify(&config, &air, &proof, &public_values).expect("verification failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
 }
 
@@ -245,13 +257,104 @@ fn test_mul_air_deg4() {
     let public_values = vec![];
 
     println!("Generating proof for degree 4...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
     );
 
     println!("Verifying proof...");
-    verify(&config, &air, &proof, &public_values).expect("verification failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
 }
+
+#[test]
+fn test_mul_air_keygen() {
+    let config = create_test_config(5);
+
+    // The boundary constraint `a² + 1 = b` is of degree 3 once multiplied by its selector
+    for (degree, max_constraint_degree, quotient_degree) in [(2, 3, 2), (3, 3, 2), (4, 4, 4)] {
+        let air = MulAir {
+            degree,
+            ..Default::default()
+        };
+        let vk = keygen(&config, &air);
+        assert_eq!(vk.main_width, TRACE_WIDTH);
+        assert_eq!(vk.num_constraints, 3 * REPETITIONS);
+        assert_eq!(vk.max_constraint_degree, max_constraint_degree);
+        assert_eq!(vk.quotient_degree(), quotient_degree);
+
+        let trace = air.random_valid_trace(1 << 5);
+        let (pk, _) = setup(&config, &air);
+        let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+        assert_eq!(proof.quotient_chunks.len(), quotient_degree);
+        verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+    }
+}
+
+#[test]
+fn test_mul_air_rejects_other_degree() {
+    let log_n = 5;
+    let config = create_test_config(log_n);
+
+    let air = MulAir {
+        degree: 2,
+        ..Default::default()
+    };
+
+    let trace = air.random_valid_trace(1 << log_n);
+    let public_values = vec![];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+
+    // The key records the degree, so it rejects an AIR of the same widths but another degree
+    let other_air = MulAir {
+        degree: 3,
+        ..Default::default()
+    };
+    assert!(matches!(
+        verify(&config, &other_air, &vk, &proof, &public_values),
+        Err(VerificationError::VerifyingKeyMismatch(
+            "instance parameters do not match the AIR"
+        ))
+    ));
+}
+
+/// Asserts `a² = b` on every row, many times over.
+struct RepeatedSquareAir {
+    repetitions: usize,
+}
+
+impl<F> BaseAir<F> for RepeatedSquareAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for RepeatedSquareAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        for _ in 0..self.repetitions {
+            builder.assert_eq(local[0].clone() * local[0].clone(), local[1].clone());
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RepeatedSquareAir {}
+
+#[test]
+fn test_keygen_counts_any_number_of_constraints() {
+    let config = create_test_config(3);
+
+    let vk = keygen(
+        &config,
+        &RepeatedSquareAir {
+            repetitions: 10_000,
+        },
+    );
+    assert_eq!(vk.num_constraints, 10_000);
+    assert_eq!(vk.max_constraint_degree, 2);
+}
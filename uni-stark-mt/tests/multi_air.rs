@@ -0,0 +1,280 @@
+//! Test for proving several AIRs in one proof
+//!
+//! A Fibonacci table (8 rows, no aux trace) and a running-sum table (16 rows, one aux
+//! phase) are proven together under one transcript, and through the batch API with one AIR
+//! shared by several statements.
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, ExtensionBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    prove_batch, prove_multi, setup, verify_batch, verify_multi, AuxBuilder, AuxTraceBuilder,
+    BatchInstance, BatchStatement, StarkConfig, VerificationError,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// The tables of the test machine
+pub enum Chip {
+    /// `(a, b) -> (b, a + b)` from `(0, 1)`, last `b` is the public value
+    Fibonacci,
+    /// Main column `a`, aux column `z` with `z[0] = a[0]` and `z[i+1] = z[i] + r·a[i+1]`
+    RunningSum,
+}
+
+impl<F> BaseAir<F> for Chip {
+    fn width(&self) -> usize {
+        match self {
+            Self::Fibonacci => 2,
+            Self::RunningSum => 1,
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for Chip {
+    fn aux_width(&self) -> usize {
+        match self {
+            Self::Fibonacci => 0,
+            Self::RunningSum => 1,
+        }
+    }
+
+    fn num_challenges(&self) -> usize {
+        match self {
+            Self::Fibonacci => 0,
+            Self::RunningSum => 1,
+        }
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let r = challenges[0];
+        let mut sum = EF::ZERO;
+        let values = main
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, &a)| {
+                sum += if i == 0 { EF::from(a) } else { r * a };
+                sum
+            })
+            .collect();
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder + AirBuilderWithPublicValues> Air<AB> for Chip {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+
+        match self {
+            Self::Fibonacci => {
+                let x = builder.public_values()[0];
+                let (left, right) = (local[0].clone(), local[1].clone());
+
+                builder.when_first_row().assert_zero(left.clone());
+                builder.when_first_row().assert_one(right.clone());
+                builder
+                    .when_transition()
+                    .assert_eq(right.clone(), next[0].clone());
+                builder
+                    .when_transition()
+                    .assert_eq(left + right.clone(), next[1].clone());
+                builder.when_last_row().assert_eq(right, x);
+            }
+            Self::RunningSum => {
+                let a: AB::Expr = local[0].clone().into();
+                let a_next: AB::Expr = next[0].clone().into();
+                let r: AB::ExprEF = builder.aux_challenges()[0].into();
+
+                let aux = builder.aux();
+                let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+                let z_next: AB::ExprEF =
+                    aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+                builder
+                    .when_first_row()
+                    .assert_eq_ext(z.clone(), AB::ExprEF::from(a));
+                builder
+                    .when_transition()
+                    .assert_eq_ext(z_next, z + r * AB::ExprEF::from(a_next));
+            }
+        }
+    }
+}
+
+fn fibonacci_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut left, mut right) = (F::ZERO, F::ONE);
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, 2)
+}
+
+fn counter_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..n).map(F::from_usize).collect())
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_multi_table_proof() {
+    let config = create_config();
+    let airs = [Chip::Fibonacci, Chip::RunningSum];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let traces = vec![fibonacci_trace::<Val>(1 << 3), counter_trace::<Val>(1 << 4)];
+    let public_values = vec![vec![Val::from_u64(21)], vec![]];
+
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert_eq!(proof.tables.len(), 2);
+    assert_eq!(proof.tables[0].log_degree, 3);
+    assert_eq!(proof.tables[1].log_degree, 4);
+    assert_eq!(proof.tables[1].aux_commits.len(), 1);
+
+    verify_multi(&config, &airs, &vks, &proof, &public_values).expect("verification failed");
+
+    // Wrong public values of one table
+    let wrong_public_values = vec![vec![Val::from_u64(22)], vec![]];
+    assert!(verify_multi(&config, &airs, &vks, &proof, &wrong_public_values).is_err());
+
+    // Tables in a different order than they were proven
+    let swapped = [Chip::RunningSum, Chip::Fibonacci];
+    assert!(verify_multi(&config, &swapped, &vks, &proof, &public_values).is_err());
+
+    // A proof of another protocol revision
+    let mut other_version = proof.clone();
+    other_version.version += 1;
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &other_version, &public_values),
+        Err(VerificationError::UnsupportedVersion { .. })
+    ));
+
+    // A missing table
+    let mut truncated = proof.clone();
+    truncated.tables.pop();
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &truncated, &public_values),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_multi_table_wrong_trace_rejected() {
+    let config = create_config();
+    let airs = [Chip::Fibonacci, Chip::RunningSum];
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let mut fibonacci = fibonacci_trace::<Val>(1 << 3);
+    fibonacci.values[5] += Val::ONE;
+    let traces = vec![fibonacci, counter_trace::<Val>(1 << 4)];
+    let public_values = vec![vec![Val::from_u64(21)], vec![]];
+
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert!(verify_multi(&config, &airs, &vks, &proof, &public_values).is_err());
+}
+
+#[test]
+fn test_batch_shares_airs() {
+    let config = create_config();
+    let (fibonacci, running_sum) = (Chip::Fibonacci, Chip::RunningSum);
+    let (fibonacci_pk, fibonacci_vk) = setup(&config, &fibonacci);
+    let (running_sum_pk, running_sum_vk) = setup(&config, &running_sum);
+
+    // Two statements of the running-sum AIR, of different heights, around a Fibonacci one
+    let statement = |air, pk, main_trace, public_values| BatchStatement {
+        air,
+        pk,
+        main_trace,
+        public_values,
+    };
+    let proof = prove_batch(
+        &config,
+        vec![
+            statement(&running_sum, &running_sum_pk, counter_trace(1 << 4), vec![]),
+            statement(
+                &fibonacci,
+                &fibonacci_pk,
+                fibonacci_trace(1 << 3),
+                vec![Val::from_u64(21)],
+            ),
+            statement(&running_sum, &running_sum_pk, counter_trace(1 << 3), vec![]),
+        ],
+    )
+    .expect("proving failed");
+    assert_eq!(proof.tables.len(), 3);
+
+    let public_value = [Val::from_u64(21)];
+    let instance = |air, vk, public_values| BatchInstance {
+        air,
+        vk,
+        public_values,
+    };
+    let instances = [
+        instance(&running_sum, &running_sum_vk, &[][..]),
+        instance(&fibonacci, &fibonacci_vk, &public_value[..]),
+        instance(&running_sum, &running_sum_vk, &[][..]),
+    ];
+    verify_batch(&config, &instances, &proof).expect("verification failed");
+
+    // The batch is an ordinary multi-table proof
+    verify_multi(
+        &config,
+        &[Chip::RunningSum, Chip::Fibonacci, Chip::RunningSum],
+        &[
+            running_sum_vk.clone(),
+            fibonacci_vk.clone(),
+            running_sum_vk.clone(),
+        ],
+        &proof,
+        &[vec![], vec![Val::from_u64(21)], vec![]],
+    )
+    .expect("verification failed");
+
+    // Statements in a different order than they were proven
+    let reordered = [
+        instance(&fibonacci, &fibonacci_vk, &public_value[..]),
+        instance(&running_sum, &running_sum_vk, &[][..]),
+        instance(&running_sum, &running_sum_vk, &[][..]),
+    ];
+    assert!(verify_batch(&config, &reordered, &proof).is_err());
+}
@@ -0,0 +1,154 @@
+//! Test for an AIR with a preprocessed column
+//!
+//! The preprocessed trace is a fixed table `k[i] = i^2`, committed once by `setup`. The
+//! main trace is a single column `a` constrained to `a = 3k`.
+
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_fri::{create_test_fri_params, TwoAdicFriPcs};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark_mt::{
+    prove, setup, verify, AuxTraceBuilder, StarkConfig, VerificationError, VerifyingKey,
+};
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+
+/// `a = 3k` against a preprocessed table of squares
+pub struct SquaresAir {
+    /// Height of the preprocessed table
+    pub height: usize,
+    /// Offset added to every table entry, to build a different table
+    pub offset: u64,
+}
+
+impl<F: Field> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        Some(RowMajorMatrix::new_col(
+            (0..self.height)
+                .map(|i| F::from_usize(i * i) + F::from_u64(self.offset))
+                .collect(),
+        ))
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SquaresAir {}
+
+impl<AB: PairBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+
+        let preprocessed = builder.preprocessed();
+        let k: AB::Expr = preprocessed.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+
+        builder.assert_eq(a, k * AB::Expr::from_u8(3));
+    }
+}
+
+fn generate_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new_col((0..n).map(|i| F::from_usize(3 * i * i)).collect())
+}
+
+// Type aliases for test configuration
+type Val = BabyBear;
+type Perm = Poseidon2BabyBear<16>;
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, 8>;
+type Challenge = BinomialExtensionField<Val, 4>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+type Dft = Radix2DitParallel<Val>;
+type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn create_config() -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_params = create_test_fri_params(challenge_mmcs, 2);
+    let pcs = Pcs::new(dft, val_mmcs, fri_params);
+    let challenger = Challenger::new(perm);
+    MyConfig::new(pcs, challenger)
+}
+
+#[test]
+fn test_preprocessed_squares() {
+    let config = create_config();
+    let air = SquaresAir {
+        height: 1 << 4,
+        offset: 0,
+    };
+    let (pk, vk) = setup(&config, &air);
+    assert_eq!(vk.preprocessed_width(), 1);
+
+    let proof =
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).expect("proving failed");
+    assert_eq!(proof.preprocessed_local.len(), 1);
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_preprocessed_wrong_trace_rejected() {
+    let config = create_config();
+    let air = SquaresAir {
+        height: 1 << 4,
+        offset: 0,
+    };
+    let (pk, vk) = setup(&config, &air);
+
+    let mut trace = generate_trace::<Val>(1 << 4);
+    trace.values[5] += Val::ONE;
+    let proof = prove(&config, &air, &pk, trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_preprocessed_other_key_rejected() {
+    let config = create_config();
+    let air = SquaresAir {
+        height: 1 << 4,
+        offset: 0,
+    };
+    let (pk, _) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, generate_trace::<Val>(1 << 4), &[]).expect("proving failed");
+
+    // A key committing to a different table does not accept the proof
+    let other_air = SquaresAir {
+        height: 1 << 4,
+        offset: 1,
+    };
+    let (_, other_vk) = setup(&config, &other_air);
+    assert!(verify(&config, &air, &other_vk, &proof, &[]).is_err());
+
+    // Neither does a key without preprocessed columns
+    let empty_vk = VerifyingKey {
+        preprocessed: None,
+        ..other_vk
+    };
+    assert!(matches!(
+        verify(&config, &air, &empty_vk, &proof, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
@@ -0,0 +1,172 @@
+//! The config presets prove and verify, at the cost their models predict
+
+#![cfg(feature = "presets")]
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, TwoAdicField};
+use p3_goldilocks::Goldilocks;
+use p3_koala_bear::KoalaBear;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::presets::{
+    baby_bear_keccak_config, baby_bear_keccak_cost_model, baby_bear_poseidon2_config,
+    baby_bear_poseidon2_cost_model, goldilocks_poseidon2_config, goldilocks_poseidon2_cost_model,
+    koala_bear_poseidon2_config, koala_bear_poseidon2_cost_model,
+};
+use p3_uni_stark_mt::{
+    prove, serialized_size, setup, verify, verify_metered, AirShape, AuxTraceBuilder,
+    StarkGenericConfig, WorkBudget,
+};
+
+/// One column counting up from 0
+pub struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let next = main.row_slice(1).expect("Matrix only has 1 row?")[0].clone();
+
+        builder.when_first_row().assert_zero(local.clone());
+        builder
+            .when_transition()
+            .assert_eq(next, local + AB::Expr::ONE);
+    }
+}
+
+fn counter_trace<F: Field>(log_n: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new((0..1 << log_n).map(F::from_usize).collect(), 1)
+}
+
+#[test]
+fn test_baby_bear_poseidon2_preset() {
+    let config = baby_bear_poseidon2_config(100);
+    let model = baby_bear_poseidon2_cost_model(100);
+    let air = CounterAir;
+    let log_n = 5;
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, counter_trace::<BabyBear>(log_n), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // The model charges exactly what verifying the proof costs
+    let estimate = model.estimate(log_n, &AirShape::new(&vk, &air, 0));
+    let mut budget = WorkBudget::new(estimate.cost);
+    verify_metered(&config, &air, &vk, &proof, &[], &mut budget).expect("verification failed");
+    assert_eq!(budget.used, estimate.cost);
+
+    // A proof at one security level does not pass for another
+    let weaker = baby_bear_poseidon2_config(80);
+    assert!(verify(&weaker, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_baby_bear_keccak_preset() {
+    let config = baby_bear_keccak_config(100);
+    let model = baby_bear_keccak_cost_model(100);
+    let air = CounterAir;
+    let log_n = 5;
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, counter_trace::<BabyBear>(log_n), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    let estimate = model.estimate(log_n, &AirShape::new(&vk, &air, 0));
+    let mut budget = WorkBudget::new(estimate.cost);
+    verify_metered(&config, &air, &vk, &proof, &[], &mut budget).expect("verification failed");
+    assert_eq!(budget.used, estimate.cost);
+
+    // 32-byte digests, as the model assumes
+    let size = serialized_size(&proof);
+    assert!(
+        estimate.proof_size.abs_diff(size) * 10 <= size,
+        "estimated {} bytes, proof has {size}",
+        estimate.proof_size
+    );
+}
+
+#[test]
+fn test_koala_bear_poseidon2_preset() {
+    let config = koala_bear_poseidon2_config(100);
+    let model = koala_bear_poseidon2_cost_model(100);
+    let air = CounterAir;
+    let log_n = 5;
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, counter_trace::<KoalaBear>(log_n), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    let estimate = model.estimate(log_n, &AirShape::new(&vk, &air, 0));
+    let mut budget = WorkBudget::new(estimate.cost);
+    verify_metered(&config, &air, &vk, &proof, &[], &mut budget).expect("verification failed");
+    assert_eq!(budget.used, estimate.cost);
+}
+
+#[test]
+fn test_goldilocks_poseidon2_preset() {
+    let config = goldilocks_poseidon2_config(100);
+    let model = goldilocks_poseidon2_cost_model(100);
+    let air = CounterAir;
+    let log_n = 5;
+
+    let (pk, vk) = setup(&config, &air);
+    let proof =
+        prove(&config, &air, &pk, counter_trace::<Goldilocks>(log_n), &[]).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    let estimate = model.estimate(log_n, &AirShape::new(&vk, &air, 0));
+    let mut budget = WorkBudget::new(estimate.cost);
+    verify_metered(&config, &air, &vk, &proof, &[], &mut budget).expect("verification failed");
+    assert_eq!(budget.used, estimate.cost);
+
+    // Every call builds the same permutation, so proofs verify across config instances
+    let config = goldilocks_poseidon2_config(100);
+    verify(&config, &air, &vk, &proof, &[]).expect("verification failed");
+
+    // A trace breaking the counter must not verify
+    let mut bad_trace = counter_trace::<Goldilocks>(log_n);
+    bad_trace.values[3] = Goldilocks::ZERO;
+    let proof = prove(&config, &air, &pk, bad_trace, &[]).expect("proving failed");
+    assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_presets_bound_the_trace_height() {
+    // The LDE must fit in the two-adic subgroup
+    let max_log_degree = |two_adicity: usize, log_blowup: usize| Some(two_adicity - log_blowup);
+    let log_blowup = baby_bear_poseidon2_cost_model(100).log_blowup;
+    assert_eq!(
+        baby_bear_poseidon2_config(100).max_supported_log_degree(),
+        max_log_degree(BabyBear::TWO_ADICITY, log_blowup)
+    );
+    assert_eq!(
+        baby_bear_keccak_config(100).max_supported_log_degree(),
+        max_log_degree(BabyBear::TWO_ADICITY, log_blowup)
+    );
+    assert_eq!(
+        koala_bear_poseidon2_config(100).max_supported_log_degree(),
+        max_log_degree(KoalaBear::TWO_ADICITY, log_blowup)
+    );
+    assert_eq!(
+        goldilocks_poseidon2_config(100).max_supported_log_degree(),
+        max_log_degree(Goldilocks::TWO_ADICITY, log_blowup)
+    );
+}
+
+#[test]
+#[should_panic(expected = "1 to 100 bits of security")]
+fn test_baby_bear_poseidon2_preset_rejects_zero_security() {
+    baby_bear_poseidon2_config(0);
+}
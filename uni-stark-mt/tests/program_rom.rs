@@ -0,0 +1,196 @@
+//! Test for the program ROM chip
+//!
+//! A toy CPU runs a straight-line program of ADD and MUL instructions on an accumulator,
+//! fetching each instruction from a ROM committed as a preprocessed table; the two tables
+//! are proven together.
+
+#![cfg(feature = "lookups")]
+
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove_multi, setup, verify_multi, AuxBuilder, AuxTraceBuilder, BusInteractions,
+    InteractionBuilder, Multiplicity, ProgramRom, ProgramRomCounts, VerificationError,
+};
+
+const FETCH_BUS: usize = 0;
+const PC: usize = 0;
+const OPCODE: usize = 1;
+const ARG: usize = 2;
+const ACC: usize = 3;
+
+const ADD: u64 = 0;
+const MUL: u64 = 1;
+
+/// `(opcode, arg)` per instruction
+const PROGRAM: [(u64, u64); 8] = [
+    (ADD, 3),
+    (MUL, 4),
+    (ADD, 1),
+    (MUL, 2),
+    (ADD, 5),
+    (MUL, 3),
+    (ADD, 7),
+    (MUL, 2),
+];
+
+fn rom(program: &[(u64, u64)]) -> ProgramRom {
+    let instructions = program.iter().flat_map(|&(op, arg)| [op, arg]).collect();
+    ProgramRom::new(FETCH_BUS, 2, instructions)
+}
+
+/// The tables of the test machine
+pub enum Table {
+    /// One row per executed instruction: `pc`, the instruction and the accumulator before it
+    Cpu(BusInteractions),
+    /// The program
+    Rom(ProgramRom),
+}
+
+fn tables() -> [Table; 2] {
+    let rom = rom(&PROGRAM);
+    let cpu = rom
+        .register(
+            InteractionBuilder::new(),
+            PC,
+            &[OPCODE, ARG],
+            Multiplicity::One,
+        )
+        .build();
+    [Table::Cpu(cpu), Table::Rom(rom)]
+}
+
+impl<F: Field> BaseAir<F> for Table {
+    fn width(&self) -> usize {
+        match self {
+            Self::Cpu(_) => 4,
+            Self::Rom(rom) => BaseAir::<F>::width(rom),
+        }
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        match self {
+            Self::Cpu(_) => None,
+            Self::Rom(rom) => rom.preprocessed_trace(),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for Table {
+    fn aux_width(&self) -> usize {
+        match self {
+            Self::Cpu(bus) => bus.aux_width(),
+            Self::Rom(rom) => AuxTraceBuilder::<F, EF>::aux_width(rom),
+        }
+    }
+
+    fn num_challenges(&self) -> usize {
+        BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        match self {
+            Self::Cpu(bus) => bus.build_aux_trace(main, challenges),
+            Self::Rom(rom) => rom.build_aux_trace(main, challenges),
+        }
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        match self {
+            Self::Cpu(bus) => vec![bus.cumulative_sum(aux_phase, 0)],
+            Self::Rom(rom) => vec![rom.cumulative_sum(aux_phase)],
+        }
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for Table {
+    fn eval(&self, builder: &mut AB) {
+        match self {
+            Self::Cpu(bus) => {
+                let main = builder.main();
+                let local = main.row_slice(0).expect("Matrix is empty?");
+                let next = main.row_slice(1).expect("Matrix only has 1 row?");
+                let [pc, opcode, arg, acc]: [AB::Expr; 4] =
+                    [PC, OPCODE, ARG, ACC].map(|c| local[c].clone().into());
+                let (pc_next, acc_next): (AB::Expr, AB::Expr) =
+                    (next[PC].clone().into(), next[ACC].clone().into());
+
+                builder.when_first_row().assert_zero(pc.clone());
+                builder.when_first_row().assert_zero(acc.clone());
+                builder
+                    .when_transition()
+                    .assert_eq(pc_next, pc + AB::Expr::ONE);
+                let sum = acc.clone() + arg.clone();
+                let product = acc * arg;
+                builder
+                    .when_transition()
+                    .assert_eq(acc_next, sum.clone() + opcode * (product - sum));
+                bus.eval(builder, 0, 0);
+            }
+            Self::Rom(rom) => rom.eval(builder),
+        }
+    }
+}
+
+/// Rows `(pc, opcode, arg, acc)` running `program` from `acc = 0`
+fn cpu_trace<F: Field>(program: &[(u64, u64)]) -> RowMajorMatrix<F> {
+    let mut acc = 0;
+    let values = program
+        .iter()
+        .enumerate()
+        .flat_map(|(pc, &(opcode, arg))| {
+            let row = [pc as u64, opcode, arg, acc].map(F::from_u64);
+            acc = if opcode == ADD { acc + arg } else { acc * arg };
+            row
+        })
+        .collect();
+    RowMajorMatrix::new(values, 4)
+}
+
+/// Prove an execution of `executed` against the ROM of [`PROGRAM`]
+fn prove_and_verify(executed: &[(u64, u64)]) -> Result<(), VerificationError> {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = tables();
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let cpu = cpu_trace::<Val>(executed);
+    let mut counts = ProgramRomCounts::new(&rom(&PROGRAM));
+    counts.record_column(&cpu, PC, Multiplicity::One);
+    let traces = vec![cpu, counts.trace()];
+
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    verify_multi(&config, &airs, &vks, &proof, &public_values)
+}
+
+#[test]
+fn test_program_rom_fetches() {
+    prove_and_verify(&PROGRAM).expect("verification failed");
+}
+
+#[test]
+fn test_instruction_outside_program_rejected() {
+    // The execution multiplies where the program adds
+    let mut executed = PROGRAM;
+    executed[4].0 = MUL;
+    assert!(matches!(
+        prove_and_verify(&executed),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+}
@@ -0,0 +1,35 @@
+//! Property-based prove/verify round trips over synthetic AIRs
+
+#![cfg(all(feature = "proptest", feature = "prover"))]
+
+use p3_uni_stark_mt::fixtures::{baby_bear_config, FIXTURE_SEED};
+use p3_uni_stark_mt::strategies::{corrupted_trace, synthetic_air, synthetic_instance};
+use p3_uni_stark_mt::{prove, setup, verify};
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn test_synthetic_round_trip((air, trace) in synthetic_instance()) {
+        let config = baby_bear_config(FIXTURE_SEED);
+        let (pk, vk) = setup(&config, &air);
+        prop_assert_eq!(vk.max_constraint_degree, air.degree + 1);
+
+        let proof = prove(&config, &air, &pk, trace, &[]).expect("trace is valid");
+        prop_assert!(verify(&config, &air, &vk, &proof, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_synthetic_corrupted_trace_rejected(
+        (air, trace) in synthetic_air().prop_flat_map(|air| (Just(air), corrupted_trace(air)))
+    ) {
+        let config = baby_bear_config(FIXTURE_SEED);
+        let (pk, vk) = setup(&config, &air);
+
+        // The prover does not check the trace: it either fails or proves a false statement
+        if let Ok(proof) = prove(&config, &air, &pk, trace, &[]) {
+            prop_assert!(verify(&config, &air, &vk, &proof, &[]).is_err());
+        }
+    }
+}
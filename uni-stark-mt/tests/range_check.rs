@@ -0,0 +1,176 @@
+//! Test for the range-check chip
+//!
+//! A table of 16-bit values split into two byte limbs sends both limbs to an 8-bit
+//! range-check chip, whose preprocessed table holds `0..256`; the two are proven together.
+
+#![cfg(feature = "lookups")]
+
+use p3_air::{Air, AirBuilder, BaseAir, PairBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove_multi, setup, verify_multi, AuxBuilder, AuxTraceBuilder, BusInteractions,
+    InteractionBuilder, RangeCheckChip, RangeCheckCounts, VerificationError,
+};
+
+const RANGE_BUS: usize = 1;
+const VALUE: usize = 0;
+const LOW: usize = 1;
+const HIGH: usize = 2;
+
+/// The tables of the test machine
+pub enum Table {
+    /// `value = low + 256·high`, with both limbs range checked
+    Limbs(BusInteractions),
+    /// The 8-bit range-check chip
+    Range(RangeCheckChip),
+}
+
+fn tables() -> [Table; 2] {
+    let range = RangeCheckChip::new(8, RANGE_BUS);
+    let limbs = range
+        .register(InteractionBuilder::new(), &[LOW, HIGH])
+        .build();
+    [Table::Limbs(limbs), Table::Range(range)]
+}
+
+impl<F: Field> BaseAir<F> for Table {
+    fn width(&self) -> usize {
+        match self {
+            Self::Limbs(_) => 3,
+            Self::Range(range) => BaseAir::<F>::width(range),
+        }
+    }
+
+    fn preprocessed_trace(&self) -> Option<RowMajorMatrix<F>> {
+        match self {
+            Self::Limbs(_) => None,
+            Self::Range(range) => range.preprocessed_trace(),
+        }
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for Table {
+    fn aux_width(&self) -> usize {
+        match self {
+            Self::Limbs(bus) => bus.aux_width(),
+            Self::Range(range) => AuxTraceBuilder::<F, EF>::aux_width(range),
+        }
+    }
+
+    fn num_challenges(&self) -> usize {
+        BusInteractions::NUM_CHALLENGES
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        match self {
+            Self::Limbs(bus) => bus.build_aux_trace(main, challenges),
+            Self::Range(range) => range.build_aux_trace(main, challenges),
+        }
+    }
+
+    fn aux_phase_num_exposed_values(&self, _phase: usize) -> usize {
+        1
+    }
+
+    fn aux_phase_exposed_values(
+        &self,
+        _phase: usize,
+        aux_phase: &RowMajorMatrix<EF>,
+        _challenges: &[EF],
+    ) -> Vec<EF> {
+        match self {
+            Self::Limbs(bus) => vec![bus.cumulative_sum(aux_phase, 0)],
+            Self::Range(range) => vec![range.cumulative_sum(aux_phase)],
+        }
+    }
+
+    fn interaction_sum(&self) -> Option<(usize, usize)> {
+        Some((0, 0))
+    }
+}
+
+impl<AB: AuxBuilder + PairBuilder> Air<AB> for Table {
+    fn eval(&self, builder: &mut AB) {
+        match self {
+            Self::Limbs(bus) => {
+                let main = builder.main();
+                let local = main.row_slice(0).expect("Matrix is empty?");
+                let (value, low, high) = (
+                    local[VALUE].clone(),
+                    local[LOW].clone(),
+                    local[HIGH].clone(),
+                );
+                builder.assert_eq(value, low + high * AB::Expr::from_u16(256));
+                bus.eval(builder, 0, 0);
+            }
+            Self::Range(range) => range.eval(builder),
+        }
+    }
+}
+
+/// Rows `(value, low, high)` for the values `1000·i + 7`
+fn limbs_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let values = (0..n)
+        .flat_map(|i| {
+            let value = 1000 * i + 7;
+            [value, value % 256, value / 256].map(F::from_usize)
+        })
+        .collect();
+    RowMajorMatrix::new(values, 3)
+}
+
+#[test]
+fn test_range_checked_limbs() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = tables();
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    let limbs = limbs_trace::<Val>(1 << 4);
+    let mut counts = RangeCheckCounts::new(8);
+    counts.record_column(&limbs, LOW);
+    counts.record_column(&limbs, HIGH);
+    let traces = vec![limbs, counts.trace()];
+
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert_eq!(proof.tables[1].log_degree, 8);
+    verify_multi(&config, &airs, &vks, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_out_of_range_limb_rejected() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let airs = tables();
+    let (pks, vks): (Vec<_>, Vec<_>) = airs.iter().map(|air| setup(&config, air)).unzip();
+
+    // Row 3 splits 3007 as 447 + 256·10, with a low limb past the table
+    let mut limbs = limbs_trace::<Val>(1 << 4);
+    limbs.values[3 * 3 + LOW] = Val::from_u16(447);
+    limbs.values[3 * 3 + HIGH] = Val::from_u16(10);
+    let mut counts = RangeCheckCounts::new(8);
+    for row in limbs.values.chunks_exact(3) {
+        for limb in [row[LOW], row[HIGH]] {
+            let limb = limb.as_canonical_u64();
+            if limb < 256 {
+                counts.record(limb);
+            }
+        }
+    }
+    let traces = vec![limbs, counts.trace()];
+
+    let public_values = vec![vec![], vec![]];
+    let proof = prove_multi(&config, &airs, &pks, traces, &public_values).expect("proving failed");
+    assert!(matches!(
+        verify_multi(&config, &airs, &vks, &proof, &public_values),
+        Err(VerificationError::UnbalancedInteractions)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "out of the range-check table")]
+fn test_counts_reject_out_of_range_values() {
+    RangeCheckCounts::new(8).record(256);
+}
@@ -0,0 +1,160 @@
+//! Test for segment proof aggregation
+//!
+//! A Fibonacci execution is split into segments of 8 steps, each proven on its own; the
+//! segments carry their start and end states as public values and are verified together.
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{baby_bear_config, FixtureConfig, Val, FIXTURE_SEED};
+use p3_uni_stark_mt::{
+    prove, setup, verify_segments, AuxTraceBuilder, Proof, SegmentLayout, SegmentProof,
+    VerificationError,
+};
+
+const SEGMENT_STEPS: usize = 8;
+
+/// One segment of a Fibonacci execution
+///
+/// Public values: `[left, right, left_end, right_end]`, the pair on the first row and the
+/// pair following the last row.
+pub struct FibonacciSegmentAir;
+
+impl<F> BaseAir<F> for FibonacciSegmentAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FibonacciSegmentAir {
+    fn aux_width(&self) -> usize {
+        0
+    }
+
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciSegmentAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let [left, right, left_end, right_end] = [0, 1, 2, 3].map(|i| builder.public_values()[i]);
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let next = main.row_slice(1).expect("Matrix only has 1 row?");
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local[0].clone(), left);
+        when_first_row.assert_eq(local[1].clone(), right);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1].clone(), next[0].clone());
+        when_transition.assert_eq(local[0].clone() + local[1].clone(), next[1].clone());
+
+        let mut when_last_row = builder.when_last_row();
+        when_last_row.assert_eq(local[1].clone(), left_end);
+        when_last_row.assert_eq(local[0].clone() + local[1].clone(), right_end);
+    }
+}
+
+/// Trace and public values of each segment of `num_segments` running from `(0, 1)`
+fn segments(num_segments: usize) -> Vec<(RowMajorMatrix<Val>, Vec<Val>)> {
+    let (mut left, mut right) = (Val::ZERO, Val::ONE);
+    (0..num_segments)
+        .map(|_| {
+            let start = [left, right];
+            let mut values = Vec::with_capacity(2 * SEGMENT_STEPS);
+            for _ in 0..SEGMENT_STEPS {
+                values.extend([left, right]);
+                (left, right) = (right, left + right);
+            }
+            let public_values = [start, [left, right]].concat();
+            (RowMajorMatrix::new(values, 2), public_values)
+        })
+        .collect()
+}
+
+/// Segments `order` of `proofs`
+fn in_order<'a>(
+    proofs: &'a [Proof<FixtureConfig>],
+    public_values: &'a [Vec<Val>],
+    order: &[usize],
+) -> Vec<SegmentProof<'a, FixtureConfig>> {
+    order
+        .iter()
+        .map(|&i| SegmentProof {
+            proof: &proofs[i],
+            public_values: &public_values[i],
+        })
+        .collect()
+}
+
+fn layout() -> SegmentLayout {
+    SegmentLayout {
+        start: 0..2,
+        end: 2..4,
+    }
+}
+
+#[test]
+fn test_segment_aggregation() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = FibonacciSegmentAir;
+    let (pk, vk) = setup(&config, &air);
+
+    let segments = segments(3);
+    let public_values: Vec<Vec<Val>> = segments.iter().map(|(_, pv)| pv.clone()).collect();
+    let proofs: Vec<Proof<FixtureConfig>> = segments
+        .into_iter()
+        .map(|(trace, pv)| prove(&config, &air, &pk, trace, &pv).expect("proving failed"))
+        .collect();
+    let segment_proofs = |order: &[usize]| in_order(&proofs, &public_values, order);
+
+    // F(24) = 46368 and F(25) = 75025 follow the last step
+    let initial_state = [Val::ZERO, Val::ONE];
+    let final_state = [Val::from_u32(46368), Val::from_u32(75025)];
+    let verify = |segments: &[SegmentProof<'_, FixtureConfig>], final_state: &[Val]| {
+        verify_segments(
+            &config,
+            &air,
+            &vk,
+            &layout(),
+            segments,
+            &initial_state,
+            final_state,
+        )
+    };
+
+    verify(&segment_proofs(&[0, 1, 2]), &final_state).expect("verification failed");
+
+    // Segments out of order or missing do not chain
+    assert!(matches!(
+        verify(&segment_proofs(&[0, 2, 1]), &final_state),
+        Err((1, VerificationError::SegmentChainBroken))
+    ));
+    assert!(matches!(
+        verify(&segment_proofs(&[0, 2]), &final_state),
+        Err((1, VerificationError::SegmentChainBroken))
+    ));
+    // A shorter execution does not reach the claimed final state
+    assert!(matches!(
+        verify(&segment_proofs(&[0, 1]), &final_state),
+        Err((1, VerificationError::SegmentChainBroken))
+    ));
+    assert!(matches!(
+        verify(&[], &final_state),
+        Err((0, VerificationError::InvalidProof(_)))
+    ));
+
+    // A segment claiming a state its proof does not bind chains, but fails its proof
+    let forged = [Val::ZERO, Val::ONE, Val::ONE, Val::ONE];
+    let forged_segment = [SegmentProof {
+        proof: &proofs[0],
+        public_values: &forged,
+    }];
+    assert!(matches!(
+        verify(&forged_segment, &[Val::ONE, Val::ONE]),
+        Err((0, error)) if !matches!(error, VerificationError::SegmentChainBroken)
+    ));
+}
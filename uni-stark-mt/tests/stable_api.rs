@@ -0,0 +1,45 @@
+//! Proving and verifying through the semver-committed API only
+
+#![cfg(feature = "fixtures")]
+
+use p3_uni_stark_mt::fixtures::{baby_bear_config, fibonacci_trace, FibonacciAir, FIXTURE_SEED};
+use p3_uni_stark_mt::stable::{prove, setup, verify, Proof, VerificationError, PROOF_VERSION};
+
+#[test]
+fn test_stable_prove_and_verify() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = FibonacciAir;
+    let trace = fibonacci_trace(4);
+    let public_values = vec![trace.values[trace.values.len() - 1]];
+
+    let (pk, vk) = setup(&config, &air);
+    let proof: Proof<_> = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &vk, &proof, &public_values).expect("verification failed");
+
+    let mut wrong_values = public_values;
+    wrong_values[0] += wrong_values[0];
+    let result: Result<(), VerificationError> = verify(&config, &air, &vk, &proof, &wrong_values);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_stable_rejects_other_proof_versions() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let air = FibonacciAir;
+    let trace = fibonacci_trace(4);
+    let public_values = vec![trace.values[trace.values.len() - 1]];
+
+    let (pk, vk) = setup(&config, &air);
+    let mut proof = prove(&config, &air, &pk, trace, &public_values).expect("proving failed");
+    assert_eq!(proof.version, PROOF_VERSION);
+
+    // Reported before any of the proof is looked at, e.g. a proof of an earlier revision
+    proof.version = 0;
+    assert!(matches!(
+        verify(&config, &air, &vk, &proof, &public_values),
+        Err(VerificationError::UnsupportedVersion {
+            found: 0,
+            expected: PROOF_VERSION,
+        })
+    ));
+}
@@ -0,0 +1,182 @@
+//! Quotient evaluation from a constraint DAG
+//!
+//! `DagBackend` compiles the constraints once and evaluates the compiled DAG at every
+//! point; its proofs, which `prove` makes from the DAG of the proving key, must be those
+//! of `CpuBackend`, which runs the AIR there.
+
+#![cfg(all(feature = "fixtures", feature = "prover"))]
+
+use p3_air::{Air, AirBuilder, BaseAir, ExtensionBuilder};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::fixtures::{
+    baby_bear_config, fibonacci_trace, Challenge, FibonacciAir, FixtureConfig, Val, FIXTURE_SEED,
+};
+use p3_uni_stark_mt::{
+    keygen, prove, prove_with_backend, setup, verify, AuxBuilder, AuxTraceBuilder, ConstraintDag,
+    CpuBackend, DagBackend, MultiTraceAir, ProverFolder, SymbolicBuilder, SymbolicConstraint,
+    VerifierFolder,
+};
+
+/// Grand product AIR: `a[i] = i` and `z[i] = (r - a[0]) * ... * (r - a[i])`
+struct GrandProductAir;
+
+impl<F> BaseAir<F> for GrandProductAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for GrandProductAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_challenges(&self) -> usize {
+        1
+    }
+
+    fn build_aux_trace(&self, main: &RowMajorMatrix<F>, challenges: &[EF]) -> RowMajorMatrix<EF> {
+        let mut product = EF::ONE;
+        let values = main
+            .values
+            .iter()
+            .map(|&a| {
+                product *= challenges[0] - a;
+                product
+            })
+            .collect();
+        RowMajorMatrix::new(values, 1)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for GrandProductAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let a: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let a_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+        let r: AB::ExprEF = builder.aux_challenges()[0].into();
+
+        let aux = builder.aux();
+        let z: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let z_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder.when_first_row().assert_zero(a.clone());
+        builder
+            .when_first_row()
+            .assert_eq_ext(z.clone(), r.clone() - AB::ExprEF::from(a.clone()));
+        builder
+            .when_transition()
+            .assert_eq(a_next.clone(), a + AB::Expr::ONE);
+        builder
+            .when_transition()
+            .assert_eq_ext(z_next, z * (r - AB::ExprEF::from(a_next)));
+    }
+}
+
+/// Asserts `a^20 = b`
+struct PowerAir;
+
+impl<F> BaseAir<F> for PowerAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PowerAir {}
+
+impl<AB: AirBuilder> Air<AB> for PowerAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let a: AB::Expr = local[0].clone().into();
+        builder.assert_eq(a.exp_u64(20), local[1].clone());
+    }
+}
+
+fn grand_product_trace(log_n: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new((0..1 << log_n).map(Val::from_usize).collect(), 1)
+}
+
+/// Prove `trace` of `air` with both backends and check the proofs are the same.
+fn check_backends_agree<A>(air: &A, trace: RowMajorMatrix<Val>, public_values: &[Val])
+where
+    A: MultiTraceAir<Val, Challenge>
+        + for<'a> Air<ProverFolder<'a, FixtureConfig>>
+        + for<'a> Air<VerifierFolder<'a, FixtureConfig>>
+        + Air<SymbolicBuilder<Val, Challenge>>,
+{
+    let config = baby_bear_config(FIXTURE_SEED);
+    let (pk, vk) = setup(&config, air);
+    let backend = DagBackend::new(air, &vk);
+    assert_eq!(backend.dag().num_constraints(), vk.num_constraints);
+    assert_eq!(backend.dag().num_nodes(), pk.constraint_dag().num_nodes());
+
+    let proof = prove_with_backend(&config, air, &pk, trace.clone(), public_values, &backend)
+        .expect("proving failed");
+    verify(&config, air, &vk, &proof, public_values).expect("verification failed");
+    let expected = prove_with_backend(&config, air, &pk, trace.clone(), public_values, &CpuBackend)
+        .expect("proving failed");
+    let default = prove(&config, air, &pk, trace, public_values).expect("proving failed");
+    let json = |proof| serde_json::to_string(proof).expect("serialization failed");
+    assert_eq!(json(&proof), json(&expected));
+    assert_eq!(json(&default), json(&expected));
+}
+
+#[test]
+fn test_dag_backend_fibonacci() {
+    let trace = fibonacci_trace::<Val>(4);
+    let public_values = [trace.get(trace.height() - 1, 1).expect("trace is empty")];
+    check_backends_agree(&FibonacciAir, trace, &public_values);
+}
+
+#[test]
+fn test_dag_backend_aux_trace() {
+    check_backends_agree(&GrandProductAir, grand_product_trace(4), &[]);
+}
+
+#[test]
+fn test_dag_shares_subexpressions() {
+    let mut builder = SymbolicBuilder::<Val, Challenge>::new(&GrandProductAir, 0);
+    GrandProductAir.eval(&mut builder);
+    let constraints = builder.constraints;
+    assert_eq!(constraints.len(), 4);
+    assert!(matches!(constraints[0], SymbolicConstraint::Base(_)));
+    assert!(matches!(constraints[1], SymbolicConstraint::Ext(_)));
+    let dag = ConstraintDag::from_constraints(&constraints);
+
+    // Asserting the same constraints again adds no node
+    let twice: Vec<_> = constraints.iter().chain(&constraints).cloned().collect();
+    let dag_twice = ConstraintDag::from_constraints(&twice);
+    assert_eq!(dag_twice.num_constraints(), 8);
+    assert_eq!(dag_twice.num_nodes(), dag.num_nodes());
+}
+
+#[test]
+fn test_constraint_degrees_and_phases() {
+    // Selectors count for one, challenges for none; `z` belongs to aux phase 0
+    let dag = ConstraintDag::<Val, Challenge>::new(&GrandProductAir, 0);
+    assert_eq!(dag.constraint_degrees(), [2, 2, 2, 3]);
+    assert_eq!(dag.constraint_phases(&GrandProductAir), [0, 1, 0, 1]);
+
+    let config = baby_bear_config(FIXTURE_SEED);
+    let vk = keygen(&config, &GrandProductAir);
+    assert_eq!(vk.num_constraints, 4);
+    assert_eq!(vk.max_constraint_degree, 3);
+
+    // The degree is read off the expressions, however high
+    assert_eq!(keygen(&config, &PowerAir).max_constraint_degree, 20);
+}
+
+#[test]
+#[should_panic(expected = "the AIR was not set up as this verifying key")]
+fn test_dag_backend_rejects_other_air() {
+    let config = baby_bear_config(FIXTURE_SEED);
+    let (_, vk) = setup(&config, &FibonacciAir);
+    let _ = DagBackend::new(&GrandProductAir, &vk);
+}
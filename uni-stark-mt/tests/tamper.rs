@@ -0,0 +1,71 @@
+//! Negative tests: every tampered proof is rejected
+
+#![cfg(all(feature = "tamper", feature = "fixtures", feature = "prover"))]
+
+use std::collections::HashSet;
+use std::mem;
+
+use p3_uni_stark_mt::fixtures::{fibonacci_proof, FIXTURE_SEED};
+use p3_uni_stark_mt::tamper::{CommitmentSlot, Mutation, ProofMutator, ValueSlot};
+use p3_uni_stark_mt::verify;
+
+#[test]
+fn test_every_mutation_rejected() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let mutator = ProofMutator::new(&fixture.proof);
+    let mutations = mutator.mutations();
+
+    // Every kind of mutation applies, except dropping an aux commitment: there is none
+    let kinds: HashSet<_> = mutations.iter().map(mem::discriminant).collect();
+    assert_eq!(kinds.len(), 7);
+    assert!(mutations.contains(&Mutation::CommitmentByte {
+        commitment: CommitmentSlot::Quotient(0),
+        byte: 0,
+    }));
+    assert!(mutations.contains(&Mutation::Value {
+        slot: ValueSlot::QuotientChunk(0),
+        index: 0,
+    }));
+    assert!(mutator.mutants().count() > 64);
+
+    let accepted = mutator.accepted_mutations(|mutant| {
+        verify(
+            &fixture.config,
+            &fixture.air,
+            &fixture.vk,
+            mutant,
+            &fixture.public_values,
+        )
+    });
+    assert!(accepted.is_empty(), "accepted mutants: {accepted:?}");
+}
+
+#[test]
+fn test_mutation_out_of_shape() {
+    let fixture = fibonacci_proof(FIXTURE_SEED, 3);
+    let mutator = ProofMutator::new(&fixture.proof);
+
+    // The Fibonacci AIR has no aux phase and no public values digest
+    assert!(mutator.apply(Mutation::DropAuxCommit(0)).is_none());
+    assert!(mutator
+        .apply(Mutation::Value {
+            slot: ValueSlot::PublicValuesDigest,
+            index: 0,
+        })
+        .is_none());
+    assert!(mutator
+        .apply(Mutation::CommitmentByte {
+            commitment: CommitmentSlot::Main,
+            byte: usize::MAX,
+        })
+        .is_none());
+
+    let mutant = mutator
+        .apply(Mutation::Value {
+            slot: ValueSlot::MainLocal,
+            index: 1,
+        })
+        .expect("the main trace has two columns");
+    assert_ne!(mutant.main_local, fixture.proof.main_local);
+    assert_eq!(mutant.main_next, fixture.proof.main_next);
+}
@@ -0,0 +1,86 @@
+//! Conformance test vectors
+//!
+//! Set `UPDATE_TEST_VECTORS=1` to rewrite `test_vectors/fibonacci.json`, e.g. after a
+//! `PROOF_VERSION` bump.
+
+#![cfg(feature = "test-vectors")]
+
+use std::fs;
+use std::path::Path;
+
+use p3_field::{BasedVectorSpace, PrimeCharacteristicRing, PrimeField32};
+use p3_uni_stark_mt::fixtures::{baby_bear_config, Challenge, FibonacciAir, Val};
+use p3_uni_stark_mt::test_vectors::{generate_vector, generate_vectors, TEST_VECTOR_CASES};
+use p3_uni_stark_mt::{replay_challenges, setup, verify, Proof};
+
+const VECTORS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test_vectors/fibonacci.json");
+
+#[test]
+fn test_vectors_match_file() {
+    let current = serde_json::to_value(generate_vectors()).expect("serialization failed");
+    let path = Path::new(VECTORS_FILE);
+    if std::env::var_os("UPDATE_TEST_VECTORS").is_some() || !path.exists() {
+        fs::create_dir_all(path.parent().expect("vectors file has a directory"))
+            .expect("failed to create the test vectors directory");
+        let json = serde_json::to_string_pretty(&current).expect("serialization failed");
+        fs::write(path, json + "\n").expect("failed to write the test vectors file");
+        return;
+    }
+
+    let json = fs::read_to_string(path).expect("failed to read the test vectors file");
+    let recorded: serde_json::Value = serde_json::from_str(&json).expect("invalid vectors file");
+    assert!(
+        recorded == current,
+        "test vectors changed; rerun with UPDATE_TEST_VECTORS=1 if intended"
+    );
+}
+
+#[test]
+fn test_vectors_deterministic() {
+    let case = &TEST_VECTOR_CASES[0];
+    let first = serde_json::to_value(generate_vector(case)).expect("serialization failed");
+    let second = serde_json::to_value(generate_vector(case)).expect("serialization failed");
+    assert_eq!(first, second);
+
+    // Another seed gives another proof
+    let other =
+        serde_json::to_value(generate_vector(&TEST_VECTOR_CASES[2])).expect("serialization failed");
+    assert_ne!(first["proof"], other["proof"]);
+}
+
+#[test]
+fn test_vectors_replay() {
+    for vector in generate_vectors() {
+        let config = baby_bear_config(vector.seed);
+        let air = FibonacciAir;
+        let (_, vk) = setup(&config, &air);
+        assert_eq!(vk.log_quotient_degree, vector.log_quotient_degree);
+
+        // The recorded proof decodes and verifies against the recorded public values
+        let json = serde_json::to_string(&vector.proof).expect("serialization failed");
+        let proof: Proof<_> = serde_json::from_str(&json).expect("deserialization failed");
+        let public_values: Vec<Val> = vector
+            .public_values
+            .iter()
+            .map(|&value| Val::from_u32(value))
+            .collect();
+        verify(&config, &air, &vk, &proof, &public_values)
+            .unwrap_or_else(|e| panic!("{}: {e:?}", vector.name));
+
+        let challenges = replay_challenges(&config, &air, &vk, &proof, &public_values);
+        let coordinates = |value: &Challenge| {
+            value
+                .as_basis_coefficients_slice()
+                .iter()
+                .map(PrimeField32::as_canonical_u32)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vector.challenges.zeta, coordinates(&challenges.zeta));
+        assert_eq!(vector.challenges.alpha, coordinates(&challenges.alpha));
+        assert_eq!(vector.openings.main_local.len(), 2);
+        assert_eq!(
+            vector.openings.quotient_chunks.len(),
+            1 << vector.log_quotient_degree
+        );
+    }
+}
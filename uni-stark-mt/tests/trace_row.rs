@@ -0,0 +1,51 @@
+//! Test for the typed row views of `trace_row!` structs
+
+use p3_baby_bear::BabyBear;
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::{trace_row, RowSlice, TraceRow};
+
+trace_row! {
+    /// A counter and a pair of limbs
+    pub struct LimbRow<F> {
+        pub counter: F,
+        pub limbs: [F; 2],
+    }
+}
+
+#[test]
+fn test_trace_row_views() {
+    assert_eq!(<LimbRow<BabyBear> as TraceRow<BabyBear>>::WIDTH, 3);
+
+    let mut values = BabyBear::zero_vec(12);
+    for (i, row) in values
+        .rows_mut_as::<LimbRow<BabyBear>>()
+        .iter_mut()
+        .enumerate()
+    {
+        row.counter = BabyBear::from_usize(i);
+        row.limbs = [BabyBear::from_usize(2 * i), BabyBear::from_usize(2 * i + 1)];
+    }
+    assert_eq!(values[6..9], [2, 4, 5].map(BabyBear::from_u8));
+
+    let row: &LimbRow<BabyBear> = values[9..].row_as();
+    assert_eq!(row.counter, BabyBear::from_u8(3));
+    values[3..6].row_mut_as::<LimbRow<BabyBear>>().limbs[1] = BabyBear::ONE;
+    assert_eq!(
+        values.rows_as::<LimbRow<BabyBear>>()[1].limbs[1],
+        BabyBear::ONE
+    );
+}
+
+#[test]
+#[should_panic(expected = "slice is not a single row")]
+fn test_trace_row_wrong_width() {
+    let values = BabyBear::zero_vec(6);
+    let _: &LimbRow<BabyBear> = values.row_as();
+}
+
+#[test]
+#[should_panic(expected = "slice is not a whole number of rows")]
+fn test_trace_rows_partial() {
+    let mut values = BabyBear::zero_vec(7);
+    values.rows_mut_as::<LimbRow<BabyBear>>();
+}